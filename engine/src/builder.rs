@@ -10,8 +10,9 @@ use autocxx_parser::file_locations::FileLocationStrategy;
 use miette::Diagnostic;
 use thiserror::Error;
 
-use crate::{generate_rs_single, CodegenOptions};
+use crate::{generate_rs_single, ApiCache, CodegenOptions, ImplBlockPlacement};
 use crate::{get_cxx_header_bytes, CppCodegenOptions, ParseError, RebuildDependencyRecorder};
+use std::cell::RefCell;
 use std::ffi::OsStr;
 use std::ffi::OsString;
 use std::fs::File;
@@ -81,6 +82,7 @@ pub struct Builder<'a, BuilderContext> {
     dependency_recorder: Option<Box<dyn RebuildDependencyRecorder>>,
     custom_gendir: Option<PathBuf>,
     auto_allowlist: bool,
+    api_cache_dir: Option<PathBuf>,
     codegen_options: CodegenOptions<'a>,
     // This member is to ensure that this type is parameterized
     // by a BuilderContext. The goal is to balance three needs:
@@ -116,6 +118,7 @@ impl<CTX: BuilderContext> Builder<'_, CTX> {
             dependency_recorder: CTX::get_dependency_recorder(),
             custom_gendir: None,
             auto_allowlist: false,
+            api_cache_dir: None,
             codegen_options: CodegenOptions::default(),
             ctx: PhantomData,
         }
@@ -163,6 +166,38 @@ impl<CTX: BuilderContext> Builder<'_, CTX> {
         self
     }
 
+    /// A directory in which to store the output of each build, such that
+    /// a later build can reuse it if none of the inputs have changed.
+    /// See [`ApiCache`] for details.
+    pub fn api_cache_dir(mut self, api_cache_dir: PathBuf) -> Self {
+        self.api_cache_dir = Some(api_cache_dir);
+        self
+    }
+
+    /// Where to put the `impl` blocks generated for each namespace.
+    /// See [`ImplBlockPlacement`] for details.
+    pub fn impl_block_placement(mut self, placement: ImplBlockPlacement) -> Self {
+        self.codegen_options.impl_block_placement = placement;
+        self
+    }
+
+    /// Whether to mark `#[inline]` the thin Rust wrappers which forward
+    /// calls to C++, and the accessors for inherited fields, so that
+    /// they can be inlined into other crates.
+    pub fn inline_wrappers(mut self, do_it: bool) -> Self {
+        self.codegen_options.inline_wrappers = do_it;
+        self
+    }
+
+    /// Whether to mark `#[must_use]` the functions generated for C++
+    /// functions declared `[[nodiscard]]`, or returning a type so declared.
+    /// This is off by default since finding such attributes can make
+    /// bindgen much slower.
+    pub fn nodiscard_as_must_use(mut self, do_it: bool) -> Self {
+        self.codegen_options.nodiscard_as_must_use = do_it;
+        self
+    }
+
     #[doc(hidden)]
     /// Whether to force autocxx always to generate extra Rust and C++
     /// side shims. This is only used by the integration test suite to
@@ -225,6 +260,13 @@ impl<CTX: BuilderContext> Builder<'_, CTX> {
             .map(|s| &s[..])
             .collect::<Vec<_>>();
         rust_version_check();
+        let api_cache = self
+            .api_cache_dir
+            .map(|dir| RefCell::new(ApiCache::new_on_disk(dir)));
+        let codegen_options = CodegenOptions {
+            api_cache: api_cache.as_ref(),
+            ..self.codegen_options
+        };
         let gen_location_strategy = match self.custom_gendir {
             None => FileLocationStrategy::new(),
             Some(custom_dir) => FileLocationStrategy::Custom(custom_dir),
@@ -243,11 +285,7 @@ impl<CTX: BuilderContext> Builder<'_, CTX> {
         write_to_file(
             &incdir,
             "cxx.h",
-            &get_cxx_header_bytes(
-                self.codegen_options
-                    .cpp_codegen_options
-                    .suppress_system_headers,
-            ),
+            &get_cxx_header_bytes(codegen_options.cpp_codegen_options.suppress_system_headers),
         )?;
 
         let autocxx_inc = build_autocxx_inc(self.autocxx_incs, &incdir);
@@ -260,7 +298,7 @@ impl<CTX: BuilderContext> Builder<'_, CTX> {
                 autocxx_inc,
                 clang_args,
                 self.dependency_recorder,
-                &self.codegen_options,
+                &codegen_options,
             )
             .map_err(BuilderError::ParseError)?;
         let mut counter = 0;
@@ -274,7 +312,7 @@ impl<CTX: BuilderContext> Builder<'_, CTX> {
         builder.includes(parsed_file.include_dirs());
         for include_cpp in parsed_file.get_cpp_buildables() {
             let generated_code = include_cpp
-                .generate_h_and_cxx(&self.codegen_options.cpp_codegen_options)
+                .generate_h_and_cxx(&codegen_options.cpp_codegen_options)
                 .map_err(BuilderError::InvalidCxx)?;
             for filepair in generated_code.0 {
                 let fname = format!("gen{counter}.cxx");
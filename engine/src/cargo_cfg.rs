@@ -0,0 +1,99 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Evaluation of `cfg` predicates when generating C++. rustc decides
+//! which of our generated Rust items exist, but the C++ has to match,
+//! and it's generated from a build script, to which Cargo describes the
+//! target in `CARGO_CFG_*` and `CARGO_FEATURE_*` environment variables.
+//! Anywhere else, no `cfg` is set.
+
+use cxx_gen::{CfgEvaluator, CfgResult};
+use syn::{punctuated::Punctuated, token::Comma, Expr, ExprLit, Lit, Meta};
+
+/// Lets cxx decide which items in a `cxx::bridge` to generate C++ for.
+pub(crate) struct CargoEnvCfgEvaluator;
+
+impl CfgEvaluator for CargoEnvCfgEvaluator {
+    fn eval(&self, name: &str, value: Option<&str>) -> CfgResult {
+        if is_set(name, value) {
+            CfgResult::True
+        } else {
+            CfgResult::False
+        }
+    }
+}
+
+/// Whether a predicate such as `all(unix, not(target_os = "macos"))`
+/// holds for the target we're building for.
+pub(crate) fn is_enabled(predicate: &Meta) -> bool {
+    match predicate {
+        Meta::Path(path) => path
+            .get_ident()
+            .is_some_and(|name| is_set(&name.to_string(), None)),
+        Meta::NameValue(name_value) => match (name_value.path.get_ident(), &name_value.value) {
+            (
+                Some(name),
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(value),
+                    ..
+                }),
+            ) => is_set(&name.to_string(), Some(&value.value())),
+            _ => false,
+        },
+        Meta::List(list) => {
+            let predicates = list
+                .parse_args_with(Punctuated::<Meta, Comma>::parse_terminated)
+                .unwrap_or_default();
+            if list.path.is_ident("all") {
+                predicates.iter().all(is_enabled)
+            } else if list.path.is_ident("any") {
+                predicates.iter().any(is_enabled)
+            } else if list.path.is_ident("not") {
+                predicates.len() == 1 && !is_enabled(&predicates[0])
+            } else {
+                false
+            }
+        }
+    }
+}
+
+fn is_set(name: &str, value: Option<&str>) -> bool {
+    if name == "feature" {
+        return value.is_some_and(|feature| {
+            let var = format!("CARGO_FEATURE_{}", feature.to_uppercase().replace('-', "_"));
+            std::env::var_os(var).is_some()
+        });
+    }
+    match std::env::var(format!("CARGO_CFG_{}", name.to_uppercase())) {
+        Ok(values) => value.map_or(true, |value| values.split(',').any(|v| v == value)),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_enabled;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_is_enabled() {
+        std::env::set_var("CARGO_CFG_AUTOCXX_TEST_FLAVOR", "sweet,sour");
+        assert!(is_enabled(&parse_quote! { autocxx_test_flavor }));
+        assert!(is_enabled(&parse_quote! { autocxx_test_flavor = "sour" }));
+        assert!(!is_enabled(
+            &parse_quote! { autocxx_test_flavor = "bitter" }
+        ));
+        assert!(!is_enabled(&parse_quote! { autocxx_test_unset }));
+        assert!(is_enabled(&parse_quote! {
+            all(autocxx_test_flavor, not(autocxx_test_unset))
+        }));
+        assert!(!is_enabled(&parse_quote! {
+            any(autocxx_test_unset, autocxx_test_flavor = "salty")
+        }));
+    }
+}
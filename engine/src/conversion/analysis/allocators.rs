@@ -91,6 +91,8 @@ fn create_alloc_and_free(ty_name: QualifiedName) -> impl Iterator<Item = Api<Pod
                     synthetic_cpp: Some((cpp_function_body, CppFunctionKind::Function)),
                     add_to_trait: Some(synthesis),
                     is_deleted: DeletedOrDefaulted::Neither,
+                    is_explicit: false,
+                    must_use: false,
                     provenance: Provenance::SynthesizedOther,
                     variadic: false,
                 }),
@@ -0,0 +1,89 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use indexmap::map::IndexMap as HashMap;
+use indexmap::set::IndexSet as HashSet;
+
+use crate::{
+    conversion::{api::Api, apivec::ApiVec, convert_error::SkippedItems, ConvertErrorFromCpp},
+    types::QualifiedName,
+};
+
+use super::{
+    fun::{FnAnalysis, FnKind, FnPhase, PodAndDepAnalysis},
+    pod::PodAnalysis,
+};
+
+/// Checks that none of the accessors requested by `base_field_accessors!`
+/// has the same name as a method of the struct, or as another such
+/// accessor, since either would stop the generated code compiling. We
+/// can only tell once all the methods have been named, and have
+/// survived garbage collection.
+pub(crate) fn check_base_field_accessors(
+    apis: ApiVec<FnPhase>,
+    skipped: &mut SkippedItems,
+) -> Result<ApiVec<FnPhase>, ConvertErrorFromCpp> {
+    let mut method_names: HashMap<QualifiedName, HashSet<String>> = HashMap::new();
+    for api in apis.iter() {
+        if let Api::Function {
+            analysis:
+                FnAnalysis {
+                    kind: FnKind::Method { impl_for, .. },
+                    rust_name,
+                    ..
+                },
+            ..
+        } = api
+        {
+            method_names
+                .entry(impl_for.clone())
+                .or_default()
+                .insert(rust_name.clone());
+        }
+    }
+    let mut results = ApiVec::new();
+    for mut api in apis.into_iter() {
+        if let Api::Struct {
+            name,
+            analysis:
+                PodAndDepAnalysis {
+                    pod:
+                        PodAnalysis {
+                            base_field_accessors,
+                            ..
+                        },
+                    ..
+                },
+            ..
+        } = &mut api
+        {
+            let mut taken = method_names.swap_remove(&name.name).unwrap_or_default();
+            let mut kept = Vec::new();
+            for accessor in std::mem::take(base_field_accessors) {
+                let field = accessor.field.to_string();
+                let accessor_names = [format!("{field}_mut"), field];
+                match accessor_names.iter().find(|n| taken.contains(*n)) {
+                    Some(clash) => {
+                        let cpp_name = name.name.to_cpp_name();
+                        skipped.skip(
+                            format!("{cpp_name}::{clash}"),
+                            ConvertErrorFromCpp::BaseFieldAccessorClash(cpp_name, clash.clone()),
+                        )?;
+                    }
+                    None => {
+                        taken.extend(accessor_names);
+                        kept.push(accessor);
+                    }
+                }
+            }
+            *base_field_accessors = kept;
+        }
+        results.push(api);
+    }
+    Ok(results)
+}
@@ -7,6 +7,8 @@
 // except according to those terms.
 
 use crate::minisyn::FnArg;
+use indexmap::map::IndexMap as HashMap;
+use indexmap::set::IndexSet as HashSet;
 use itertools::Itertools;
 use quote::quote;
 use syn::parse_quote;
@@ -14,8 +16,8 @@ use syn::parse_quote;
 use crate::{
     conversion::{
         api::{
-            Api, ApiName, CastMutability, DeletedOrDefaulted, Provenance, References,
-            TraitSynthesis,
+            Api, ApiName, CastMutability, CppVisibility, DeletedOrDefaulted, Provenance,
+            References, SpecialMemberKind, TraitSynthesis, Virtualness,
         },
         apivec::ApiVec,
     },
@@ -35,6 +37,7 @@ use super::{
 };
 
 pub(crate) fn add_casts(apis: ApiVec<PodPhase>) -> ApiVec<PodPhase> {
+    let virtual_destructor_types = find_public_virtual_destructors(&apis);
     apis.into_iter()
         .flat_map(|api| {
             let mut resultant_apis = match api {
@@ -42,7 +45,13 @@ pub(crate) fn add_casts(apis: ApiVec<PodPhase>) -> ApiVec<PodPhase> {
                     ref name,
                     details: _,
                     ref analysis,
-                } => create_casts(&name.name, analysis).collect_vec(),
+                } => create_casts(&name.name, analysis)
+                    .chain(create_unique_ptr_upcasts(
+                        &name.name,
+                        analysis,
+                        &virtual_destructor_types,
+                    ))
+                    .collect_vec(),
                 _ => Vec::new(),
             };
             resultant_apis.push(api);
@@ -63,6 +72,119 @@ fn create_casts<'a>(
         .flat_map(move |base| cast_types().map(|mutable| create_cast(name, base, mutable)))
 }
 
+/// Find the types whose destructors we know to be public and virtual,
+/// either because they're declared that way or because they're
+/// inherited from a base whose destructor is virtual. A destructor which
+/// overrides a virtual one is virtual even if not declared so.
+fn find_public_virtual_destructors(apis: &ApiVec<PodPhase>) -> HashSet<QualifiedName> {
+    let mut declared_destructors = HashMap::new();
+    for api in apis.iter() {
+        if let Api::Function { fun, .. } = api {
+            if let (Some(SpecialMemberKind::Destructor), Some(self_ty)) =
+                (&fun.special_member, &fun.self_ty)
+            {
+                declared_destructors.insert(
+                    self_ty.clone(),
+                    (
+                        !matches!(fun.virtualness, Virtualness::None),
+                        matches!(fun.cpp_vis, CppVisibility::Public)
+                            && !matches!(fun.is_deleted, DeletedOrDefaulted::Deleted),
+                    ),
+                );
+            }
+        }
+    }
+    let bases: HashMap<&QualifiedName, &HashSet<QualifiedName>> = apis
+        .iter()
+        .filter_map(|api| match api {
+            Api::Struct { name, analysis, .. } => Some((&name.name, &analysis.bases)),
+            _ => None,
+        })
+        .collect();
+    let mut results = HashSet::new();
+    loop {
+        let found: Vec<QualifiedName> = bases
+            .iter()
+            .filter(|(name, _)| !results.contains(**name))
+            .filter(|(name, bases)| {
+                let inherits_virtual = bases.iter().any(|base| results.contains(base));
+                match declared_destructors.get(**name) {
+                    Some((is_virtual, is_public)) => {
+                        *is_public && (*is_virtual || inherits_virtual)
+                    }
+                    None => inherits_virtual,
+                }
+            })
+            .map(|(name, _)| (*name).clone())
+            .collect();
+        if found.is_empty() {
+            break;
+        }
+        results.extend(found);
+    }
+    results
+}
+
+/// Create functions converting a `UniquePtr` to this type into a `UniquePtr`
+/// to each of its bases. The result will be dropped through a pointer to
+/// the base, so we only do this for bases with virtual destructors.
+fn create_unique_ptr_upcasts<'a>(
+    name: &'a QualifiedName,
+    analysis: &'a PodAnalysis,
+    virtual_destructor_types: &'a HashSet<QualifiedName>,
+) -> impl Iterator<Item = Api<PodPhase>> + 'a {
+    analysis
+        .castable_bases
+        .iter()
+        .filter(|base| virtual_destructor_types.contains(*base))
+        .map(move |base| create_unique_ptr_upcast(name, base))
+}
+
+fn create_unique_ptr_upcast(from: &QualifiedName, to: &QualifiedName) -> Api<PodPhase> {
+    let name = make_ident(format!(
+        "upcast_{}_to_{}",
+        from.get_final_item(),
+        to.get_final_item()
+    ));
+    let from_typ = from.to_type_path();
+    let to_typ = to.to_type_path();
+    let fnarg: FnArg = parse_quote! {
+        ptr: root::std::unique_ptr<#from_typ>
+    };
+    Api::Function {
+        name: ApiName::new_with_cpp_name(
+            from.get_namespace(),
+            name.clone(),
+            Some(format!("upcast_to_{}", to.get_final_item())),
+        ),
+        fun: Box::new(crate::conversion::api::FuncToConvert {
+            ident: name,
+            doc_attrs: Vec::new(),
+            inputs: [fnarg].into_iter().collect(),
+            output: parse_quote! {
+                -> root::std::unique_ptr<#to_typ>
+            },
+            vis: parse_quote! { pub },
+            virtualness: crate::conversion::api::Virtualness::None,
+            cpp_vis: crate::conversion::api::CppVisibility::Public,
+            special_member: None,
+            unused_template_param: false,
+            references: References::default(),
+            original_name: None,
+            self_ty: Some(from.clone()),
+            synthesized_this_type: None,
+            add_to_trait: None,
+            synthetic_cpp: Some((CppFunctionBody::Cast, CppFunctionKind::Function)),
+            is_deleted: DeletedOrDefaulted::Neither,
+            is_explicit: false,
+            must_use: false,
+            provenance: Provenance::SynthesizedOther,
+            variadic: false,
+        }),
+        analysis: (),
+    }
+}
+
 /// Iterate through the types of cast we should make.
 fn cast_types() -> impl Iterator<Item = CastMutability> {
     if SUPPORT_MUTABLE_CASTS {
@@ -117,6 +239,8 @@ fn create_cast(from: &QualifiedName, to: &QualifiedName, mutable: CastMutability
             }),
             synthetic_cpp: Some((CppFunctionBody::Cast, CppFunctionKind::Function)),
             is_deleted: DeletedOrDefaulted::Neither,
+            is_explicit: false,
+            must_use: false,
             provenance: Provenance::SynthesizedOther,
             variadic: false,
         }),
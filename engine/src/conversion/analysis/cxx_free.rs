@@ -0,0 +1,162 @@
+// Copyright 2020 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use indexmap::set::IndexSet as HashSet;
+use itertools::Itertools;
+
+use crate::{
+    conversion::{
+        api::{Api, Provenance, TypeKind},
+        apivec::ApiVec,
+        convert_error::SkippedItems,
+        ConvertErrorFromCpp,
+    },
+    known_types::known_types,
+    types::QualifiedName,
+};
+
+use super::{
+    deps::HasDependencies,
+    fun::{FnAnalysis, FnKind, FnPhase, MethodKind, PodAndDepAnalysis},
+    pod::PodAnalysis,
+};
+
+/// The types which can be passed to and from `extern "C"` functions,
+/// apart from built-in types.
+fn find_c_compatible_types(apis: &ApiVec<FnPhase>) -> HashSet<QualifiedName> {
+    apis.iter()
+        .filter(|api| {
+            matches!(
+                api,
+                Api::Struct {
+                    analysis: PodAndDepAnalysis {
+                        pod: PodAnalysis {
+                            kind: TypeKind::Pod,
+                            is_generic: false,
+                            ..
+                        },
+                        ..
+                    },
+                    ..
+                } | Api::Enum { .. }
+                    | Api::Typedef { .. }
+                    | Api::CType { .. }
+            )
+        })
+        .map(|api| api.name().clone())
+        .collect()
+}
+
+fn is_c_compatible(api: &Api<FnPhase>, c_compatible_types: &HashSet<QualifiedName>) -> bool {
+    api.deps().all(|dep| {
+        c_compatible_types.contains(dep)
+            || known_types().is_trivially_copyable(dep)
+            || known_types().is_ctype(dep)
+    })
+}
+
+/// Whether this is a free function which we can call through an
+/// `extern "C"` shim, passing its parameters and return value as they are.
+fn is_c_abi_function(api: &Api<FnPhase>, c_compatible_types: &HashSet<QualifiedName>) -> bool {
+    match api {
+        Api::Function { fun, analysis, .. } => {
+            matches!(analysis.kind, FnKind::Function)
+                && !fun.variadic
+                && analysis.ignore_reason.is_ok()
+                && analysis
+                    .param_details
+                    .iter()
+                    .all(|pd| !pd.conversion.cpp_work_needed() && !pd.conversion.rust_work_needed())
+                && analysis
+                    .ret_conversion
+                    .iter()
+                    .all(|rc| !rc.cpp_work_needed() && !rc.rust_work_needed())
+                && is_c_compatible(api, c_compatible_types)
+        }
+        _ => false,
+    }
+}
+
+/// For `c_abi_shims!`, find the functions from the original C++ which
+/// can be called through an `extern "C"` shim as well as via cxx.
+pub(crate) fn find_c_abi_functions(apis: &ApiVec<FnPhase>) -> HashSet<QualifiedName> {
+    let c_compatible_types = find_c_compatible_types(apis);
+    apis.iter()
+        .filter(|api| match api {
+            Api::Function { fun, analysis, .. } => {
+                matches!(fun.provenance, Provenance::Bindgen)
+                    && fun.synthetic_cpp.is_none()
+                    && analysis.externally_callable
+                    && is_c_abi_function(api, &c_compatible_types)
+            }
+            _ => false,
+        })
+        .map(|api| api.name().clone())
+        .collect()
+}
+
+/// The name from which we derive the name of a function's `c_abi_shims!`
+/// shim: its Rust name, after any namespaces, all joined by underscores.
+pub(crate) fn c_abi_shim_base_name(name: &QualifiedName, analysis: &FnAnalysis) -> String {
+    name.get_namespace()
+        .iter()
+        .chain(std::iter::once(&analysis.rust_name))
+        .join("_")
+}
+
+/// In `cxx_free!` mode, confirm that every API can be represented using
+/// only `#[repr(C)]` types and `extern "C"` functions. Constructors and
+/// other functions which autocxx synthesizes for each type are discarded,
+/// since POD types can be created and destroyed directly in Rust.
+/// In resilient mode, incompatible APIs are dropped and recorded in
+/// `skipped` instead.
+pub(crate) fn check_cxx_free_apis(
+    apis: ApiVec<FnPhase>,
+    skipped: &mut SkippedItems,
+) -> Result<ApiVec<FnPhase>, ConvertErrorFromCpp> {
+    let c_compatible_types = find_c_compatible_types(&apis);
+    let mut results = ApiVec::new();
+    for api in apis.into_iter() {
+        let keep = match &api {
+            // Utilities such as make_string rely on cxx.
+            Api::StringConstructor { .. } => false,
+            Api::Function {
+                analysis:
+                    FnAnalysis {
+                        kind:
+                            FnKind::TraitMethod { .. }
+                            | FnKind::Method {
+                                method_kind: MethodKind::Constructor { .. },
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => false,
+            Api::Function { .. } if is_c_abi_function(&api, &c_compatible_types) => true,
+            // POD analysis has already checked the fields of structs.
+            Api::Struct { .. } | Api::Enum { .. } if c_compatible_types.contains(api.name()) => {
+                true
+            }
+            Api::Typedef { .. } if is_c_compatible(&api, &c_compatible_types) => true,
+            Api::Const { .. } | Api::CType { .. } | Api::IgnoredItem { .. } => true,
+            _ => {
+                let name = api.name().to_cpp_name();
+                skipped.skip(
+                    name.clone(),
+                    ConvertErrorFromCpp::NotCxxFreeCompatible(name),
+                )?;
+                false
+            }
+        };
+        if keep {
+            results.push(api);
+        }
+    }
+    Ok(results)
+}
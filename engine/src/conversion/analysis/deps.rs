@@ -42,6 +42,23 @@ impl HasDependencies for Api<FnPrePhase1> {
             } => Box::new(std::iter::once(superclass)),
             Api::RustSubclassFn { details, .. } => Box::new(details.dependencies.iter()),
             Api::RustFn { deps, .. } => Box::new(deps.iter()),
+            Api::StdVariant {
+                cpp_type,
+                alternatives,
+                ..
+            } => Box::new(std::iter::once(cpp_type).chain(alternatives.iter())),
+            Api::StdMapIterator {
+                map_type,
+                key,
+                value,
+                ..
+            } => Box::new([map_type, &key.ty, &value.ty].into_iter()),
+            Api::StdTuple {
+                tuple_type,
+                elements,
+                ..
+            } => Box::new(std::iter::once(tuple_type).chain(elements.iter())),
+            Api::RangeIterator { item, .. } => Box::new(std::iter::once(item)),
             _ => Box::new(std::iter::empty()),
         }
     }
@@ -91,6 +108,23 @@ impl HasDependencies for Api<FnPhase> {
             } => Box::new(std::iter::once(superclass)),
             Api::RustSubclassFn { details, .. } => Box::new(details.dependencies.iter()),
             Api::RustFn { deps, .. } => Box::new(deps.iter()),
+            Api::StdVariant {
+                cpp_type,
+                alternatives,
+                ..
+            } => Box::new(std::iter::once(cpp_type).chain(alternatives.iter())),
+            Api::StdMapIterator {
+                map_type,
+                key,
+                value,
+                ..
+            } => Box::new([map_type, &key.ty, &value.ty].into_iter()),
+            Api::StdTuple {
+                tuple_type,
+                elements,
+                ..
+            } => Box::new(std::iter::once(tuple_type).chain(elements.iter())),
+            Api::RangeIterator { item, .. } => Box::new(std::iter::once(item)),
             _ => Box::new(std::iter::empty()),
         }
     }
@@ -46,8 +46,12 @@ impl<'a, T: HasFieldsAndBases + Debug> Iterator for DepthFirstIter<'a, T> {
     fn next(&mut self) -> Option<Self::Item> {
         let first_candidate = self.queue.front().map(|api| api.name());
         while let Some(candidate) = self.queue.pop_front() {
+            // An item can't usefully depend on itself, though it may
+            // appear to if template arguments have been erased, e.g. for
+            // a CRTP base.
             if !candidate
                 .field_and_base_deps()
+                .filter(|d| *d != candidate.name())
                 .any(|d| self.yet_to_do.contains(&d))
             {
                 self.yet_to_do.remove(candidate.name());
@@ -112,4 +116,16 @@ mod test {
         assert_eq!(it.next().unwrap().0, QualifiedName::new_from_cpp_name("b"));
         assert!(it.next().is_none());
     }
+
+    #[test]
+    fn test_self_dependency() {
+        let a = Thing(
+            QualifiedName::new_from_cpp_name("a"),
+            vec![QualifiedName::new_from_cpp_name("a")],
+        );
+        let api_list = [a];
+        let mut it = fields_and_bases_first(api_list.iter());
+        assert_eq!(it.next().unwrap().0, QualifiedName::new_from_cpp_name("a"));
+        assert!(it.next().is_none());
+    }
 }
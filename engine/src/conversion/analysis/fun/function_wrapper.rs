@@ -8,9 +8,13 @@
 
 use crate::minisyn::Ident;
 use crate::{
-    conversion::{api::SubclassName, type_helpers::extract_pinned_mutable_reference_type},
+    conversion::{
+        api::{ArithmeticOperator, SubclassName},
+        type_helpers::extract_pinned_mutable_reference_type,
+    },
     types::{Namespace, QualifiedName},
 };
+use autocxx_parser::IntegerConversions;
 use quote::ToTokens;
 use syn::{parse_quote, Type, TypeReference};
 
@@ -27,6 +31,20 @@ pub(crate) enum CppConversionType {
     FromReturnValueToPlacementPtr,
     FromPointerToReference, // unwrapped_type is always Type::Ptr
     FromReferenceToPointer, // unwrapped_type is always Type::Ptr
+    /// A slice passed to a C++ function as a pointer and a length. The
+    /// string, if any, is a C++ pointer type to which the slice's data
+    /// must be cast.
+    FromSliceToPointerAndLength(Option<String>),
+    /// A slice passed to a C++ function as a `std::span`.
+    FromSliceToSpan,
+    /// A reference passed to a C++ function as a `std::reference_wrapper`.
+    FromReferenceToReferenceWrapper,
+    /// A `std::reference_wrapper` returned from a C++ function as a
+    /// reference.
+    FromReferenceWrapperToReference,
+    /// A conversion registered using `custom_type_conversion!`; the
+    /// string is a C++ expression in which `{}` is the value to convert.
+    Custom(String),
 }
 
 impl CppConversionType {
@@ -42,6 +60,12 @@ impl CppConversionType {
             CppConversionType::FromValueToUniquePtr => CppConversionType::FromUniquePtrToValue,
             CppConversionType::FromPointerToReference => CppConversionType::FromReferenceToPointer,
             CppConversionType::FromReferenceToPointer => CppConversionType::FromPointerToReference,
+            CppConversionType::FromReferenceToReferenceWrapper => {
+                CppConversionType::FromReferenceWrapperToReference
+            }
+            CppConversionType::FromReferenceWrapperToReference => {
+                CppConversionType::FromReferenceToReferenceWrapper
+            }
             _ => panic!("Did not expect to have to invert this conversion"),
         }
     }
@@ -57,9 +81,22 @@ pub(crate) enum RustConversionType {
     FromTypeToPtr,
     FromValueParamToPtr,
     FromPlacementParamToNewReturn,
+    FromOutParamToReturn,
     FromRValueParamToPtr,
     FromReferenceWrapperToPointer, // unwrapped_type is always Type::Ptr
     FromPointerToReferenceWrapper, // unwrapped_type is always Type::Ptr
+    /// A returned `const char*`, presented as an `Option<&CStr>`. If it
+    /// borrows from the receiver, the lifetime is that of the receiver;
+    /// otherwise it's `'static`.
+    FromCharPtrToCStr {
+        borrows_receiver: bool,
+    },
+    /// A returned `const char*`, copied into a `String`.
+    FromCharPtrToString,
+    /// An integer parameter, which accepts any integer type and checks
+    /// that the value fits, as asked for using
+    /// `checked_integer_conversions!`.
+    FromCheckedInteger(IntegerConversions),
 }
 
 impl RustConversionType {
@@ -211,6 +248,7 @@ impl TypeConversionPolicy {
             RustConversionType::FromValueParamToPtr
                 | RustConversionType::FromRValueParamToPtr
                 | RustConversionType::FromPlacementParamToNewReturn
+                | RustConversionType::FromOutParamToReturn
                 | RustConversionType::FromPointerToReferenceWrapper { .. }
                 | RustConversionType::FromReferenceWrapperToPointer { .. }
         )
@@ -241,6 +279,50 @@ pub(crate) enum CppFunctionBody {
     Destructor(Namespace, Ident),
     AllocUninitialized(QualifiedName),
     FreeUninitialized(QualifiedName),
+    /// Exchange the two arguments using `swap`, found either by
+    /// argument-dependent lookup or from `std`.
+    Swap,
+    /// A call to `shared_from_this` on the receiver, which is of the given
+    /// type, casting away the constness of the resulting pointer.
+    SharedFromThis(QualifiedName),
+    /// A call to a function template specialization listed using
+    /// `instantiate!`, identified by its full C++ template-id.
+    FunctionTemplateCall(String),
+    /// A call on the receiver to a member function template
+    /// specialization listed using `instantiate_method!`, identified by
+    /// its unqualified template-id.
+    MethodTemplateCall(String),
+    /// An invocation of the receiver's function call operator,
+    /// `operator()`, which we can't otherwise name.
+    CallOperator,
+    /// The object to which the receiver's `operator->` points.
+    ArrowOperator,
+    /// A comparison of the receiver with the argument using
+    /// `operator==`, which may be a member or a free function.
+    EqualityOperator,
+    /// An application of the given binary arithmetic operator to the
+    /// receiver and the argument, which again may be a member or a free
+    /// function.
+    ArithmeticOperator(ArithmeticOperator),
+    /// An invocation of a free `operator<<` with the arguments, found
+    /// by argument-dependent lookup just as in C++.
+    LeftShiftOperator,
+    /// An invocation of the receiver's conversion operator to the
+    /// given type.
+    ConversionOperator(QualifiedName),
+    /// Construction of the given type from the arguments, using a
+    /// (non-`explicit`) converting constructor.
+    ConvertingConstructor(QualifiedName),
+    /// Construction of the given type from the arguments within a
+    /// `std::unique_ptr`, for a constructor listed using
+    /// `throwing_constructors!`. cxx catches any exception and returns
+    /// it to Rust as an `Err`.
+    FallibleConstructor(QualifiedName),
+    /// The value of the named static data member of the given class.
+    StaticMemberGet(QualifiedName, String),
+    /// Assignment of the argument to the named static data member of
+    /// the given class.
+    StaticMemberSet(QualifiedName, String),
 }
 
 #[derive(Clone, Debug)]
@@ -25,7 +25,8 @@ use crate::{
         },
         apivec::ApiVec,
         convert_error::ErrorContext,
-        convert_error::{ConvertErrorWithContext, ErrorContextType},
+        convert_error::{ConvertErrorWithContext, ErrorContextType, SkippedItems},
+        doc_attr::has_must_use_attr,
         error_reporter::{convert_apis, report_any_error},
     },
     known_types::known_types,
@@ -34,15 +35,19 @@ use crate::{
 };
 use indexmap::map::IndexMap as HashMap;
 use indexmap::set::IndexSet as HashSet;
+use std::borrow::Cow;
 
-use autocxx_parser::{ExternCppType, IncludeCppConfig, UnsafePolicy};
+use autocxx_parser::{
+    CStringReturn, CustomTypeConversion, ExternCppType, IncludeCppConfig, IntegerConversions,
+    OverloadNaming, UnsafePolicy,
+};
 use function_wrapper::{CppFunction, CppFunctionBody, TypeConversionPolicy};
 use itertools::Itertools;
 use proc_macro2::Span;
-use quote::quote;
+use quote::{quote, ToTokens};
 use syn::{
-    parse_quote, punctuated::Punctuated, token::Comma, FnArg, Ident, Pat, PatType, ReturnType,
-    Type, TypePath, TypePtr, TypeReference, Visibility,
+    parse_quote, punctuated::Punctuated, token::Comma, FnArg, GenericArgument, Ident, Pat, PatType,
+    PathArguments, ReturnType, Type, TypePath, TypePtr, TypeReference, Visibility,
 };
 
 use crate::{
@@ -95,6 +100,16 @@ pub(crate) enum TraitMethodKind {
     Destructor,
     Alloc,
     Dealloc,
+    Conversion,
+    ConvertingConstructor,
+    Deref,
+    PartialEq,
+    /// A binary arithmetic operator, implemented for a reference to its
+    /// left-hand side. If that's POD, this holds the type of its
+    /// right-hand side, and we implement it for the POD type itself too.
+    Arithmetic {
+        value_impl_rhs: Option<crate::minisyn::Type>,
+    },
 }
 
 #[derive(Clone, Debug)]
@@ -109,6 +124,9 @@ pub(crate) struct TraitMethodDetails {
     /// The function we're calling from the trait requires unsafe even
     /// though the trait and its function aren't.
     pub(crate) trait_call_is_unsafe: bool,
+    /// An associated type which the trait requires, such as `Target`
+    /// for `Deref`, and its value.
+    pub(crate) associated_type: Option<(crate::minisyn::Ident, crate::minisyn::Type)>,
 }
 
 #[derive(Clone, Debug)]
@@ -172,6 +190,16 @@ pub(crate) struct FnAnalysis {
     pub(crate) externally_callable: bool,
     /// Whether we need to generate a Rust-side calling function
     pub(crate) rust_wrapper_needed: bool,
+    /// Attributes listed using `extra_attributes!`, for the Rust-side
+    /// calling function.
+    pub(crate) extra_attrs: Vec<crate::minisyn::Attribute>,
+    /// A `#[cfg]` from `cfg_gate!`, for the cxx::bridge declaration, any
+    /// Rust-side calling function and whatever else refers to them. The
+    /// C++ wrapper is generated only if the predicate holds.
+    pub(crate) cfg_gate: Option<crate::minisyn::Attribute>,
+    /// Whether the function, or the type it returns by value, was
+    /// declared `[[nodiscard]]`.
+    pub(crate) must_use: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -184,6 +212,9 @@ pub(crate) struct ArgumentAnalysis {
     pub(crate) deps: HashSet<QualifiedName>,
     pub(crate) requires_unsafe: UnsafetyNeeded,
     pub(crate) is_placement_return_destination: bool,
+    /// The returned reference borrows from this parameter, as specified
+    /// using `return_borrows!`.
+    pub(crate) is_borrowed_by_return: bool,
 }
 
 pub(crate) struct ReturnTypeAnalysis {
@@ -285,7 +316,9 @@ pub(crate) struct FnAnalyzer<'a> {
     type_converter: TypeConverter<'a>,
     bridge_name_tracker: BridgeNameTracker,
     pod_safe_types: HashSet<QualifiedName>,
+    must_use_types: HashSet<QualifiedName>,
     moveit_safe_types: HashSet<QualifiedName>,
+    mutable_pointer_overloads: HashSet<QualifiedName>,
     config: &'a IncludeCppConfig,
     overload_trackers_by_mod: HashMap<Namespace, OverloadTracker>,
     subclasses_by_superclass: HashMap<QualifiedName, Vec<SubclassName>>,
@@ -294,6 +327,10 @@ pub(crate) struct FnAnalyzer<'a> {
     types_in_anonymous_namespace: HashSet<QualifiedName>,
     existing_superclass_trait_api_names: HashSet<QualifiedName>,
     force_wrapper_generation: bool,
+    std_byte_conversion: CustomTypeConversion,
+    /// The function and parameter names from `out_param!` directives
+    /// which we've found.
+    used_out_params: HashSet<(String, String)>,
 }
 
 impl<'a> FnAnalyzer<'a> {
@@ -302,7 +339,8 @@ impl<'a> FnAnalyzer<'a> {
         unsafe_policy: &'a UnsafePolicy,
         config: &'a IncludeCppConfig,
         force_wrapper_generation: bool,
-    ) -> ApiVec<FnPrePhase2> {
+        skipped: &mut SkippedItems,
+    ) -> Result<ApiVec<FnPrePhase2>, ConvertErrorFromCpp> {
         let mut me = Self {
             unsafe_policy,
             extra_apis: ApiVec::new(),
@@ -311,13 +349,22 @@ impl<'a> FnAnalyzer<'a> {
             config,
             overload_trackers_by_mod: HashMap::new(),
             pod_safe_types: Self::build_pod_safe_type_set(&apis),
+            must_use_types: Self::build_must_use_type_set(&apis),
             moveit_safe_types: Self::build_correctly_sized_type_set(&apis),
+            mutable_pointer_overloads: Self::build_mutable_pointer_overload_set(&apis),
             subclasses_by_superclass: subclass::subclasses_by_superclass(&apis),
             nested_type_name_map: Self::build_nested_type_map(&apis),
             generic_types: Self::build_generic_type_set(&apis),
             existing_superclass_trait_api_names: HashSet::new(),
             types_in_anonymous_namespace: Self::build_types_in_anonymous_namespace(&apis),
             force_wrapper_generation,
+            std_byte_conversion: CustomTypeConversion {
+                cpp_type: "std::byte".into(),
+                rust_type: parse_quote! { u8 },
+                to_cpp: "static_cast<std::byte>({})".into(),
+                from_cpp: "static_cast<uint8_t>({})".into(),
+            },
+            used_out_params: HashSet::new(),
         };
         let mut results = ApiVec::new();
         convert_apis(
@@ -331,7 +378,85 @@ impl<'a> FnAnalyzer<'a> {
         let mut results = me.add_constructors_present(results);
         me.add_subclass_constructors(&mut results);
         results.extend(me.extra_apis.into_iter().map(add_analysis));
-        results
+        for (function, param) in config.out_params() {
+            if !me
+                .used_out_params
+                .contains(&(function.to_string(), param.to_string()))
+            {
+                skipped.skip(
+                    format!("{function}::{param}"),
+                    ConvertErrorFromCpp::OutParamNotFound(function.into(), param.into()),
+                )?;
+            }
+        }
+        Ok(results)
+    }
+
+    /// Types declared `[[nodiscard]]`, which make any function returning
+    /// them by value `[[nodiscard]]` too.
+    fn build_must_use_type_set(apis: &ApiVec<PodPhase>) -> HashSet<QualifiedName> {
+        apis.iter()
+            .filter_map(|api| match api {
+                Api::Struct { details, .. } if has_must_use_attr(&details.item.attrs) => {
+                    Some(api.name().clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Find free functions which are overloaded purely on the constness of
+    /// their pointer (or reference) parameters, e.g. `void log(char*)` and
+    /// `void log(const char*)`. Of each such pair, we return the one taking
+    /// mutable pointers, which we'll name with a `_mut` suffix rather than
+    /// an arbitrary overload number.
+    fn build_mutable_pointer_overload_set(apis: &ApiVec<PodPhase>) -> HashSet<QualifiedName> {
+        let mut overloads_by_const_erased_signature: HashMap<_, Vec<_>> = HashMap::new();
+        for api in apis.iter() {
+            let Api::Function { name, fun, .. } = api else {
+                continue;
+            };
+            let has_this_param = fun.inputs.iter().any(|arg| match &**arg {
+                FnArg::Typed(pt) => matches!(&*pt.pat, Pat::Ident(pi) if pi.ident == "this"),
+                FnArg::Receiver(_) => true,
+            });
+            let is_method =
+                fun.self_ty.is_some() || fun.synthesized_this_type.is_some() || has_this_param;
+            if is_method || !matches!(fun.provenance, Provenance::Bindgen) {
+                continue;
+            }
+            let signature = fun
+                .inputs
+                .iter()
+                .map(|arg| match &**arg {
+                    FnArg::Typed(pt) => pt.ty.to_token_stream().to_string(),
+                    FnArg::Receiver(_) => String::new(),
+                })
+                .chain(std::iter::once(fun.output.to_token_stream().to_string()))
+                .join(", ");
+            let mutable_pointers = signature.matches("* mut ").count();
+            let cpp_name = name
+                .cpp_name_if_present()
+                .cloned()
+                .unwrap_or_else(|| fun.ident.to_string());
+            overloads_by_const_erased_signature
+                .entry((
+                    name.name.get_namespace().clone(),
+                    cpp_name,
+                    signature.replace("* const ", "* mut "),
+                ))
+                .or_default()
+                .push((mutable_pointers, name.name.clone()));
+        }
+        overloads_by_const_erased_signature
+            .into_values()
+            .filter_map(|overloads| match overloads.as_slice() {
+                [(a_mutable, a), (b_mutable, b)] if a_mutable != b_mutable => {
+                    Some(if a_mutable > b_mutable { a } else { b }.clone())
+                }
+                _ => None,
+            })
+            .collect()
     }
 
     fn build_pod_safe_type_set(apis: &ApiVec<PodPhase>) -> HashSet<QualifiedName> {
@@ -447,6 +572,24 @@ impl<'a> FnAnalyzer<'a> {
         Ok(annotated)
     }
 
+    /// Any conversion which the user has registered using
+    /// `custom_type_conversion!` for a type passed by value. `std::byte`
+    /// has a built-in conversion to `u8` unless the user overrides it.
+    fn custom_type_conversion(&self, ty: &Type) -> Option<&CustomTypeConversion> {
+        match ty {
+            Type::Path(typ) => {
+                let cpp_name = QualifiedName::from_type_path(typ).to_cpp_name();
+                self.config
+                    .get_custom_type_conversion(&cpp_name)
+                    .or_else(|| {
+                        (cpp_name == self.std_byte_conversion.cpp_type)
+                            .then_some(&self.std_byte_conversion)
+                    })
+            }
+            _ => None,
+        }
+    }
+
     fn get_cxx_bridge_name(
         &mut self,
         type_name: Option<&str>,
@@ -586,8 +729,16 @@ impl<'a> FnAnalyzer<'a> {
     fn analyze_foreign_fn_and_subclasses(
         &mut self,
         name: ApiName,
-        fun: Box<FuncToConvert>,
+        mut fun: Box<FuncToConvert>,
     ) -> Result<Box<dyn Iterator<Item = Api<FnPrePhase1>>>, ConvertErrorWithContext> {
+        // A method listed using consuming_method! is treated exactly as if
+        // it were &&-qualified.
+        if let Some(receiver) = receiver_type(&fun) {
+            let method_name = format!("{}::{}", receiver.to_cpp_name(), name.cpp_name());
+            if self.config.is_consuming_method(&method_name) {
+                fun.references.rvalue_ref_params.insert(make_ident("this"));
+            }
+        }
         let (analysis, name) =
             self.analyze_foreign_fn(name, &fun, TypeConversionSophistication::Regular, None);
         let mut results = ApiVec::new();
@@ -677,6 +828,66 @@ impl<'a> FnAnalyzer<'a> {
             }
         }
 
+        // A non-explicit constructor of a POD type taking a single, simple
+        // parameter is a C++ implicit conversion, so we additionally offer
+        // it as a `From` implementation.
+        if let FnKind::Method {
+            impl_for,
+            method_kind: MethodKind::Constructor { is_default: false },
+            ..
+        } = &analysis.kind
+        {
+            if !fun.is_explicit
+                && fun.special_member.is_none()
+                && matches!(fun.provenance, Provenance::Bindgen)
+                && analysis.ignore_reason.is_ok()
+                && self.pod_safe_types.contains(impl_for)
+                && fun.inputs.len() == 2
+                && analysis.param_details.len() == 2
+            {
+                let param = &analysis.param_details[1].conversion;
+                if !param.cpp_work_needed()
+                    && !param.rust_work_needed()
+                    && !matches!(param.cxxbridge_type(), Type::Ptr(_) | Type::Reference(_))
+                {
+                    let from_fun = create_converting_constructor_from(&fun, impl_for.clone());
+                    let from_name = ApiName::new(name.name.get_namespace(), from_fun.ident.clone());
+                    self.analyze_and_add(
+                        from_name,
+                        from_fun,
+                        &mut results,
+                        TypeConversionSophistication::Regular,
+                    );
+                }
+            }
+        }
+
+        // For types listed using throwing_constructors!, offer a version of
+        // each constructor which reports any exception as an Err.
+        if let FnKind::Method {
+            impl_for,
+            method_kind: MethodKind::Constructor { .. },
+            ..
+        } = &analysis.kind
+        {
+            if matches!(fun.provenance, Provenance::Bindgen)
+                && analysis.ignore_reason.is_ok()
+                && analysis.externally_callable
+                && self
+                    .config
+                    .has_throwing_constructors(&impl_for.to_cpp_name())
+            {
+                let try_fun = create_fallible_constructor_from(&fun, impl_for.clone());
+                let try_name = ApiName::new(name.name.get_namespace(), try_fun.ident.clone());
+                self.analyze_and_add(
+                    try_name,
+                    try_fun,
+                    &mut results,
+                    TypeConversionSophistication::Regular,
+                );
+            }
+        }
+
         results.push(Api::Function {
             fun,
             analysis,
@@ -737,14 +948,32 @@ impl<'a> FnAnalyzer<'a> {
         let initial_rust_name = fun.ident.to_string();
         let diagnostic_display_name = cpp_name.as_ref().unwrap_or(&initial_rust_name);
 
+        // The user may have asked for this free function to become a method
+        // on the type of its first parameter, in which case we'll treat that
+        // parameter just like the 'this' parameter of a real method.
+        let method_of = if fun.self_ty.is_none() && fun.synthesized_this_type.is_none() {
+            let free_fn_name =
+                QualifiedName::new(ns, make_ident(diagnostic_display_name)).to_cpp_name();
+            self.config
+                .get_method_of(&free_fn_name)
+                .map(|ty| (free_fn_name, ty.to_string()))
+        } else {
+            None
+        };
+
         // Now let's analyze all the parameters.
         // See if any have annotations which our fork of bindgen has craftily inserted...
         let (param_details, bads): (Vec<_>, Vec<_>) = fun
             .inputs
             .iter()
-            .map(|i| {
+            .enumerate()
+            .map(|(idx, i)| {
+                let i = match &method_of {
+                    Some(_) if idx == 0 => Cow::Owned(rename_to_this(i)),
+                    _ => Cow::Borrowed(&**i),
+                };
                 self.convert_fn_arg(
-                    i,
+                    &i,
                     ns,
                     diagnostic_display_name,
                     &fun.synthesized_this_type,
@@ -756,7 +985,7 @@ impl<'a> FnAnalyzer<'a> {
                     false,
                 )
                 .map_err(|err| ConvertErrorFromCpp::Argument {
-                    arg: describe_arg(i),
+                    arg: describe_arg(&i),
                     err: Box::new(err),
                 })
             })
@@ -815,6 +1044,22 @@ impl<'a> FnAnalyzer<'a> {
             }
         };
 
+        let method_of_error = method_of.as_ref().and_then(|(free_fn_name, ty)| {
+            let suitable = self_ty
+                .as_ref()
+                .map_or(false, |self_ty| self_ty.to_cpp_name() == *ty);
+            (!suitable).then(|| {
+                ConvertErrorFromCpp::MethodOfUnsuitableFirstParam(free_fn_name.clone(), ty.clone())
+            })
+        });
+
+        // The types which distinguish this from any overloads of the same name.
+        let overload_params: Vec<_> = param_details
+            .iter()
+            .filter(|pd| pd.self_type.is_none() && !pd.is_placement_return_destination)
+            .map(|pd| pd.conversion.cxxbridge_type())
+            .collect();
+
         // Part two, work out if this is a function, or method, or whatever.
         // First determine if this is actually a trait implementation.
         let trait_details = self.trait_creation_details_for_synthetic_function(
@@ -822,6 +1067,7 @@ impl<'a> FnAnalyzer<'a> {
             ns,
             &ideal_rust_name,
             &self_ty,
+            &overload_params,
         );
         let (kind, error_context, rust_name) = if let Some(trait_details) = trait_details {
             trait_details
@@ -849,8 +1095,9 @@ impl<'a> FnAnalyzer<'a> {
                 if let Some(constructor_suffix) = rust_name.strip_prefix(nested_type_ident) {
                     rust_name = format!("new{constructor_suffix}");
                 }
-                rust_name = predetermined_rust_name
-                    .unwrap_or_else(|| self.get_overload_name(ns, type_ident, rust_name));
+                rust_name = predetermined_rust_name.unwrap_or_else(|| {
+                    self.get_overload_name(ns, type_ident, rust_name, &overload_params)
+                });
                 let error_context = self.error_context_for_method(&self_ty, &rust_name);
 
                 // If this is 'None', then something weird is going on. We'll check for that
@@ -896,6 +1143,7 @@ impl<'a> FnAnalyzer<'a> {
                                 method_name: make_ident(method_name),
                                 parameter_reordering: Some(vec![1, 0]),
                                 trait_call_is_unsafe: false,
+                                associated_type: None,
                             }),
                         },
                         error_context,
@@ -912,8 +1160,9 @@ impl<'a> FnAnalyzer<'a> {
                     )
                 }
             } else if matches!(fun.special_member, Some(SpecialMemberKind::Destructor)) {
-                rust_name = predetermined_rust_name
-                    .unwrap_or_else(|| self.get_overload_name(ns, type_ident, rust_name));
+                rust_name = predetermined_rust_name.unwrap_or_else(|| {
+                    self.get_overload_name(ns, type_ident, rust_name, &overload_params)
+                });
                 let error_context = self.error_context_for_method(&self_ty, &rust_name);
                 let ty = Type::Path(self_ty.to_type_path());
                 (
@@ -932,6 +1181,7 @@ impl<'a> FnAnalyzer<'a> {
                             method_name: make_ident("drop"),
                             parameter_reordering: None,
                             trait_call_is_unsafe: false,
+                            associated_type: None,
                         }),
                     },
                     error_context,
@@ -951,8 +1201,13 @@ impl<'a> FnAnalyzer<'a> {
                     // which later code will convert to
                     // fn make_unique(...args) -> UniquePtr<Type>
                     // If there are multiple constructors, bindgen generates
-                    // new, new1, new2 etc. and we'll keep those suffixes.
-                    rust_name = format!("new{constructor_suffix}");
+                    // new, new1, new2 etc. and we'll keep those suffixes,
+                    // unless we've been asked to name overloads by their
+                    // parameter types instead.
+                    rust_name = match self.config.get_overload_naming() {
+                        OverloadNaming::Numeric => format!("new{constructor_suffix}"),
+                        OverloadNaming::ParameterTypes => "new".to_string(),
+                    };
                     MethodKind::Constructor {
                         is_default: matches!(
                             fun.special_member,
@@ -971,8 +1226,9 @@ impl<'a> FnAnalyzer<'a> {
                     }
                 };
                 // Disambiguate overloads.
-                let rust_name = predetermined_rust_name
-                    .unwrap_or_else(|| self.get_overload_name(ns, type_ident, rust_name));
+                let rust_name = predetermined_rust_name.unwrap_or_else(|| {
+                    self.get_overload_name(ns, type_ident, rust_name, &overload_params)
+                });
                 let error_context = self.error_context_for_method(&self_ty, &rust_name);
                 (
                     FnKind::Method {
@@ -986,7 +1242,12 @@ impl<'a> FnAnalyzer<'a> {
         } else {
             // Not a method.
             // What shall we call this function? It may be overloaded.
-            let rust_name = self.get_function_overload_name(ns, ideal_rust_name);
+            let ideal_rust_name = if self.mutable_pointer_overloads.contains(&name.name) {
+                format!("{ideal_rust_name}_mut")
+            } else {
+                ideal_rust_name
+            };
+            let rust_name = self.get_function_overload_name(ns, ideal_rust_name, &overload_params);
             (
                 FnKind::Function,
                 ErrorContext::new_for_item(make_ident(&rust_name)),
@@ -1126,6 +1387,9 @@ impl<'a> FnAnalyzer<'a> {
         if fun.variadic {
             set_ignore_reason(ConvertErrorFromCpp::Variadic);
         }
+        if let Some(err) = method_of_error {
+            set_ignore_reason(err);
+        }
         if let Some(problem) = bads.into_iter().next() {
             match problem {
                 Ok(_) => panic!("No error in the error"),
@@ -1210,24 +1474,267 @@ impl<'a> FnAnalyzer<'a> {
         // Sometimes, the return type will actually be a value type
         // for which we instead want to _pass_ a pointer into which the value
         // can be constructed. Handle that case here.
+        let has_placement_return = return_analysis.placement_param_needed.is_some();
         if let Some((extra_param, extra_param_details)) = return_analysis.placement_param_needed {
             param_details.push(extra_param_details);
             params.push(extra_param);
         }
 
-        let requires_unsafe = self.should_be_unsafe(&param_details, &kind);
+        // The name by which directives such as out_param! refer to this function.
+        let directive_fn_name = match kind {
+            FnKind::Method { ref impl_for, .. } | FnKind::TraitMethod { ref impl_for, .. } => {
+                format!(
+                    "{}::{}",
+                    impl_for.to_cpp_name(),
+                    cpp_name.as_ref().unwrap_or(&rust_name)
+                )
+            }
+            FnKind::Function => {
+                QualifiedName::new(ns, make_ident(cpp_name.as_ref().unwrap_or(&rust_name)))
+                    .to_cpp_name()
+            }
+        };
+        // Any parameters which the user has told us are output parameters
+        // are instead allocated by the Rust wrapper and returned.
+        let out_param_idxs = fun
+            .inputs
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, arg)| match &**arg {
+                FnArg::Typed(PatType { pat, .. }) => match pat.as_ref() {
+                    Pat::Ident(pp)
+                        if self
+                            .config
+                            .is_out_param(&directive_fn_name, &pp.ident.to_string()) =>
+                    {
+                        Some((idx, pp.ident.to_string()))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect_vec();
+        self.used_out_params.extend(
+            out_param_idxs
+                .iter()
+                .map(|(_, param_name)| (directive_fn_name.clone(), param_name.clone())),
+        );
+        for (idx, param_name) in out_param_idxs {
+            if has_placement_return {
+                set_ignore_reason(ConvertErrorFromCpp::OutParamWithPlacementReturn(param_name));
+                continue;
+            }
+            self.reanalyze_parameter(
+                idx,
+                fun,
+                ns,
+                &rust_name,
+                &mut params,
+                &mut param_details,
+                Some(RustConversionType::FromOutParamToReturn),
+                sophistication,
+                false,
+                false,
+            )
+            .unwrap_or_else(&mut set_ignore_reason);
+            // The Rust wrapper starts with a default value, which the
+            // function then overwrites.
+            match param_details[idx].conversion.cxxbridge_type() {
+                Type::Ptr(TypePtr {
+                    mutability: Some(_),
+                    elem,
+                    ..
+                }) => {
+                    let has_default = matches!(elem.as_ref(), Type::Path(typ)
+                        if known_types().has_default(&QualifiedName::from_type_path(typ)));
+                    if !has_default {
+                        set_ignore_reason(ConvertErrorFromCpp::OutParamWithoutDefault(param_name));
+                    }
+                }
+                _ => set_ignore_reason(ConvertErrorFromCpp::OutParamNotMutablePointer(param_name)),
+            }
+        }
+
+        // A pointer to POD structs followed by a count of them is presented
+        // to Rust as a single slice parameter, unless the user asks us not
+        // to. Virtual functions are excluded since subclasses would need to
+        // perform the reverse conversion.
+        if matches!(sophistication, TypeConversionSophistication::Regular)
+            && matches!(fun.virtualness, Virtualness::None)
+            && !self.config.is_no_slice_params(&directive_fn_name)
+        {
+            self.combine_slice_params(&mut params, &mut param_details);
+        }
+
+        let mut requires_unsafe = self.should_be_unsafe(&param_details, &kind);
+        // Anything else might be reading a static data member while we
+        // write to it.
+        if matches!(
+            fun.synthetic_cpp,
+            Some((CppFunctionBody::StaticMemberSet(..), _))
+        ) {
+            requires_unsafe = UnsafetyNeeded::Always;
+        }
+
+        // The user may have asserted that the returned reference lives forever,
+        // in which case it needs no input reference to borrow from.
+        let is_singleton = self.config.is_singleton(&directive_fn_name);
+        if is_singleton {
+            if !return_analysis.was_reference {
+                set_ignore_reason(ConvertErrorFromCpp::SingletonNotReturningReference(
+                    rust_name.clone(),
+                ));
+            } else if !param_details.is_empty() {
+                set_ignore_reason(ConvertErrorFromCpp::SingletonWithParameters(
+                    rust_name.clone(),
+                ));
+            } else if let ReturnType::Type(rarrow, boxed_type) = &return_analysis.rt {
+                let pointee = match boxed_type.as_ref() {
+                    Type::Reference(tyr) => tyr.elem.as_ref().clone(),
+                    Type::Path(typ) => extract_type_from_pinned_mut_ref(typ),
+                    _ => unreachable!("reference return was not a reference"),
+                };
+                let ty: Type = parse_quote! { &'static #pointee };
+                return_analysis.rt = ReturnType::Type(*rarrow, Box::new(ty.clone()));
+                return_analysis.conversion = Some(TypeConversionPolicy::new_unconverted(ty));
+                return_analysis.was_reference = false;
+                return_analysis.was_mutable_reference = false;
+            }
+        }
+
+        // The user may have told us which parameter the returned reference
+        // borrows from, in which case we can give it an explicit lifetime.
+        let mut has_borrowed_param = false;
+        if let Some(param_name) = self.config.get_return_borrow(&directive_fn_name) {
+            if !return_analysis.was_reference {
+                set_ignore_reason(ConvertErrorFromCpp::ReturnBorrowsNotReturningReference(
+                    rust_name.clone(),
+                ));
+            } else {
+                let borrowed = param_details.iter_mut().find(|pd| {
+                    let name = match &*pd.name {
+                        Pat::Ident(pp) => pp.ident.to_string(),
+                        _ => return false,
+                    };
+                    name == param_name || (name == "self" && param_name == "this")
+                });
+                match borrowed {
+                    Some(pd)
+                        if pd.has_lifetime
+                            && (pd.is_mutable_reference
+                                || !return_analysis.was_mutable_reference) =>
+                    {
+                        pd.is_borrowed_by_return = true;
+                        has_borrowed_param = true;
+                    }
+                    _ => set_ignore_reason(ConvertErrorFromCpp::ReturnBorrowsUnsuitableParam(
+                        rust_name.clone(),
+                        param_name.to_string(),
+                    )),
+                }
+            }
+        }
+
+        // cxx wouldn't accept arbitrary attributes on its own declarations,
+        // so any the user asked for go on Rust and C++ wrappers which we'll
+        // generate regardless.
+        let extra_attrs: Vec<crate::minisyn::Attribute> = self
+            .config
+            .get_extra_attributes(&directive_fn_name)
+            .cloned()
+            .map(Into::into)
+            .collect();
+        // A `cfg` predicate, on the other hand, goes on everything we
+        // generate for this function, in C++ as well as in Rust.
+        let cfg_gate: Option<crate::minisyn::Attribute> = self
+            .config
+            .get_cfg_gate(&directive_fn_name)
+            .map(|predicate| {
+                let attr: syn::Attribute = parse_quote! { #[cfg(#predicate)] };
+                attr.into()
+            });
+        let must_use = fun.must_use
+            || match &*fun.output {
+                ReturnType::Type(_, ty) => match &**ty {
+                    Type::Path(typ) => self
+                        .must_use_types
+                        .contains(&QualifiedName::from_type_path(typ)),
+                    _ => false,
+                },
+                ReturnType::Default => false,
+            };
+
+        // The user may have asked us to present a returned C string as
+        // something more Rusty. A borrowed string from a method may point
+        // into the object, so it borrows from the receiver. Anything else
+        // would have to live forever, which only the caller can promise.
+        if let Some(mapping) = self.config.get_c_string_return(&directive_fn_name) {
+            match &return_analysis.rt {
+                ReturnType::Type(_, ty) if is_const_char_ptr(ty) => {
+                    let rust_conversion = match mapping {
+                        CStringReturn::Borrowed => {
+                            let borrows_receiver = param_details
+                                .iter()
+                                .any(|pd| pd.self_type.is_some() && pd.has_lifetime);
+                            if !borrows_receiver {
+                                requires_unsafe = UnsafetyNeeded::Always;
+                            }
+                            RustConversionType::FromCharPtrToCStr { borrows_receiver }
+                        }
+                        CStringReturn::Owned => RustConversionType::FromCharPtrToString,
+                    };
+                    return_analysis.conversion = Some(TypeConversionPolicy::new(
+                        ty.as_ref().clone(),
+                        CppConversionType::None,
+                        rust_conversion,
+                    ));
+                }
+                _ => set_ignore_reason(ConvertErrorFromCpp::CStringReturnNotCharPtr(
+                    rust_name.clone(),
+                )),
+            }
+        }
+
+        // The user may have asked us to check that integer arguments fit
+        // before passing them to C++. Trait methods have to keep the
+        // signatures the traits give them, so we leave those alone.
+        let integer_conversions = self.config.get_integer_conversions();
+        let mut checks_integers = false;
+        if integer_conversions != IntegerConversions::Unchecked
+            && matches!(sophistication, TypeConversionSophistication::Regular)
+            && !matches!(kind, FnKind::TraitMethod { .. })
+        {
+            for pd in param_details.iter_mut().filter(|pd| {
+                pd.self_type.is_none()
+                    && !pd.conversion.rust_work_needed()
+                    && !pd.conversion.cpp_work_needed()
+            }) {
+                let is_integer = match pd.conversion.cxxbridge_type() {
+                    Type::Path(typ) => known_types()
+                        .integer_primitive(&QualifiedName::from_type_path(typ))
+                        .is_some(),
+                    _ => false,
+                };
+                if is_integer {
+                    pd.conversion.rust_conversion =
+                        RustConversionType::FromCheckedInteger(integer_conversions);
+                    checks_integers = true;
+                }
+            }
+        }
 
         // The following sections reject some types of function because of the arrangement
         // of Rust references. We could lift these restrictions when/if we switch to using
         // CppRef to represent C++ references.
         if return_analysis.was_reference {
             // cxx only allows functions to return a reference if they take exactly
-            // one reference as a parameter. Let's see.
+            // one reference as a parameter, unless we've been told which one
+            // it borrows from. Let's see.
             let num_input_references = param_details.iter().filter(|pd| pd.has_lifetime).count();
             if num_input_references == 0 {
                 set_ignore_reason(ConvertErrorFromCpp::NoInputReference(rust_name.clone()));
             }
-            if num_input_references > 1 {
+            if num_input_references > 1 && !has_borrowed_param {
                 set_ignore_reason(ConvertErrorFromCpp::MultipleInputReferences(
                     rust_name.clone(),
                 ));
@@ -1250,7 +1757,7 @@ impl<'a> FnAnalyzer<'a> {
                     rust_name.clone(),
                 ));
             }
-            if num_input_mutable_references > 1 {
+            if num_input_mutable_references > 1 && !has_borrowed_param {
                 set_ignore_reason(ConvertErrorFromCpp::MultipleMutableInputReferences(
                     rust_name.clone(),
                 ));
@@ -1296,10 +1803,19 @@ impl<'a> FnAnalyzer<'a> {
                 ..
             } => true,
             FnKind::Method { .. } if cxxbridge_name != rust_name => true,
+            _ if method_of.is_some() => true,
+            // Only a C++ call can pick the right overload based on
+            // the value category of the receiver.
+            _ if fun.references.is_ref_qualified_method() => true,
+            // A member function pointer to a `restrict`-qualified method
+            // has a type cxx can't spell, so call it from C++ instead.
+            _ if fun.references.restrict_this => true,
             _ if param_conversion_needed => true,
             _ if ret_type_conversion_needed => true,
             _ if cpp_name_incompatible_with_cxx => true,
             _ if fun.synthetic_cpp.is_some() => true,
+            _ if !extra_attrs.is_empty() => true,
+            _ if checks_integers => true,
             _ if self.force_wrapper_generation => true,
             _ => false,
         };
@@ -1308,18 +1824,36 @@ impl<'a> FnAnalyzer<'a> {
             // Generate a new layer of C++ code to wrap/unwrap parameters
             // and return values into/out of std::unique_ptrs.
             let cpp_construction_ident = make_ident(effective_cpp_name);
-            let joiner = if cxxbridge_name.to_string().ends_with('_') {
-                ""
-            } else {
-                "_"
-            };
             cxxbridge_name = make_ident(
-                self.config
-                    .uniquify_name_per_mod(&format!("{cxxbridge_name}{joiner}autocxx_wrapper")),
+                match (
+                    self.config.get_shim_name(&directive_fn_name),
+                    self.config.get_shim_prefix(),
+                ) {
+                    (Some(name), _) => name.to_string(),
+                    (None, Some(prefix)) => self
+                        .config
+                        .uniquify_name_per_mod(&format!("{prefix}{cxxbridge_name}")),
+                    (None, None) => {
+                        let joiner = if cxxbridge_name.to_string().ends_with('_') {
+                            ""
+                        } else {
+                            "_"
+                        };
+                        self.config.uniquify_name_per_mod(&format!(
+                            "{cxxbridge_name}{joiner}autocxx_wrapper"
+                        ))
+                    }
+                },
             );
             let (payload, cpp_function_kind) = match fun.synthetic_cpp.as_ref().cloned() {
                 Some((payload, cpp_function_kind)) => (payload, cpp_function_kind),
                 None => match kind {
+                    // The receiver is just passed on as the first argument
+                    // to the original free function.
+                    _ if method_of.is_some() => (
+                        CppFunctionBody::FunctionCall(ns.clone(), cpp_construction_ident),
+                        CppFunctionKind::Function,
+                    ),
                     FnKind::Method {
                         ref impl_for,
                         method_kind: MethodKind::Constructor { .. },
@@ -1413,6 +1947,7 @@ impl<'a> FnAnalyzer<'a> {
         let rust_wrapper_needed = match kind {
             _ if any_param_needs_rust_conversion || return_needs_rust_conversion => true,
             FnKind::TraitMethod { .. } => true,
+            _ if !extra_attrs.is_empty() => true,
             FnKind::Method { .. } => cxxbridge_name != rust_name,
             _ if self.force_wrapper_generation => true,
             _ => false,
@@ -1448,6 +1983,9 @@ impl<'a> FnAnalyzer<'a> {
             ignore_reason,
             externally_callable,
             rust_wrapper_needed,
+            extra_attrs,
+            cfg_gate,
+            must_use,
         };
         let name = ApiName::new_with_cpp_name(ns, cxxbridge_name, cpp_name);
         (analysis, name)
@@ -1466,6 +2004,113 @@ impl<'a> FnAnalyzer<'a> {
         }
     }
 
+    /// Finds pairs of parameters such as `const Point* points, size_t count`
+    /// where `Point` is a POD struct, and replaces each with a single
+    /// slice parameter, `points: &[Point]`. The C++ wrapper splits the
+    /// slice back into a pointer and length. Buffers of `std::byte`
+    /// become slices of `u8`.
+    fn combine_slice_params(
+        &self,
+        params: &mut Punctuated<FnArg, Comma>,
+        param_details: &mut Vec<ArgumentAnalysis>,
+    ) {
+        let mut idx = 0;
+        while idx + 1 < param_details.len() {
+            if let Some((slice_type, cpp_ptr_cast)) =
+                self.slice_type_for_params(&param_details[idx], &param_details[idx + 1])
+            {
+                let is_mut = matches!(
+                    &slice_type,
+                    Type::Reference(TypeReference {
+                        mutability: Some(_),
+                        ..
+                    })
+                );
+                let details = &mut param_details[idx];
+                if cpp_ptr_cast.is_some() {
+                    // The Rust side no longer mentions the C++ element type.
+                    details.deps.clear();
+                }
+                details.conversion = TypeConversionPolicy::new(
+                    slice_type.clone(),
+                    CppConversionType::FromSliceToPointerAndLength(cpp_ptr_cast),
+                    RustConversionType::None,
+                );
+                details.has_lifetime = true;
+                details.is_mutable_reference = is_mut;
+                details.requires_unsafe = UnsafetyNeeded::None;
+                param_details.remove(idx + 1);
+                *params = std::mem::take(params)
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i != idx + 1)
+                    .map(|(i, mut arg)| {
+                        if let (true, FnArg::Typed(pt)) = (i == idx, &mut arg) {
+                            *pt.ty = slice_type.clone();
+                        }
+                        arg
+                    })
+                    .collect();
+            }
+            idx += 1;
+        }
+    }
+
+    /// If `ptr` and `len` are a pointer to POD structs and a `size_t`,
+    /// returns the equivalent slice type, along with any C++ pointer type
+    /// to which the slice's data must be cast.
+    fn slice_type_for_params(
+        &self,
+        ptr: &ArgumentAnalysis,
+        len: &ArgumentAnalysis,
+    ) -> Option<(Type, Option<String>)> {
+        let is_unconverted = |pd: &ArgumentAnalysis| {
+            pd.self_type.is_none()
+                && !pd.conversion.cpp_work_needed()
+                && !pd.conversion.rust_work_needed()
+        };
+        if !is_unconverted(ptr) || !is_unconverted(len) {
+            return None;
+        }
+        match len.conversion.cxxbridge_type() {
+            Type::Path(typ) if typ.path.is_ident("usize") => {}
+            _ => return None,
+        }
+        match ptr.conversion.cxxbridge_type() {
+            Type::Ptr(TypePtr {
+                elem, mutability, ..
+            }) => match elem.as_ref() {
+                Type::Path(typ) => {
+                    let tn = QualifiedName::from_type_path(typ);
+                    let const_str = if mutability.is_some() { "" } else { "const " };
+                    if tn.to_cpp_name() == self.std_byte_conversion.cpp_type {
+                        return Some((
+                            if mutability.is_some() {
+                                parse_quote! { &mut [u8] }
+                            } else {
+                                parse_quote! { &[u8] }
+                            },
+                            Some(format!("{const_str}std::byte*")),
+                        ));
+                    }
+                    if !self.pod_safe_types.contains(&tn) || known_types().is_known_type(&tn) {
+                        return None;
+                    }
+                    Some((
+                        if mutability.is_some() {
+                            parse_quote! { &mut [#elem] }
+                        } else {
+                            parse_quote! { &[#elem] }
+                        },
+                        None,
+                    ))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
     /// Applies a specific `force_rust_conversion` to the parameter at index
     /// `param_idx`. Modifies `param_details` and `params` in place.
     #[allow(clippy::too_many_arguments)] // it's true, but sticking with it for now
@@ -1510,9 +2155,19 @@ impl<'a> FnAnalyzer<'a> {
         })
     }
 
-    fn get_overload_name(&mut self, ns: &Namespace, type_ident: &str, rust_name: String) -> String {
-        let overload_tracker = self.overload_trackers_by_mod.entry(ns.clone()).or_default();
-        overload_tracker.get_method_real_name(type_ident, rust_name)
+    fn get_overload_name(
+        &mut self,
+        ns: &Namespace,
+        type_ident: &str,
+        rust_name: String,
+        params: &[&Type],
+    ) -> String {
+        let naming = self.config.get_overload_naming();
+        let overload_tracker = self
+            .overload_trackers_by_mod
+            .entry(ns.clone())
+            .or_insert_with(|| OverloadTracker::new(naming));
+        overload_tracker.get_method_real_name(type_ident, rust_name, params)
     }
 
     /// Determine if this synthetic function should actually result in the implementation
@@ -1523,10 +2178,12 @@ impl<'a> FnAnalyzer<'a> {
         ns: &Namespace,
         ideal_rust_name: &str,
         self_ty: &Option<QualifiedName>,
+        params: &[&Type],
     ) -> Option<(FnKind, ErrorContext, String)> {
         synthesis.as_ref().and_then(|synthesis| match synthesis {
             TraitSynthesis::Cast { to_type, mutable } => {
-                let rust_name = self.get_function_overload_name(ns, ideal_rust_name.to_string());
+                let rust_name =
+                    self.get_function_overload_name(ns, ideal_rust_name.to_string(), &[]);
                 let from_type = self_ty.as_ref().unwrap();
                 let from_type_path = from_type.to_type_path();
                 let to_type = to_type.to_type_path();
@@ -1572,12 +2229,179 @@ impl<'a> FnAnalyzer<'a> {
                             method_name,
                             parameter_reordering: None,
                             trait_call_is_unsafe: false,
+                            associated_type: None,
+                        }),
+                    },
+                    ErrorContext::new_for_item(make_ident(&rust_name)),
+                    rust_name,
+                ))
+            }
+            // The receiver is passed by reference, and the result must
+            // be returned by value.
+            TraitSynthesis::Conversion { to_type } if self.pod_safe_types.contains(to_type) => {
+                let rust_name =
+                    self.get_function_overload_name(ns, ideal_rust_name.to_string(), &[]);
+                let from_type = self_ty.as_ref().unwrap();
+                let from_type_path = from_type.to_type_path();
+                Some((
+                    FnKind::TraitMethod {
+                        kind: TraitMethodKind::Conversion,
+                        impl_for: from_type.clone(),
+                        details: Box::new(TraitMethodDetails {
+                            trt: TraitImplSignature {
+                                ty: Type::Path(to_type.to_type_path()).into(),
+                                trait_signature: parse_quote! {
+                                    From < &#from_type_path >
+                                },
+                                unsafety: None,
+                            },
+                            avoid_self: true,
+                            method_name: make_ident("from"),
+                            parameter_reordering: None,
+                            trait_call_is_unsafe: false,
+                            associated_type: None,
+                        }),
+                    },
+                    ErrorContext::new_for_item(make_ident(&rust_name)),
+                    rust_name,
+                ))
+            }
+            TraitSynthesis::Conversion { .. } => None,
+            // The single parameter is passed by value, and so is the result.
+            TraitSynthesis::ConvertingConstructor { to_type } => {
+                let rust_name =
+                    self.get_function_overload_name(ns, ideal_rust_name.to_string(), &[]);
+                let from_type = params.first()?;
+                Some((
+                    FnKind::TraitMethod {
+                        kind: TraitMethodKind::ConvertingConstructor,
+                        impl_for: to_type.clone(),
+                        details: Box::new(TraitMethodDetails {
+                            trt: TraitImplSignature {
+                                ty: Type::Path(to_type.to_type_path()).into(),
+                                trait_signature: parse_quote! {
+                                    From < #from_type >
+                                },
+                                unsafety: None,
+                            },
+                            avoid_self: true,
+                            method_name: make_ident("from"),
+                            parameter_reordering: None,
+                            trait_call_is_unsafe: false,
+                            associated_type: None,
+                        }),
+                    },
+                    ErrorContext::new_for_item(make_ident(&rust_name)),
+                    rust_name,
+                ))
+            }
+            // The receiver and the result are both references.
+            TraitSynthesis::Deref { target } => {
+                let rust_name =
+                    self.get_function_overload_name(ns, ideal_rust_name.to_string(), &[]);
+                let self_ty = self_ty.as_ref()?;
+                Some((
+                    FnKind::TraitMethod {
+                        kind: TraitMethodKind::Deref,
+                        impl_for: self_ty.clone(),
+                        details: Box::new(TraitMethodDetails {
+                            trt: TraitImplSignature {
+                                ty: Type::Path(self_ty.to_type_path()).into(),
+                                trait_signature: parse_quote! {
+                                    ::core::ops::Deref
+                                },
+                                unsafety: None,
+                            },
+                            avoid_self: false,
+                            method_name: make_ident("deref"),
+                            parameter_reordering: None,
+                            trait_call_is_unsafe: false,
+                            associated_type: Some((
+                                make_ident("Target"),
+                                Type::Path(target.to_type_path()).into(),
+                            )),
+                        }),
+                    },
+                    ErrorContext::new_for_item(make_ident(&rust_name)),
+                    rust_name,
+                ))
+            }
+            // The receiver and the right-hand side are both references.
+            TraitSynthesis::PartialEq => {
+                let Type::Reference(rhs) = params.first()? else {
+                    return None;
+                };
+                let rhs = &rhs.elem;
+                let rust_name =
+                    self.get_function_overload_name(ns, ideal_rust_name.to_string(), params);
+                let self_ty = self_ty.as_ref()?;
+                Some((
+                    FnKind::TraitMethod {
+                        kind: TraitMethodKind::PartialEq,
+                        impl_for: self_ty.clone(),
+                        details: Box::new(TraitMethodDetails {
+                            trt: TraitImplSignature {
+                                ty: Type::Path(self_ty.to_type_path()).into(),
+                                trait_signature: parse_quote! {
+                                    PartialEq < #rhs >
+                                },
+                                unsafety: None,
+                            },
+                            avoid_self: false,
+                            method_name: make_ident("eq"),
+                            parameter_reordering: None,
+                            trait_call_is_unsafe: false,
+                            associated_type: None,
+                        }),
+                    },
+                    ErrorContext::new_for_item(make_ident(&rust_name)),
+                    rust_name,
+                ))
+            }
+            // The receiver is passed by reference, the right-hand side
+            // either by value (e.g. a scalar) or by reference, and the
+            // result must be returned by value.
+            TraitSynthesis::Arithmetic { op, output } if self.pod_safe_types.contains(output) => {
+                let rhs = match params.first()? {
+                    rhs @ (Type::Path(_) | Type::Reference(_)) => rhs,
+                    _ => return None,
+                };
+                let rust_name =
+                    self.get_function_overload_name(ns, ideal_rust_name.to_string(), params);
+                let self_ty = self_ty.as_ref()?;
+                let self_ty_path = self_ty.to_type_path();
+                let trait_name = make_ident(op.trait_name());
+                let value_impl_rhs = self
+                    .pod_safe_types
+                    .contains(self_ty)
+                    .then(|| rhs.clone().into());
+                Some((
+                    FnKind::TraitMethod {
+                        kind: TraitMethodKind::Arithmetic { value_impl_rhs },
+                        impl_for: self_ty.clone(),
+                        details: Box::new(TraitMethodDetails {
+                            trt: TraitImplSignature {
+                                ty: parse_quote! { &#self_ty_path },
+                                trait_signature: parse_quote! {
+                                    ::core::ops::#trait_name < #rhs >
+                                },
+                                unsafety: None,
+                            },
+                            avoid_self: false,
+                            method_name: make_ident(op.method_name()),
+                            parameter_reordering: None,
+                            trait_call_is_unsafe: false,
+                            associated_type: Some((
+                                make_ident("Output"),
+                                Type::Path(output.to_type_path()).into(),
+                            )),
                         }),
                     },
                     ErrorContext::new_for_item(make_ident(&rust_name)),
                     rust_name,
                 ))
             }
+            TraitSynthesis::Arithmetic { .. } => None,
             TraitSynthesis::AllocUninitialized(ty) => self.generate_alloc_or_deallocate(
                 ideal_rust_name,
                 ty,
@@ -1601,7 +2425,7 @@ impl<'a> FnAnalyzer<'a> {
         kind: TraitMethodKind,
     ) -> Option<(FnKind, ErrorContext, String)> {
         let rust_name =
-            self.get_function_overload_name(ty.get_namespace(), ideal_rust_name.to_string());
+            self.get_function_overload_name(ty.get_namespace(), ideal_rust_name.to_string(), &[]);
         let typ = ty.to_type_path();
         Some((
             FnKind::TraitMethod {
@@ -1616,6 +2440,7 @@ impl<'a> FnAnalyzer<'a> {
                     method_name: make_ident(method_name),
                     parameter_reordering: None,
                     trait_call_is_unsafe: false,
+                    associated_type: None,
                 }),
                 kind,
             },
@@ -1624,9 +2449,18 @@ impl<'a> FnAnalyzer<'a> {
         ))
     }
 
-    fn get_function_overload_name(&mut self, ns: &Namespace, ideal_rust_name: String) -> String {
-        let overload_tracker = self.overload_trackers_by_mod.entry(ns.clone()).or_default();
-        overload_tracker.get_function_real_name(ideal_rust_name)
+    fn get_function_overload_name(
+        &mut self,
+        ns: &Namespace,
+        ideal_rust_name: String,
+        params: &[&Type],
+    ) -> String {
+        let naming = self.config.get_overload_naming();
+        let overload_tracker = self
+            .overload_trackers_by_mod
+            .entry(ns.clone())
+            .or_insert_with(|| OverloadTracker::new(naming));
+        overload_tracker.get_function_real_name(ideal_rust_name, params)
     }
 
     fn subclasses_by_superclass(&self, sup: &QualifiedName) -> impl Iterator<Item = SubclassName> {
@@ -1697,7 +2531,11 @@ impl<'a> FnAnalyzer<'a> {
                         }?;
                         self_type = Some(this_type);
                         is_placement_return_destination = construct_into_self;
-                        if treat_this_as_reference {
+                        if treat_this_as_reference && references.is_rvalue_qualified_method() {
+                            // This method consumes its receiver, so we
+                            // can't represent it as any sort of 'self'.
+                            pointer_treatment = PointerTreatment::RValueReference;
+                        } else if treat_this_as_reference {
                             pp.ident = Ident::new("self", pp.ident.span());
                             pointer_treatment = PointerTreatment::Reference;
                         }
@@ -1717,6 +2555,123 @@ impl<'a> FnAnalyzer<'a> {
                         force_rust_conversion,
                         Some(RustConversionType::FromPlacementParamToNewReturn)
                     );
+                let is_out_param = matches!(
+                    force_rust_conversion,
+                    Some(RustConversionType::FromOutParamToReturn)
+                );
+                if let Some(custom) = self.custom_type_conversion(&pt.ty) {
+                    let rust_type = custom.rust_type.clone();
+                    pt.pat = Box::new(new_pat.clone());
+                    pt.ty = Box::new(rust_type.clone());
+                    return Ok((
+                        FnArg::Typed(pt),
+                        ArgumentAnalysis {
+                            self_type,
+                            name: new_pat.into(),
+                            conversion: TypeConversionPolicy::new(
+                                rust_type,
+                                CppConversionType::Custom(custom.to_cpp.clone()),
+                                RustConversionType::None,
+                            ),
+                            has_lifetime: false,
+                            is_mutable_reference: false,
+                            deps: HashSet::new(),
+                            requires_unsafe: UnsafetyNeeded::None,
+                            is_placement_return_destination,
+                            is_borrowed_by_return: false,
+                        },
+                    ));
+                }
+                // A `std::span` of POD types becomes a slice, just like
+                // a pointer followed by a count.
+                if let (Some(elem), syn::Pat::Ident(pp), TypeConversionSophistication::Regular) = (
+                    template_argument(&pt.ty, "std::span"),
+                    &new_pat,
+                    sophistication,
+                ) {
+                    let elem = self.convert_boxed_type(elem, ns, PointerTreatment::Pointer)?;
+                    if let Type::Path(typ) = elem.ty.as_ref() {
+                        if self
+                            .pod_safe_types
+                            .contains(&QualifiedName::from_type_path(typ))
+                        {
+                            let is_mut = !references
+                                .const_template_arg_params
+                                .contains(&pp.ident.clone().into());
+                            let slice_type: Type = if is_mut {
+                                parse_quote! { &mut [#typ] }
+                            } else {
+                                parse_quote! { &[#typ] }
+                            };
+                            pt.pat = Box::new(new_pat.clone());
+                            pt.ty = Box::new(slice_type.clone());
+                            return Ok((
+                                FnArg::Typed(pt),
+                                ArgumentAnalysis {
+                                    self_type,
+                                    name: new_pat.into(),
+                                    conversion: TypeConversionPolicy::new(
+                                        slice_type,
+                                        CppConversionType::FromSliceToSpan,
+                                        RustConversionType::None,
+                                    ),
+                                    has_lifetime: true,
+                                    is_mutable_reference: is_mut,
+                                    deps: elem.types_encountered,
+                                    requires_unsafe: UnsafetyNeeded::None,
+                                    is_placement_return_destination,
+                                    is_borrowed_by_return: false,
+                                },
+                            ));
+                        }
+                    }
+                }
+                // A `std::reference_wrapper` becomes a reference.
+                if let (
+                    Some(referent),
+                    syn::Pat::Ident(pp),
+                    TypeConversionSophistication::Regular,
+                ) = (
+                    template_argument(&pt.ty, "std::reference_wrapper"),
+                    &new_pat,
+                    sophistication,
+                ) {
+                    if !self.config.unsafe_policy.requires_cpprefs() {
+                        let is_mut = !references
+                            .const_template_arg_params
+                            .contains(&pp.ident.clone().into());
+                        let ptr: Type = if is_mut {
+                            parse_quote! { *mut #referent }
+                        } else {
+                            parse_quote! { *const #referent }
+                        };
+                        let annotated_type = self.convert_boxed_type(
+                            Box::new(ptr),
+                            ns,
+                            PointerTreatment::Reference,
+                        )?;
+                        pt.pat = Box::new(new_pat.clone());
+                        pt.ty = annotated_type.ty.clone();
+                        return Ok((
+                            FnArg::Typed(pt),
+                            ArgumentAnalysis {
+                                self_type,
+                                name: new_pat.into(),
+                                conversion: TypeConversionPolicy::new(
+                                    *annotated_type.ty,
+                                    CppConversionType::FromReferenceToReferenceWrapper,
+                                    RustConversionType::None,
+                                ),
+                                has_lifetime: true,
+                                is_mutable_reference: is_mut,
+                                deps: annotated_type.types_encountered,
+                                requires_unsafe: UnsafetyNeeded::None,
+                                is_placement_return_destination,
+                                is_borrowed_by_return: false,
+                            },
+                        ));
+                    }
+                }
                 let annotated_type = self.convert_boxed_type(pt.ty, ns, pointer_treatment)?;
                 let conversion = self.argument_conversion_details(
                     &annotated_type,
@@ -1732,6 +2687,7 @@ impl<'a> FnAnalyzer<'a> {
                 let requires_unsafe =
                     if matches!(annotated_type.kind, type_converter::TypeKind::Pointer)
                         && !is_placement_return_destination
+                        && !is_out_param
                     {
                         UnsafetyNeeded::Always
                     } else if conversion.bridge_unsafe_needed() || is_placement_return_destination {
@@ -1757,6 +2713,7 @@ impl<'a> FnAnalyzer<'a> {
                         deps: annotated_type.types_encountered,
                         requires_unsafe,
                         is_placement_return_destination,
+                        is_borrowed_by_return: false,
                     },
                 )
             }
@@ -1786,7 +2743,7 @@ impl<'a> FnAnalyzer<'a> {
         let rust_conversion_forced = force_rust_conversion.is_some();
         let ty = &*annotated_type.ty;
         if let Some(holder_id) = is_subclass_holder {
-            let subclass = SubclassName::from_holder_name(holder_id);
+            let subclass = SubclassName::from_holder_name(holder_id, self.config);
             return {
                 let ty = parse_quote! {
                     rust::Box<#holder_id>
@@ -1925,6 +2882,46 @@ impl<'a> FnAnalyzer<'a> {
     ) -> Result<ReturnTypeAnalysis, ConvertErrorFromCpp> {
         Ok(match rt {
             ReturnType::Default => ReturnTypeAnalysis::default(),
+            ReturnType::Type(rarrow, boxed_type)
+                if self.custom_type_conversion(boxed_type).is_some() =>
+            {
+                let custom = self.custom_type_conversion(boxed_type).unwrap();
+                let rust_type = custom.rust_type.clone();
+                ReturnTypeAnalysis {
+                    rt: ReturnType::Type(*rarrow, Box::new(rust_type.clone())),
+                    conversion: Some(TypeConversionPolicy::new(
+                        rust_type,
+                        CppConversionType::Custom(custom.from_cpp.clone()),
+                        RustConversionType::None,
+                    )),
+                    ..Default::default()
+                }
+            }
+            ReturnType::Type(rarrow, boxed_type)
+                if matches!(sophistication, TypeConversionSophistication::Regular)
+                    && !self.config.unsafe_policy.requires_cpprefs()
+                    && template_argument(boxed_type, "std::reference_wrapper").is_some() =>
+            {
+                // We don't know whether the referent is const, so we always
+                // return a shared reference.
+                let referent = template_argument(boxed_type, "std::reference_wrapper").unwrap();
+                let annotated_type = self.convert_boxed_type(
+                    parse_quote! { *const #referent },
+                    ns,
+                    PointerTreatment::Reference,
+                )?;
+                ReturnTypeAnalysis {
+                    rt: ReturnType::Type(*rarrow, annotated_type.ty.clone()),
+                    conversion: Some(TypeConversionPolicy::new(
+                        *annotated_type.ty,
+                        CppConversionType::FromReferenceWrapperToReference,
+                        RustConversionType::None,
+                    )),
+                    was_reference: true,
+                    deps: annotated_type.types_encountered,
+                    ..Default::default()
+                }
+            }
             ReturnType::Type(rarrow, boxed_type) => {
                 let annotated_type =
                     self.convert_boxed_type(boxed_type.clone(), ns, references.return_treatment())?;
@@ -2174,6 +3171,8 @@ impl<'a> FnAnalyzer<'a> {
                         original_name: None,
                         synthesized_this_type: None,
                         is_deleted: DeletedOrDefaulted::Neither,
+                        is_explicit: false,
+                        must_use: false,
                         add_to_trait: None,
                         synthetic_cpp: None,
                         provenance: Provenance::SynthesizedOther,
@@ -2246,7 +3245,15 @@ impl Api<FnPhase> {
                 },
                 ..
             } | Api::StringConstructor { .. }
+                | Api::StdVariant { .. }
+                | Api::StdMapIterator { .. }
+                | Api::StdTuple { .. }
+                | Api::RangeIterator { .. }
                 | Api::ConcreteType { .. }
+                | Api::Enum {
+                    cpp_definition: Some(..),
+                    ..
+                }
                 | Api::CType { .. }
                 | Api::RustSubclassFn { .. }
                 | Api::Subclass { .. }
@@ -2342,6 +3349,126 @@ impl HasFieldsAndBases for Api<FnPrePhase2> {
     }
 }
 
+/// Makes a free function which calls a converting constructor, and which
+/// we'll use to implement `From` for the constructed type.
+fn create_converting_constructor_from(
+    fun: &FuncToConvert,
+    to_type: QualifiedName,
+) -> Box<FuncToConvert> {
+    let to_type_path = to_type.to_type_path();
+    Box::new(FuncToConvert {
+        ident: make_ident(format!("{}_autocxx_from", fun.ident)),
+        inputs: fun.inputs.iter().skip(1).cloned().collect(),
+        output: parse_quote! {
+            -> #to_type_path
+        },
+        self_ty: None,
+        original_name: None,
+        synthesized_this_type: None,
+        add_to_trait: Some(TraitSynthesis::ConvertingConstructor {
+            to_type: to_type.clone(),
+        }),
+        synthetic_cpp: Some((
+            CppFunctionBody::ConvertingConstructor(to_type),
+            CppFunctionKind::Function,
+        )),
+        provenance: Provenance::SynthesizedOther,
+        ..fun.clone()
+    })
+}
+
+/// Makes a static `try_new` function which constructs the given type within
+/// a `UniquePtr` using the same arguments as the given constructor, but
+/// returns any exception as an `Err`.
+fn create_fallible_constructor_from(
+    fun: &FuncToConvert,
+    self_ty: QualifiedName,
+) -> Box<FuncToConvert> {
+    let self_ty_path = self_ty.to_type_path();
+    Box::new(FuncToConvert {
+        ident: make_ident(format!("{}_autocxx_try_new", fun.ident)),
+        inputs: fun.inputs.iter().skip(1).cloned().collect(),
+        output: parse_quote! {
+            -> root::std::unique_ptr<#self_ty_path>
+        },
+        self_ty: Some(self_ty.clone()),
+        original_name: Some("try_new".into()),
+        synthesized_this_type: None,
+        special_member: None,
+        add_to_trait: None,
+        synthetic_cpp: Some((
+            CppFunctionBody::FallibleConstructor(self_ty),
+            CppFunctionKind::Function,
+        )),
+        provenance: Provenance::SynthesizedOther,
+        is_explicit: false,
+        ..fun.clone()
+    })
+}
+
+/// Whether this is `const char*`.
+fn is_const_char_ptr(ty: &Type) -> bool {
+    match ty {
+        Type::Ptr(TypePtr {
+            const_token: Some(_),
+            elem,
+            ..
+        }) => matches!(
+            elem.as_ref(),
+            Type::Path(typ) if typ.path.segments.last().is_some_and(|seg| seg.ident == "c_char")
+        ),
+        _ => false,
+    }
+}
+
+/// If this is a specialization of the given template, such as `std::span`,
+/// its first template argument. Any `const` is recorded separately, in
+/// [`References::const_template_arg_params`].
+fn template_argument(ty: &Type, template: &str) -> Option<Box<Type>> {
+    let Type::Path(typ) = ty else {
+        return None;
+    };
+    let mut unspecialized = typ.clone();
+    let args = std::mem::take(&mut unspecialized.path.segments.last_mut()?.arguments);
+    if QualifiedName::from_type_path(&unspecialized).to_cpp_name() != template {
+        return None;
+    }
+    match args {
+        PathArguments::AngleBracketed(args) => match args.args.first()? {
+            GenericArgument::Type(elem) => Some(Box::new(elem.clone())),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The type of the 'this' parameter of a method, if any.
+fn receiver_type(fun: &FuncToConvert) -> Option<QualifiedName> {
+    fun.inputs.iter().find_map(|arg| match &**arg {
+        FnArg::Typed(PatType { pat, ty, .. }) => match (pat.as_ref(), ty.as_ref()) {
+            (Pat::Ident(pp), Type::Ptr(TypePtr { elem, .. })) if pp.ident == "this" => {
+                match elem.as_ref() {
+                    Type::Path(typ) => Some(QualifiedName::from_type_path(typ)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+/// Renames a parameter to 'this', so that we treat it as the receiver.
+fn rename_to_this(arg: &FnArg) -> FnArg {
+    let mut arg = arg.clone();
+    if let FnArg::Typed(PatType { pat, .. }) = &mut arg {
+        if let Pat::Ident(pti) = pat.as_mut() {
+            pti.ident = make_ident("this");
+        }
+    }
+    arg
+}
+
 /// Stringify a function argument for diagnostics
 fn describe_arg(arg: &FnArg) -> String {
     match arg {
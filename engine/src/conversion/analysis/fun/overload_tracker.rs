@@ -8,6 +8,9 @@
 
 use std::collections::HashMap;
 
+use autocxx_parser::OverloadNaming;
+use syn::{GenericArgument, PathArguments, Type};
+
 type Offsets = HashMap<String, usize>;
 
 /// Registry of all the overloads of a function found within a given
@@ -20,22 +23,46 @@ type Offsets = HashMap<String, usize>;
 /// If bindgen adds a suffix it will be included in 'found_name'
 /// but not 'original_name' which is an annotation added by our autocxx-bindgen
 /// fork.
-#[derive(Default)]
+/// Depending on the [`OverloadNaming`] in use, the suffix is either a
+/// number or made up from the types of the parameters.
 pub(crate) struct OverloadTracker {
+    naming: OverloadNaming,
     offset_by_name: Offsets,
     offset_by_type_and_name: HashMap<String, Offsets>,
 }
 
 impl OverloadTracker {
-    pub(crate) fn get_function_real_name(&mut self, found_name: String) -> String {
-        self.get_name(None, found_name)
+    pub(crate) fn new(naming: OverloadNaming) -> Self {
+        Self {
+            naming,
+            offset_by_name: Offsets::default(),
+            offset_by_type_and_name: HashMap::default(),
+        }
+    }
+
+    pub(crate) fn get_function_real_name(
+        &mut self,
+        found_name: String,
+        params: &[&Type],
+    ) -> String {
+        self.get_name(None, found_name, params)
     }
 
-    pub(crate) fn get_method_real_name(&mut self, type_name: &str, found_name: String) -> String {
-        self.get_name(Some(type_name), found_name)
+    pub(crate) fn get_method_real_name(
+        &mut self,
+        type_name: &str,
+        found_name: String,
+        params: &[&Type],
+    ) -> String {
+        self.get_name(Some(type_name), found_name, params)
     }
 
-    fn get_name(&mut self, type_name: Option<&str>, cpp_method_name: String) -> String {
+    fn get_name(
+        &mut self,
+        type_name: Option<&str>,
+        cpp_method_name: String,
+        params: &[&Type],
+    ) -> String {
         let registry = match type_name {
             Some(type_name) => self
                 .offset_by_type_and_name
@@ -47,31 +74,123 @@ impl OverloadTracker {
         let this_offset = *offset;
         *offset += 1;
         if this_offset == 0 {
-            cpp_method_name
-        } else {
-            format!("{cpp_method_name}{this_offset}")
+            return cpp_method_name;
+        }
+        if self.naming == OverloadNaming::ParameterTypes {
+            if let Some(suffix) = abbreviate_params(params) {
+                let candidate = format!("{cpp_method_name}_{suffix}");
+                // Two overloads can abbreviate identically (e.g. differing
+                // only in constness) in which case we fall back to a number.
+                if !registry.contains_key(&candidate) {
+                    registry.insert(candidate.clone(), 1);
+                    return candidate;
+                }
+            }
+        }
+        format!("{cpp_method_name}{this_offset}")
+    }
+}
+
+/// Build a suffix such as `u32_f64` from the parameter types of an
+/// overload, or `void` if it has none.
+fn abbreviate_params(params: &[&Type]) -> Option<String> {
+    if params.is_empty() {
+        return Some("void".into());
+    }
+    params
+        .iter()
+        .map(|ty| abbreviate_type(ty))
+        .collect::<Option<Vec<_>>>()
+        .map(|abbrevs| abbrevs.join("_"))
+}
+
+fn abbreviate_type(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Reference(r) => abbreviate_type(&r.elem),
+        Type::Ptr(p) => abbreviate_type(&p.elem).map(|elem| format!("{elem}_ptr")),
+        Type::Group(g) => abbreviate_type(&g.elem),
+        Type::Paren(p) => abbreviate_type(&p.elem),
+        Type::Path(typ) => {
+            let seg = typ.path.segments.last()?;
+            let ident = seg.ident.to_string();
+            let args: Vec<_> = match &seg.arguments {
+                PathArguments::AngleBracketed(ab) => ab
+                    .args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        GenericArgument::Type(ty) => Some(ty),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            match (ident.as_str(), args.as_slice()) {
+                // Wrappers which tell us nothing about the C++ type.
+                ("Pin" | "UniquePtr" | "CppRef" | "CppMutRef", [inner]) => abbreviate_type(inner),
+                (_, []) => Some(ident.strip_prefix("c_").unwrap_or(&ident).to_lowercase()),
+                (_, args) => {
+                    let args = args
+                        .iter()
+                        .map(|ty| abbreviate_type(ty))
+                        .collect::<Option<Vec<_>>>()?;
+                    Some(format!("{}_{}", ident.to_lowercase(), args.join("_")))
+                }
+            }
         }
+        _ => None,
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use autocxx_parser::OverloadNaming;
+    use syn::{parse_quote, Type};
+
     use super::OverloadTracker;
 
     #[test]
     fn test_by_function() {
-        let mut ot = OverloadTracker::default();
-        assert_eq!(ot.get_function_real_name("bob".into()), "bob");
-        assert_eq!(ot.get_function_real_name("bob".into()), "bob1");
-        assert_eq!(ot.get_function_real_name("bob".into()), "bob2");
+        let mut ot = OverloadTracker::new(OverloadNaming::Numeric);
+        assert_eq!(ot.get_function_real_name("bob".into(), &[]), "bob");
+        assert_eq!(ot.get_function_real_name("bob".into(), &[]), "bob1");
+        assert_eq!(ot.get_function_real_name("bob".into(), &[]), "bob2");
     }
 
     #[test]
     fn test_by_method() {
-        let mut ot = OverloadTracker::default();
-        assert_eq!(ot.get_method_real_name("Ty1", "bob".into()), "bob");
-        assert_eq!(ot.get_method_real_name("Ty1", "bob".into()), "bob1");
-        assert_eq!(ot.get_method_real_name("Ty2", "bob".into()), "bob");
-        assert_eq!(ot.get_method_real_name("Ty2", "bob".into()), "bob1");
+        let mut ot = OverloadTracker::new(OverloadNaming::Numeric);
+        assert_eq!(ot.get_method_real_name("Ty1", "bob".into(), &[]), "bob");
+        assert_eq!(ot.get_method_real_name("Ty1", "bob".into(), &[]), "bob1");
+        assert_eq!(ot.get_method_real_name("Ty2", "bob".into(), &[]), "bob");
+        assert_eq!(ot.get_method_real_name("Ty2", "bob".into(), &[]), "bob1");
+    }
+
+    #[test]
+    fn test_by_parameter_types() {
+        let mut ot = OverloadTracker::new(OverloadNaming::ParameterTypes);
+        let int: Type = parse_quote! { autocxx::c_int };
+        let double: Type = parse_quote! { f64 };
+        let bar: Type = parse_quote! { &root::Bar };
+        let bar_ptr: Type = parse_quote! { *const root::Bar };
+        let string: Type = parse_quote! { cxx::UniquePtr<cxx::CxxString> };
+        assert_eq!(ot.get_function_real_name("bob".into(), &[&int]), "bob");
+        assert_eq!(
+            ot.get_function_real_name("bob".into(), &[&double, &bar]),
+            "bob_f64_bar"
+        );
+        assert_eq!(ot.get_function_real_name("bob".into(), &[]), "bob_void");
+        assert_eq!(
+            ot.get_function_real_name("bob".into(), &[&bar_ptr]),
+            "bob_bar_ptr"
+        );
+        assert_eq!(
+            ot.get_function_real_name("bob".into(), &[&string]),
+            "bob_cxxstring"
+        );
+        // Abbreviates the same as an earlier overload.
+        assert_eq!(
+            ot.get_function_real_name("bob".into(), &[&double, &bar]),
+            "bob5"
+        );
     }
 }
@@ -70,6 +70,8 @@ pub(super) fn create_subclass_fn_wrapper(
         references: fun.references.clone(),
         add_to_trait: fun.add_to_trait.clone(),
         is_deleted: fun.is_deleted,
+        is_explicit: fun.is_explicit,
+        must_use: fun.must_use,
         synthetic_cpp: None,
         provenance: Provenance::SynthesizedOther,
         variadic: fun.variadic,
@@ -254,6 +256,8 @@ pub(super) fn create_subclass_constructor(
         self_ty: Some(cpp),
         add_to_trait: None,
         is_deleted: fun.is_deleted,
+        is_explicit: fun.is_explicit,
+        must_use: fun.must_use,
         synthetic_cpp: None,
         provenance: Provenance::SynthesizedSubclassConstructor(subclass_constructor_details),
         variadic: fun.variadic,
@@ -10,13 +10,17 @@ use indexmap::map::IndexMap as HashMap;
 use indexmap::set::IndexSet as HashSet;
 
 use autocxx_parser::IncludeCppConfig;
+use syn::{GenericArgument, PathArguments, Type};
 
 use crate::{
     conversion::{api::Api, apivec::ApiVec},
     types::QualifiedName,
 };
 
-use super::{deps::HasDependencies, fun::FnPhase};
+use super::{
+    deps::HasDependencies,
+    fun::{FnAnalysis, FnKind, FnPhase, TraitMethodDetails},
+};
 
 /// This is essentially mark-and-sweep garbage collection of the
 /// [Api]s that we've discovered. Why do we do this, you might wonder?
@@ -35,6 +39,12 @@ use super::{deps::HasDependencies, fun::FnPhase};
 ///    some methods from a given struct/class. In which case, we
 ///    don't care about the other parameter types passed into those
 ///    APIs either.
+/// Trait impls which we generate count as edges too: any type named
+/// in the impl's signature must be kept, even if nothing else refers
+/// to it. Likewise the template arguments of any concrete template
+/// instantiation we've synthesized, e.g. `T` for a `std::vector<T>`,
+/// which may not be named anywhere else, and any enums we've copied out of
+/// a class template for one of its instantiations.
 pub(crate) fn filter_apis_by_following_edges_from_allowlist(
     apis: ApiVec<FnPhase>,
     config: &IncludeCppConfig,
@@ -48,6 +58,7 @@ pub(crate) fn filter_apis_by_following_edges_from_allowlist(
         .map(Api::name)
         .cloned()
         .collect();
+    let instantiated_enums = find_instantiated_enums(&apis);
     let mut by_typename: HashMap<QualifiedName, ApiVec<FnPhase>> = HashMap::new();
     for api in apis.into_iter() {
         let tn = api.name().clone();
@@ -62,9 +73,147 @@ pub(crate) fn filter_apis_by_following_edges_from_allowlist(
         }
         if let Some(mut these_apis) = by_typename.remove(&todo) {
             todos.extend(these_apis.iter().flat_map(|api| api.deps().cloned()));
+            todos.extend(these_apis.iter().flat_map(trait_impl_deps));
+            todos.extend(these_apis.iter().flat_map(template_argument_deps));
+            todos.extend(
+                these_apis
+                    .iter()
+                    .flat_map(|api| instantiated_enum_deps(api, &instantiated_enums)),
+            );
             output.append(&mut these_apis);
         } // otherwise, probably an intrinsic e.g. uint32_t.
         done.insert(todo);
     }
     output
 }
+
+/// Types named in the signature of a trait impl we'll generate for this
+/// API, e.g. both `A` and `B` for `impl AsRef<B> for A`, along with any
+/// associated type such as the `Output` of an `Add`. Some of these
+/// may be Rust or autocxx types rather than APIs, which is harmless.
+fn trait_impl_deps(api: &Api<FnPhase>) -> Vec<QualifiedName> {
+    match api {
+        Api::Function {
+            analysis:
+                FnAnalysis {
+                    kind: FnKind::TraitMethod { details, .. },
+                    ..
+                },
+            ..
+        } => trait_details_deps(details),
+        _ => Vec::new(),
+    }
+}
+
+fn trait_details_deps(details: &TraitMethodDetails) -> Vec<QualifiedName> {
+    let mut deps = Vec::new();
+    add_type_deps(&details.trt.ty, &mut deps);
+    add_type_deps(&details.trt.trait_signature, &mut deps);
+    if let Some((_, ty)) = &details.associated_type {
+        add_type_deps(ty, &mut deps);
+    }
+    deps
+}
+
+/// Types used as template arguments within a concrete type.
+fn template_argument_deps(api: &Api<FnPhase>) -> Vec<QualifiedName> {
+    let mut deps = Vec::new();
+    if let Api::ConcreteType {
+        rs_definition: Some(rs_definition),
+        ..
+    } = api
+    {
+        add_type_deps(rs_definition, &mut deps);
+    }
+    deps
+}
+
+/// Enums copied out of a class template for one of its instantiations,
+/// keyed by the C++ name of that instantiation, e.g. `Container<int>`.
+fn find_instantiated_enums(apis: &ApiVec<FnPhase>) -> HashMap<String, Vec<QualifiedName>> {
+    let mut instantiated_enums: HashMap<String, Vec<QualifiedName>> = HashMap::new();
+    for api in apis.iter() {
+        if let Api::Enum {
+            name,
+            cpp_definition: Some(cpp_definition),
+            ..
+        } = api
+        {
+            if let Some((instantiation, _)) = cpp_definition.rsplit_once("::") {
+                instantiated_enums
+                    .entry(instantiation.to_string())
+                    .or_default()
+                    .push(name.name.clone());
+            }
+        }
+    }
+    instantiated_enums
+}
+
+/// Enums copied out of the class template of which this is a concrete
+/// instantiation.
+fn instantiated_enum_deps(
+    api: &Api<FnPhase>,
+    instantiated_enums: &HashMap<String, Vec<QualifiedName>>,
+) -> Vec<QualifiedName> {
+    match api {
+        Api::ConcreteType { cpp_definition, .. } => instantiated_enums
+            .get(cpp_definition)
+            .cloned()
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+fn add_type_deps(ty: &Type, deps: &mut Vec<QualifiedName>) {
+    match ty {
+        Type::Path(typ) => {
+            deps.push(QualifiedName::from_type_path(typ));
+            for seg in &typ.path.segments {
+                if let PathArguments::AngleBracketed(args) = &seg.arguments {
+                    for arg in &args.args {
+                        if let GenericArgument::Type(ty) = arg {
+                            add_type_deps(ty, deps);
+                        }
+                    }
+                }
+            }
+        }
+        Type::Reference(typr) => add_type_deps(&typr.elem, deps),
+        Type::Ptr(typp) => add_type_deps(&typp.elem, deps),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_quote;
+
+    use crate::{
+        conversion::{analysis::fun::TraitMethodDetails, api::TraitImplSignature},
+        types::{make_ident, QualifiedName},
+    };
+
+    use super::trait_details_deps;
+
+    #[test]
+    fn test_associated_type_deps() {
+        // Nothing in the impl's signature names Target, so only the
+        // associated type can keep it alive.
+        let details = TraitMethodDetails {
+            trt: TraitImplSignature {
+                ty: parse_quote! { Holder },
+                trait_signature: parse_quote! { ::core::ops::Deref },
+                unsafety: None,
+            },
+            avoid_self: false,
+            method_name: make_ident("deref"),
+            parameter_reordering: None,
+            trait_call_is_unsafe: false,
+            associated_type: Some((make_ident("Target"), parse_quote! { Target })),
+        };
+        let deps = trait_details_deps(&details);
+        assert!(deps.contains(&QualifiedName::new_from_cpp_name("Holder")));
+        assert!(deps.contains(&QualifiedName::new_from_cpp_name("Target")));
+    }
+}
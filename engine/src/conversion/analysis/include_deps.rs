@@ -0,0 +1,185 @@
+// Copyright 2020 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::BTreeMap;
+
+use indexmap::map::IndexMap as HashMap;
+use indexmap::set::IndexSet as HashSet;
+
+use crate::{
+    conversion::{api::Api, apivec::ApiVec},
+    types::{make_ident, QualifiedName},
+};
+
+use super::{
+    deps::HasDependencies,
+    fun::{FnAnalysis, FnKind, FnPhase},
+};
+
+/// Works out which entries of the `include_list` each generated API
+/// relies upon, so that build systems can construct minimal translation
+/// units. The result maps from the C++ name of each API (methods being
+/// named `Type::method`) to the relevant `#include`s, in the order in
+/// which they were listed.
+///
+/// `source_files` records the file in which bindgen found each item.
+/// Items which bindgen didn't locate (for example, synthesized
+/// constructors or typedefs) inherit the includes of their dependencies.
+/// Any item declared in a header which was reached only indirectly is
+/// reported as depending upon the whole `include_list`, since we can't
+/// tell which entry pulled it in.
+pub(crate) fn find_include_dependencies(
+    apis: &ApiVec<FnPhase>,
+    source_files: &HashMap<QualifiedName, String>,
+    include_list: &[String],
+) -> BTreeMap<String, Vec<String>> {
+    let apis_by_name: HashMap<&QualifiedName, Vec<&Api<FnPhase>>> =
+        apis.iter().fold(HashMap::new(), |mut map, api| {
+            map.entry(api.name()).or_default().push(api);
+            map
+        });
+    let mut finder = IncludeFinder {
+        apis_by_name,
+        source_files,
+        include_list,
+        cache: HashMap::new(),
+    };
+    let mut results: BTreeMap<String, HashSet<usize>> = BTreeMap::new();
+    for api in apis.iter() {
+        let name = match api {
+            Api::IgnoredItem { .. } => continue,
+            Api::Function {
+                analysis: FnAnalysis {
+                    kind, rust_name, ..
+                },
+                ..
+            } => match kind {
+                FnKind::Function => {
+                    QualifiedName::new(api.name().get_namespace(), make_ident(rust_name))
+                        .to_cpp_name()
+                }
+                FnKind::Method { impl_for, .. } => {
+                    format!("{}::{}", impl_for.to_cpp_name(), rust_name)
+                }
+                // Trait implementations aren't items in their own right.
+                FnKind::TraitMethod { .. } => continue,
+            },
+            _ => api.name().to_cpp_name(),
+        };
+        let includes = finder.includes_for_api(api);
+        if !includes.is_empty() {
+            results.entry(name).or_default().extend(includes);
+        }
+    }
+    results
+        .into_iter()
+        .map(|(name, includes)| {
+            let mut includes: Vec<_> = includes.into_iter().collect();
+            includes.sort_unstable();
+            (
+                name,
+                includes
+                    .into_iter()
+                    .map(|idx| include_list[idx].clone())
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+struct IncludeFinder<'a> {
+    apis_by_name: HashMap<&'a QualifiedName, Vec<&'a Api<FnPhase>>>,
+    source_files: &'a HashMap<QualifiedName, String>,
+    include_list: &'a [String],
+    /// Indices into `include_list` for each name we've already examined.
+    cache: HashMap<QualifiedName, HashSet<usize>>,
+}
+
+impl<'a> IncludeFinder<'a> {
+    fn includes_for_api(&mut self, api: &Api<FnPhase>) -> HashSet<usize> {
+        let key = match api {
+            // Functions are recorded under the name bindgen gave them,
+            // before any overload renaming.
+            Api::Function { fun, .. } => {
+                QualifiedName::new(api.name().get_namespace(), fun.ident.clone())
+            }
+            _ => api.name().clone(),
+        };
+        match self.source_files.get(&key) {
+            Some(file) => self.includes_for_file(file),
+            None => {
+                let mut includes = HashSet::new();
+                for dep in api.deps() {
+                    includes.extend(self.includes_for_name(dep));
+                }
+                includes
+            }
+        }
+    }
+
+    fn includes_for_name(&mut self, name: &QualifiedName) -> HashSet<usize> {
+        if let Some(includes) = self.cache.get(name) {
+            return includes.clone();
+        }
+        // Insert a placeholder to avoid infinite recursion through
+        // mutually dependent types.
+        self.cache.insert(name.clone(), HashSet::new());
+        let apis = self.apis_by_name.get(name).cloned().unwrap_or_default();
+        let mut includes = HashSet::new();
+        for api in apis {
+            includes.extend(self.includes_for_api(api));
+        }
+        self.cache.insert(name.clone(), includes.clone());
+        includes
+    }
+
+    fn includes_for_file(&self, file: &str) -> HashSet<usize> {
+        match find_include_for_file(file, self.include_list) {
+            Some(idx) => [idx].into_iter().collect(),
+            None => (0..self.include_list.len()).collect(),
+        }
+    }
+}
+
+/// Finds the `include_list` entry which names a given file: that is, the
+/// longest entry which matches the end of the path.
+fn find_include_for_file(file: &str, include_list: &[String]) -> Option<usize> {
+    let file = file.replace('\\', "/");
+    include_list
+        .iter()
+        .enumerate()
+        .filter(|(_, include)| {
+            let include = include.replace('\\', "/");
+            file == include || file.ends_with(&format!("/{include}"))
+        })
+        .max_by_key(|(_, include)| include.len())
+        .map(|(idx, _)| idx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_include_for_file;
+
+    #[test]
+    fn test_find_include_for_file() {
+        let include_list = vec!["input.h".to_string(), "sub/input.h".to_string()];
+        assert_eq!(
+            find_include_for_file("/tmp/foo/input.h", &include_list),
+            Some(0)
+        );
+        assert_eq!(
+            find_include_for_file("/tmp/foo/sub/input.h", &include_list),
+            Some(1)
+        );
+        assert_eq!(
+            find_include_for_file("C:\\foo\\sub\\input.h", &include_list),
+            Some(1)
+        );
+        assert_eq!(find_include_for_file("/tmp/myinput.h", &include_list), None);
+    }
+}
@@ -8,18 +8,23 @@
 
 pub(crate) mod abstract_types;
 pub(crate) mod allocators;
+pub(crate) mod base_field_accessors;
 pub(crate) mod casts;
 pub(crate) mod constructor_deps;
 pub(crate) mod ctypes;
+pub(crate) mod cxx_free;
 pub(crate) mod deps;
 mod depth_first;
 mod doc_label;
 pub(crate) mod fun;
 pub(crate) mod gc;
+pub(crate) mod include_deps;
 mod name_check;
 pub(crate) mod pod; // hey, that rhymes
 pub(crate) mod remove_ignored;
 mod replace_hopeless_typedef_targets;
+pub(crate) mod shared_from_this;
+pub(crate) mod swaps;
 pub(crate) mod tdef;
 mod type_converter;
 
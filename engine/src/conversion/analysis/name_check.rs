@@ -50,7 +50,7 @@ pub(crate) fn check_names(apis: ApiVec<FnPhase>) -> ApiVec<FnPhase> {
             Ok(Box::new(std::iter::once(api)))
         }
         Api::Subclass {
-            name: SubclassName(ref name),
+            name: SubclassName(ref name, _),
             ref superclass,
         } => {
             validate_all_segments_ok_for_cxx(name.name.segment_iter())?;
@@ -67,6 +67,10 @@ pub(crate) fn check_names(apis: ApiVec<FnPhase>) -> ApiVec<FnPhase> {
         }
         Api::ConcreteType { .. }
         | Api::CType { .. }
+        | Api::StdVariant { .. }
+        | Api::StdMapIterator { .. }
+        | Api::StdTuple { .. }
+        | Api::RangeIterator { .. }
         | Api::StringConstructor { .. }
         | Api::RustType { .. }
         | Api::RustSubclassFn { .. }
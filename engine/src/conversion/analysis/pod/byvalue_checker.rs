@@ -12,6 +12,7 @@ use crate::{
     conversion::{
         analysis::tdef::TypedefPhase,
         api::{Api, TypedefKind},
+        convert_error::SkippedItems,
     },
     types::{Namespace, QualifiedName},
 };
@@ -72,9 +73,12 @@ impl ByValueChecker {
 
     /// Scan APIs to work out which are by-value safe. Constructs a [ByValueChecker]
     /// that others can use to query the results.
+    /// Any `generate_pod!` request which can't be satisfied is recorded
+    /// in `skipped`, and the type is instead treated as non-POD.
     pub(crate) fn new_from_apis(
         apis: &ApiVec<TypedefPhase>,
         config: &IncludeCppConfig,
+        skipped: &mut SkippedItems,
     ) -> Result<ByValueChecker, ConvertErrorFromCpp> {
         let mut byvalue_checker = ByValueChecker::new();
         for blocklisted in config.get_blocklist() {
@@ -134,14 +138,16 @@ impl ByValueChecker {
                 byvalue_checker.ingest_struct(&details.item, api.name().get_namespace())
             }
         }
-        let pod_requests = config
-            .get_pod_requests()
-            .iter()
-            .map(|ty| QualifiedName::new_from_cpp_name(ty))
-            .collect();
-        byvalue_checker
-            .satisfy_requests(pod_requests)
-            .map_err(ConvertErrorFromCpp::UnsafePodType)?;
+        for ty in config.get_pod_requests() {
+            if let Err(err) =
+                byvalue_checker.satisfy_requests(vec![QualifiedName::new_from_cpp_name(ty)])
+            {
+                let err = format!(
+                    "{err} To use {ty} anyway, ask for it with generate! rather than generate_pod!, so that it's only ever held by reference."
+                );
+                skipped.skip(ty.clone(), ConvertErrorFromCpp::UnsafePodType(err))?;
+            }
+        }
         Ok(byvalue_checker)
     }
 
@@ -150,7 +156,7 @@ impl ByValueChecker {
         let tyname = QualifiedName::new(ns, def.ident.clone().into());
         let mut field_safety_problem = PodState::SafeToBePod;
         let fieldlist = Self::get_field_types(def);
-        for ty_id in &fieldlist {
+        for (field, ty_id) in &fieldlist {
             match self.results.get(ty_id) {
                 None if ty_id.get_final_item() == "__BindgenUnionField" => {
                     field_safety_problem = PodState::UnsafeToBePod(format!(
@@ -166,13 +172,13 @@ impl ByValueChecker {
                 }
                 None => {
                     field_safety_problem = PodState::UnsafeToBePod(format!(
-                        "Type {tyname} could not be POD because its dependent type {ty_id} isn't known"
+                        "Type {tyname} could not be POD because its field {field} has type {ty_id}, which isn't known."
                     ));
                     break;
                 }
                 Some(deets) => {
                     if let PodState::UnsafeToBePod(reason) = &deets.state {
-                        let new_reason = format!("Type {tyname} could not be POD because its dependent type {ty_id} isn't safe to be POD. Because: {reason}");
+                        let new_reason = format!("Type {tyname} could not be POD because its field {field} has type {ty_id}, which isn't safe to be POD. Because: {reason}");
                         field_safety_problem = PodState::UnsafeToBePod(new_reason);
                         break;
                     }
@@ -185,7 +191,7 @@ impl ByValueChecker {
             field_safety_problem = PodState::UnsafeToBePod(reason);
         }
         let mut my_details = StructDetails::new(field_safety_problem);
-        my_details.dependent_structs = fieldlist;
+        my_details.dependent_structs = fieldlist.into_iter().map(|(_, ty_id)| ty_id).collect();
         self.results.insert(tyname, my_details);
     }
 
@@ -252,13 +258,18 @@ impl ByValueChecker {
     /// This is a miniature version of the analysis in `super::get_struct_field_types`.
     /// It would be nice to unify them. However, this version only cares about spotting
     /// fields which may be non-POD, so can largely concern itself with just `Type::Path`
-    /// fields.
-    fn get_field_types(def: &ItemStruct) -> Vec<QualifiedName> {
+    /// fields. Each is returned along with the name of the field, for use in
+    /// diagnostics.
+    fn get_field_types(def: &ItemStruct) -> Vec<(String, QualifiedName)> {
         let mut results = Vec::new();
-        for f in &def.fields {
+        for (i, f) in def.fields.iter().enumerate() {
             let fty = &f.ty;
             if let Type::Path(p) = fty {
-                results.push(QualifiedName::from_type_path(p));
+                let field = match &f.ident {
+                    Some(id) => id.to_string(),
+                    None => i.to_string(),
+                };
+                results.push((field, QualifiedName::from_type_path(p)));
             }
             // TODO handle anything else which bindgen might spit out, e.g. arrays?
         }
@@ -358,4 +369,37 @@ mod tests {
         bvc.ingest_struct(&t, &Namespace::new());
         assert!(bvc.satisfy_requests(vec![t_id]).is_err());
     }
+
+    #[test]
+    fn test_nested_cxxstring_reason() {
+        let mut bvc = ByValueChecker::new();
+        let t: ItemStruct = parse_quote! {
+            struct Foo {
+                a: i32,
+                name: cxx::CxxString,
+            }
+        };
+        bvc.ingest_struct(&t, &Namespace::new());
+        let t: ItemStruct = parse_quote! {
+            struct Bar {
+                b: i64,
+                foo: Foo,
+            }
+        };
+        let t_id = ty_from_ident(&t.ident);
+        bvc.ingest_struct(&t, &Namespace::new());
+        let err = bvc.satisfy_requests(vec![t_id]).unwrap_err();
+        assert!(
+            err.starts_with(
+                "Type Bar could not be POD because its field foo has type Foo, which isn't safe to be POD."
+            ),
+            "{err}"
+        );
+        assert!(
+            err.contains(
+                "Type Foo could not be POD because its field name has type cxx::CxxString, which isn't safe to be POD."
+            ),
+            "{err}"
+        );
+    }
 }
@@ -13,14 +13,14 @@ use indexmap::set::IndexSet as HashSet;
 
 use autocxx_parser::IncludeCppConfig;
 use byvalue_checker::ByValueChecker;
-use syn::{ItemStruct, Type, Visibility};
+use syn::{Field, GenericArgument, Ident, ItemStruct, PathArguments, Type, TypePath, Visibility};
 
 use crate::{
     conversion::{
         analysis::type_converter::{self, add_analysis, TypeConversionContext, TypeConverter},
         api::{AnalysisPhase, Api, ApiName, NullPhase, StructDetails, TypeKind},
         apivec::ApiVec,
-        convert_error::{ConvertErrorWithContext, ErrorContext},
+        convert_error::{ConvertErrorWithContext, ErrorContext, SkippedItems},
         error_reporter::convert_apis,
         parse::BindgenSemanticAttributes,
         ConvertErrorFromCpp,
@@ -37,6 +37,18 @@ pub(crate) struct FieldInfo {
     pub(crate) type_kind: type_converter::TypeKind,
 }
 
+/// A public data member which a struct inherits from one of its bases,
+/// for which we'll generate accessors as requested by
+/// `base_field_accessors!`.
+#[derive(std::fmt::Debug)]
+pub(crate) struct BaseFieldAccessor {
+    /// The chain of bindgen `_base` fields leading from the derived
+    /// struct to the base which declares this field.
+    pub(crate) base_path: Vec<Ident>,
+    pub(crate) field: Ident,
+    pub(crate) ty: Type,
+}
+
 #[derive(std::fmt::Debug)]
 pub(crate) struct PodAnalysis {
     pub(crate) kind: TypeKind,
@@ -55,6 +67,7 @@ pub(crate) struct PodAnalysis {
     pub(crate) field_info: Vec<FieldInfo>,
     pub(crate) is_generic: bool,
     pub(crate) in_anonymous_namespace: bool,
+    pub(crate) base_field_accessors: Vec<BaseFieldAccessor>,
 }
 
 #[derive(std::fmt::Debug)]
@@ -74,12 +87,16 @@ impl AnalysisPhase for PodPhase {
 pub(crate) fn analyze_pod_apis(
     apis: ApiVec<TypedefPhase>,
     config: &IncludeCppConfig,
+    skipped: &mut SkippedItems,
 ) -> Result<ApiVec<PodPhase>, ConvertErrorFromCpp> {
     // This next line will return an error if any of the 'generate_pod'
     // directives from the user can't be met because, for instance,
     // a type contains a std::string or some other type which can't be
-    // held safely by value in Rust.
-    let byvalue_checker = ByValueChecker::new_from_apis(&apis, config)?;
+    // held safely by value in Rust. (Unless we're in resilient mode, in
+    // which case such types are merely skipped.)
+    let byvalue_checker = ByValueChecker::new_from_apis(&apis, config, skipped)?;
+    let mut base_field_accessors =
+        find_base_field_accessors(&apis, config, &byvalue_checker, skipped)?;
     let mut extra_apis = ApiVec::new();
     let mut type_converter = TypeConverter::new(config, &apis);
     let mut results = ApiVec::new();
@@ -92,6 +109,7 @@ pub(crate) fn analyze_pod_apis(
                 &byvalue_checker,
                 &mut type_converter,
                 &mut extra_apis,
+                &mut base_field_accessors,
                 name,
                 details,
                 config,
@@ -113,6 +131,7 @@ pub(crate) fn analyze_pod_apis(
                 &byvalue_checker,
                 &mut type_converter,
                 &mut more_extra_apis,
+                &mut base_field_accessors,
                 name,
                 details,
                 config,
@@ -128,16 +147,22 @@ pub(crate) fn analyze_pod_apis(
 fn analyze_enum(
     name: ApiName,
     mut item: crate::minisyn::ItemEnum,
+    cpp_definition: Option<String>,
 ) -> Result<Box<dyn Iterator<Item = Api<PodPhase>>>, ConvertErrorWithContext> {
     let metadata = BindgenSemanticAttributes::new_retaining_others(&mut item.attrs);
     metadata.check_for_fatal_attrs(&name.name.get_final_ident())?;
-    Ok(Box::new(std::iter::once(Api::Enum { name, item })))
+    Ok(Box::new(std::iter::once(Api::Enum {
+        name,
+        item,
+        cpp_definition,
+    })))
 }
 
 fn analyze_struct(
     byvalue_checker: &ByValueChecker,
     type_converter: &mut TypeConverter,
     extra_apis: &mut ApiVec<NullPhase>,
+    base_field_accessors: &mut HashMap<QualifiedName, Vec<BaseFieldAccessor>>,
     name: ApiName,
     mut details: Box<StructDetails>,
     config: &IncludeCppConfig,
@@ -177,10 +202,12 @@ fn analyze_struct(
     } else {
         TypeKind::NonPod
     };
+    let crtp_bases = get_crtp_bases(&details.item, &name.name);
     let castable_bases = bases
         .iter()
         .filter(|(_, is_public)| **is_public)
         .map(|(base, _)| base)
+        .filter(|base| !crtp_bases.contains(*base))
         .filter(|base| config.is_on_allowlist(&base.to_cpp_name()))
         .cloned()
         .collect();
@@ -189,6 +216,7 @@ fn analyze_struct(
         .name
         .ns_segment_iter()
         .any(|ns| ns.starts_with("_bindgen_mod"));
+    let base_field_accessors = base_field_accessors.remove(&name.name).unwrap_or_default();
     Ok(Box::new(std::iter::once(Api::Struct {
         name,
         details,
@@ -201,10 +229,137 @@ fn analyze_struct(
             field_info,
             is_generic,
             in_anonymous_namespace,
+            base_field_accessors,
         },
     })))
 }
 
+/// For each struct listed in `base_field_accessors!`, walk its base
+/// classes to find the public data members it inherits.
+fn find_base_field_accessors(
+    apis: &ApiVec<TypedefPhase>,
+    config: &IncludeCppConfig,
+    byvalue_checker: &ByValueChecker,
+    skipped: &mut SkippedItems,
+) -> Result<HashMap<QualifiedName, Vec<BaseFieldAccessor>>, ConvertErrorFromCpp> {
+    let structs: HashMap<&QualifiedName, &ItemStruct> = apis
+        .iter()
+        .filter_map(|api| match api {
+            Api::Struct { details, .. } => Some((api.name(), &*details.item)),
+            _ => None,
+        })
+        .collect();
+    let mut results = HashMap::new();
+    for cpp_name in config.base_field_accessors() {
+        match find_base_field_accessors_for(cpp_name, &structs, byvalue_checker) {
+            Ok((name, accessors)) => {
+                results.insert(name, accessors);
+            }
+            Err(err) => skipped.skip(cpp_name.to_string(), err)?,
+        }
+    }
+    Ok(results)
+}
+
+fn find_base_field_accessors_for(
+    cpp_name: &str,
+    structs: &HashMap<&QualifiedName, &ItemStruct>,
+    byvalue_checker: &ByValueChecker,
+) -> Result<(QualifiedName, Vec<BaseFieldAccessor>), ConvertErrorFromCpp> {
+    let Some((name, item)) = structs
+        .iter()
+        .find(|(name, _)| name.to_cpp_name() == cpp_name)
+    else {
+        return Err(ConvertErrorFromCpp::BaseFieldAccessorsNotFound(
+            cpp_name.to_string(),
+        ));
+    };
+    // We generate the accessors in a plain `impl` block, and reach the
+    // fields directly, so we need the struct's Rust layout to be real.
+    if !byvalue_checker.is_pod(name) || !item.generics.params.is_empty() {
+        return Err(ConvertErrorFromCpp::BaseFieldAccessorsNotPod(
+            cpp_name.to_string(),
+        ));
+    }
+    // Fields declared in the derived class hide those of the same name
+    // in its bases.
+    let mut seen: HashSet<String> = item
+        .fields
+        .iter()
+        .filter_map(|f| f.ident.as_ref().map(|id| id.to_string()))
+        .collect();
+    let mut accessors = Vec::new();
+    add_inherited_fields(
+        item,
+        structs,
+        &mut Vec::new(),
+        &mut HashSet::from([(*name).clone()]),
+        &mut seen,
+        &mut accessors,
+    )
+    .map_err(|base| {
+        ConvertErrorFromCpp::BaseFieldAccessorsUnknownBase(cpp_name.to_string(), base.to_cpp_name())
+    })?;
+    if accessors.is_empty() {
+        return Err(ConvertErrorFromCpp::BaseFieldAccessorsNoFields(
+            cpp_name.to_string(),
+        ));
+    }
+    Ok(((*name).clone(), accessors))
+}
+
+/// Adds accessors for the public fields of each of the public bases of
+/// `item`, and of their bases in turn. Fails with the name of any such
+/// base whose fields we can't be sure of.
+fn add_inherited_fields(
+    item: &ItemStruct,
+    structs: &HashMap<&QualifiedName, &ItemStruct>,
+    base_path: &mut Vec<Ident>,
+    visited: &mut HashSet<QualifiedName>,
+    seen: &mut HashSet<String>,
+    accessors: &mut Vec<BaseFieldAccessor>,
+) -> Result<(), QualifiedName> {
+    for (base_field, typ) in base_fields(item) {
+        // A private base's fields aren't accessible, even if public
+        // within the base.
+        if !matches!(base_field.vis, Visibility::Public(_)) {
+            continue;
+        }
+        let base_name = QualifiedName::from_type_path(typ);
+        // Template arguments are erased from base names, so we can't know
+        // the fields of a base which instantiates a template, nor tell
+        // apart two bases which instantiate the same one.
+        let base = match structs.get(&base_name) {
+            Some(base) if base.generics.params.is_empty() && !visited.contains(&base_name) => base,
+            _ => return Err(base_name),
+        };
+        visited.insert(base_name);
+        base_path.push(base_field.ident.clone().unwrap());
+        for bf in &base.fields {
+            let Some(id) = &bf.ident else {
+                continue;
+            };
+            let id_str = id.to_string();
+            if is_base_field(bf)
+                || id_str.starts_with("__bindgen_padding")
+                || id_str == "vtable_"
+                || !matches!(bf.vis, Visibility::Public(_))
+                || !seen.insert(id_str)
+            {
+                continue;
+            }
+            accessors.push(BaseFieldAccessor {
+                base_path: base_path.clone(),
+                field: id.clone(),
+                ty: bf.ty.clone(),
+            });
+        }
+        add_inherited_fields(base, structs, base_path, visited, seen, accessors)?;
+        base_path.pop();
+    }
+    Ok(())
+}
+
 fn get_struct_field_types(
     type_converter: &mut TypeConverter,
     ns: &Namespace,
@@ -258,20 +413,72 @@ fn get_struct_field_types(
     convert_errors
 }
 
-/// Map to whether the bases are public.
+/// Map to whether the bases are public. bindgen makes the field for a
+/// base `pub` only if it's inherited publicly, so a private or protected
+/// base still contributes to the layout of the derived type, but its
+/// interface isn't exposed through it.
+/// Whether this is one of the fields by which bindgen represents a
+/// struct's base classes, which it names `_base`, `_base_1` and so on.
+fn is_base_field(f: &Field) -> bool {
+    f.ident
+        .as_ref()
+        .map_or(false, |id| id.to_string().starts_with("_base"))
+}
+
+/// The fields representing a struct's base classes, along with the type
+/// of each base.
+fn base_fields(item: &ItemStruct) -> impl Iterator<Item = (&Field, &TypePath)> {
+    item.fields.iter().filter_map(|f| match &f.ty {
+        Type::Path(typ) if is_base_field(f) => Some((f, typ)),
+        _ => None,
+    })
+}
+
 fn get_bases(item: &ItemStruct) -> HashMap<QualifiedName, bool> {
-    item.fields
-        .iter()
-        .filter_map(|f| {
-            let is_public = matches!(f.vis, Visibility::Public(_));
-            match &f.ty {
-                Type::Path(typ) => f
-                    .ident
-                    .as_ref()
-                    .filter(|id| id.to_string().starts_with("_base"))
-                    .map(|_| (QualifiedName::from_type_path(typ), is_public)),
-                _ => None,
-            }
-        })
+    let mut bases = HashMap::new();
+    for (f, typ) in base_fields(item) {
+        let is_public = matches!(f.vis, Visibility::Public(_));
+        // Template arguments are erased from base names, so two different
+        // bases may have the same name here, and one of them may be
+        // private. Either way, we can't treat the name as a public base.
+        bases
+            .entry(QualifiedName::from_type_path(typ))
+            .and_modify(|was_public| *was_public = false)
+            .or_insert(is_public);
+    }
+    bases
+}
+
+/// Bases which are instantiations of a template with the derived struct
+/// itself as an argument, as in the curiously recurring template
+/// pattern (`class Derived : public Base<Derived>`). We know such a base
+/// only by its template name, so we mustn't try to cast to it.
+fn get_crtp_bases(item: &ItemStruct, derived: &QualifiedName) -> HashSet<QualifiedName> {
+    base_fields(item)
+        .filter(|(_, typ)| template_args_mention(typ, derived))
+        .map(|(_, typ)| QualifiedName::from_type_path(typ))
         .collect()
 }
+
+/// Whether any template argument within this type path, however
+/// deeply nested, names the given type.
+fn template_args_mention(typ: &TypePath, target: &QualifiedName) -> bool {
+    typ.path.segments.iter().any(|seg| match &seg.arguments {
+        PathArguments::AngleBracketed(args) => args.args.iter().any(|arg| match arg {
+            GenericArgument::Type(ty) => type_mentions(ty, target),
+            _ => false,
+        }),
+        _ => false,
+    })
+}
+
+fn type_mentions(ty: &Type, target: &QualifiedName) -> bool {
+    match ty {
+        Type::Path(typ) => {
+            QualifiedName::from_type_path(typ) == *target || template_args_mention(typ, target)
+        }
+        Type::Ptr(ptr) => type_mentions(&ptr.elem, target),
+        Type::Reference(reference) => type_mentions(&reference.elem, target),
+        _ => false,
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Code to create `shared_from_this` methods for types deriving from
+//! `std::enable_shared_from_this`.
+
+use syn::{parse_quote, punctuated::Punctuated, token::Comma, FnArg, ReturnType};
+
+use crate::{
+    conversion::{
+        api::{
+            Api, ApiName, CppVisibility, DeletedOrDefaulted, FuncToConvert, Provenance, References,
+            Virtualness,
+        },
+        apivec::ApiVec,
+    },
+    minisyn::minisynize_punctuated,
+    types::{make_ident, QualifiedName},
+};
+
+use super::{
+    doc_label::make_doc_attrs,
+    fun::function_wrapper::{CppFunctionBody, CppFunctionKind},
+    pod::PodPhase,
+};
+
+/// The C++ name of the base which enables `shared_from_this`. bindgen
+/// erases its template argument when it names the base.
+const ENABLE_SHARED_FROM_THIS: &str = "std::enable_shared_from_this";
+
+pub(crate) fn add_shared_from_this(apis: ApiVec<PodPhase>) -> ApiVec<PodPhase> {
+    let base = QualifiedName::new_from_cpp_name(ENABLE_SHARED_FROM_THIS);
+    apis.into_iter()
+        .flat_map(|api| {
+            let mut resultant_apis = match api {
+                Api::Struct {
+                    ref name,
+                    ref analysis,
+                    ..
+                } if analysis.bases.contains(&base) && !analysis.is_generic => {
+                    vec![create_shared_from_this(&name.name)]
+                }
+                _ => Vec::new(),
+            };
+            resultant_apis.push(api);
+            resultant_apis.into_iter()
+        })
+        .collect()
+}
+
+/// Makes a `shared_from_this` method. The base itself is a template,
+/// so bindgen never tells us about its methods. Nothing stops Rust code
+/// calling this on an object which isn't owned by a `std::shared_ptr`,
+/// where the real `shared_from_this` would throw, so the generated C++
+/// returns null in that case instead. We take `&self`, since that's all
+/// a `cxx::SharedPtr` gives access to, and so have to cast away the
+/// constness of the resulting pointer.
+fn create_shared_from_this(ty_name: &QualifiedName) -> Api<PodPhase> {
+    let typ = ty_name.to_type_path();
+    let inputs: Punctuated<FnArg, Comma> = parse_quote! {
+        this: *const #typ
+    };
+    let output: ReturnType = parse_quote! {
+        -> root::std::shared_ptr<#typ>
+    };
+    let ident = make_ident(format!(
+        "{}_autocxx_shared_from_this",
+        ty_name.get_final_item()
+    ));
+    Api::Function {
+        name: ApiName::new_with_cpp_name(
+            ty_name.get_namespace(),
+            ident.clone(),
+            Some("shared_from_this".into()),
+        ),
+        fun: Box::new(FuncToConvert {
+            ident,
+            doc_attrs: make_doc_attrs(
+                "Returns a shared pointer sharing ownership of this object, or a null pointer if it is not already owned by one."
+                    .into(),
+            )
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+            inputs: minisynize_punctuated(inputs),
+            output: output.into(),
+            vis: parse_quote! { pub },
+            virtualness: Virtualness::None,
+            cpp_vis: CppVisibility::Public,
+            special_member: None,
+            unused_template_param: false,
+            references: References {
+                ref_params: std::iter::once(make_ident("this")).collect(),
+                ..Default::default()
+            },
+            original_name: None,
+            self_ty: None,
+            synthesized_this_type: None,
+            add_to_trait: None,
+            synthetic_cpp: Some((
+                CppFunctionBody::SharedFromThis(ty_name.clone()),
+                CppFunctionKind::Function,
+            )),
+            is_deleted: DeletedOrDefaulted::Neither,
+            is_explicit: false,
+            must_use: false,
+            provenance: Provenance::SynthesizedOther,
+            variadic: false,
+        }),
+        analysis: (),
+    }
+}
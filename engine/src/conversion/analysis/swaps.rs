@@ -0,0 +1,96 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Code to create `swap` methods for types listed using `swappable!`.
+
+use autocxx_parser::IncludeCppConfig;
+use syn::{parse_quote, punctuated::Punctuated, token::Comma, FnArg, ReturnType};
+
+use crate::{
+    conversion::{
+        api::{
+            Api, ApiName, CppVisibility, DeletedOrDefaulted, FuncToConvert, Provenance, References,
+            Virtualness,
+        },
+        apivec::ApiVec,
+    },
+    minisyn::minisynize_punctuated,
+    types::{make_ident, QualifiedName},
+};
+
+use super::{
+    doc_label::make_doc_attrs,
+    fun::function_wrapper::{CppFunctionBody, CppFunctionKind},
+    pod::PodPhase,
+};
+
+pub(crate) fn add_swaps(apis: ApiVec<PodPhase>, config: &IncludeCppConfig) -> ApiVec<PodPhase> {
+    apis.into_iter()
+        .flat_map(|api| {
+            let mut resultant_apis = match api {
+                Api::Struct { ref name, .. } if config.is_swappable(&name.name.to_cpp_name()) => {
+                    vec![create_swap(&name.name)]
+                }
+                _ => Vec::new(),
+            };
+            resultant_apis.push(api);
+            resultant_apis.into_iter()
+        })
+        .collect()
+}
+
+/// Makes a method which exchanges the contents of two instances of the
+/// type using the C++ `swap`, so that any custom `swap` found by
+/// argument-dependent lookup is used in preference to `std::swap`.
+fn create_swap(ty_name: &QualifiedName) -> Api<PodPhase> {
+    let typ = ty_name.to_type_path();
+    let inputs: Punctuated<FnArg, Comma> = parse_quote! {
+        this: *mut #typ, other: *mut #typ
+    };
+    let ident = make_ident(format!("{}_autocxx_swap", ty_name.get_final_item()));
+    Api::Function {
+        name: ApiName::new_with_cpp_name(
+            ty_name.get_namespace(),
+            ident.clone(),
+            Some("swap".into()),
+        ),
+        fun: Box::new(FuncToConvert {
+            ident,
+            doc_attrs: make_doc_attrs(
+                "Exchanges the contents of this object with another, using the C++ `swap`.".into(),
+            )
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+            inputs: minisynize_punctuated(inputs),
+            output: ReturnType::Default.into(),
+            vis: parse_quote! { pub },
+            virtualness: Virtualness::None,
+            cpp_vis: CppVisibility::Public,
+            special_member: None,
+            unused_template_param: false,
+            references: References {
+                ref_params: [make_ident("this"), make_ident("other")]
+                    .into_iter()
+                    .collect(),
+                ..Default::default()
+            },
+            original_name: None,
+            self_ty: None,
+            synthesized_this_type: None,
+            add_to_trait: None,
+            synthetic_cpp: Some((CppFunctionBody::Swap, CppFunctionKind::Function)),
+            is_deleted: DeletedOrDefaulted::Neither,
+            is_explicit: false,
+            must_use: false,
+            provenance: Provenance::SynthesizedOther,
+            variadic: false,
+        }),
+        analysis: (),
+    }
+}
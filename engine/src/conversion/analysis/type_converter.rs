@@ -737,6 +737,10 @@ pub(crate) fn find_types<A: AnalysisPhase>(apis: &ApiVec<A>) -> HashSet<Qualifie
             | Api::ExternCppType { .. }
             | Api::RustType { .. } => Some(api.name()),
             Api::StringConstructor { .. }
+            | Api::StdVariant { .. }
+            | Api::StdMapIterator { .. }
+            | Api::StdTuple { .. }
+            | Api::RangeIterator { .. }
             | Api::Function { .. }
             | Api::Const { .. }
             | Api::CType { .. }
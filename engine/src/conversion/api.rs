@@ -11,8 +11,10 @@ use std::fmt::Display;
 
 use syn::{
     parse::Parse,
+    parse_quote,
     punctuated::Punctuated,
     token::{Comma, Unsafe},
+    TypePath,
 };
 
 use crate::minisyn::{
@@ -20,7 +22,7 @@ use crate::minisyn::{
     Pat, ReturnType, Type, Visibility,
 };
 use crate::types::{make_ident, Namespace, QualifiedName};
-use autocxx_parser::{ExternCppType, RustFun, RustPath};
+use autocxx_parser::{ExternCppType, IncludeCppConfig, RustFun, RustPath};
 use itertools::Itertools;
 use quote::ToTokens;
 
@@ -56,7 +58,7 @@ pub(crate) enum CppVisibility {
 }
 
 /// Details about a C++ struct.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct StructDetails {
     pub(crate) item: ItemStruct,
     pub(crate) layout: Option<Layout>,
@@ -103,7 +105,53 @@ pub(crate) enum CastMutability {
     MutToMut,
 }
 
-/// Indicates that this function (which is synthetic) should
+/// A C++ binary arithmetic operator, each of which corresponds to one
+/// of the traits in `std::ops`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum ArithmeticOperator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl ArithmeticOperator {
+    pub(crate) fn cpp_symbol(&self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Rem => "%",
+        }
+    }
+
+    /// The name of the corresponding trait in `std::ops`.
+    pub(crate) fn trait_name(&self) -> &'static str {
+        match self {
+            Self::Add => "Add",
+            Self::Sub => "Sub",
+            Self::Mul => "Mul",
+            Self::Div => "Div",
+            Self::Rem => "Rem",
+        }
+    }
+
+    /// The name of the method of the corresponding trait, which is
+    /// also what we call the operator if we can't implement that trait.
+    pub(crate) fn method_name(&self) -> &'static str {
+        match self {
+            Self::Add => "add",
+            Self::Sub => "sub",
+            Self::Mul => "mul",
+            Self::Div => "div",
+            Self::Rem => "rem",
+        }
+    }
+}
+
+/// Indicates that this function (which is usually synthetic) should
 /// be a trait implementation rather than a method or free function.
 #[derive(Clone, Debug)]
 pub(crate) enum TraitSynthesis {
@@ -113,6 +161,30 @@ pub(crate) enum TraitSynthesis {
     },
     AllocUninitialized(QualifiedName),
     FreeUninitialized(QualifiedName),
+    /// An implicit C++ conversion operator, which becomes `From` if
+    /// `to_type` can be returned by value.
+    Conversion {
+        to_type: QualifiedName,
+    },
+    /// A const `operator->`, which becomes `Deref` to `target`.
+    Deref {
+        target: QualifiedName,
+    },
+    /// An `operator==`, which becomes `PartialEq` with its right-hand
+    /// side.
+    PartialEq,
+    /// A binary arithmetic operator such as `operator+`, which becomes
+    /// the corresponding trait, e.g. `Add`, with its right-hand side, if
+    /// `output` can be returned by value.
+    Arithmetic {
+        op: ArithmeticOperator,
+        output: QualifiedName,
+    },
+    /// An implicit C++ converting constructor of a POD type, which
+    /// becomes `From` its single parameter.
+    ConvertingConstructor {
+        to_type: QualifiedName,
+    },
 }
 
 /// Details of a subclass constructor.
@@ -149,6 +221,13 @@ pub(crate) struct References {
     pub(crate) ref_params: HashSet<Ident>,
     pub(crate) ref_return: bool,
     pub(crate) rvalue_ref_return: bool,
+    /// Whether this is a method declared `void f() __restrict`.
+    pub(crate) restrict_this: bool,
+    /// Parameters which are templates specialized for a const type, e.g.
+    /// `std::span<const float>`. bindgen drops that `const` from the
+    /// types themselves, so this comes from its annotations or, failing
+    /// that, the header.
+    pub(crate) const_template_arg_params: HashSet<Ident>,
 }
 
 impl References {
@@ -159,6 +238,16 @@ impl References {
             ..Default::default()
         }
     }
+    /// Whether this is a method whose receiver is explicitly ref-qualified,
+    /// i.e. `void f() &` or `void f() &&`. The latter case consumes `this`.
+    pub(crate) fn is_ref_qualified_method(&self) -> bool {
+        let this = make_ident("this");
+        self.ref_params.contains(&this) || self.is_rvalue_qualified_method()
+    }
+    /// Whether this is a method declared `void f() &&`.
+    pub(crate) fn is_rvalue_qualified_method(&self) -> bool {
+        self.rvalue_ref_params.contains(&make_ident("this"))
+    }
     pub(crate) fn param_treatment(&self, param: &Ident) -> PointerTreatment {
         if self.rvalue_ref_params.contains(param) {
             PointerTreatment::RValueReference
@@ -297,6 +386,11 @@ pub(crate) struct FuncToConvert {
     pub(crate) synthetic_cpp: Option<(CppFunctionBody, CppFunctionKind)>,
     /// =delete
     pub(crate) is_deleted: DeletedOrDefaulted,
+    /// Whether this was declared `explicit`. We only bother to find out
+    /// for converting constructors and conversion operators.
+    pub(crate) is_explicit: bool,
+    /// Whether this was declared `[[nodiscard]]`.
+    pub(crate) must_use: bool,
 }
 
 /// Layers of analysis which may be applied to decorate each API.
@@ -308,7 +402,7 @@ pub(crate) trait AnalysisPhase: std::fmt::Debug {
 }
 
 /// No analysis has been applied to this API.
-#[derive(std::fmt::Debug)]
+#[derive(Clone, std::fmt::Debug)]
 pub(crate) struct NullPhase;
 
 impl AnalysisPhase for NullPhase {
@@ -390,18 +484,44 @@ impl std::fmt::Debug for ApiName {
 /// we can consistently generate the names of the various subsidiary
 /// types which are required both in C++ and Rust codegen.
 #[derive(Clone, Hash, PartialEq, Eq, Debug)]
-pub(crate) struct SubclassName(pub(crate) ApiName);
+pub(crate) struct SubclassName(pub(crate) ApiName, pub(crate) SubclassKind);
+
+/// Where the Rust side of a subclass comes from.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+pub(crate) enum SubclassKind {
+    /// A struct written by the user, alongside the `include_cpp!` mod.
+    UserDefined,
+    /// A struct synthesized by autocxx within the bindgen mod, which
+    /// forwards calls to an arbitrary Rust implementation of an interface.
+    InterfaceImpl,
+}
 
 impl SubclassName {
-    pub(crate) fn new(id: Ident) -> Self {
-        Self(ApiName::new_in_root_namespace(id))
-    }
-    pub(crate) fn from_holder_name(id: &Ident) -> Self {
-        Self::new(make_ident(id.to_string().strip_suffix("Holder").unwrap()))
+    pub(crate) fn new(id: Ident, kind: SubclassKind) -> Self {
+        Self(ApiName::new_in_root_namespace(id), kind)
+    }
+    pub(crate) fn from_holder_name(id: &Ident, config: &IncludeCppConfig) -> Self {
+        let id = id.to_string();
+        let id = id.strip_suffix("Holder").unwrap();
+        let kind = if config.is_interface_impl(id) {
+            SubclassKind::InterfaceImpl
+        } else {
+            SubclassKind::UserDefined
+        };
+        Self::new(make_ident(id), kind)
     }
     pub(crate) fn id(&self) -> Ident {
         self.0.name.get_final_ident()
     }
+    /// The path to the Rust subclass struct, as seen from within the
+    /// root of the bindgen mod.
+    pub(crate) fn rust_path(&self) -> TypePath {
+        let id = self.id();
+        match self.1 {
+            SubclassKind::UserDefined => parse_quote! { super::super::super::#id },
+            SubclassKind::InterfaceImpl => parse_quote! { #id },
+        }
+    }
     /// Generate the name for the 'Holder' type
     pub(crate) fn holder(&self) -> Ident {
         self.with_suffix("Holder")
@@ -448,7 +568,16 @@ impl SubclassName {
     }
 }
 
-#[derive(std::fmt::Debug)]
+/// The key or value type of a `std::map` which we're iterating over.
+#[derive(Clone, Debug)]
+pub(crate) struct StdMapEntryType {
+    pub(crate) ty: QualifiedName,
+    /// Whether this is copied out of the map, as opposed to being
+    /// returned by reference.
+    pub(crate) by_value: bool,
+}
+
+#[derive(Clone, std::fmt::Debug)]
 /// Different types of API we might encounter.
 ///
 /// This type is parameterized over an `ApiAnalysis`. This is any additional
@@ -491,6 +620,38 @@ pub(crate) enum Api<T: AnalysisPhase> {
         rs_definition: Option<Box<Type>>,
         cpp_definition: String,
     },
+    /// A Rust enum mirroring a `std::variant` instantiation listed using
+    /// `variant!`. The C++ variant itself is a separate [`Api::ConcreteType`]
+    /// named `cpp_type`.
+    StdVariant {
+        name: ApiName,
+        cpp_type: QualifiedName,
+        alternatives: Vec<QualifiedName>,
+    },
+    /// A Rust iterator over the entries of a `std::map` instantiation
+    /// listed using `concrete!`. The map itself is a separate
+    /// [`Api::ConcreteType`] named `map_type`.
+    StdMapIterator {
+        name: ApiName,
+        map_type: QualifiedName,
+        key: StdMapEntryType,
+        value: StdMapEntryType,
+    },
+    /// A Rust iterator over the items of the range returned by a C++
+    /// function listed using `range!`.
+    RangeIterator {
+        name: ApiName,
+        function: String,
+        item: QualifiedName,
+    },
+    /// A Rust tuple type alias, along with conversions to and from a
+    /// `std::tuple` instantiation listed using `concrete!`. The C++ tuple
+    /// itself is a separate [`Api::ConcreteType`] named `tuple_type`.
+    StdTuple {
+        name: ApiName,
+        tuple_type: QualifiedName,
+        elements: Vec<QualifiedName>,
+    },
     /// A simple note that we want to make a constructor for
     /// a `std::string` on the heap.
     StringConstructor { name: ApiName },
@@ -515,7 +676,15 @@ pub(crate) enum Api<T: AnalysisPhase> {
     },
     /// An enum encountered in the
     /// `bindgen` output.
-    Enum { name: ApiName, item: ItemEnum },
+    Enum {
+        name: ApiName,
+        item: ItemEnum,
+        /// For a copy of an enum nested within a class template, made for
+        /// an instantiation of that template listed using `concrete!`, the
+        /// C++ name of the enum within the instantiation, e.g.
+        /// `Container<int>::State`. We generate a C++ typedef for it.
+        cpp_definition: Option<String>,
+    },
     /// A struct encountered in the
     /// `bindgen` output.
     Struct {
@@ -568,10 +737,13 @@ pub(crate) enum Api<T: AnalysisPhase> {
         name: ApiName,
         details: ExternCppType,
         pod: bool,
+        /// For an `extern_cpp_enum!`, the enum which bindgen generated for
+        /// the C++ type, if any, against which we check the Rust enum.
+        enum_item: Option<ItemEnum>,
     },
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct RustSubclassFnDetails {
     pub(crate) params: Punctuated<FnArg, Comma>,
     pub(crate) ret: ReturnType,
@@ -597,6 +769,10 @@ impl<T: AnalysisPhase> Api<T> {
             Api::ForwardDeclaration { name, .. } => name,
             Api::OpaqueTypedef { name, .. } => name,
             Api::ConcreteType { name, .. } => name,
+            Api::StdVariant { name, .. } => name,
+            Api::StdMapIterator { name, .. } => name,
+            Api::StdTuple { name, .. } => name,
+            Api::RangeIterator { name, .. } => name,
             Api::StringConstructor { name } => name,
             Api::Function { name, .. } => name,
             Api::Const { name, .. } => name,
@@ -712,11 +888,16 @@ impl<T: AnalysisPhase> Api<T> {
     pub(crate) fn enum_unchanged(
         name: ApiName,
         item: ItemEnum,
+        cpp_definition: Option<String>,
     ) -> Result<Box<dyn Iterator<Item = Api<T>>>, ConvertErrorWithContext>
     where
         T: 'static,
     {
-        Ok(Box::new(std::iter::once(Api::Enum { name, item })))
+        Ok(Box::new(std::iter::once(Api::Enum {
+            name,
+            item,
+            cpp_definition,
+        })))
     }
 }
 
@@ -101,11 +101,14 @@ impl TypeConversionPolicy {
             CppConversionType::FromUniquePtrToValue | CppConversionType::FromPtrToMove => {
                 Some(format!("std::move(*{var_name})"))
             }
-            CppConversionType::FromValueToUniquePtr => Some(format!(
-                "std::make_unique<{}>({})",
-                self.unconverted_type(cpp_name_map)?,
-                var_name
-            )),
+            // Not std::make_unique, which would need to move the value:
+            // constructing directly from the returned prvalue lets the
+            // compiler elide that, so types like RAII guards which can
+            // be neither copied nor moved can still be returned.
+            CppConversionType::FromValueToUniquePtr => {
+                let ty = self.unconverted_type(cpp_name_map)?;
+                Some(format!("std::unique_ptr<{ty}>(new {ty}({var_name}))"))
+            }
             CppConversionType::FromPtrToValue => {
                 let dereference = format!("*{var_name}");
                 Some(if is_return {
@@ -116,6 +119,20 @@ impl TypeConversionPolicy {
             }
             CppConversionType::IgnoredPlacementPtrParameter => None,
             CppConversionType::FromReferenceToPointer { .. } => Some(format!("&{var_name}")),
+            CppConversionType::FromSliceToPointerAndLength(None) => {
+                Some(format!("{var_name}.data(), {var_name}.size()"))
+            }
+            CppConversionType::FromSliceToPointerAndLength(Some(ref ptr_type)) => Some(format!(
+                "reinterpret_cast<{ptr_type}>({var_name}.data()), {var_name}.size()"
+            )),
+            CppConversionType::FromSliceToSpan => {
+                Some(format!("autocxx_slice_as_span({var_name})"))
+            }
+            CppConversionType::FromReferenceToReferenceWrapper => {
+                Some(format!("std::ref({var_name})"))
+            }
+            CppConversionType::FromReferenceWrapperToReference => Some(format!("{var_name}.get()")),
+            CppConversionType::Custom(ref expr) => Some(expr.replace("{}", var_name)),
         })
     }
 }
@@ -11,7 +11,11 @@ mod new_and_delete_prelude;
 pub(crate) mod type_to_cpp;
 
 use crate::{
-    conversion::analysis::fun::{function_wrapper::CppFunctionKind, FnAnalysis},
+    cargo_cfg,
+    conversion::analysis::fun::{
+        function_wrapper::{CppConversionType, CppFunctionKind},
+        FnAnalysis,
+    },
     types::{make_ident, QualifiedName},
     CppCodegenOptions, CppFilePair,
 };
@@ -24,13 +28,14 @@ use type_to_cpp::CppNameMap;
 
 use super::{
     analysis::{
+        cxx_free::{c_abi_shim_base_name, find_c_abi_functions},
         fun::{
             function_wrapper::{CppFunction, CppFunctionBody},
             FnPhase, PodAndDepAnalysis,
         },
         pod::PodAnalysis,
     },
-    api::{Api, Provenance, SubclassName, TypeKind},
+    api::{Api, ApiName, Provenance, SubclassName, TypeKind},
     apivec::ApiVec,
     ConvertErrorFromCpp,
 };
@@ -102,6 +107,8 @@ pub(crate) struct CppCodeGenerator<'a> {
     config: &'a IncludeCppConfig,
     cpp_codegen_options: &'a CppCodegenOptions<'a>,
     cxxgen_header_name: &'a str,
+    generated_slice_as_span: bool,
+    generated_shared_from_this: bool,
 }
 
 struct SubclassFunction<'a> {
@@ -124,10 +131,19 @@ impl<'a> CppCodeGenerator<'a> {
             config,
             cpp_codegen_options,
             cxxgen_header_name,
+            generated_slice_as_span: false,
+            generated_shared_from_this: false,
         };
-        // The 'filter' on the following line is designed to ensure we don't accidentally
-        // end up out of sync with needs_cpp_codegen
-        gen.add_needs(apis.iter().filter(|api| api.needs_cpp_codegen()))?;
+        if config.is_cxx_free() {
+            gen.add_cxx_free_needs(apis)?;
+        } else {
+            // The 'filter' on the following line is designed to ensure we don't accidentally
+            // end up out of sync with needs_cpp_codegen
+            gen.add_needs(apis.iter().filter(|api| api.needs_cpp_codegen()))?;
+            if config.generates_c_abi_shims() {
+                gen.add_c_abi_shims(apis)?;
+            }
+        }
         Ok(gen.generate())
     }
 
@@ -148,11 +164,21 @@ impl<'a> CppCodeGenerator<'a> {
                             cpp_wrapper: Some(cpp_wrapper),
                             ignore_reason: Ok(_),
                             externally_callable: true,
+                            cfg_gate,
                             ..
                         },
                     fun,
                     ..
                 } => {
+                    // The function may not even be declared unless its
+                    // `cfg_gate!` predicate holds.
+                    let cfg_enabled = cfg_gate
+                        .as_ref()
+                        .and_then(|attr| attr.parse_args::<syn::Meta>().ok())
+                        .map_or(true, |predicate| cargo_cfg::is_enabled(&predicate));
+                    if !cfg_enabled {
+                        continue;
+                    }
                     if let Provenance::SynthesizedSubclassConstructor(details) = &fun.provenance {
                         constructors_by_subclass
                             .entry(details.subclass.clone())
@@ -176,6 +202,30 @@ impl<'a> CppCodeGenerator<'a> {
                     self.generate_typedef(api.name(), &effective_cpp_definition)
                 }
                 Api::CType { typename, .. } => self.generate_ctype_typedef(typename),
+                Api::Enum {
+                    name,
+                    cpp_definition: Some(cpp_definition),
+                    ..
+                } => self.generate_typedef(&name.name, cpp_definition),
+                Api::StdVariant {
+                    name,
+                    cpp_type,
+                    alternatives,
+                } => self.generate_std_variant(&name.name, cpp_type, alternatives),
+                Api::StdMapIterator {
+                    map_type,
+                    key,
+                    value,
+                    ..
+                } => self.generate_std_map_iterator(map_type, key.by_value, value.by_value),
+                Api::StdTuple {
+                    tuple_type,
+                    elements,
+                    ..
+                } => self.generate_std_tuple(tuple_type, elements.len()),
+                Api::RangeIterator { name, function, .. } => {
+                    self.generate_range_iterator(name.name.get_final_item(), function)
+                }
                 Api::Subclass { .. } => deferred_apis.push(api),
                 Api::RustSubclassFn {
                     subclass, details, ..
@@ -304,6 +354,111 @@ impl<'a> CppCodeGenerator<'a> {
         })
     }
 
+    /// For `c_abi_shims!`, add an `extern "C"` shim with a predictable
+    /// name for each function which can be called that way.
+    fn add_c_abi_shims(&mut self, apis: &ApiVec<FnPhase>) -> Result<(), ConvertErrorFromCpp> {
+        let c_abi_functions = find_c_abi_functions(apis);
+        for api in apis.iter() {
+            if let Api::Function { name, analysis, .. } = api {
+                if c_abi_functions.contains(&name.name) {
+                    let shim_name = self
+                        .config
+                        .get_c_abi_shim_name(&c_abi_shim_base_name(&name.name, analysis));
+                    self.add_c_abi_shim(name, api, &shim_name)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Add an `extern "C"` function which calls the given function,
+    /// passing its parameters and return value straight through.
+    fn add_c_abi_shim(
+        &mut self,
+        name: &ApiName,
+        api: &Api<FnPhase>,
+        shim_name: &str,
+    ) -> Result<(), ConvertErrorFromCpp> {
+        let Api::Function { fun, .. } = api else {
+            return Ok(());
+        };
+        let args: Result<Vec<_>, _> = fun
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(counter, arg)| match &**arg {
+                syn::FnArg::Typed(pt) => Ok(format!(
+                    "{} arg{}",
+                    self.original_name_map.type_to_cpp(&pt.ty)?,
+                    counter
+                )),
+                syn::FnArg::Receiver(_) => Err(ConvertErrorFromCpp::NotCxxFreeCompatible(
+                    name.name.to_cpp_name(),
+                )),
+            })
+            .collect();
+        let args = args?.join(", ");
+        let ret_type = match &*fun.output {
+            syn::ReturnType::Default => "void".to_string(),
+            syn::ReturnType::Type(_, ty) => self.original_name_map.type_to_cpp(ty)?,
+        };
+        let arg_list = (0..fun.inputs.len())
+            .map(|counter| format!("arg{counter}"))
+            .join(", ");
+        let underlying_function = QualifiedName::new(
+            name.name.get_namespace(),
+            make_ident(api.effective_cpp_name()),
+        )
+        .to_cpp_name();
+        let declaration = format!("extern \"C\" {ret_type} {shim_name}({args})");
+        self.additional_functions.push(ExtraCpp {
+            declaration: Some(format!("{declaration};")),
+            definition: Some(format!(
+                "{declaration} {{ return {underlying_function}({arg_list}); }}"
+            )),
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    /// In `cxx_free!` mode, every function is called through an
+    /// `extern "C"` shim, and there's nothing else to generate apart from
+    /// some assertions about the POD types.
+    fn add_cxx_free_needs(&mut self, apis: &ApiVec<FnPhase>) -> Result<(), ConvertErrorFromCpp> {
+        for api in apis.iter() {
+            match api {
+                Api::Function { name, analysis, .. } => {
+                    let shim_name = self
+                        .config
+                        .get_cxx_free_shim_name(&analysis.cxxbridge_name.to_string());
+                    self.add_c_abi_shim(name, api, &shim_name)?;
+                }
+                Api::Struct {
+                    name,
+                    analysis:
+                        PodAndDepAnalysis {
+                            pod:
+                                PodAnalysis {
+                                    kind: TypeKind::Pod,
+                                    ..
+                                },
+                            ..
+                        },
+                    ..
+                } => {
+                    let name = name.qualified_cpp_name();
+                    self.additional_functions.push(ExtraCpp {
+                        declaration: Some(format!("static_assert(std::is_trivially_copyable<{name}>::value, \"type {name} should be trivially copyable to be used with cxx_free! in autocxx\");")),
+                        headers: vec![Header::System("type_traits")],
+                        ..Default::default()
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
     fn generate_string_constructor(&mut self) {
         let makestring_name = self.config.get_makestring_name();
         let declaration = Some(format!("inline std::unique_ptr<std::string> {makestring_name}(::rust::Str str) {{ return std::make_unique<std::string>(std::string(str)); }}"));
@@ -318,7 +473,148 @@ impl<'a> CppCodeGenerator<'a> {
         })
     }
 
+    fn generate_std_variant(
+        &mut self,
+        name: &QualifiedName,
+        cpp_type: &QualifiedName,
+        alternatives: &[QualifiedName],
+    ) {
+        let rust_id = name.get_final_item();
+        let cpp_type = cpp_type.to_cpp_name();
+        let index_fn = self.config.get_std_variant_fn_name(rust_id, "index");
+        let mut declarations = vec![format!(
+            "inline std::size_t {index_fn}(const {cpp_type}& v) {{ return v.index(); }}"
+        )];
+        for (i, alternative) in alternatives.iter().enumerate() {
+            let alternative = alternative.to_cpp_name();
+            let get_fn = self
+                .config
+                .get_std_variant_fn_name(rust_id, &format!("get_{i}"));
+            let from_fn = self
+                .config
+                .get_std_variant_fn_name(rust_id, &format!("from_{i}"));
+            declarations.push(format!(
+                "inline {alternative} {get_fn}(const {cpp_type}& v) {{ return std::get<{i}>(v); }}"
+            ));
+            declarations.push(format!("inline std::unique_ptr<{cpp_type}> {from_fn}({alternative} v) {{ return std::make_unique<{cpp_type}>(std::in_place_index<{i}>, v); }}"));
+        }
+        self.additional_functions.push(ExtraCpp {
+            declaration: Some(declarations.join("\n")),
+            headers: vec![
+                Header::System("cstddef"),
+                Header::System("memory"),
+                Header::System("variant"),
+            ],
+            ..Default::default()
+        })
+    }
+
+    fn generate_std_tuple(&mut self, tuple_type: &QualifiedName, element_count: usize) {
+        let rust_id = tuple_type.get_final_item();
+        let tuple_type = tuple_type.to_cpp_name();
+        let element_type = |i: usize| format!("std::tuple_element_t<{i}, {tuple_type}>");
+        let mut declarations: Vec<_> = (0..element_count)
+            .map(|i| {
+                let get_fn = self
+                    .config
+                    .get_std_tuple_fn_name(rust_id, &format!("get_{i}"));
+                format!(
+                    "inline {} {get_fn}(const {tuple_type}& t) {{ return std::get<{i}>(t); }}",
+                    element_type(i)
+                )
+            })
+            .collect();
+        let make_fn = self.config.get_std_tuple_fn_name(rust_id, "make");
+        let params = (0..element_count)
+            .map(|i| format!("{} e{i}", element_type(i)))
+            .join(", ");
+        let args = (0..element_count).map(|i| format!("e{i}")).join(", ");
+        declarations.push(format!(
+            "inline std::unique_ptr<{tuple_type}> {make_fn}({params}) {{ return std::make_unique<{tuple_type}>({args}); }}"
+        ));
+        self.additional_functions.push(ExtraCpp {
+            declaration: Some(declarations.join("\n")),
+            headers: vec![Header::System("memory"), Header::System("tuple")],
+            ..Default::default()
+        })
+    }
+
+    fn generate_range_iterator(&mut self, rust_id: &str, function: &str) {
+        let item_name = |suffix: &str| self.config.get_range_item_name(rust_id, suffix);
+        let state = item_name("state");
+        let begin_fn = item_name("begin");
+        let valid_fn = item_name("valid");
+        let advance_fn = item_name("advance");
+        let get_fn = item_name("get");
+        // The state holds the range itself, or a reference to it if
+        // that's what the function returns, along with iterators into it.
+        // It's constructed in place so that the iterators stay valid.
+        let declarations = [
+            format!("struct {state} {{ decltype({function}()) range; decltype(std::begin(range)) pos = std::begin(range); decltype(std::end(range)) end = std::end(range); }};"),
+            format!("inline std::unique_ptr<{state}> {begin_fn}() {{ return std::unique_ptr<{state}>(new {state}{{{function}()}}); }}"),
+            format!("inline bool {valid_fn}(const {state}& it) {{ return it.pos != it.end; }}"),
+            format!("inline void {advance_fn}({state}& it) {{ ++it.pos; }}"),
+            format!("inline std::decay_t<decltype(*std::declval<const {state}&>().pos)> {get_fn}(const {state}& it) {{ return *it.pos; }}"),
+        ];
+        self.additional_functions.push(ExtraCpp {
+            declaration: Some(declarations.join("\n")),
+            headers: vec![
+                Header::System("iterator"),
+                Header::System("memory"),
+                Header::System("type_traits"),
+                Header::System("utility"),
+            ],
+            ..Default::default()
+        })
+    }
+
+    fn generate_std_map_iterator(
+        &mut self,
+        map_type: &QualifiedName,
+        key_by_value: bool,
+        value_by_value: bool,
+    ) {
+        let rust_id = map_type.get_final_item();
+        let map_type = map_type.to_cpp_name();
+        let item_name = |suffix: &str| self.config.get_std_map_item_name(rust_id, suffix);
+        let state = item_name("iterator");
+        let begin_fn = item_name("begin");
+        let valid_fn = item_name("valid");
+        let advance_fn = item_name("advance");
+        let accessor = |fn_name: String, member: &str, member_type: &str, by_value: bool| {
+            let ret = if by_value {
+                format!("{map_type}::{member_type}")
+            } else {
+                format!("const {map_type}::{member_type}&")
+            };
+            format!("inline {ret} {fn_name}(const {map_type}&, const {state}& it) {{ return it.pos->{member}; }}")
+        };
+        let declarations = [
+            format!("struct {state} {{ {map_type}::const_iterator pos; {map_type}::const_iterator end; }};"),
+            format!("inline std::unique_ptr<{state}> {begin_fn}(const {map_type}& m) {{ return std::make_unique<{state}>({state}{{m.begin(), m.end()}}); }}"),
+            format!("inline bool {valid_fn}(const {state}& it) {{ return it.pos != it.end; }}"),
+            format!("inline void {advance_fn}({state}& it) {{ ++it.pos; }}"),
+            accessor(item_name("key"), "first", "key_type", key_by_value),
+            accessor(item_name("value"), "second", "mapped_type", value_by_value),
+        ];
+        self.additional_functions.push(ExtraCpp {
+            declaration: Some(declarations.join("\n")),
+            headers: vec![Header::System("map"), Header::System("memory")],
+            ..Default::default()
+        })
+    }
+
     fn generate_cpp_function(&mut self, details: &CppFunction) -> Result<(), ConvertErrorFromCpp> {
+        if details
+            .argument_conversion
+            .iter()
+            .any(|conv| matches!(conv.cpp_conversion, CppConversionType::FromSliceToSpan))
+        {
+            self.generate_slice_as_span();
+        }
+        if matches!(&details.payload, CppFunctionBody::SharedFromThis(_)) {
+            self.generate_shared_from_this();
+        }
         self.additional_functions
             .push(self.generate_cpp_function_inner(
                 details,
@@ -330,6 +626,84 @@ impl<'a> CppCodeGenerator<'a> {
         Ok(())
     }
 
+    /// Slices are passed to functions taking a `std::span` by way of this
+    /// type, which converts to whichever sort of span the function takes,
+    /// including a span of `const` elements if we couldn't tell that's what
+    /// it wanted.
+    /// We can't see the extent of a span in bindgen's output, so if it's
+    /// fixed, we can only check the length of the slice at runtime.
+    fn generate_slice_as_span(&mut self) {
+        if self.generated_slice_as_span {
+            return;
+        }
+        self.generated_slice_as_span = true;
+        self.additional_functions.push(ExtraCpp {
+            // Each include_cpp! section has its own header, and a
+            // translation unit may include more than one.
+            type_definition: Some(indoc::indoc! {"
+                #ifndef AUTOCXX_SLICE_AS_SPAN
+                #define AUTOCXX_SLICE_AS_SPAN
+                template <typename T> struct autocxx_slice_as_span {
+                  autocxx_slice_as_span(::rust::Slice<T> slice) : data(slice.data()), size(slice.size()) {}
+                  template <typename U, std::size_t N,
+                            typename = std::enable_if_t<std::is_convertible_v<T (*)[], U (*)[]>>>
+                  operator std::span<U, N>() const {
+                    if constexpr (N != std::dynamic_extent) {
+                      if (size != N) {
+                        std::abort();
+                      }
+                    }
+                    return std::span<U, N>(data, size);
+                  }
+                  T* data;
+                  std::size_t size;
+                };
+                #endif // AUTOCXX_SLICE_AS_SPAN
+            "}.to_string()),
+            headers: vec![
+                Header::System("cstdlib"),
+                Header::System("span"),
+                Header::System("type_traits"),
+                Header::CxxH,
+            ],
+            ..Default::default()
+        });
+    }
+
+    /// `shared_from_this` throws (or, before C++17, may do anything) if the
+    /// object isn't owned by a `std::shared_ptr`, and our wrappers are
+    /// `noexcept`. `weak_from_this` tells us that safely, but only from
+    /// C++17; before that, the standard libraries we know of throw.
+    fn generate_shared_from_this(&mut self) {
+        if self.generated_shared_from_this {
+            return;
+        }
+        self.generated_shared_from_this = true;
+        self.additional_functions.push(ExtraCpp {
+            type_definition: Some(
+                indoc::indoc! {"
+                #ifndef AUTOCXX_SHARED_FROM_THIS
+                #define AUTOCXX_SHARED_FROM_THIS
+                template <typename T> std::shared_ptr<T> autocxx_shared_from_this(const T& obj) {
+                #if __cplusplus >= 201703L
+                  return std::const_pointer_cast<T>(obj.weak_from_this().lock());
+                #else
+                  try {
+                    return std::const_pointer_cast<T>(obj.shared_from_this());
+                  } catch (const std::bad_weak_ptr&) {
+                    return nullptr;
+                  }
+                #endif
+                }
+                #endif // AUTOCXX_SHARED_FROM_THIS
+            "}
+                .to_string(),
+            ),
+            headers: vec![Header::System("memory")],
+            ..Default::default()
+        });
+    }
+
     fn generate_cpp_function_inner(
         &self,
         details: &CppFunction,
@@ -556,6 +930,79 @@ impl<'a> CppCodeGenerator<'a> {
                 "".to_string(),
                 true,
             ),
+            CppFunctionBody::Swap => (
+                format!("{{ using std::swap; swap({arg_list}); }}"),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::SharedFromThis(ty) => (
+                format!(
+                    "autocxx_shared_from_this<{}>({arg_list})",
+                    self.namespaced_name(ty)
+                ),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::FunctionTemplateCall(template_id) => {
+                (format!("{template_id}({arg_list})"), "".to_string(), false)
+            }
+            CppFunctionBody::MethodTemplateCall(template_id) => (
+                format!("{}.{template_id}({arg_list})", receiver.unwrap()),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::CallOperator => (
+                format!("{}({arg_list})", receiver.unwrap()),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::ArrowOperator => (
+                format!("*{}.operator->()", receiver.unwrap()),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::EqualityOperator => (
+                format!("{} == {arg_list}", receiver.unwrap()),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::ArithmeticOperator(op) => (
+                format!("{} {} {arg_list}", receiver.unwrap(), op.cpp_symbol()),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::LeftShiftOperator => {
+                (format!("operator<<({arg_list})"), "".to_string(), false)
+            }
+            CppFunctionBody::ConversionOperator(to_type) => (
+                format!(
+                    "static_cast<{}>({})",
+                    self.namespaced_name(to_type),
+                    receiver.unwrap()
+                ),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::ConvertingConstructor(ty) => (
+                format!("{}({arg_list})", self.namespaced_name(ty)),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::FallibleConstructor(ty) => (
+                format!("std::make_unique<{}>({arg_list})", self.namespaced_name(ty)),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::StaticMemberGet(ty, member) => (
+                format!("{}::{member}", self.namespaced_name(ty)),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::StaticMemberSet(ty, member) => (
+                format!("{}::{member} = {arg_list}", self.namespaced_name(ty)),
+                "".to_string(),
+                false,
+            ),
         };
         if let Some(ret) = &details.return_conversion {
             let call_itself = match conversion_direction {
@@ -575,6 +1022,9 @@ impl<'a> CppCodeGenerator<'a> {
 
             underlying_function_call = match placement_param {
                 Some(placement_param) => {
+                    // Placement new from the returned prvalue already builds
+                    // the result straight into the caller's storage: the copy
+                    // or move is elided (guaranteed as of C++17).
                     let tyname = self.original_name_map.type_to_cpp(ret.cxxbridge_type())?;
                     format!("new({placement_param}) {tyname}({call_itself})")
                 }
@@ -612,6 +1062,9 @@ impl<'a> CppCodeGenerator<'a> {
             headers.push(Header::System("stddef.h"));
             headers.push(Header::NewDeletePrelude);
         }
+        if matches!(details.payload, CppFunctionBody::Swap) {
+            headers.push(Header::System("utility"));
+        }
         Ok(ExtraCpp {
             declaration,
             definition,
@@ -107,6 +107,15 @@ impl CppNameMap {
             }
             Type::Reference(typr) => match &*typr.elem {
                 Type::Path(typ) if typ.path.is_ident("str") => Ok("rust::Str".into()),
+                Type::Slice(slice) => Ok(format!(
+                    "rust::Slice<{}{}>",
+                    if typr.mutability.is_some() {
+                        ""
+                    } else {
+                        "const "
+                    },
+                    self.type_to_cpp(slice.elem.as_ref())?
+                )),
                 _ => Ok(format!(
                     "{}{}&",
                     get_mut_string(&typr.mutability),
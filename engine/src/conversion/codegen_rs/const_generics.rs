@@ -0,0 +1,259 @@
+// Copyright 2020 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Support for exposing several instantiations of a C++ template which
+//! differ only in an integer argument (e.g. `FixedVector<float, 4>` and
+//! `FixedVector<float, 8>`) as a single const-generic Rust type alias.
+//! Each instantiation remains a distinct opaque type; the alias simply
+//! selects between them, so `FixedVector_float_N<8>` names the same type
+//! as whatever `FixedVector<float, 8>` was called.
+
+use indexmap::map::IndexMap as HashMap;
+use itertools::Itertools;
+use proc_macro2::Literal;
+use syn::{parse_quote, Ident, Item};
+
+use crate::{
+    conversion::{
+        analysis::fun::FnPhase,
+        api::{Api, ApiName},
+        apivec::ApiVec,
+    },
+    types::{make_ident, QualifiedName},
+};
+
+/// A set of concrete template instantiations which can be addressed
+/// using a const generic parameter.
+pub(crate) struct ConstGenericFamily {
+    /// A description of the template, e.g. `FixedVector<float, N>`.
+    description: String,
+    /// The name of the const-generic Rust type alias.
+    alias: Ident,
+    /// Whether the integer argument may be negative.
+    signed: bool,
+}
+
+/// Membership of a concrete type within a [`ConstGenericFamily`].
+pub(crate) struct ConstGenericMember {
+    family: usize,
+    value: i128,
+    /// Whether this is the first member of the family, which is
+    /// responsible for emitting the shared items.
+    first: bool,
+}
+
+pub(crate) struct ConstGenericFamilies {
+    families: Vec<ConstGenericFamily>,
+    members: HashMap<QualifiedName, ConstGenericMember>,
+}
+
+/// Splits `Template<A, B>` into `Template` and its top-level arguments.
+fn split_template_args(cpp_definition: &str) -> Option<(&str, Vec<&str>)> {
+    let open = cpp_definition.find('<')?;
+    let inner = cpp_definition.trim_end().strip_suffix('>')?;
+    let template = cpp_definition[..open].trim();
+    let inner = &inner[open + 1..];
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut args = Vec::new();
+    for (i, c) in inner.char_indices() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                args.push(inner[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return None;
+    }
+    args.push(inner[start..].trim());
+    Some((template, args))
+}
+
+/// Parses a C++ integer literal, ignoring any `u`/`l` suffix.
+fn parse_integer_arg(arg: &str) -> Option<i128> {
+    let digits = arg.trim_end_matches(['u', 'U', 'l', 'L']);
+    let (negative, digits) = match digits.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, digits),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let value: i128 = digits.parse().ok()?;
+    Some(if negative { -value } else { value })
+}
+
+impl ConstGenericFamilies {
+    /// Finds concrete types which are instantiations of the same template
+    /// differing only in a single integer argument.
+    pub(crate) fn new_from_apis(apis: &ApiVec<FnPhase>) -> Self {
+        let mut candidates: HashMap<String, Vec<(QualifiedName, i128)>> = HashMap::new();
+        for api in apis.iter() {
+            if let Api::ConcreteType {
+                name: ApiName { name, .. },
+                cpp_definition,
+                ..
+            } = api
+            {
+                let (template, args) = match split_template_args(cpp_definition) {
+                    Some(split) => split,
+                    None => continue,
+                };
+                let integer_args = args
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, arg)| parse_integer_arg(arg).map(|value| (i, value)))
+                    .collect_vec();
+                // More than one integer argument would need several const
+                // generic parameters; leave those as distinct opaque types.
+                if let [(position, value)] = integer_args[..] {
+                    let mut args = args;
+                    args[position] = "N";
+                    let description = format!("{}<{}>", template, args.join(", "));
+                    candidates
+                        .entry(description)
+                        .or_default()
+                        .push((name.clone(), value));
+                }
+            }
+        }
+        let existing_names: Vec<String> = apis
+            .iter()
+            .map(|api| api.name().get_final_item().to_string())
+            .collect();
+        let mut families = Vec::new();
+        let mut members = HashMap::new();
+        for (description, instantiations) in candidates {
+            // The same value spelled differently (e.g. `8` and `8u`) would
+            // result in conflicting trait impls.
+            if !instantiations.iter().map(|(_, value)| value).all_unique() {
+                continue;
+            }
+            let alias = description
+                .replace(|c: char| !(c.is_ascii_alphanumeric() || c == '_'), "_")
+                .split('_')
+                .filter(|s| !s.is_empty())
+                .join("_");
+            if existing_names.contains(&alias) {
+                continue;
+            }
+            let signed = instantiations.iter().any(|(_, value)| *value < 0);
+            let fits = |value: i128| {
+                if signed {
+                    i64::try_from(value).is_ok()
+                } else {
+                    u64::try_from(value).is_ok()
+                }
+            };
+            if !instantiations.iter().all(|(_, value)| fits(*value)) {
+                continue;
+            }
+            let family = families.len();
+            families.push(ConstGenericFamily {
+                description,
+                alias: make_ident(alias).into(),
+                signed,
+            });
+            for (i, (name, value)) in instantiations.into_iter().enumerate() {
+                members.insert(
+                    name,
+                    ConstGenericMember {
+                        family,
+                        value,
+                        first: i == 0,
+                    },
+                );
+            }
+        }
+        Self { families, members }
+    }
+
+    /// Generates the items needed in the bindgen mod for a given concrete
+    /// type, plus the names of any new items which should be exposed
+    /// to the user.
+    pub(crate) fn generate(&self, name: &QualifiedName) -> (Vec<Item>, Vec<Ident>) {
+        let member = match self.members.get(name) {
+            Some(member) => member,
+            None => return (Vec::new(), Vec::new()),
+        };
+        let family = &self.families[member.family];
+        let alias = &family.alias;
+        let selector = make_ident(format!("{alias}_Select"));
+        let instantiation = make_ident(format!("{alias}_Instantiation"));
+        let const_ty = make_ident(if family.signed { "i64" } else { "usize" });
+        let value = if family.signed {
+            Literal::i64_unsuffixed(member.value as i64)
+        } else {
+            Literal::u64_unsuffixed(member.value as u64)
+        };
+        let id = name.get_final_ident();
+        let mut items = vec![Item::Impl(parse_quote! {
+            impl #instantiation for #selector<#value> {
+                type Type = #id;
+            }
+        })];
+        let mut exposed = Vec::new();
+        if member.first {
+            let selector_doc = format!(
+                "Selects an instantiation of the C++ template `{}` by the value of `N`.",
+                family.description
+            );
+            let alias_doc = format!(
+                "The instantiation of the C++ template `{}` for a given `N`. \
+                Only values of `N` for which bindings were generated may be used.",
+                family.description
+            );
+            items.push(parse_quote! {
+                #[doc = #selector_doc]
+                #[allow(non_camel_case_types)]
+                pub struct #selector<const N: #const_ty>;
+            });
+            items.push(parse_quote! {
+                /// Implemented for each instantiation for which bindings exist.
+                #[allow(non_camel_case_types)]
+                pub trait #instantiation {
+                    type Type;
+                }
+            });
+            items.push(parse_quote! {
+                #[doc = #alias_doc]
+                #[allow(non_camel_case_types)]
+                pub type #alias<const N: #const_ty> = <#selector<N> as #instantiation>::Type;
+            });
+            exposed.extend([selector.into(), instantiation.into(), alias.clone()]);
+        }
+        (items, exposed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_integer_arg, split_template_args};
+
+    #[test]
+    fn test_split_template_args() {
+        assert_eq!(
+            split_template_args("ns::FixedVector<std::pair<int, int>, 8>"),
+            Some(("ns::FixedVector", vec!["std::pair<int, int>", "8"]))
+        );
+        assert_eq!(split_template_args("Foo"), None);
+    }
+
+    #[test]
+    fn test_parse_integer_arg() {
+        assert_eq!(parse_integer_arg("8"), Some(8));
+        assert_eq!(parse_integer_arg("16u"), Some(16));
+        assert_eq!(parse_integer_arg("-3"), Some(-3));
+        assert_eq!(parse_integer_arg("float"), None);
+    }
+}
@@ -6,7 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use autocxx_parser::IncludeCppConfig;
+use autocxx_parser::{IncludeCppConfig, IntegerConversions};
+use indexmap::map::IndexMap as HashMap;
 use indexmap::set::IndexSet as HashSet;
 use std::borrow::Cow;
 
@@ -17,7 +18,8 @@ use syn::{
     parse_quote,
     punctuated::Punctuated,
     token::{Comma, Unsafe},
-    Attribute, FnArg, ForeignItem, Ident, ImplItem, Item, ReturnType,
+    Attribute, FnArg, ForeignItem, Ident, ImplItem, Item, Pat, ReturnType, TraitItem, Type,
+    TypePtr,
 };
 
 use super::{
@@ -29,10 +31,11 @@ use super::{
 use crate::{
     conversion::{
         analysis::fun::{
-            function_wrapper::TypeConversionPolicy, ArgumentAnalysis, FnAnalysis, FnKind,
-            MethodKind, RustRenameStrategy, TraitMethodDetails,
+            function_wrapper::{CppFunctionBody, RustConversionType, TypeConversionPolicy},
+            ArgumentAnalysis, FnAnalysis, FnKind, MethodKind, RustRenameStrategy,
+            TraitMethodDetails, TraitMethodKind,
         },
-        api::{Pointerness, UnsafetyNeeded},
+        api::{Pointerness, TraitImplSignature, UnsafetyNeeded},
     },
     minisyn::minisynize_vec,
     types::{Namespace, QualifiedName},
@@ -91,7 +94,9 @@ pub(super) fn gen_function(
     analysis: FnAnalysis,
     cpp_call_name: String,
     non_pod_types: &HashSet<QualifiedName>,
+    std_tuples: &HashMap<QualifiedName, Ident>,
     config: &IncludeCppConfig,
+    inline: bool,
 ) -> RsCodegenResult {
     if analysis.ignore_reason.is_err() || !analysis.externally_callable {
         return RsCodegenResult::default();
@@ -106,15 +111,41 @@ pub(super) fn gen_function(
     let vis = analysis.vis;
     let kind = analysis.kind;
     let doc_attrs = minisynize_vec(fun.doc_attrs);
+    let cfg_gate: Option<Attribute> = analysis.cfg_gate.map(Into::into);
+    let must_use_attr: Option<Attribute> = analysis.must_use.then(|| parse_quote! { #[must_use] });
+    let extra_attrs: Vec<Attribute> = cfg_gate
+        .iter()
+        .cloned()
+        .chain(minisynize_vec(analysis.extra_attrs))
+        .collect();
+    // A constructor listed using throwing_constructors! returns any
+    // exception as an Err, which cxx does for us if we ask it to
+    // return a Result.
+    let fallible = matches!(
+        fun.synthetic_cpp,
+        Some((CppFunctionBody::FallibleConstructor(_), _))
+    );
 
     let mut cpp_name_attr = Vec::new();
     let mut impl_entry = None;
-    let mut trait_impl_entry = None;
+    let mut trait_impl_entries = Vec::new();
     let mut bindgen_mod_items = Vec::new();
     let always_unsafe_due_to_trait_definition = match kind {
         FnKind::TraitMethod { ref details, .. } => details.trait_call_is_unsafe,
         _ => false,
     };
+    let placement_return_path = match placement_return_type(&param_details) {
+        Some(Type::Path(typ)) if !matches!(kind, FnKind::TraitMethod { .. }) => Some(typ),
+        _ => None,
+    };
+    // A std::tuple listed using concrete! is returned as the corresponding
+    // Rust tuple, which lives alongside it in the root namespace.
+    let tuple_return = placement_return_path.and_then(|typ| {
+        let tuple_id = std_tuples.get(&QualifiedName::from_type_path(typ))?;
+        let mut typ = typ.clone();
+        typ.path.segments.last_mut().unwrap().ident = tuple_id.clone();
+        Some(Type::Path(typ))
+    });
     let fn_generator = FnGenerator {
         param_details: &param_details,
         cxxbridge_name: &cxxbridge_name,
@@ -122,10 +153,19 @@ pub(super) fn gen_function(
         unsafety: &analysis.requires_unsafe,
         always_unsafe_due_to_trait_definition,
         doc_attrs: &doc_attrs,
+        extra_attrs: &extra_attrs,
+        must_use_attr: &must_use_attr,
         non_pod_types,
         ret_type: &ret_type,
         ret_conversion: &ret_conversion,
         reference_wrappers: config.unsafe_policy.requires_cpprefs(),
+        pin_box_return: tuple_return.is_none()
+            && placement_return_path.is_some_and(|typ| {
+                config.is_pin_box_type(&QualifiedName::from_type_path(typ).to_cpp_name())
+            }),
+        tuple_return,
+        fallible,
+        inline,
     };
     // In rare occasions, we might need to give an explicit lifetime.
     let (lifetime_tokens, params, ret_type) = add_explicit_lifetime_if_necessary(
@@ -150,14 +190,27 @@ pub(super) fn gen_function(
                 ref method_kind,
                 ..
             } => {
-                // Method, or static method.
+                // Method, or static method. A method which consumes its
+                // receiver instead takes it as an ordinary parameter.
                 impl_entry = Some(fn_generator.generate_method_impl(
-                    matches!(method_kind, MethodKind::Constructor { .. }),
+                    matches!(method_kind, MethodKind::Constructor { .. })
+                        || fun.references.is_rvalue_qualified_method(),
                     impl_for,
                 ));
             }
-            FnKind::TraitMethod { ref details, .. } => {
-                trait_impl_entry = Some(fn_generator.generate_trait_impl(details));
+            FnKind::TraitMethod {
+                ref details,
+                ref kind,
+                ref impl_for,
+            } => {
+                trait_impl_entries.push(fn_generator.generate_trait_impl(details));
+                if let TraitMethodKind::Arithmetic {
+                    value_impl_rhs: Some(rhs),
+                } = kind
+                {
+                    trait_impl_entries
+                        .push(fn_generator.generate_arithmetic_value_impl(details, impl_for, rhs));
+                }
             }
             _ => {
                 // Generate plain old function
@@ -196,6 +249,10 @@ pub(super) fn gen_function(
     // which the user has declared.
     let params = unqualify_params(params);
     let ret_type = unqualify_ret_type(ret_type.into_owned());
+    let ret_type = match ret_type {
+        ReturnType::Type(_, ty) if fallible => parse_quote! { -> Result<#ty> },
+        _ => ret_type,
+    };
     // And we need to make an attribute for the namespace that the function
     // itself is in.
     let namespace_attr = if ns.is_empty() || wrapper_function_needed {
@@ -214,18 +271,32 @@ pub(super) fn gen_function(
         #(#namespace_attr)*
         #(#cpp_name_attr)*
         #(#doc_attrs)*
+        #(#must_use_attr)*
+        #(#cfg_gate)*
         #vis #bridge_unsafety fn #cxxbridge_name #lifetime_tokens ( #params ) #ret_type;
     ));
     RsCodegenResult {
         extern_c_mod_items: vec![extern_c_mod_item],
         bindgen_mod_items,
         impl_entry,
-        trait_impl_entry,
+        trait_impl_entries,
         materializations: materialization.into_iter().collect(),
+        materialization_cfg: cfg_gate,
         ..Default::default()
     }
 }
 
+/// The type of object which a function constructs in place, if any.
+fn placement_return_type(param_details: &[ArgumentAnalysis]) -> Option<&Type> {
+    param_details
+        .iter()
+        .filter(|pd| pd.is_placement_return_destination)
+        .find_map(|pd| match pd.conversion.cxxbridge_type() {
+            Type::Ptr(TypePtr { elem, .. }) => Some(elem.as_ref()),
+            _ => None,
+        })
+}
+
 /// Knows how to generate a given function.
 #[derive(Clone)]
 struct FnGenerator<'a> {
@@ -237,11 +308,27 @@ struct FnGenerator<'a> {
     unsafety: &'a UnsafetyNeeded,
     always_unsafe_due_to_trait_definition: bool,
     doc_attrs: &'a Vec<Attribute>,
+    /// Attributes listed using `extra_attributes!`, which go only on
+    /// the wrapper, not on the `cxx::bridge` declaration, preceded by
+    /// any `#[cfg]` from `cfg_gate!`, which goes on both.
+    extra_attrs: &'a Vec<Attribute>,
+    /// `#[must_use]`, if the function is `[[nodiscard]]`. This has no
+    /// effect on trait methods, so goes only on inherent ones.
+    must_use_attr: &'a Option<Attribute>,
     non_pod_types: &'a HashSet<QualifiedName>,
     reference_wrappers: bool,
+    pin_box_return: bool,
+    tuple_return: Option<Type>,
+    fallible: bool,
+    /// Whether to mark the wrapper `#[inline]`.
+    inline: bool,
 }
 
 impl<'a> FnGenerator<'a> {
+    fn inline_attr(&self) -> Option<TokenStream> {
+        self.inline.then(|| quote! { #[inline] })
+    }
+
     fn common_parts<'b>(
         &'b self,
         avoid_self: bool,
@@ -262,6 +349,10 @@ impl<'a> FnGenerator<'a> {
             .unwrap_or_else(|| Cow::Borrowed(self.ret_type));
         let mut any_conversion_requires_unsafe = false;
         let mut variable_counter = 0usize;
+        let mut out_params = Vec::new();
+        // Checks that integer arguments fit, which must happen before we
+        // construct anything so that we can return early if not.
+        let mut integer_checks = Vec::new();
         for pd in self.param_details {
             let wrapper_arg_name: syn::Pat = if pd.self_type.is_some() && !avoid_self {
                 parse_quote!(self)
@@ -279,7 +370,14 @@ impl<'a> FnGenerator<'a> {
                     conversion_requires_unsafe,
                 } => {
                     arg_list.push(conversion.clone());
-                    local_variables.append(&mut these_local_variables);
+                    if matches!(
+                        pd.conversion.rust_conversion,
+                        RustConversionType::FromCheckedInteger(_)
+                    ) {
+                        integer_checks.append(&mut these_local_variables);
+                    } else {
+                        local_variables.append(&mut these_local_variables);
+                    }
                     if pd.is_placement_return_destination {
                         ptr_arg_name = Some(conversion);
                     } else {
@@ -298,11 +396,26 @@ impl<'a> FnGenerator<'a> {
                     });
                     arg_list.push(pd.name.to_token_stream());
                 }
+                RustParamConversion::OutParam { ty } => {
+                    let name = &pd.name;
+                    local_variables.push(MaybeUnsafeStmt::new(quote! {
+                        let mut #name: #ty = Default::default();
+                    }));
+                    arg_list.push(quote! { &mut #name });
+                    out_params.push((name.to_token_stream(), ty));
+                }
             }
         }
         if let Some(parameter_reordering) = &parameter_reordering {
             wrapper_params = Self::reorder_parameters(wrapper_params, parameter_reordering);
         }
+        if let Some(tuple_ty) = &self.tuple_return {
+            ret_type = Cow::Owned(parse_quote! { -> #tuple_ty });
+        } else if self.pin_box_return {
+            if let Some(ty) = placement_return_type(self.param_details) {
+                ret_type = Cow::Owned(parse_quote! { -> ::core::pin::Pin<Box<#ty>> });
+            }
+        }
         let (lifetime_tokens, wrapper_params, ret_type) = add_explicit_lifetime_if_necessary(
             self.param_details,
             wrapper_params,
@@ -360,6 +473,67 @@ impl<'a> FnGenerator<'a> {
             }
             _ => (call_body, ret_type),
         };
+        let (call_body, ret_type) = if out_params.is_empty() {
+            (call_body, ret_type)
+        } else {
+            // Return any output parameters alongside the original return
+            // value, if there was one.
+            let (out_names, out_types): (Vec<_>, Vec<_>) = out_params.into_iter().unzip();
+            let expr = maybe_unsafes_to_tokens(vec![call_body], context_is_unsafe);
+            let (ret_vals, ret_types) = match ret_type.as_ref() {
+                ReturnType::Default => {
+                    local_variables.push(MaybeUnsafeStmt::new(quote! { #expr; }));
+                    (out_names, out_types)
+                }
+                ReturnType::Type(_, ty) => {
+                    local_variables.push(MaybeUnsafeStmt::new(quote! {
+                        let autocxx_ret_val = #expr;
+                    }));
+                    (
+                        std::iter::once(quote! { autocxx_ret_val })
+                            .chain(out_names)
+                            .collect(),
+                        std::iter::once(ty.as_ref().clone())
+                            .chain(out_types)
+                            .collect(),
+                    )
+                }
+            };
+            if ret_vals.len() == 1 {
+                (
+                    MaybeUnsafeStmt::new(quote! { #(#ret_vals)* }),
+                    Cow::Owned(parse_quote! { -> #(#ret_types)* }),
+                )
+            } else {
+                (
+                    MaybeUnsafeStmt::new(quote! { ( #(#ret_vals),* ) }),
+                    Cow::Owned(parse_quote! { -> ( #(#ret_types),* ) }),
+                )
+            }
+        };
+        let ret_type = match ret_type.as_ref() {
+            ReturnType::Type(_, ty) if self.fallible => Cow::Owned(parse_quote! {
+                -> ::core::result::Result<#ty, cxx::Exception>
+            }),
+            _ => ret_type,
+        };
+        let integer_checks_return_result = self.param_details.iter().any(|pd| {
+            matches!(
+                pd.conversion.rust_conversion,
+                RustConversionType::FromCheckedInteger(IntegerConversions::Result)
+            )
+        });
+        let ret_type = if integer_checks_return_result {
+            let ty = match ret_type.as_ref() {
+                ReturnType::Default => parse_quote! { () },
+                ReturnType::Type(_, ty) => ty.as_ref().clone(),
+            };
+            Cow::Owned(parse_quote! {
+                -> ::core::result::Result<#ty, autocxx::IntegerConversionError>
+            })
+        } else {
+            ret_type
+        };
 
         let call_stmts = if let Some(ptr_arg_name) = ptr_arg_name {
             let mut closure_stmts = local_variables;
@@ -369,17 +543,44 @@ impl<'a> FnGenerator<'a> {
             ));
             closure_stmts.push(call_body);
             let closure_stmts = maybe_unsafes_to_tokens(closure_stmts, true);
-            vec![MaybeUnsafeStmt::needs_unsafe(parse_quote! {
+            let new_expr = quote! {
                 autocxx::moveit::new::by_raw(move |#ptr_arg_name| {
                     #closure_stmts
                 })
-            })]
+            };
+            if self.tuple_return.is_some() {
+                // Construct the C++ tuple only for long enough to copy
+                // its elements into a Rust tuple.
+                vec![MaybeUnsafeStmt::needs_unsafe(quote! {
+                    autocxx::WithinBox::within_box(#new_expr).to_tuple()
+                })]
+            } else if self.pin_box_return {
+                // Rather than returning the impl New, construct the
+                // object in place within a box straight away.
+                vec![MaybeUnsafeStmt::needs_unsafe(quote! {
+                    autocxx::WithinBox::within_box(#new_expr)
+                })]
+            } else {
+                vec![MaybeUnsafeStmt::needs_unsafe(new_expr)]
+            }
         } else {
             let mut call_stmts = local_variables;
             call_stmts.push(call_body);
             call_stmts
         };
         let call_body = maybe_unsafes_to_tokens(call_stmts, context_is_unsafe);
+        let integer_checks = maybe_unsafes_to_tokens(integer_checks, context_is_unsafe);
+        let call_body = if integer_checks_return_result {
+            quote! {
+                #integer_checks
+                ::core::result::Result::Ok({ #call_body })
+            }
+        } else {
+            quote! {
+                #integer_checks
+                #call_body
+            }
+        };
         (lifetime_tokens, wrapper_params, ret_type, call_body)
     }
 
@@ -394,6 +595,9 @@ impl<'a> FnGenerator<'a> {
         let rust_name = make_ident(self.rust_name);
         let unsafety = self.unsafety.wrapper_token();
         let doc_attrs = self.doc_attrs;
+        let must_use_attr = self.must_use_attr;
+        let extra_attrs = self.extra_attrs;
+        let inline_attr = self.inline_attr();
         let receiver_pointerness = self
             .param_details
             .iter()
@@ -422,6 +626,9 @@ impl<'a> FnGenerator<'a> {
         Box::new(ImplBlockDetails {
             item: ImplItem::Fn(parse_quote! {
                 #(#doc_attrs)*
+                #(#must_use_attr)*
+                #(#extra_attrs)*
+                #inline_attr
                 pub #unsafety fn #rust_name #lifetime_tokens ( #wrapper_params ) #ret_type {
                     #call_body
                 }
@@ -432,19 +639,78 @@ impl<'a> FnGenerator<'a> {
 
     /// Generate an 'impl Trait for Type { methods-go-here }' in its entrety.
     fn generate_trait_impl(&self, details: &TraitMethodDetails) -> Box<TraitImplBlockDetails> {
-        let (lifetime_tokens, wrapper_params, ret_type, call_body) =
+        let (lifetime_tokens, mut wrapper_params, ret_type, call_body) =
             self.common_parts(details.avoid_self, &details.parameter_reordering, None);
+        // A trait implemented for a reference, such as `Add for &T`, must
+        // take `self` as exactly that reference.
+        if let Some(FnArg::Typed(pt)) = wrapper_params.first() {
+            let is_self = matches!(&*pt.pat, Pat::Ident(pi) if pi.ident == "self");
+            if is_self && matches!(*details.trt.ty, Type::Reference(_)) {
+                wrapper_params[0] = parse_quote! { self };
+            }
+        }
         let doc_attrs = self.doc_attrs;
+        let extra_attrs = self.extra_attrs;
+        let inline_attr = self.inline_attr();
         let unsafety = self.unsafety.wrapper_token();
         let key = details.trt.clone();
         let method_name = &details.method_name;
-        let item = parse_quote! {
+        let mut items: Vec<TraitItem> = details
+            .associated_type
+            .iter()
+            .map(|(name, ty)| {
+                parse_quote! {
+                    type #name = #ty;
+                }
+            })
+            .collect();
+        items.push(parse_quote! {
             #(#doc_attrs)*
+            #(#extra_attrs)*
+            #inline_attr
             #unsafety fn #method_name #lifetime_tokens ( #wrapper_params ) #ret_type {
                 #call_body
             }
-        };
-        Box::new(TraitImplBlockDetails { item, key })
+        });
+        Box::new(TraitImplBlockDetails { items, key })
+    }
+
+    /// Implement an arithmetic operator for a POD type itself, given its
+    /// implementation for a reference to that type, to which we forward.
+    /// This lets Rust code write `a + b` as well as `&a + b`.
+    fn generate_arithmetic_value_impl(
+        &self,
+        details: &TraitMethodDetails,
+        impl_for: &QualifiedName,
+        rhs: &Type,
+    ) -> Box<TraitImplBlockDetails> {
+        let ref_ty = &details.trt.ty;
+        let trt = &details.trt.trait_signature;
+        let method_name = &details.method_name;
+        let inline_attr = self.inline_attr();
+        let mut items: Vec<TraitItem> = details
+            .associated_type
+            .iter()
+            .map(|(name, ty)| {
+                parse_quote! {
+                    type #name = #ty;
+                }
+            })
+            .collect();
+        items.push(parse_quote! {
+            #inline_attr
+            fn #method_name(self, rhs: #rhs) -> Self::Output {
+                <#ref_ty as #trt>::#method_name(&self, rhs)
+            }
+        });
+        Box::new(TraitImplBlockDetails {
+            items,
+            key: TraitImplSignature {
+                ty: Type::Path(impl_for.to_type_path()).into(),
+                trait_signature: trt.clone(),
+                unsafety: None,
+            },
+        })
     }
 
     /// Generate a 'impl Type { methods-go-here }' item which is a constructor
@@ -458,11 +724,15 @@ impl<'a> FnGenerator<'a> {
             self.common_parts(true, &None, Some(ret_type));
         let rust_name = make_ident(self.rust_name);
         let doc_attrs = self.doc_attrs;
+        let extra_attrs = self.extra_attrs;
+        let inline_attr = self.inline_attr();
         let unsafety = self.unsafety.wrapper_token();
         let ty = impl_block_type_name.get_final_ident();
         let ty = parse_quote! { #ty };
         let stuff = quote! {
                 #(#doc_attrs)*
+                #(#extra_attrs)*
+                #inline_attr
                 pub #unsafety fn #rust_name #lifetime_tokens ( #wrapper_params ) #ret_type {
                     #call_body
                 }
@@ -479,9 +749,15 @@ impl<'a> FnGenerator<'a> {
             self.common_parts(false, &None, None);
         let rust_name = make_ident(self.rust_name);
         let doc_attrs = self.doc_attrs;
+        let must_use_attr = self.must_use_attr;
+        let extra_attrs = self.extra_attrs;
+        let inline_attr = self.inline_attr();
         let unsafety = self.unsafety.wrapper_token();
         Item::Fn(parse_quote! {
             #(#doc_attrs)*
+            #(#must_use_attr)*
+            #(#extra_attrs)*
+            #inline_attr
             pub #unsafety fn #rust_name #lifetime_tokens ( #wrapper_params ) #ret_type {
                 #call_body
             }
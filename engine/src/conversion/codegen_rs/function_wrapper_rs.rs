@@ -6,14 +6,16 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use autocxx_parser::IntegerConversions;
 use proc_macro2::TokenStream;
 use syn::{Expr, Type, TypePtr};
 
 use crate::{
     conversion::analysis::fun::function_wrapper::{RustConversionType, TypeConversionPolicy},
-    types::make_ident,
+    known_types::known_types,
+    types::{make_ident, QualifiedName},
 };
-use quote::quote;
+use quote::{quote, ToTokens};
 use syn::parse_quote;
 
 use super::MaybeUnsafeStmt;
@@ -29,6 +31,9 @@ pub(super) enum RustParamConversion {
     ReturnValue {
         ty: Type,
     },
+    OutParam {
+        ty: Type,
+    },
 }
 
 impl TypeConversionPolicy {
@@ -48,9 +53,9 @@ impl TypeConversionPolicy {
             },
             RustConversionType::ToBoxedUpHolder(ref sub) => {
                 let holder_type = sub.holder();
-                let id = sub.id();
+                let rust_path = sub.rust_path();
                 let ty = parse_quote! { autocxx::subclass::CppSubclassRustPeerHolder<
-                    super::super::super:: #id>
+                    #rust_path>
                 };
                 RustParamConversion::Param {
                     ty,
@@ -164,6 +169,16 @@ impl TypeConversionPolicy {
                 };
                 RustParamConversion::ReturnValue { ty }
             }
+            // This parameter appears in the cxx::bridge but not in the
+            // arguments for the wrapper function, because the wrapper
+            // allocates the value itself and returns it.
+            RustConversionType::FromOutParamToReturn => {
+                let ty = match self.cxxbridge_type() {
+                    Type::Ptr(TypePtr { elem, .. }) => *(*elem).clone(),
+                    _ => panic!("Not a ptr"),
+                };
+                RustParamConversion::OutParam { ty }
+            }
             RustConversionType::FromPointerToReferenceWrapper => {
                 let (is_mut, ty) = match self.cxxbridge_type() {
                     Type::Ptr(TypePtr {
@@ -213,6 +228,86 @@ impl TypeConversionPolicy {
                     conversion_requires_unsafe: false,
                 }
             }
+            RustConversionType::FromCharPtrToCStr { borrows_receiver } => {
+                RustParamConversion::Param {
+                    // Lifetime elision ties the string to the receiver.
+                    ty: if borrows_receiver {
+                        parse_quote! { Option<&::std::ffi::CStr> }
+                    } else {
+                        parse_quote! { Option<&'static ::std::ffi::CStr> }
+                    },
+                    local_variables: Vec::new(),
+                    conversion: quote! {
+                        {
+                            let ptr = #var;
+                            if ptr.is_null() {
+                                None
+                            } else {
+                                Some(unsafe { ::std::ffi::CStr::from_ptr(ptr) })
+                            }
+                        }
+                    },
+                    conversion_requires_unsafe: false,
+                }
+            }
+            RustConversionType::FromCharPtrToString => RustParamConversion::Param {
+                ty: parse_quote! { String },
+                local_variables: Vec::new(),
+                conversion: quote! {
+                    {
+                        let ptr = #var;
+                        if ptr.is_null() {
+                            String::new()
+                        } else {
+                            unsafe { ::std::ffi::CStr::from_ptr(ptr) }
+                                .to_string_lossy()
+                                .into_owned()
+                        }
+                    }
+                },
+                conversion_requires_unsafe: false,
+            },
+            RustConversionType::FromCheckedInteger(policy) => {
+                let tn = match self.cxxbridge_type() {
+                    Type::Path(typ) => QualifiedName::from_type_path(typ),
+                    _ => panic!("Not an integer"),
+                };
+                let primitive = known_types()
+                    .integer_primitive(&tn)
+                    .expect("Not an integer");
+                let param_name = var.to_token_stream().to_string();
+                let on_overflow = match policy {
+                    IntegerConversions::Result => quote! {
+                        return ::core::result::Result::Err(
+                            autocxx::IntegerConversionError::new(#param_name)
+                        )
+                    },
+                    _ => {
+                        let message = format!(
+                            "integer argument `{param_name}` is out of range for its C++ type"
+                        );
+                        quote! { panic!(#message) }
+                    }
+                };
+                // Our wrappers around C integer types convert from the
+                // primitive types they wrap.
+                let conversion = if known_types().is_ctype(&tn) {
+                    quote! { #var.into() }
+                } else {
+                    quote! { #var }
+                };
+                RustParamConversion::Param {
+                    ty: parse_quote! { impl ::core::convert::TryInto<#primitive> },
+                    local_variables: vec![MaybeUnsafeStmt::new(quote! {
+                        let #var: #primitive = match ::core::convert::TryInto::try_into(#var) {
+                            ::core::result::Result::Ok(val) => val,
+                            ::core::result::Result::Err(_) => #on_overflow,
+                        };
+                    })],
+                    conversion,
+                    conversion_requires_unsafe: false,
+                }
+            }
         }
     }
 }
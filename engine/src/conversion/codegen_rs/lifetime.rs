@@ -16,7 +16,7 @@ use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
 use std::borrow::Cow;
 use syn::{
-    parse_quote, punctuated::Punctuated, token::Comma, FnArg, GenericArgument, PatType, Path,
+    parse_quote, punctuated::Punctuated, token::Comma, FnArg, GenericArgument, Pat, PatType, Path,
     PathSegment, ReturnType, Type, TypePath, TypeReference,
 };
 
@@ -31,6 +31,9 @@ use syn::{
 ///    built-in type
 /// 3) Any parameter is any form of reference, and we're returning an `impl New`
 ///    3a) an 'impl ValueParam' counts as a reference.
+/// 4) The user has told us which parameter the returned reference borrows
+///    from, using `return_borrows!`. In this case only that parameter gets
+///    the lifetime.
 pub(crate) fn add_explicit_lifetime_if_necessary<'r>(
     param_details: &[ArgumentAnalysis],
     mut params: Punctuated<FnArg, Comma>,
@@ -58,7 +61,16 @@ pub(crate) fn add_explicit_lifetime_if_necessary<'r>(
     let ret_type_pod = return_type_is_pod_or_known_type_reference(&ret_type, non_pod_types);
     let returning_impl_with_a_reference_param = return_type_is_impl && any_param_is_reference;
     let hits_1024_bug = non_pod_ref_param && ret_type_pod;
-    if !(has_mutable_receiver || hits_1024_bug || returning_impl_with_a_reference_param) {
+    let borrowed_params: HashSet<String> = param_details
+        .iter()
+        .filter(|pd| pd.is_borrowed_by_return)
+        .filter_map(|pd| pat_name(&pd.name))
+        .collect();
+    if !(has_mutable_receiver
+        || hits_1024_bug
+        || returning_impl_with_a_reference_param
+        || !borrowed_params.is_empty())
+    {
         return (None, params, ret_type);
     }
     let new_return_type = match ret_type.as_ref() {
@@ -91,9 +103,21 @@ pub(crate) fn add_explicit_lifetime_if_necessary<'r>(
     match new_return_type {
         None => (None, params, ret_type),
         Some(new_return_type) => {
-            for FnArg::Typed(PatType { ty, .. }) | FnArg::Receiver(syn::Receiver { ty, .. }) in
-                params.iter_mut()
-            {
+            for param in params.iter_mut() {
+                if !borrowed_params.is_empty() {
+                    let name = match param {
+                        FnArg::Receiver(_) => Some("self".to_string()),
+                        FnArg::Typed(PatType { pat, .. }) => pat_name(pat),
+                    };
+                    if !name
+                        .map(|name| borrowed_params.contains(&name))
+                        .unwrap_or_default()
+                    {
+                        continue;
+                    }
+                }
+                let (FnArg::Typed(PatType { ty, .. }) | FnArg::Receiver(syn::Receiver { ty, .. })) =
+                    param;
                 match ty.as_mut() {
                     Type::Path(TypePath {
                         path: Path { segments, .. },
@@ -110,6 +134,13 @@ pub(crate) fn add_explicit_lifetime_if_necessary<'r>(
     }
 }
 
+fn pat_name(pat: &Pat) -> Option<String> {
+    match pat {
+        Pat::Ident(pp) => Some(pp.ident.to_string()),
+        _ => None,
+    }
+}
+
 fn reference_parameter_is_non_pod_reference(
     params: &Punctuated<FnArg, Comma>,
     non_pod_types: &HashSet<QualifiedName>,
@@ -6,6 +6,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+mod const_generics;
 mod fun_codegen;
 mod function_wrapper_rs;
 mod impl_item_creator;
@@ -17,42 +18,50 @@ pub(crate) mod unqualify;
 use indexmap::map::IndexMap as HashMap;
 use indexmap::set::IndexSet as HashSet;
 
-use autocxx_parser::{ExternCppType, IncludeCppConfig, RustFun, UnsafePolicy};
+use autocxx_parser::{IncludeCppConfig, LongMapping, RustFun, UnsafePolicy};
 
 use itertools::Itertools;
 use proc_macro2::{Span, TokenStream};
+use syn::visit_mut::{visit_type_mut, VisitMut};
 use syn::{
     parse_quote, punctuated::Punctuated, token::Comma, Attribute, Expr, FnArg, ForeignItem,
-    ForeignItemFn, Ident, ImplItem, Item, ItemForeignMod, ItemMod, Lifetime, TraitItem, Type,
-    TypePath,
+    ForeignItemFn, Ident, ImplItem, Item, ItemEnum, ItemForeignMod, ItemMod, ItemStruct, Lifetime,
+    Meta, TraitItem, Type, TypePath, Visibility,
 };
 
 use crate::{
     conversion::codegen_rs::{
-        non_pod_struct::{make_non_pod, new_non_pod_struct},
+        non_pod_struct::{make_lit_int, make_non_pod, new_non_pod_struct},
         unqualify::{unqualify_params, unqualify_ret_type},
     },
+    known_types::known_types,
     minisyn::minisynize_punctuated,
     types::{make_ident, Namespace, QualifiedName},
+    ImplBlockPlacement,
 };
 use impl_item_creator::create_impl_items;
 
 use self::{
+    const_generics::ConstGenericFamilies,
     fun_codegen::gen_function,
     namespace_organizer::{HasNs, NamespaceEntries},
 };
 
 use super::{
     analysis::{
-        fun::{FnPhase, PodAndDepAnalysis, ReceiverMutability},
-        pod::PodAnalysis,
+        cxx_free::{c_abi_shim_base_name, find_c_abi_functions},
+        fun::{FnAnalysis, FnPhase, PodAndDepAnalysis, ReceiverMutability},
+        pod::{BaseFieldAccessor, PodAnalysis},
     },
-    api::{AnalysisPhase, Api, SubclassName, TypeKind, TypedefKind},
+    api::{AnalysisPhase, Api, SubclassKind, SubclassName, TypeKind, TypedefKind},
     convert_error::ErrorContextType,
     doc_attr::get_doc_attrs,
 };
 use super::{
-    api::{Layout, Provenance, RustSubclassFnDetails, SuperclassMethod, TraitImplSignature},
+    api::{
+        FuncToConvert, Layout, Provenance, RustSubclassFnDetails, StdMapEntryType,
+        SuperclassMethod, TraitImplSignature,
+    },
     apivec::ApiVec,
     codegen_cpp::type_to_cpp::CppNameMap,
 };
@@ -72,7 +81,7 @@ struct ImplBlockDetails {
 }
 
 struct TraitImplBlockDetails {
-    item: TraitItem,
+    items: Vec<TraitItem>,
     key: TraitImplSignature,
 }
 
@@ -144,6 +153,9 @@ pub(crate) struct RsCodeGenerator<'a> {
     original_name_map: CppNameMap,
     config: &'a IncludeCppConfig,
     header_name: Option<String>,
+    const_generic_families: ConstGenericFamilies,
+    impl_block_placement: ImplBlockPlacement,
+    inline_wrappers: bool,
 }
 
 impl<'a> RsCodeGenerator<'a> {
@@ -155,6 +167,8 @@ impl<'a> RsCodeGenerator<'a> {
         bindgen_mod: ItemMod,
         config: &'a IncludeCppConfig,
         header_name: Option<String>,
+        impl_block_placement: ImplBlockPlacement,
+        inline_wrappers: bool,
     ) -> Vec<Item> {
         let c = Self {
             unsafe_policy,
@@ -163,6 +177,9 @@ impl<'a> RsCodeGenerator<'a> {
             original_name_map: CppNameMap::new_from_apis(&all_apis),
             config,
             header_name,
+            const_generic_families: ConstGenericFamilies::new_from_apis(&all_apis),
+            impl_block_placement,
+            inline_wrappers,
         };
         c.rs_codegen(all_apis)
     }
@@ -175,6 +192,12 @@ impl<'a> RsCodeGenerator<'a> {
         let subclasses_with_a_single_trivial_constructor =
             find_trivially_constructed_subclasses(&all_apis);
         let non_pod_types = find_non_pod_types(&all_apis);
+        let std_tuples = find_std_tuples(&all_apis);
+        let c_abi_functions = if self.config.generates_c_abi_shims() && !self.config.is_cxx_free() {
+            find_c_abi_functions(&all_apis)
+        } else {
+            HashSet::new()
+        };
         // Now let's generate the Rust code.
         let (rs_codegen_results_and_namespaces, additional_cpp_needs): (Vec<_>, Vec<_>) = all_apis
             .into_iter()
@@ -186,6 +209,8 @@ impl<'a> RsCodeGenerator<'a> {
                     &methods_by_superclass,
                     &subclasses_with_a_single_trivial_constructor,
                     &non_pod_types,
+                    &std_tuples,
+                    &c_abi_functions,
                 );
                 ((name, gen), more_cpp_needed)
             })
@@ -193,7 +218,7 @@ impl<'a> RsCodeGenerator<'a> {
         // First, the hierarchy of mods containing lots of 'use' statements
         // which is the final API exposed as 'ffi'.
         let mut use_statements =
-            Self::generate_final_use_statements(&rs_codegen_results_and_namespaces);
+            Self::generate_final_use_statements(&rs_codegen_results_and_namespaces, self.config);
         // And work out what we need for the bindgen mod.
         let bindgen_root_items =
             self.generate_final_bindgen_mods(&rs_codegen_results_and_namespaces);
@@ -255,12 +280,14 @@ impl<'a> RsCodeGenerator<'a> {
             })];
             all_items.push(Item::Mod(self.bindgen_mod));
         }
-        all_items.push(Item::Mod(parse_quote! {
-            #[cxx::bridge]
-            mod cxxbridge {
-                #(#bridge_items)*
-            }
-        }));
+        if !self.config.is_cxx_free() {
+            all_items.push(Item::Mod(parse_quote! {
+                #[cxx::bridge]
+                mod cxxbridge {
+                    #(#bridge_items)*
+                }
+            }));
+        }
 
         all_items.push(Item::Use(parse_quote! {
             #[allow(unused_imports)]
@@ -319,31 +346,58 @@ impl<'a> RsCodeGenerator<'a> {
     /// mod hierarchy according to C++ namespaces.
     fn generate_final_use_statements(
         input_items: &[(QualifiedName, RsCodegenResult)],
+        config: &IncludeCppConfig,
     ) -> Vec<Item> {
         let mut output_items = Vec::new();
-        let ns_entries = NamespaceEntries::new(input_items);
-        Self::append_child_use_namespace(&ns_entries, &mut output_items);
+        let mut ns_entries = NamespaceEntries::new(input_items);
+        if config.flatten_namespaces() {
+            ns_entries.flatten_single_item_chains(&|(name, codegen)| {
+                Self::materialized_name(name, codegen)
+            });
+        }
+        Self::append_child_use_namespace(&ns_entries, &mut output_items, 0, config);
         output_items
     }
 
+    /// The name by which an API is known in the output mod hierarchy,
+    /// if it appears there at all.
+    fn materialized_name(name: &QualifiedName, codegen: &RsCodegenResult) -> Option<String> {
+        if codegen.materializations.is_empty() {
+            return None;
+        }
+        codegen
+            .materializations
+            .iter()
+            .find_map(|materialization| match materialization {
+                Use::UsedFromCxxBridgeWithAlias(alias) => Some(alias.to_string()),
+                _ => None,
+            })
+            .or_else(|| Some(name.get_final_item().to_string()))
+    }
+
     fn append_child_use_namespace(
         ns_entries: &NamespaceEntries<(QualifiedName, RsCodegenResult)>,
         output_items: &mut Vec<Item>,
+        depth: usize,
+        config: &IncludeCppConfig,
     ) {
         for (name, codegen) in ns_entries.entries() {
+            let vis = config.get_item_visibility(&name.to_cpp_name());
             output_items.extend(codegen.materializations.iter().map(|materialization| {
-                match materialization {
+                let item = match materialization {
                     Use::UsedFromCxxBridgeWithAlias(ref alias) => {
-                        Self::generate_cxx_use_stmt(name, Some(alias))
+                        Self::generate_cxx_use_stmt(name, Some(alias), depth)
                     }
-                    Use::UsedFromCxxBridge => Self::generate_cxx_use_stmt(name, None),
-                    Use::UsedFromBindgen => Self::generate_bindgen_use_stmt(name),
+                    Use::UsedFromCxxBridge => Self::generate_cxx_use_stmt(name, None, depth),
+                    Use::UsedFromBindgen => Self::generate_bindgen_use_stmt(name, depth),
                     Use::SpecificNameFromBindgen(id) => {
                         let name = QualifiedName::new(name.get_namespace(), id.clone().into());
-                        Self::generate_bindgen_use_stmt(&name)
+                        Self::generate_bindgen_use_stmt(&name, depth)
                     }
                     Use::Custom(item) => *item.clone(),
-                }
+                };
+                let item = Self::apply_cfg(item, codegen.materialization_cfg.as_ref());
+                Self::apply_visibility(item, vis)
             }));
         }
         for (child_name, child_ns_entries) in ns_entries.children() {
@@ -358,27 +412,64 @@ impl<'a> RsCodeGenerator<'a> {
             Self::append_child_use_namespace(
                 child_ns_entries,
                 &mut new_mod.content.as_mut().unwrap().1,
+                depth + 1,
+                config,
             );
             output_items.push(Item::Mod(new_mod));
         }
     }
 
-    fn append_uses_for_ns(&mut self, items: &mut Vec<Item>, ns: &Namespace) {
-        let mut imports_from_super = vec!["cxxbridge"];
-        if !self.config.exclude_utilities() {
-            imports_from_super.push("ToCppString");
+    /// Overrides the visibility of a `use` statement, if the user asked
+    /// for the item to be given a different visibility from the default.
+    fn apply_cfg(item: Item, cfg: Option<&Attribute>) -> Item {
+        match (item, cfg) {
+            (Item::Use(mut item_use), Some(cfg)) => {
+                item_use.attrs.push(cfg.clone());
+                Item::Use(item_use)
+            }
+            (item, _) => item,
+        }
+    }
+
+    fn apply_visibility(item: Item, vis: Option<&Visibility>) -> Item {
+        match (item, vis) {
+            (Item::Use(mut item_use), Some(vis)) => {
+                item_use.vis = vis.clone();
+                // The item may no longer be used by anything.
+                if !matches!(vis, Visibility::Public(_))
+                    && !item_use
+                        .attrs
+                        .iter()
+                        .any(|attr| attr.path().is_ident("allow"))
+                {
+                    item_use
+                        .attrs
+                        .push(parse_quote! { #[allow(unused_imports)] });
+                }
+                Item::Use(item_use)
+            }
+            (item, _) => item,
         }
-        let imports_from_super = imports_from_super.into_iter().map(make_ident);
+    }
+
+    fn append_uses_for_ns(&mut self, items: &mut Vec<Item>, ns: &Namespace) {
         let super_duper = std::iter::repeat(make_ident("super")); // I'll get my coat
-        let supers = super_duper.clone().take(ns.depth() + 2);
-        items.push(Item::Use(parse_quote! {
-            #[allow(unused_imports)]
-            use self::
-                #(#supers)::*
-            ::{
-                #(#imports_from_super),*
-            };
-        }));
+        if !self.config.is_cxx_free() {
+            let mut imports_from_super = vec!["cxxbridge"];
+            if !self.config.exclude_utilities() {
+                imports_from_super.push("ToCppString");
+            }
+            let imports_from_super = imports_from_super.into_iter().map(make_ident);
+            let supers = super_duper.clone().take(ns.depth() + 2);
+            items.push(Item::Use(parse_quote! {
+                #[allow(unused_imports)]
+                use self::
+                    #(#supers)::*
+                ::{
+                    #(#imports_from_super),*
+                };
+            }));
+        }
         let supers = super_duper.take(ns.depth() + 1);
         items.push(Item::Use(parse_quote! {
             #[allow(unused_imports)]
@@ -404,17 +495,18 @@ impl<'a> RsCodeGenerator<'a> {
                     .or_default()
                     .push(&impl_entry.item);
             }
-            if let Some(trait_impl_entry) = &item.1.trait_impl_entry {
+            for trait_impl_entry in &item.1.trait_impl_entries {
                 trait_impl_entries_by_trait_and_ty
                     .entry(trait_impl_entry.key.clone())
                     .or_default()
-                    .push(&trait_impl_entry.item);
+                    .extend(&trait_impl_entry.items);
             }
         }
+        let mut impl_blocks = Vec::new();
         for (ty, entries) in impl_entries_by_type.into_iter() {
             let lt = ty.lifetime.map(|lt| quote! { < #lt > });
             let ty = ty.ty;
-            output_items.push(Item::Impl(parse_quote! {
+            impl_blocks.push(Item::Impl(parse_quote! {
                 impl #lt #ty {
                     #(#entries)*
                 }
@@ -424,12 +516,13 @@ impl<'a> RsCodeGenerator<'a> {
             let unsafety = key.unsafety;
             let ty = key.ty;
             let trt = key.trait_signature;
-            output_items.push(Item::Impl(parse_quote! {
+            impl_blocks.push(Item::Impl(parse_quote! {
                 #unsafety impl #trt for #ty {
                     #(#entries)*
                 }
             }))
         }
+        self.append_impl_blocks(impl_blocks, output_items);
         for (child_name, child_ns_entries) in ns_entries.children() {
             let new_ns = ns.push((*child_name).clone());
             let child_id = make_ident(child_name);
@@ -448,6 +541,29 @@ impl<'a> RsCodeGenerator<'a> {
         }
     }
 
+    /// Adds `impl` blocks to a namespace's mod, dividing them among
+    /// sub-mods if the placement asks for that. `impl` blocks may live
+    /// anywhere in the crate, so this doesn't change any paths.
+    fn append_impl_blocks(&self, impl_blocks: Vec<Item>, output_items: &mut Vec<Item>) {
+        match self.impl_block_placement {
+            ImplBlockPlacement::InSubModsOf(max_size)
+                if max_size > 0 && impl_blocks.len() > max_size =>
+            {
+                for (i, chunk) in impl_blocks.chunks(max_size).enumerate() {
+                    let chunk_id = make_ident(format!("autocxx_impls_{i}"));
+                    output_items.push(Item::Mod(parse_quote! {
+                        mod #chunk_id {
+                            #[allow(unused_imports)]
+                            use super::*;
+                            #(#chunk)*
+                        }
+                    }));
+                }
+            }
+            _ => output_items.extend(impl_blocks),
+        }
+    }
+
     fn id_to_expr(id: &Ident) -> Expr {
         parse_quote! { #id }
     }
@@ -470,6 +586,8 @@ impl<'a> RsCodeGenerator<'a> {
         associated_methods: &HashMap<QualifiedName, Vec<SuperclassMethod>>,
         subclasses_with_a_single_trivial_constructor: &HashSet<QualifiedName>,
         non_pod_types: &HashSet<QualifiedName>,
+        std_tuples: &HashMap<QualifiedName, Ident>,
+        c_abi_functions: &HashSet<QualifiedName>,
     ) -> RsCodegenResult {
         let name = api.name().clone();
         let id = name.get_final_ident();
@@ -488,14 +606,51 @@ impl<'a> RsCodeGenerator<'a> {
                     ..Default::default()
                 }
             }
-            Api::Function { fun, analysis, .. } => gen_function(
-                name.get_namespace(),
-                *fun,
-                analysis,
-                cpp_call_name,
-                non_pod_types,
-                self.config,
-            ),
+            Api::StdVariant {
+                cpp_type,
+                alternatives,
+                ..
+            } => self.generate_std_variant(&id, &cpp_type, &alternatives),
+            Api::StdMapIterator {
+                map_type,
+                key,
+                value,
+                ..
+            } => self.generate_std_map_iterator(&id, &map_type, &key, &value),
+            Api::StdTuple {
+                tuple_type,
+                elements,
+                ..
+            } => self.generate_std_tuple(&id, &tuple_type, &elements),
+            Api::RangeIterator { function, item, .. } => {
+                self.generate_range_iterator(&id, &function, &item)
+            }
+            Api::Function { fun, analysis, .. } if self.config.is_cxx_free() => {
+                self.generate_cxx_free_function(*fun, analysis)
+            }
+            Api::CType { .. } if self.config.is_cxx_free() => RsCodegenResult::default(),
+            Api::Function { fun, analysis, .. } => {
+                let c_abi_shim = c_abi_functions
+                    .contains(&name)
+                    .then(|| self.generate_c_abi_shim_declaration(&name, &fun, &analysis));
+                let mut result = gen_function(
+                    name.get_namespace(),
+                    *fun,
+                    analysis,
+                    cpp_call_name,
+                    non_pod_types,
+                    std_tuples,
+                    self.config,
+                    self.inline_wrappers,
+                );
+                if let Some((item, rust_name)) = c_abi_shim {
+                    result.bindgen_mod_items.push(item);
+                    result
+                        .materializations
+                        .push(Use::SpecificNameFromBindgen(rust_name));
+                }
+                result
+            }
             Api::Const { const_item, .. } => RsCodegenResult {
                 bindgen_mod_items: vec![Item::Const(const_item.into())],
                 materializations: vec![Use::UsedFromBindgen],
@@ -515,7 +670,10 @@ impl<'a> RsCodeGenerator<'a> {
                     PodAndDepAnalysis {
                         pod:
                             PodAnalysis {
-                                is_generic, kind, ..
+                                is_generic,
+                                kind,
+                                base_field_accessors,
+                                ..
                             },
                         constructors,
                         ..
@@ -524,9 +682,13 @@ impl<'a> RsCodeGenerator<'a> {
             } => {
                 let doc_attrs = get_doc_attrs(&details.item.attrs);
                 let layout = details.layout.clone();
-                self.generate_type(
+                let newtype_conversions = (matches!(kind, TypeKind::Pod)
+                    && self.config.is_newtype_typedef(&name.to_cpp_name()))
+                .then(|| Self::generate_newtype_conversions(&details.item))
+                .unwrap_or_default();
+                let mut result = self.generate_type(
                     &name,
-                    id,
+                    id.clone(),
                     kind,
                     constructors.move_constructor,
                     constructors.destructor,
@@ -534,11 +696,34 @@ impl<'a> RsCodeGenerator<'a> {
                     associated_methods,
                     layout,
                     is_generic,
+                );
+                if !base_field_accessors.is_empty() {
+                    result
+                        .bindgen_mod_items
+                        .push(self.generate_base_field_accessors(&id, &base_field_accessors));
+                }
+                result.bindgen_mod_items.extend(newtype_conversions);
+                result
+            }
+            Api::Enum { item, .. } if self.config.is_bitflags_enum(&name.to_cpp_name()) => {
+                let doc_attrs = get_doc_attrs(&item.attrs);
+                let bitflags = Self::generate_enum_bitflags(&item, &doc_attrs);
+                self.generate_type(
+                    &name,
+                    id,
+                    TypeKind::Pod,
+                    true,
+                    true,
+                    || Some((bitflags, doc_attrs)),
+                    associated_methods,
+                    None,
+                    false,
                 )
             }
             Api::Enum { item, .. } => {
                 let doc_attrs = get_doc_attrs(&item.attrs);
-                self.generate_type(
+                let try_from_impl = Self::generate_enum_try_from(&item);
+                let mut result = self.generate_type(
                     &name,
                     id,
                     TypeKind::Pod,
@@ -548,19 +733,29 @@ impl<'a> RsCodeGenerator<'a> {
                     associated_methods,
                     None,
                     false,
-                )
+                );
+                result.bindgen_mod_items.push(try_from_impl);
+                result
+            }
+            Api::ConcreteType { .. } => {
+                let mut result = self.generate_type(
+                    &name,
+                    id,
+                    TypeKind::Abstract,
+                    false, // assume for now that these types can't be kept in a Vector
+                    true,  // assume for now that these types can be put in a smart pointer
+                    || None,
+                    associated_methods,
+                    None,
+                    false,
+                );
+                let (items, exposed) = self.const_generic_families.generate(&name);
+                result.bindgen_mod_items.extend(items);
+                result
+                    .materializations
+                    .extend(exposed.into_iter().map(Use::SpecificNameFromBindgen));
+                result
             }
-            Api::ConcreteType { .. } => self.generate_type(
-                &name,
-                id,
-                TypeKind::Abstract,
-                false, // assume for now that these types can't be kept in a Vector
-                true,  // assume for now that these types can be put in a smart pointer
-                || None,
-                associated_methods,
-                None,
-                false,
-            ),
             Api::ForwardDeclaration { .. } | Api::OpaqueTypedef { .. } => self.generate_type(
                 &name,
                 id,
@@ -631,13 +826,21 @@ impl<'a> RsCodeGenerator<'a> {
                     // TODO: Create an UnsafeCppPeerConstructor trait for calling an unsafe
                     // constructor instead? Need to create unsafe versions of everything that uses
                     // it too.
-                    matches!(self.unsafe_policy, UnsafePolicy::AllFunctionsSafe);
+                    (matches!(self.unsafe_policy, UnsafePolicy::AllFunctionsSafe)
+                        || matches!(name.1, SubclassKind::InterfaceImpl));
                 self.generate_subclass(name, &superclass, methods, generate_peer_constructor)
             }
             Api::ExternCppType {
-                details: ExternCppType { rust_path, .. },
-                ..
-            } => self.generate_extern_cpp_type(&name, rust_path, name.ns_segment_iter().count()),
+                details, enum_item, ..
+            } => {
+                let depth = name.ns_segment_iter().count();
+                let mut result = self.generate_extern_cpp_type(&name, details.rust_path, depth);
+                if details.is_enum {
+                    let items = Self::generate_extern_enum_items(&name, enum_item.as_deref());
+                    result.bindgen_mod_items.extend(items);
+                }
+                result
+            }
             Api::IgnoredItem {
                 err,
                 ctx: Some(ctx),
@@ -657,7 +860,7 @@ impl<'a> RsCodeGenerator<'a> {
         let super_name = superclass.get_final_item();
         let super_path = superclass.to_type_path();
         let super_cxxxbridge_id = superclass.get_final_ident();
-        let id = sub.id();
+        let rust_path = sub.rust_path();
         let holder = sub.holder();
         let full_cpp = sub.cpp();
         let cpp_path = full_cpp.to_type_path();
@@ -672,7 +875,7 @@ impl<'a> RsCodeGenerator<'a> {
                 pub use cxxbridge::#cpp_id;
             },
             parse_quote! {
-                pub struct #holder(pub autocxx::subclass::CppSubclassRustPeerHolder<super::super::super::#id>);
+                pub struct #holder(pub autocxx::subclass::CppSubclassRustPeerHolder<#rust_path>);
             },
             parse_quote! {
                 impl autocxx::subclass::CppSubclassCppPeer for #cpp_id {
@@ -718,15 +921,28 @@ impl<'a> RsCodeGenerator<'a> {
             if !methods_impls.is_empty() {
                 bindgen_mod_items.push(parse_quote! {
                     #[allow(non_snake_case)]
-                    impl #supers for super::super::super::#id {
+                    impl #supers for #rust_path {
                         #(#methods_impls)*
                     }
                 });
             }
         }
-        if generate_peer_constructor {
+        let is_interface_impl = matches!(sub.1, SubclassKind::InterfaceImpl);
+        if generate_peer_constructor && is_interface_impl {
+            // Nobody else can implement this trait for our synthesized struct,
+            // so we do so irrespective of the safety policy.
             bindgen_mod_items.push(parse_quote! {
-                impl autocxx::subclass::CppPeerConstructor<#cpp_id> for super::super::super::#id {
+                impl autocxx::subclass::CppPeerConstructor<#cpp_id> for #rust_path {
+                    #[allow(unused_unsafe)]
+                    fn make_peer(&mut self, peer_holder: autocxx::subclass::CppSubclassRustPeerHolder<Self>) -> cxx::UniquePtr<#cpp_path> {
+                        use autocxx::moveit::Emplace;
+                        cxx::UniquePtr::emplace(unsafe { #cpp_id :: new(peer_holder) })
+                    }
+                }
+            })
+        } else if generate_peer_constructor {
+            bindgen_mod_items.push(parse_quote! {
+                impl autocxx::subclass::CppPeerConstructor<#cpp_id> for #rust_path {
                     fn make_peer(&mut self, peer_holder: autocxx::subclass::CppSubclassRustPeerHolder<Self>) -> cxx::UniquePtr<#cpp_path> {
                         use autocxx::moveit::Emplace;
                         cxx::UniquePtr::emplace(#cpp_id :: new(peer_holder))
@@ -734,6 +950,14 @@ impl<'a> RsCodeGenerator<'a> {
                 }
             })
         };
+        if is_interface_impl {
+            bindgen_mod_items.extend(Self::generate_interface_impl(
+                &sub,
+                superclass,
+                methods,
+                generate_peer_constructor,
+            ));
+        }
 
         // Once for each superclass, in future...
         let as_id = make_ident(format!("As_{super_name}"));
@@ -749,7 +973,7 @@ impl<'a> RsCodeGenerator<'a> {
             fn #as_unique_ptr_id(u: UniquePtr<#cpp_id>) -> UniquePtr<#super_cxxxbridge_id>;
         });
         bindgen_mod_items.push(parse_quote! {
-            impl AsRef<#super_path> for super::super::super::#id {
+            impl AsRef<#super_path> for #rust_path {
                 fn as_ref(&self) -> &cxxbridge::#super_cxxxbridge_id {
                     use autocxx::subclass::CppSubclass;
                     self.peer().#as_id()
@@ -758,7 +982,7 @@ impl<'a> RsCodeGenerator<'a> {
         });
         // TODO it would be nice to impl AsMut here but pin prevents us
         bindgen_mod_items.push(parse_quote! {
-            impl super::super::super::#id {
+            impl #rust_path {
                 pub fn pin_mut(&mut self) -> ::core::pin::Pin<&mut cxxbridge::#super_cxxxbridge_id> {
                     use autocxx::subclass::CppSubclass;
                     self.peer_mut().#as_mut_id()
@@ -767,7 +991,7 @@ impl<'a> RsCodeGenerator<'a> {
         });
         let rs_as_unique_ptr_id = make_ident(format!("as_{super_name}_unique_ptr"));
         bindgen_mod_items.push(parse_quote! {
-            impl super::super::super::#id {
+            impl #rust_path {
                 pub fn #rs_as_unique_ptr_id(u: cxx::UniquePtr<#cpp_id>) -> cxx::UniquePtr<cxxbridge::#super_cxxxbridge_id> {
                     cxxbridge::#as_unique_ptr_id(u)
                 }
@@ -803,6 +1027,594 @@ impl<'a> RsCodeGenerator<'a> {
         }
     }
 
+    /// In `cxx_free!` mode, declare a function in a plain `extern "C"`
+    /// block, linking against the C++ shim which calls the real function.
+    fn generate_cxx_free_function(
+        &self,
+        fun: FuncToConvert,
+        analysis: FnAnalysis,
+    ) -> RsCodegenResult {
+        let shim_name = self
+            .config
+            .get_cxx_free_shim_name(&analysis.cxxbridge_name.to_string());
+        let rust_name = make_ident(&analysis.rust_name);
+        let doc_attrs = fun.doc_attrs;
+        let inputs = fun.inputs;
+        let output = fun.output;
+        RsCodegenResult {
+            bindgen_mod_items: vec![parse_quote! {
+                extern "C" {
+                    #(#doc_attrs)*
+                    #[link_name = #shim_name]
+                    pub fn #rust_name(#inputs) #output;
+                }
+            }],
+            materializations: vec![Use::SpecificNameFromBindgen(rust_name.into())],
+            ..Default::default()
+        }
+    }
+
+    /// For `c_abi_shims!`, declare a function's `extern "C"` shim
+    /// alongside the usual cxx binding, under the Rust name suffixed
+    /// with `_c_abi`.
+    fn generate_c_abi_shim_declaration(
+        &self,
+        name: &QualifiedName,
+        fun: &FuncToConvert,
+        analysis: &FnAnalysis,
+    ) -> (Item, Ident) {
+        let shim_name = self
+            .config
+            .get_c_abi_shim_name(&c_abi_shim_base_name(name, analysis));
+        let rust_name = make_ident(format!("{}_c_abi", analysis.rust_name));
+        let doc_attrs = &fun.doc_attrs;
+        let inputs = &fun.inputs;
+        let output = &fun.output;
+        let item = parse_quote! {
+            extern "C" {
+                #(#doc_attrs)*
+                #[link_name = #shim_name]
+                pub fn #rust_name(#inputs) #output;
+            }
+        };
+        (item, rust_name.into())
+    }
+
+    /// Generate a Rust iterator over the entries of a `std::map`, driven
+    /// by C++ helper functions which hold the C++ map iterator.
+    fn generate_std_map_iterator(
+        &self,
+        id: &Ident,
+        map_type: &QualifiedName,
+        key: &StdMapEntryType,
+        value: &StdMapEntryType,
+    ) -> RsCodegenResult {
+        let map_id = map_type.get_final_ident();
+        let item_name = |suffix: &str| {
+            make_ident(
+                self.config
+                    .get_std_map_item_name(map_type.get_final_item(), suffix),
+            )
+        };
+        let state = item_name("iterator");
+        let begin_fn = item_name("begin");
+        let valid_fn = item_name("valid");
+        let advance_fn = item_name("advance");
+        let mut extern_c_mod_items = vec![
+            ForeignItem::Verbatim(quote! {
+                type #state;
+            }),
+            ForeignItem::Fn(parse_quote! {
+                fn #begin_fn(m: &#map_id) -> UniquePtr<#state>;
+            }),
+            ForeignItem::Fn(parse_quote! {
+                fn #valid_fn(it: &#state) -> bool;
+            }),
+            ForeignItem::Fn(parse_quote! {
+                fn #advance_fn(it: Pin<&mut #state>);
+            }),
+        ];
+        let mut item_types = Vec::new();
+        let mut item_getters = Vec::new();
+        for (entry, fn_name) in [(key, item_name("key")), (value, item_name("value"))] {
+            let ty = Type::Path(
+                known_types()
+                    .known_type_type_path(&entry.ty)
+                    .unwrap_or_else(|| entry.ty.to_type_path()),
+            );
+            if entry.by_value {
+                let bridge_ty = unqualify_ret_type(parse_quote! { -> #ty });
+                extern_c_mod_items.push(ForeignItem::Fn(parse_quote! {
+                    fn #fn_name(m: &#map_id, it: &#state) #bridge_ty;
+                }));
+                item_types.push(ty);
+            } else {
+                let bridge_ty = unqualify_ret_type(parse_quote! { -> &'a #ty });
+                extern_c_mod_items.push(ForeignItem::Fn(parse_quote! {
+                    fn #fn_name<'a>(m: &'a #map_id, it: &#state) #bridge_ty;
+                }));
+                item_types.push(parse_quote! { &'a #ty });
+            }
+            item_getters.push(quote! { cxxbridge::#fn_name(self.map, &self.state) });
+        }
+        let bindgen_mod_items = vec![
+            parse_quote! {
+                /// An iterator over the entries of a C++ `std::map`,
+                /// in key order.
+                pub struct #id<'a> {
+                    map: &'a #map_id,
+                    state: cxx::UniquePtr<cxxbridge::#state>,
+                }
+            },
+            parse_quote! {
+                impl<'a> Iterator for #id<'a> {
+                    type Item = (#(#item_types),*);
+                    fn next(&mut self) -> Option<Self::Item> {
+                        if !cxxbridge::#valid_fn(&self.state) {
+                            return None;
+                        }
+                        let item = (#(#item_getters),*);
+                        cxxbridge::#advance_fn(self.state.pin_mut());
+                        Some(item)
+                    }
+                }
+            },
+            parse_quote! {
+                impl #map_id {
+                    /// Iterates over the entries of this map, in key order.
+                    pub fn iter(&self) -> #id<'_> {
+                        #id {
+                            map: self,
+                            state: cxxbridge::#begin_fn(self),
+                        }
+                    }
+                }
+            },
+        ];
+        RsCodegenResult {
+            extern_c_mod_items,
+            bindgen_mod_items,
+            materializations: vec![Use::UsedFromBindgen],
+            ..Default::default()
+        }
+    }
+
+    /// Generate a Rust tuple type corresponding to a `std::tuple`, along
+    /// with methods to convert between the two using C++ helper functions.
+    /// Generate a Rust iterator over the items of the range returned by a
+    /// C++ function, driven by C++ helper functions which hold the range
+    /// and the C++ iterators into it.
+    fn generate_range_iterator(
+        &self,
+        id: &Ident,
+        function: &str,
+        item: &QualifiedName,
+    ) -> RsCodegenResult {
+        let item_name =
+            |suffix: &str| make_ident(self.config.get_range_item_name(&id.to_string(), suffix));
+        let state = item_name("state");
+        let begin_fn = item_name("begin");
+        let valid_fn = item_name("valid");
+        let advance_fn = item_name("advance");
+        let get_fn = item_name("get");
+        let ty = Type::Path(
+            known_types()
+                .known_type_type_path(item)
+                .unwrap_or_else(|| item.to_type_path()),
+        );
+        let bridge_ty = unqualify_ret_type(parse_quote! { -> #ty });
+        let extern_c_mod_items = vec![
+            ForeignItem::Verbatim(quote! {
+                type #state;
+            }),
+            ForeignItem::Fn(parse_quote! {
+                fn #begin_fn() -> UniquePtr<#state>;
+            }),
+            ForeignItem::Fn(parse_quote! {
+                fn #valid_fn(it: &#state) -> bool;
+            }),
+            ForeignItem::Fn(parse_quote! {
+                fn #advance_fn(it: Pin<&mut #state>);
+            }),
+            ForeignItem::Fn(parse_quote! {
+                fn #get_fn(it: &#state) #bridge_ty;
+            }),
+        ];
+        let struct_doc = format!(
+            " An iterator over the items of the range returned by the C++ function `{function}`."
+        );
+        let new_doc = format!(" Calls `{function}`, ready to iterate over the range it returns.");
+        let bindgen_mod_items = vec![
+            parse_quote! {
+                #[doc = #struct_doc]
+                pub struct #id {
+                    state: cxx::UniquePtr<cxxbridge::#state>,
+                }
+            },
+            parse_quote! {
+                impl #id {
+                    #[doc = #new_doc]
+                    #[allow(clippy::new_without_default)]
+                    pub fn new() -> Self {
+                        Self {
+                            state: cxxbridge::#begin_fn(),
+                        }
+                    }
+                }
+            },
+            parse_quote! {
+                impl Iterator for #id {
+                    type Item = #ty;
+                    fn next(&mut self) -> Option<Self::Item> {
+                        if !cxxbridge::#valid_fn(&self.state) {
+                            return None;
+                        }
+                        let item = cxxbridge::#get_fn(&self.state);
+                        cxxbridge::#advance_fn(self.state.pin_mut());
+                        Some(item)
+                    }
+                }
+            },
+        ];
+        RsCodegenResult {
+            extern_c_mod_items,
+            bindgen_mod_items,
+            materializations: vec![Use::UsedFromBindgen],
+            ..Default::default()
+        }
+    }
+
+    fn generate_std_tuple(
+        &self,
+        id: &Ident,
+        tuple_type: &QualifiedName,
+        elements: &[QualifiedName],
+    ) -> RsCodegenResult {
+        let tuple_id = tuple_type.get_final_ident();
+        let shim_name = |suffix: &str| {
+            make_ident(
+                self.config
+                    .get_std_tuple_fn_name(tuple_type.get_final_item(), suffix),
+            )
+        };
+        let types: Vec<Type> = elements
+            .iter()
+            .map(|element| {
+                Type::Path(
+                    known_types()
+                        .known_type_type_path(element)
+                        .unwrap_or_else(|| element.to_type_path()),
+                )
+            })
+            .collect();
+        let mut extern_c_mod_items = Vec::new();
+        let mut getters = Vec::new();
+        for (i, ty) in types.iter().enumerate() {
+            let get_fn = shim_name(&format!("get_{i}"));
+            let bridge_ty = unqualify_ret_type(parse_quote! { -> #ty });
+            extern_c_mod_items.push(ForeignItem::Fn(parse_quote! {
+                fn #get_fn(t: &#tuple_id) #bridge_ty;
+            }));
+            getters.push(quote! { cxxbridge::#get_fn(self) });
+        }
+        let make_fn = shim_name("make");
+        let element_names = (0..types.len())
+            .map(|i| make_ident(format!("e{i}")))
+            .collect_vec();
+        let bridge_params = unqualify_params(parse_quote! { #(#element_names: #types),* });
+        extern_c_mod_items.push(ForeignItem::Fn(parse_quote! {
+            fn #make_fn(#bridge_params) -> UniquePtr<#tuple_id>;
+        }));
+        let indices = (0..types.len()).map(syn::Index::from);
+        let bindgen_mod_items = vec![
+            parse_quote! {
+                /// A Rust tuple corresponding to a C++ `std::tuple`.
+                pub type #id = (#(#types,)*);
+            },
+            parse_quote! {
+                impl #tuple_id {
+                    /// Copies the elements of this C++ tuple into a Rust tuple.
+                    pub fn to_tuple(&self) -> #id {
+                        (#(#getters,)*)
+                    }
+
+                    /// Creates a new C++ tuple holding these elements.
+                    pub fn from_tuple(t: #id) -> cxx::UniquePtr<Self> {
+                        cxxbridge::#make_fn(#(t.#indices),*)
+                    }
+                }
+            },
+        ];
+        RsCodegenResult {
+            extern_c_mod_items,
+            bindgen_mod_items,
+            materializations: vec![Use::UsedFromBindgen],
+            ..Default::default()
+        }
+    }
+
+    /// Generate a Rust enum corresponding to a `std::variant`, along with
+    /// the C++ helper functions used to convert to and from it.
+    fn generate_std_variant(
+        &self,
+        id: &Ident,
+        cpp_type: &QualifiedName,
+        alternatives: &[QualifiedName],
+    ) -> RsCodegenResult {
+        let cpp_type = cpp_type.get_final_ident();
+        let shim_name =
+            |suffix: &str| make_ident(self.config.get_std_variant_fn_name(&id.to_string(), suffix));
+        let types: Vec<Type> = alternatives
+            .iter()
+            .map(|alt| {
+                Type::Path(
+                    known_types()
+                        .known_type_type_path(alt)
+                        .unwrap_or_else(|| alt.to_type_path()),
+                )
+            })
+            .collect();
+        // Name each variant after its type, unless that would be ambiguous.
+        let mut variant_names: Vec<String> = types
+            .iter()
+            .map(|ty| {
+                let name = match ty {
+                    Type::Path(typ) => typ.path.segments.last().unwrap().ident.to_string(),
+                    _ => unreachable!(),
+                };
+                let mut chars = name.chars();
+                chars
+                    .next()
+                    .map(|c| c.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            })
+            .collect();
+        if variant_names.iter().duplicates().next().is_some()
+            || variant_names
+                .iter()
+                .any(|name| name == "ValuelessByException")
+        {
+            variant_names = (0..types.len()).map(|i| format!("V{i}")).collect();
+        }
+        let variant_names = variant_names.into_iter().map(make_ident).collect_vec();
+        let index_fn = shim_name("index");
+        let mut extern_c_mod_items = vec![ForeignItem::Fn(parse_quote! {
+            fn #index_fn(v: &#cpp_type) -> usize;
+        })];
+        let mut from_arms = Vec::new();
+        let mut into_arms = Vec::new();
+        for (i, (ty, variant_name)) in types.iter().zip(variant_names.iter()).enumerate() {
+            let get_fn = shim_name(&format!("get_{i}"));
+            let from_fn = shim_name(&format!("from_{i}"));
+            let bridge_ty = unqualify_ret_type(parse_quote! { -> #ty });
+            extern_c_mod_items.push(ForeignItem::Fn(parse_quote! {
+                fn #get_fn(v: &#cpp_type) #bridge_ty;
+            }));
+            let bridge_params = unqualify_params(parse_quote! { v: #ty });
+            extern_c_mod_items.push(ForeignItem::Fn(parse_quote! {
+                fn #from_fn(#bridge_params) -> UniquePtr<#cpp_type>;
+            }));
+            from_arms.push(quote! {
+                #i => Self::#variant_name(cxxbridge::#get_fn(v)),
+            });
+            into_arms.push(quote! {
+                Self::#variant_name(v) => cxxbridge::#from_fn(v),
+            });
+        }
+        let bindgen_mod_items = vec![
+            parse_quote! {
+                /// A Rust representation of a C++ `std::variant`.
+                pub enum #id {
+                    #(#variant_names(#types),)*
+                    /// The C++ variant did not hold a value, because an
+                    /// exception was thrown during an earlier assignment.
+                    ValuelessByException,
+                }
+            },
+            parse_quote! {
+                impl #id {
+                    /// Copies the current alternative out of the C++ variant.
+                    pub fn from_cpp(v: &#cpp_type) -> Self {
+                        match cxxbridge::#index_fn(v) {
+                            #(#from_arms)*
+                            _ => Self::ValuelessByException,
+                        }
+                    }
+
+                    /// Creates a new C++ variant holding this alternative.
+                    /// Returns a null pointer for `ValuelessByException`,
+                    /// since a C++ variant can't be created in that state.
+                    pub fn into_cpp(self) -> cxx::UniquePtr<#cpp_type> {
+                        match self {
+                            #(#into_arms)*
+                            Self::ValuelessByException => cxx::UniquePtr::null(),
+                        }
+                    }
+                }
+            },
+        ];
+        RsCodegenResult {
+            extern_c_mod_items,
+            bindgen_mod_items,
+            materializations: vec![Use::UsedFromBindgen],
+            ..Default::default()
+        }
+    }
+
+    /// Generate conversions in each direction between a newtype made from
+    /// a typedef listed using `newtype_typedef!` and the type it wraps.
+    fn generate_newtype_conversions(item: &ItemStruct) -> Vec<Item> {
+        let id = &item.ident;
+        let Some(inner) = item.fields.iter().next().map(|f| &f.ty) else {
+            return Vec::new();
+        };
+        vec![
+            parse_quote! {
+                impl From<#inner> for #id {
+                    fn from(value: #inner) -> Self {
+                        Self(value)
+                    }
+                }
+            },
+            parse_quote! {
+                impl From<#id> for #inner {
+                    fn from(value: #id) -> Self {
+                        value.0
+                    }
+                }
+            },
+        ]
+    }
+
+    /// Generate a `bitflags` type in place of an enum, with one flag per
+    /// enumerator and the same underlying representation.
+    fn generate_enum_bitflags(item: &ItemEnum, doc_attrs: &[Attribute]) -> Item {
+        let id = &item.ident;
+        let repr = Self::enum_repr(item);
+        let flags = item.variants.iter().filter_map(|v| {
+            let name = &v.ident;
+            v.discriminant.as_ref().map(|(_, value)| {
+                quote! {
+                    #[allow(non_upper_case_globals)]
+                    const #name = #value;
+                }
+            })
+        });
+        Item::Verbatim(quote! {
+            ::autocxx::bitflags::bitflags! {
+                #(#doc_attrs)*
+                #[repr(transparent)]
+                #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+                pub struct #id: #repr {
+                    #(#flags)*
+                }
+            }
+        })
+    }
+
+    /// Generate a checked conversion from the enum's underlying integer
+    /// type, which fails for any value not matching one of the enum's
+    /// discriminants.
+    fn generate_enum_try_from(item: &ItemEnum) -> Item {
+        let id = &item.ident;
+        let repr = Self::enum_repr(item);
+        let arms = item.variants.iter().map(|v| {
+            let variant = &v.ident;
+            quote! {
+                x if x == Self::#variant as #repr => Ok(Self::#variant),
+            }
+        });
+        parse_quote! {
+            impl ::core::convert::TryFrom<#repr> for #id {
+                type Error = #repr;
+                fn try_from(value: #repr) -> ::core::result::Result<Self, Self::Error> {
+                    match value {
+                        #(#arms)*
+                        _ => Err(value),
+                    }
+                }
+            }
+        }
+    }
+
+    /// The integer type underlying an enum, from its `#[repr]` attribute.
+    fn enum_repr(item: &ItemEnum) -> Ident {
+        item.attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("repr"))
+            .filter_map(|attr| attr.parse_args::<Ident>().ok())
+            .find(|repr| {
+                matches!(
+                    repr.to_string().as_str(),
+                    "i8" | "u8" | "i16" | "u16" | "i32" | "u32" | "i64" | "u64" | "isize" | "usize"
+                )
+            })
+            .unwrap_or_else(|| Ident::new("i32", Span::call_site()))
+    }
+
+    /// Generate the struct which acts as the Rust side of a subclass
+    /// implementing an `interface!`. This forwards every method to an
+    /// arbitrary boxed implementation of the interface's methods trait.
+    fn generate_interface_impl(
+        sub: &SubclassName,
+        superclass: &QualifiedName,
+        methods: Option<&Vec<SuperclassMethod>>,
+        can_construct: bool,
+    ) -> Vec<Item> {
+        let id = sub.id();
+        let cpp_id = sub.cpp().get_final_ident();
+        let super_path = superclass.to_type_path();
+        let methods_trait = SubclassName::get_methods_trait_name(superclass).to_type_path();
+        let mut items = vec![
+            parse_quote! {
+                #[doc(hidden)]
+                #[allow(non_camel_case_types)]
+                pub struct #id {
+                    cpp_peer: autocxx::subclass::CppSubclassCppPeerHolder<#cpp_id>,
+                    imp: Box<dyn #methods_trait>,
+                }
+            },
+            parse_quote! {
+                impl autocxx::subclass::CppSubclass<#cpp_id> for #id {
+                    fn peer_holder(&self) -> &autocxx::subclass::CppSubclassCppPeerHolder<#cpp_id> {
+                        &self.cpp_peer
+                    }
+                    fn peer_holder_mut(&mut self) -> &mut autocxx::subclass::CppSubclassCppPeerHolder<#cpp_id> {
+                        &mut self.cpp_peer
+                    }
+                }
+            },
+        ];
+        let forwarding_impls: Vec<ImplItem> = methods
+            .into_iter()
+            .flatten()
+            .map(|m| {
+                let name = &m.name;
+                let mut params = m.params.clone();
+                *(params.iter_mut().next().unwrap()) = match m.receiver_mutability {
+                    ReceiverMutability::Const => parse_quote!(&self),
+                    ReceiverMutability::Mutable => parse_quote!(&mut self),
+                };
+                let param_names = m.param_names.iter().skip(1);
+                let ret = &m.ret_type;
+                let unsafe_token = m.requires_unsafe.wrapper_token();
+                parse_quote! {
+                    #unsafe_token fn #name(#params) #ret {
+                        self.imp.#name(#(#param_names),*)
+                    }
+                }
+            })
+            .collect();
+        items.push(parse_quote! {
+            #[allow(non_snake_case)]
+            impl #methods_trait for #id {
+                #(#forwarding_impls)*
+            }
+        });
+        if can_construct {
+            let as_unique_ptr_id = make_ident(format!(
+                "{cpp_id}_As_{}_UniquePtr",
+                superclass.get_final_item()
+            ));
+            items.push(parse_quote! {
+                impl #super_path {
+                    /// Creates a C++ object implementing this interface, which
+                    /// forwards every virtual method call to the given Rust
+                    /// implementation.
+                    pub fn from_rust_impl(imp: impl #methods_trait + 'static) -> cxx::UniquePtr<Self> {
+                        use autocxx::subclass::CppSubclass;
+                        let me = #id {
+                            cpp_peer: Default::default(),
+                            imp: Box::new(imp),
+                        };
+                        cxxbridge::#as_unique_ptr_id(#id::new_cpp_owned(me))
+                    }
+                }
+            });
+        }
+        items
+    }
+
     fn generate_subclass_fn(
         api_name: Ident,
         details: RustSubclassFnDetails,
@@ -857,6 +1669,37 @@ impl<'a> RsCodeGenerator<'a> {
         }
     }
 
+    /// Accessors for fields inherited from base classes. We reach them
+    /// through bindgen's `_base` fields, so they live wherever the
+    /// base class subobject lives within the derived type.
+    fn generate_base_field_accessors(&self, id: &Ident, accessors: &[BaseFieldAccessor]) -> Item {
+        let inline_attr = self.inline_wrappers.then(|| quote! { #[inline] });
+        let fns = accessors.iter().map(|accessor| {
+            let base_path = &accessor.base_path;
+            let field = &accessor.field;
+            let field_mut = make_ident(format!("{field}_mut"));
+            let ty = &accessor.ty;
+            let doc = format!("Returns the `{field}` field inherited from a base class.");
+            quote! {
+                #[doc = #doc]
+                #inline_attr
+                pub fn #field(&self) -> &#ty {
+                    &self #(.#base_path)* .#field
+                }
+                #[doc = #doc]
+                #inline_attr
+                pub fn #field_mut(&mut self) -> &mut #ty {
+                    &mut self #(.#base_path)* .#field
+                }
+            }
+        });
+        Item::Impl(parse_quote! {
+            impl #id {
+                #(#fns)*
+            }
+        })
+    }
+
     fn args_from_sig(params: &Punctuated<FnArg, Comma>) -> impl Iterator<Item = Expr> + '_ {
         params.iter().skip(1).filter_map(|fnarg| match fnarg {
             syn::FnArg::Receiver(_) => None,
@@ -914,6 +1757,7 @@ impl<'a> RsCodeGenerator<'a> {
                 let mut item = orig_item
                     .expect("Instantiable types must provide instance")
                     .0;
+                let mut layout_assertions = None;
                 if matches!(type_kind, TypeKind::NonPod) {
                     if let Item::Struct(ref mut s) = item {
                         // Retain generics and doc attrs.
@@ -922,10 +1766,17 @@ impl<'a> RsCodeGenerator<'a> {
                         // enum
                         item = Item::Struct(new_non_pod_struct(id.clone().into()));
                     }
+                } else if let Item::Struct(s) = &mut item {
+                    Self::apply_long_mapping(s, self.config.get_long_mapping());
+                    if let (Some(layout), false) = (layout, is_generic) {
+                        Self::apply_pod_alignment(s, &layout);
+                        layout_assertions = Some(Self::generate_layout_assertions(&id, &layout));
+                    }
                 }
                 bindgen_mod_items.push(item);
+                bindgen_mod_items.extend(layout_assertions);
 
-                if is_generic {
+                if is_generic || self.config.is_cxx_free() {
                     // Still generate the type as emitted by bindgen,
                     // but don't attempt to tell cxx about it
                     RsCodegenResult {
@@ -968,6 +1819,85 @@ impl<'a> RsCodeGenerator<'a> {
         }
     }
 
+    /// Ensures a POD struct is as aligned as the C++ type, which may have
+    /// been over-aligned using `alignas`.
+    /// bindgen represents `long` fields as `c_long`, whose width depends
+    /// on the target. If the user has pinned `long` to a fixed width,
+    /// substitute that instead. The layout assertions will catch any
+    /// platform where that's not actually the width of `long`.
+    fn apply_long_mapping(s: &mut ItemStruct, mapping: LongMapping) {
+        struct LongSubstituter(LongMapping);
+        impl VisitMut for LongSubstituter {
+            fn visit_type_mut(&mut self, ty: &mut Type) {
+                if let Type::Path(typ) = ty {
+                    let mut segments = typ.path.segments.iter().rev().map(|seg| &seg.ident);
+                    let unsigned = match segments.next() {
+                        Some(id) if id == "c_long" => Some(false),
+                        Some(id) if id == "c_ulong" => Some(true),
+                        _ => None,
+                    };
+                    let within_ctypes_mod =
+                        segments.next().is_some_and(|id| id == "raw" || id == "ffi");
+                    if let (Some(unsigned), true) = (unsigned, within_ctypes_mod) {
+                        if let Some(pinned) = self.0.pinned_type(unsigned) {
+                            let pinned = make_ident(pinned);
+                            *ty = parse_quote! { #pinned };
+                            return;
+                        }
+                    }
+                }
+                visit_type_mut(self, ty)
+            }
+        }
+        if mapping != LongMapping::Platform {
+            for field in s.fields.iter_mut() {
+                LongSubstituter(mapping).visit_type_mut(&mut field.ty);
+            }
+        }
+    }
+
+    fn apply_pod_alignment(s: &mut ItemStruct, layout: &Layout) {
+        if layout.packed {
+            // Rust doesn't allow packed types to be over-aligned.
+            return;
+        }
+        // Replace any alignment which bindgen specified, so as not to
+        // give conflicting hints.
+        s.attrs = std::mem::take(&mut s.attrs)
+            .into_iter()
+            .filter_map(|attr| {
+                if !attr.path().is_ident("repr") {
+                    return Some(attr);
+                }
+                let hints = attr
+                    .parse_args_with(Punctuated::<Meta, Comma>::parse_terminated)
+                    .ok()?;
+                let hints = hints
+                    .into_iter()
+                    .filter(|hint| !hint.path().is_ident("align"))
+                    .collect::<Vec<_>>();
+                (!hints.is_empty()).then(|| parse_quote! { #[repr(#(#hints),*)] })
+            })
+            .collect();
+        let align = make_lit_int(layout.align);
+        s.attrs.push(parse_quote! {
+            #[repr(align(#align))]
+        });
+    }
+
+    /// Checks at compile time that a POD struct's size and alignment
+    /// match those of the C++ type.
+    fn generate_layout_assertions(id: &Ident, layout: &Layout) -> Item {
+        let size = make_lit_int(layout.size);
+        let align = make_lit_int(layout.align);
+        parse_quote! {
+            const _: () = {
+                assert!(::core::mem::size_of::<#id>() == #size);
+                assert!(::core::mem::align_of::<#id>() == #align);
+            };
+        }
+    }
+
     fn add_superclass_stuff_to_type(
         name: &QualifiedName,
         bindgen_mod_items: &mut Vec<Item>,
@@ -1064,6 +1994,39 @@ impl<'a> RsCodeGenerator<'a> {
         }
     }
 
+    /// Makes an existing Rust enum usable as a C++ enum. If we know what
+    /// bindgen made of the C++ enum, also check at compile time that the
+    /// Rust enum has the same size and a variant with the same discriminant
+    /// for each enumerator.
+    fn generate_extern_enum_items(name: &QualifiedName, enum_item: Option<&ItemEnum>) -> Vec<Item> {
+        let id = name.get_final_ident();
+        let cpp_name = name.to_cpp_name();
+        let mut items = vec![parse_quote! {
+            unsafe impl cxx::ExternType for #id {
+                type Id = cxx::type_id!(#cpp_name);
+                type Kind = cxx::kind::Trivial;
+            }
+        }];
+        if let Some(enum_item) = enum_item {
+            let repr = Self::enum_repr(enum_item);
+            let checks = enum_item.variants.iter().filter_map(|v| {
+                let variant = &v.ident;
+                v.discriminant.as_ref().map(|(_, discriminant)| {
+                    quote! {
+                        assert!(#id::#variant as #repr == #discriminant);
+                    }
+                })
+            });
+            items.push(parse_quote! {
+                const _: () = {
+                    assert!(::core::mem::size_of::<#id>() == ::core::mem::size_of::<#repr>());
+                    #(#checks)*
+                };
+            });
+        }
+        items
+    }
+
     /// Generates something in the output mod that will carry a docstring
     /// explaining why a given type or function couldn't have bindings
     /// generated.
@@ -1112,8 +2075,8 @@ impl<'a> RsCodeGenerator<'a> {
         }
     }
 
-    fn generate_cxx_use_stmt(name: &QualifiedName, alias: Option<&Ident>) -> Item {
-        let segs = Self::find_output_mod_root(name.get_namespace())
+    fn generate_cxx_use_stmt(name: &QualifiedName, alias: Option<&Ident>, depth: usize) -> Item {
+        let segs = Self::find_output_mod_root(depth)
             .chain(std::iter::once(make_ident("cxxbridge")))
             .chain(std::iter::once(name.get_final_ident()));
         Item::Use(match alias {
@@ -1126,9 +2089,8 @@ impl<'a> RsCodeGenerator<'a> {
         })
     }
 
-    fn generate_bindgen_use_stmt(name: &QualifiedName) -> Item {
-        let segs =
-            Self::find_output_mod_root(name.get_namespace()).chain(name.get_bindgen_path_idents());
+    fn generate_bindgen_use_stmt(name: &QualifiedName, depth: usize) -> Item {
+        let segs = Self::find_output_mod_root(depth).chain(name.get_bindgen_path_idents());
         Item::Use(parse_quote! {
             #[allow(unused_imports)]
             pub use #(#segs)::*;
@@ -1210,8 +2172,11 @@ impl<'a> RsCodeGenerator<'a> {
         ForeignItem::Verbatim(for_extern_c_ts)
     }
 
-    fn find_output_mod_root(ns: &Namespace) -> impl Iterator<Item = crate::minisyn::Ident> {
-        std::iter::repeat(make_ident("super")).take(ns.depth())
+    /// Find the root of the output mod hierarchy from a mod at the given
+    /// depth. This may differ from the depth of the C++ namespace if
+    /// namespaces have been flattened.
+    fn find_output_mod_root(depth: usize) -> impl Iterator<Item = crate::minisyn::Ident> {
+        std::iter::repeat(make_ident("super")).take(depth)
     }
 }
 
@@ -1238,6 +2203,19 @@ fn find_trivially_constructed_subclasses(apis: &ApiVec<FnPhase>) -> HashSet<Qual
         .collect()
 }
 
+/// The Rust tuple type corresponding to each `std::tuple` listed using
+/// `concrete!`, for functions which return such a tuple.
+fn find_std_tuples(apis: &ApiVec<FnPhase>) -> HashMap<QualifiedName, Ident> {
+    apis.iter()
+        .filter_map(|api| match api {
+            Api::StdTuple {
+                name, tuple_type, ..
+            } => Some((tuple_type.clone(), name.name.get_final_ident())),
+            _ => None,
+        })
+        .collect()
+}
+
 fn find_non_pod_types(apis: &ApiVec<FnPhase>) -> HashSet<QualifiedName> {
     apis.iter()
         .filter_map(|api| match api {
@@ -1281,8 +2259,11 @@ struct RsCodegenResult {
     global_items: Vec<Item>,
     bindgen_mod_items: Vec<Item>,
     impl_entry: Option<Box<ImplBlockDetails>>,
-    trait_impl_entry: Option<Box<TraitImplBlockDetails>>,
+    trait_impl_entries: Vec<Box<TraitImplBlockDetails>>,
     materializations: Vec<Use>,
+    /// A `#[cfg]` from `cfg_gate!`, under which the `materializations`
+    /// exist.
+    materialization_cfg: Option<Attribute>,
 }
 
 /// An [`Item`] that always needs to be in an unsafe block.
@@ -36,6 +36,45 @@ impl<'a, T: HasNs> NamespaceEntries<'a, T> {
         self.children.iter()
     }
 
+    /// Collapse chains of namespaces which contain only a single item,
+    /// such that the item instead appears in the outermost namespace of
+    /// the chain. `name_of` returns the name by which an item is known
+    /// within its namespace, or `None` if it doesn't contribute any
+    /// named output. Items are left in place if moving them would
+    /// collide with a name already present in the outer namespace.
+    pub(crate) fn flatten_single_item_chains<F>(&mut self, name_of: &F)
+    where
+        F: Fn(&T) -> Option<String>,
+    {
+        let child_names: Vec<_> = self.children.keys().cloned().collect();
+        for child_name in child_names {
+            let child = self.children.get_mut(child_name).unwrap();
+            child.flatten_single_item_chains(name_of);
+            if !child.children.is_empty() {
+                continue;
+            }
+            let mut names = child.entries.iter().filter_map(|entry| name_of(entry));
+            let item_name = match (names.next(), names.next()) {
+                (Some(item_name), None) => item_name,
+                _ => continue,
+            };
+            let collides = self
+                .entries
+                .iter()
+                .filter_map(|entry| name_of(entry))
+                .any(|name| name == item_name)
+                || self
+                    .children
+                    .keys()
+                    .any(|other| *other != child_name && **other == item_name);
+            if collides {
+                continue;
+            }
+            let child = self.children.remove(child_name).unwrap();
+            self.entries.extend(child.entries);
+        }
+    }
+
     fn sort_by_inner_namespace(apis: Vec<&'a T>, depth: usize) -> Self {
         let mut root = NamespaceEntries {
             entries: Vec::new(),
@@ -122,6 +161,36 @@ mod tests {
         assert_ident(k_nse_entries[1], "M");
     }
 
+    #[test]
+    fn test_ns_entries_flatten() {
+        let entries = vec![
+            make_api(None, "E"),
+            make_api(Some("A::B::C"), "D"),
+            make_api(Some("F::G"), "E"),
+            make_api(Some("H"), "I"),
+            make_api(Some("H"), "J"),
+        ];
+        let mut ns = NamespaceEntries::new(&entries);
+        ns.flatten_single_item_chains(&|api: &TestApi| Some(api.0.to_string()));
+        let root_entries = ns.entries();
+        assert_eq!(root_entries.len(), 2);
+        assert_ident(root_entries[0], "E");
+        assert_ident(root_entries[1], "D");
+        let mut kids = ns.children();
+        // F::G::E would collide with the root E, so it only moves up one level.
+        let (f_id, f_nse) = kids.next().unwrap();
+        assert_eq!(f_id.to_string(), "F");
+        assert!(f_nse.children().next().is_none());
+        let f_nse_entries = f_nse.entries();
+        assert_eq!(f_nse_entries.len(), 1);
+        assert_ident(f_nse_entries[0], "E");
+        // H contains more than one item, so is left alone.
+        let (h_id, h_nse) = kids.next().unwrap();
+        assert_eq!(h_id.to_string(), "H");
+        assert_eq!(h_nse.entries().len(), 2);
+        assert!(kids.next().is_none());
+    }
+
     fn assert_ident(api: &TestApi, expected: &str) {
         assert_eq!(api.0, expected);
     }
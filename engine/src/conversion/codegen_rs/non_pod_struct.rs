@@ -25,8 +25,8 @@ pub(crate) fn new_non_pod_struct(id: Ident) -> ItemStruct {
 
 pub(crate) fn make_non_pod(s: &mut ItemStruct, layout: Option<Layout>) {
     // Make an opaque struct. If we have layout information, we pass
-    // that through to Rust. We keep only doc attrs, plus add a #[repr(C)]
-    // if necessary.
+    // that through to Rust. We keep only doc and must_use attrs, plus add
+    // a #[repr(C)] if necessary.
     // Constraints here (thanks to dtolnay@ for this explanation of why the
     // following is needed:)
     // (1) If the real alignment of the C++ type is smaller and a reference
@@ -81,7 +81,12 @@ pub(crate) fn make_non_pod(s: &mut ItemStruct, layout: Option<Layout>) {
     let doc_attr = s
         .attrs
         .iter()
-        .filter(|a| a.path().get_ident().iter().any(|p| *p == "doc"))
+        .filter(|a| {
+            a.path()
+                .get_ident()
+                .iter()
+                .any(|p| *p == "doc" || *p == "must_use")
+        })
         .cloned();
     let repr_attr = if let Some(layout) = &layout {
         let align = make_lit_int(layout.align);
@@ -153,6 +158,6 @@ pub(crate) fn make_non_pod(s: &mut ItemStruct, layout: Option<Layout>) {
     } })
 }
 
-fn make_lit_int(val: usize) -> LitInt {
+pub(super) fn make_lit_int(val: usize) -> LitInt {
     LitInt::new(&val.to_string(), Span::call_site())
 }
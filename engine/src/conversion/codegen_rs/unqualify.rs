@@ -61,6 +61,10 @@ fn unqualify_type(typ: Type) -> Type {
             typeptr.elem = unqualify_boxed_type(typeptr.elem);
             Type::Ptr(typeptr)
         }
+        Type::Slice(mut typeslice) => {
+            typeslice.elem = unqualify_boxed_type(typeslice.elem);
+            Type::Slice(typeslice)
+        }
         _ => typ,
     }
 }
@@ -35,6 +35,7 @@ fn do_test(input: ItemMod) {
         inclusions,
         &CodegenOptions::default(),
         "",
+        "cxxgen.h".to_string(),
     )
     .unwrap();
 }
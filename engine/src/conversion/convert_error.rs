@@ -15,6 +15,7 @@ use proc_macro2::Span;
 use thiserror::Error;
 
 use crate::{
+    conversion::api::ApiName,
     known_types, proc_macro_span_to_miette_span,
     types::{make_ident, InvalidIdentError, Namespace, QualifiedName},
 };
@@ -67,7 +68,7 @@ pub enum ConvertErrorFromCpp {
     UnsupportedType(String),
     #[error("Encountered type not yet known by autocxx: {0}")]
     UnknownType(String),
-    #[error("Encountered mutable static data, not yet supported: {0}")]
+    #[error("Encountered static data which autocxx couldn't identify as a member of a class (it may be a global, or a member of a class template), not yet supported: {0}")]
     StaticData(String),
     #[error("Encountered typedef to itself - this is a known bindgen bug: {0}")]
     InfinitelyRecursiveTypedef(QualifiedName),
@@ -125,6 +126,8 @@ pub enum ConvertErrorFromCpp {
     MethodOfNonAllowlistedType,
     #[error("This type is templated, so we can't generate bindings. We will instead generate bindings for each instantiation.")]
     MethodOfGenericType,
+    #[error("This enum is nested within a class template, so it can't be named without the template's arguments. List an instantiation of the template using concrete! to generate bindings for its nested enums.")]
+    EnumNestedInTemplate,
     #[error("bindgen generated multiple different APIs (functions/types) with this name. autocxx doesn't know how to disambiguate them, so we won't generate bindings for any of them.")]
     DuplicateItemsFoundInParsing,
     #[error(
@@ -153,6 +156,52 @@ pub enum ConvertErrorFromCpp {
     ReferringToGenericTypeParam,
     #[error("This forward declaration was nested within another struct/class. autocxx is unable to represent inner types if they are forward declarations.")]
     ForwardDeclaredNestedType,
+    #[error("{} was listed as an interface!, but has virtual methods which aren't pure virtual. Use subclass! instead.", .0.to_cpp_name())]
+    InterfaceNotPureVirtual(QualifiedName),
+    #[error("Parameter {0} was listed as an out_param!, but it is not a mutable pointer.")]
+    OutParamNotMutablePointer(String),
+    #[error("Parameter {0} was listed as an out_param!, but autocxx can't make an initial value of its type for the function to overwrite. Only integer, floating point and bool types are supported.")]
+    OutParamWithoutDefault(String),
+    #[error("out_param!({0:?}, {1:?}) was specified, but autocxx found no such function with such a parameter.")]
+    OutParamNotFound(String, String),
+    #[error("Parameter {0} was listed as an out_param!, but the function returns a type which must be constructed in place, which can't yet be combined with output parameters.")]
+    OutParamWithPlacementReturn(String),
+    #[error("Function {0} was listed as a singleton!, but does not return a reference.")]
+    SingletonNotReturningReference(String),
+    #[error("Function {0} was listed as a singleton!, but takes parameters. Only static methods and free functions with no parameters are supported.")]
+    SingletonWithParameters(String),
+    #[error("Function {0} was listed in return_borrows!, but does not return a reference.")]
+    ReturnBorrowsNotReturningReference(String),
+    #[error("Function {0} was listed in return_borrows!, but has no reference parameter called {1}, or it is immutable while the returned reference is mutable.")]
+    ReturnBorrowsUnsuitableParam(String, String),
+    #[error("Function {0} was listed in c_string_return!, but does not return a const char*.")]
+    CStringReturnNotCharPtr(String),
+    #[error("{0} was listed using method_of! as a method of {1}, but its first parameter isn't a pointer or reference to {1}.")]
+    MethodOfUnsuitableFirstParam(String, String),
+    #[error("cxx_free!() was specified, but {0} can't be represented without cxx. Only POD types, enums, constants and free functions which take and return such types are supported.")]
+    NotCxxFreeCompatible(String),
+    #[error("{0} was listed as an alternative within a variant!, but only fixed-width integers, bool, float, double and types listed using generate_pod! are supported.")]
+    UnsupportedVariantAlternative(String),
+    #[error("{0} is an element of a std::tuple listed using concrete!, but only integers, bool, float, double and types listed using generate_pod! can be copied into a Rust tuple.")]
+    UnsupportedTupleElement(String),
+    #[error("{0} was listed as the item type of a range!, but only integers, bool, float, double and types listed using generate_pod! can be copied out of a range.")]
+    UnsupportedRangeItem(String),
+    #[error("{0} was listed using instantiate!, but autocxx could not determine its signature. It must name exactly one specialization of a function template, which must not be variadic.")]
+    UnsupportedFunctionInstantiation(String),
+    #[error("{0} was listed using base_field_accessors!, but autocxx found no such struct.")]
+    BaseFieldAccessorsNotFound(String),
+    #[error("{0} was listed using base_field_accessors!, but it isn't POD, or is a template, so its inherited fields can't be reached from Rust. Use generate_pod! for it.")]
+    BaseFieldAccessorsNotPod(String),
+    #[error("{0} was listed using base_field_accessors!, but autocxx doesn't know the definition of its base class {1}, or can't tell it apart from another base class instantiating the same template.")]
+    BaseFieldAccessorsUnknownBase(String, String),
+    #[error("{0} was listed using base_field_accessors!, but it inherits no public data members.")]
+    BaseFieldAccessorsNoFields(String),
+    #[error("{0} was listed using base_field_accessors!, but the accessor {1} would clash with a method of the same name, or with the accessor for another inherited field.")]
+    BaseFieldAccessorClash(String, String),
+    #[error("autocxx couldn't tell whether this method is const, ref-qualified or restrict-qualified, because it didn't recognize its mangled name and couldn't find its declaration.")]
+    UnknownMethodQualifiers,
+    #[error("autocxx couldn't tell which operator this is, because it didn't understand the template arguments in its mangled name.")]
+    UnrecognizedOperator,
     #[error("Problem handling function argument {arg}: {err}")]
     Argument {
         arg: String,
@@ -279,6 +328,54 @@ impl std::fmt::Display for ErrorContext {
     }
 }
 
+/// The C++ name of an item we couldn't convert. Errors in methods are
+/// recorded against their type, so we look to the context to name them
+/// as `Type::method`.
+pub(crate) fn ignored_item_cpp_name(name: &ApiName, ctx: Option<&ErrorContext>) -> String {
+    match ctx.map(|ctx| ctx.get_type()) {
+        Some(ErrorContextType::Method { method, .. }) => {
+            format!("{}::{method}", name.qualified_cpp_name())
+        }
+        _ => name.qualified_cpp_name(),
+    }
+}
+
+/// The items we've skipped over when converting in resilient mode (see
+/// [`crate::CodegenOptions::resilient`]), each with the reason why.
+/// Outside resilient mode, the first such item instead fails the whole
+/// conversion.
+pub(crate) struct SkippedItems {
+    resilient: bool,
+    pub(crate) items: Vec<(String, String)>,
+}
+
+impl SkippedItems {
+    pub(crate) fn new(resilient: bool) -> Self {
+        Self {
+            resilient,
+            items: Vec::new(),
+        }
+    }
+
+    pub(crate) fn is_resilient(&self) -> bool {
+        self.resilient
+    }
+
+    /// Skips over the named item, which we can't convert. Outside
+    /// resilient mode, this is fatal.
+    pub(crate) fn skip(
+        &mut self,
+        name: String,
+        err: ConvertErrorFromCpp,
+    ) -> Result<(), ConvertErrorFromCpp> {
+        if !self.resilient {
+            return Err(err);
+        }
+        self.items.push((name, err.to_string()));
+        Ok(())
+    }
+}
+
 #[derive(Clone)]
 pub(crate) struct ConvertErrorWithContext(
     pub(crate) ConvertErrorFromCpp,
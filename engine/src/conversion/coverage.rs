@@ -0,0 +1,89 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::{
+    api::{Api, NullPhase, Provenance},
+    apivec::ApiVec,
+    convert_error::ignored_item_cpp_name,
+};
+
+/// An estimate of how much of a header autocxx can bind, made without
+/// generating any code. See [`crate::IncludeCppEngine::estimate_coverage`].
+///
+/// This reflects only whether each declaration could be parsed. Some
+/// declarations counted as bindable may still be dropped by later
+/// analysis, for instance functions taking a parameter of an
+/// unsupported type.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CoverageReport {
+    /// The C++ names of the declarations we could bind. Methods are
+    /// named `Type::method`.
+    pub bindable: Vec<String>,
+    /// The C++ names of the declarations we can't bind, each along with
+    /// the reason why not.
+    pub unsupported: Vec<(String, String)>,
+}
+
+impl CoverageReport {
+    pub(crate) fn from_apis(apis: &ApiVec<NullPhase>) -> Self {
+        let mut report = Self::default();
+        for api in apis.iter() {
+            match api {
+                Api::IgnoredItem { name, err, ctx } => report
+                    .unsupported
+                    .push((ignored_item_cpp_name(name, ctx.as_ref()), err.to_string())),
+                Api::ForwardDeclaration {
+                    name,
+                    err: Some(err),
+                } => report
+                    .unsupported
+                    .push((name.qualified_cpp_name(), err.0.to_string())),
+                Api::Function { name, fun, .. } => {
+                    // Ignore anything we made up ourselves.
+                    if !matches!(fun.provenance, Provenance::Bindgen) {
+                        continue;
+                    }
+                    report.bindable.push(match &fun.self_ty {
+                        Some(self_ty) => format!("{}::{}", self_ty.to_cpp_name(), name.cpp_name()),
+                        None => name.qualified_cpp_name(),
+                    })
+                }
+                Api::Struct { name, .. }
+                | Api::Enum { name, .. }
+                | Api::Typedef { name, .. }
+                | Api::Const { name, .. }
+                | Api::ForwardDeclaration { name, .. }
+                | Api::OpaqueTypedef { name, .. } => {
+                    report.bindable.push(name.qualified_cpp_name())
+                }
+                _ => {}
+            }
+        }
+        report
+    }
+
+    /// The number of declarations we could bind.
+    pub fn bindable_count(&self) -> usize {
+        self.bindable.len()
+    }
+
+    /// The number of declarations we can't bind.
+    pub fn unsupported_count(&self) -> usize {
+        self.unsupported.len()
+    }
+
+    /// The proportion of declarations we could bind, between 0 and 1.
+    pub fn fraction_bindable(&self) -> f64 {
+        let total = self.bindable_count() + self.unsupported_count();
+        if total == 0 {
+            1.0
+        } else {
+            self.bindable_count() as f64 / total as f64
+        }
+    }
+}
@@ -16,3 +16,9 @@ pub(super) fn get_doc_attrs(attrs: &[Attribute]) -> Vec<Attribute> {
         .cloned()
         .collect()
 }
+
+/// Whether there's a `#[must_use]` attribute, which bindgen emits for
+/// items marked `[[nodiscard]]` in C++ if asked to look for them.
+pub(super) fn has_must_use_attr(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|a| a.path().is_ident("must_use"))
+}
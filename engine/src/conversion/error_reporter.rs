@@ -78,6 +78,7 @@ pub(crate) fn convert_apis<FF, SF, EF, TF, A, B>(
     EF: FnMut(
         ApiName,
         ItemEnum,
+        Option<String>,
     ) -> Result<Box<dyn Iterator<Item = Api<B>>>, ConvertErrorWithContext>,
     TF: FnMut(
         ApiName,
@@ -115,6 +116,44 @@ pub(crate) fn convert_apis<FF, SF, EF, TF, A, B>(
             Api::StringConstructor { name } => {
                 Ok(Box::new(std::iter::once(Api::StringConstructor { name })))
             }
+            Api::StdVariant {
+                name,
+                cpp_type,
+                alternatives,
+            } => Ok(Box::new(std::iter::once(Api::StdVariant {
+                name,
+                cpp_type,
+                alternatives,
+            }))),
+            Api::StdMapIterator {
+                name,
+                map_type,
+                key,
+                value,
+            } => Ok(Box::new(std::iter::once(Api::StdMapIterator {
+                name,
+                map_type,
+                key,
+                value,
+            }))),
+            Api::StdTuple {
+                name,
+                tuple_type,
+                elements,
+            } => Ok(Box::new(std::iter::once(Api::StdTuple {
+                name,
+                tuple_type,
+                elements,
+            }))),
+            Api::RangeIterator {
+                name,
+                function,
+                item,
+            } => Ok(Box::new(std::iter::once(Api::RangeIterator {
+                name,
+                function,
+                item,
+            }))),
             Api::Const { name, const_item } => {
                 Ok(Box::new(std::iter::once(Api::Const { name, const_item })))
             }
@@ -159,15 +198,23 @@ pub(crate) fn convert_apis<FF, SF, EF, TF, A, B>(
                     details,
                 })))
             }
-            Api::ExternCppType { name, details, pod } => {
-                Ok(Box::new(std::iter::once(Api::ExternCppType {
-                    name,
-                    details,
-                    pod,
-                })))
-            }
+            Api::ExternCppType {
+                name,
+                details,
+                pod,
+                enum_item,
+            } => Ok(Box::new(std::iter::once(Api::ExternCppType {
+                name,
+                details,
+                pod,
+                enum_item,
+            }))),
             // Apply a mapping to the following
-            Api::Enum { name, item } => enum_conversion(name, item),
+            Api::Enum {
+                name,
+                item,
+                cpp_definition,
+            } => enum_conversion(name, item, cpp_definition),
             Api::Typedef {
                 name,
                 item,
@@ -14,6 +14,7 @@ mod codegen_rs;
 #[cfg(test)]
 mod conversion_tests;
 mod convert_error;
+mod coverage;
 mod doc_attr;
 mod error_reporter;
 mod parse;
@@ -25,7 +26,11 @@ use autocxx_parser::IncludeCppConfig;
 pub(crate) use codegen_cpp::CppCodeGenerator;
 pub(crate) use convert_error::ConvertError;
 use convert_error::ConvertErrorFromCpp;
+pub use coverage::CoverageReport;
 use itertools::Itertools;
+pub use parse::ApiCache;
+pub(crate) use parse::{hash_file, CachedOutput, HeaderRecorder};
+use std::collections::BTreeMap;
 use syn::{Item, ItemMod};
 
 use crate::{CodegenOptions, CppFilePair, UnsafePolicy};
@@ -34,18 +39,24 @@ use self::{
     analysis::{
         abstract_types::{discard_ignored_functions, mark_types_abstract},
         allocators::create_alloc_and_frees,
+        base_field_accessors::check_base_field_accessors,
         casts::add_casts,
         check_names,
         constructor_deps::decorate_types_with_constructor_deps,
+        cxx_free::check_cxx_free_apis,
         gc::filter_apis_by_following_edges_from_allowlist,
+        include_deps::find_include_dependencies,
         pod::analyze_pod_apis,
         remove_ignored::filter_apis_by_ignored_dependents,
         replace_hopeless_typedef_targets,
+        shared_from_this::add_shared_from_this,
+        swaps::add_swaps,
         tdef::convert_typedef_targets,
     },
-    api::AnalysisPhase,
+    api::{AnalysisPhase, Api},
     apivec::ApiVec,
     codegen_rs::RsCodeGenerator,
+    convert_error::{ignored_item_cpp_name, SkippedItems},
     parse::ParseBindgen,
 };
 
@@ -73,6 +84,11 @@ pub(crate) struct CodegenResults {
     pub(crate) rs: Vec<Item>,
     pub(crate) cpp: Option<CppFilePair>,
     pub(crate) cxxgen_header_name: String,
+    /// The `include_list` entries needed by each generated item.
+    pub(crate) include_dependencies: BTreeMap<String, Vec<String>>,
+    /// In resilient mode, the C++ names of items we skipped over, each
+    /// along with the reason why.
+    pub(crate) skipped: Vec<(String, String)>,
 }
 
 impl<'a> BridgeConverter<'a> {
@@ -96,6 +112,29 @@ impl<'a> BridgeConverter<'a> {
         }
     }
 
+    /// Estimate how much of the bindgen-generated bindings we could
+    /// convert, by parsing them but going no further.
+    pub(crate) fn estimate_coverage(
+        &self,
+        mut bindgen_mod: ItemMod,
+        source_file_contents: &str,
+    ) -> Result<CoverageReport, ConvertError> {
+        match &mut bindgen_mod.content {
+            None => Err(ConvertError::NoContent),
+            Some((_, items)) => {
+                let items_to_process = std::mem::take(items);
+                let parser = ParseBindgen::new(self.config, None);
+                let (apis, _) = parser.parse_items(
+                    items_to_process,
+                    source_file_contents,
+                    &mut SkippedItems::new(false),
+                )?;
+                Self::dump_apis("parsing", &apis);
+                Ok(CoverageReport::from_apis(&apis))
+            }
+        }
+    }
+
     /// Convert a TokenStream of bindgen-generated bindings to a form
     /// suitable for cxx.
     ///
@@ -109,14 +148,19 @@ impl<'a> BridgeConverter<'a> {
         inclusions: String,
         codegen_options: &CodegenOptions,
         source_file_contents: &str,
+        cxxgen_header_name: String,
     ) -> Result<CodegenResults, ConvertError> {
         match &mut bindgen_mod.content {
             None => Err(ConvertError::NoContent),
             Some((_, items)) => {
                 // Parse the bindgen mod.
                 let items_to_process = std::mem::take(items);
-                let parser = ParseBindgen::new(self.config);
-                let apis = parser.parse_items(items_to_process, source_file_contents)?;
+                let parser = ParseBindgen::new(self.config, codegen_options.api_cache);
+                // In resilient mode, we note anything we can't convert
+                // here rather than giving up.
+                let mut skipped = SkippedItems::new(codegen_options.resilient);
+                let (apis, source_files) =
+                    parser.parse_items(items_to_process, source_file_contents, &mut skipped)?;
                 Self::dump_apis("parsing", &apis);
                 // Inside parse_results, we now have a list of APIs.
                 // We now enter various analysis phases.
@@ -132,11 +176,13 @@ impl<'a> BridgeConverter<'a> {
                 // This returns a new list of `Api`s, which will be parameterized with
                 // the analysis results.
                 let analyzed_apis =
-                    analyze_pod_apis(apis, self.config).map_err(ConvertError::Cpp)?;
+                    analyze_pod_apis(apis, self.config, &mut skipped).map_err(ConvertError::Cpp)?;
                 Self::dump_apis("pod analysis", &analyzed_apis);
                 let analyzed_apis = replace_hopeless_typedef_targets(self.config, analyzed_apis);
                 let analyzed_apis = add_casts(analyzed_apis);
                 let analyzed_apis = create_alloc_and_frees(analyzed_apis);
+                let analyzed_apis = add_swaps(analyzed_apis, self.config);
+                let analyzed_apis = add_shared_from_this(analyzed_apis);
                 // Next, figure out how we materialize different functions.
                 // Some will be simple entries in the cxx::bridge module; others will
                 // require C++ wrapper functions. This is probably the most complex
@@ -148,7 +194,9 @@ impl<'a> BridgeConverter<'a> {
                     &unsafe_policy,
                     self.config,
                     codegen_options.force_wrapper_gen,
-                );
+                    &mut skipped,
+                )
+                .map_err(ConvertError::Cpp)?;
                 // If any of those functions turned out to be pure virtual, don't attempt
                 // to generate UniquePtr implementations for the type, since it can't
                 // be instantiated.
@@ -176,12 +224,34 @@ impl<'a> BridgeConverter<'a> {
                 // Determine what variably-sized C types (e.g. int) we need to include
                 analysis::ctypes::append_ctype_information(&mut analyzed_apis);
                 Self::dump_apis("GC", &analyzed_apis);
+                // Make sure the accessors for inherited fields won't clash
+                // with any of the methods which survived.
+                let analyzed_apis = check_base_field_accessors(analyzed_apis, &mut skipped)
+                    .map_err(ConvertError::Cpp)?;
+                // Anything which survived GC but which we couldn't convert
+                // would usually be reported as an error, or at least a
+                // comment in the generated code. In resilient mode, report
+                // it to the caller too.
+                if skipped.is_resilient() {
+                    for api in analyzed_apis.iter() {
+                        if let Api::IgnoredItem { name, err, ctx } = api {
+                            skipped
+                                .items
+                                .push((ignored_item_cpp_name(name, ctx.as_ref()), err.to_string()));
+                        }
+                    }
+                }
+                // If the user has asked us not to use cxx, make sure that's possible.
+                let analyzed_apis = if self.config.is_cxx_free() {
+                    check_cxx_free_apis(analyzed_apis, &mut skipped).map_err(ConvertError::Cpp)?
+                } else {
+                    analyzed_apis
+                };
+                // Note which headers are needed for each API which survived.
+                let include_dependencies =
+                    find_include_dependencies(&analyzed_apis, &source_files, self.include_list);
                 // And finally pass them to the code gen phases, which outputs
                 // code suitable for cxx to consume.
-                let cxxgen_header_name = codegen_options
-                    .cpp_codegen_options
-                    .cxxgen_header_namer
-                    .name_header();
                 let cpp = CppCodeGenerator::generate_cpp_code(
                     inclusions,
                     &analyzed_apis,
@@ -197,11 +267,15 @@ impl<'a> BridgeConverter<'a> {
                     bindgen_mod,
                     self.config,
                     cpp.as_ref().map(|file_pair| file_pair.header_name.clone()),
+                    codegen_options.impl_block_placement,
+                    codegen_options.inline_wrappers,
                 );
                 Ok(CodegenResults {
                     rs,
                     cpp,
                     cxxgen_header_name,
+                    include_dependencies,
+                    skipped: skipped.items,
                 })
             }
         }
@@ -0,0 +1,210 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use indexmap::map::IndexMap as HashMap;
+use syn::{
+    visit_mut::{visit_item_type_mut, visit_type_mut, VisitMut},
+    GenericArgument, GenericParam, Ident, Item, ItemType, PathArguments, Type, TypePath,
+};
+
+use crate::types::{Namespace, QualifiedName};
+
+/// How deeply we'll expand aliases within aliases, in case one somehow
+/// refers to itself.
+const MAX_EXPANSION_DEPTH: usize = 16;
+
+/// A C++ alias template, such as
+/// `template<typename T> using Vec = std::vector<T>`.
+struct AliasTemplate {
+    params: Vec<Ident>,
+    ty: Type,
+}
+
+/// bindgen represents a C++ alias template as a generic type alias, and
+/// refers to each use of it as a specialization of that alias, e.g.
+/// `root::Vec<i32>`. We can't do anything with generic type aliases, so
+/// here we replace each such use with the type for which it's an alias,
+/// e.g. `root::std::vector<i32>`, before we parse anything else.
+pub(super) fn resolve_alias_templates(items: &mut [Item]) {
+    let mut aliases = HashMap::new();
+    find_alias_templates(items, &Namespace::new(), &mut aliases);
+    if aliases.is_empty() {
+        return;
+    }
+    let mut resolver = AliasTemplateResolver {
+        aliases: &aliases,
+        depth: 0,
+    };
+    for item in items.iter_mut() {
+        resolver.visit_item_mut(item);
+    }
+}
+
+fn find_alias_templates(
+    items: &[Item],
+    ns: &Namespace,
+    aliases: &mut HashMap<QualifiedName, AliasTemplate>,
+) {
+    for item in items {
+        match item {
+            Item::Type(ity) if !ity.generics.params.is_empty() => {
+                let params: Option<Vec<_>> = ity
+                    .generics
+                    .params
+                    .iter()
+                    .map(|param| match param {
+                        GenericParam::Type(param) => Some(param.ident.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                if let Some(params) = params {
+                    aliases.insert(
+                        QualifiedName::new(ns, ity.ident.clone().into()),
+                        AliasTemplate {
+                            params,
+                            ty: (*ity.ty).clone(),
+                        },
+                    );
+                }
+            }
+            Item::Mod(itm) => {
+                if let Some((_, items)) = &itm.content {
+                    find_alias_templates(items, &ns.push(itm.ident.to_string()), aliases);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+struct AliasTemplateResolver<'a> {
+    aliases: &'a HashMap<QualifiedName, AliasTemplate>,
+    depth: usize,
+}
+
+impl<'a> AliasTemplateResolver<'a> {
+    /// If this type is a specialization of an alias template, the type
+    /// for which it's an alias.
+    fn expand(&self, ty: &Type) -> Option<Type> {
+        let Type::Path(TypePath { qself: None, path }) = ty else {
+            return None;
+        };
+        if path.segments.first()?.ident != "root" {
+            return None;
+        }
+        let PathArguments::AngleBracketed(args) = &path.segments.last()?.arguments else {
+            return None;
+        };
+        let mut unspecialized = TypePath {
+            qself: None,
+            path: path.clone(),
+        };
+        unspecialized.path.segments.last_mut()?.arguments = PathArguments::None;
+        let alias = self
+            .aliases
+            .get(&QualifiedName::from_type_path(&unspecialized))?;
+        let args: Vec<_> = args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect();
+        if args.len() != alias.params.len() {
+            return None;
+        }
+        let mut expansion = alias.ty.clone();
+        ParamSubstituter {
+            params: &alias.params,
+            args: &args,
+        }
+        .visit_type_mut(&mut expansion);
+        Some(expansion)
+    }
+}
+
+impl<'a> VisitMut for AliasTemplateResolver<'a> {
+    fn visit_item_type_mut(&mut self, ity: &mut ItemType) {
+        // Leave the alias templates themselves alone.
+        if ity.generics.params.is_empty() {
+            visit_item_type_mut(self, ity)
+        }
+    }
+
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        visit_type_mut(self, ty);
+        if self.depth < MAX_EXPANSION_DEPTH {
+            if let Some(expansion) = self.expand(ty) {
+                *ty = expansion;
+                // The alias may itself refer to other aliases.
+                self.depth += 1;
+                self.visit_type_mut(ty);
+                self.depth -= 1;
+            }
+        }
+    }
+}
+
+/// Replaces the parameters of an alias template with the arguments of
+/// one of its specializations.
+struct ParamSubstituter<'a> {
+    params: &'a [Ident],
+    args: &'a [&'a Type],
+}
+
+impl<'a> VisitMut for ParamSubstituter<'a> {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        if let Type::Path(TypePath { qself: None, path }) = ty {
+            if let Some(id) = path.get_ident() {
+                if let Some(idx) = self.params.iter().position(|param| param == id) {
+                    *ty = self.args[idx].clone();
+                    return;
+                }
+            }
+        }
+        visit_type_mut(self, ty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use syn::{parse_quote, Item};
+
+    use super::resolve_alias_templates;
+
+    #[test]
+    fn test_resolve_alias_templates() {
+        let mut items: Vec<Item> = vec![
+            parse_quote! {
+                pub type Vec<T> = root::std::vector<T>;
+            },
+            parse_quote! {
+                pub mod outer {
+                    pub type Nested<T> = root::Vec<root::Vec<T>>;
+                }
+            },
+            parse_quote! {
+                extern "C" {
+                    pub fn take(v: *const root::outer::Nested<u32>) -> root::Vec<f64>;
+                }
+            },
+        ];
+        resolve_alias_templates(&mut items);
+        let expected: Item = parse_quote! {
+            extern "C" {
+                pub fn take(v: *const root::std::vector<root::std::vector<u32> >) -> root::std::vector<f64>;
+            }
+        };
+        assert_eq!(
+            items[2].to_token_stream().to_string(),
+            expected.to_token_stream().to_string()
+        );
+    }
+}
@@ -0,0 +1,292 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use quote::ToTokens;
+use serde_json::{json, Value};
+use syn::{Item, ItemMod};
+
+use crate::{
+    conversion::api::UnanalyzedApi,
+    types::{Namespace, QualifiedName},
+    CppFilePair, RebuildDependencyRecorder,
+};
+
+/// The results of parsing a single item of bindgen output.
+#[derive(Clone)]
+pub(super) struct CachedItem {
+    pub(super) apis: Vec<UnanalyzedApi>,
+    /// Any item which we couldn't parse, recorded as an
+    /// `Api::IgnoredItem`.
+    pub(super) errors: Vec<UnanalyzedApi>,
+    pub(super) source_files: Vec<(QualifiedName, String)>,
+}
+
+/// The complete output of an earlier run, as stored on disk.
+pub(crate) struct CachedOutput {
+    pub(crate) rs: ItemMod,
+    pub(crate) cpp: Option<CppFilePair>,
+    pub(crate) include_dependencies: BTreeMap<String, Vec<String>>,
+    pub(crate) skipped: Vec<(String, String)>,
+    /// Every header file which bindgen read, along with a hash of its
+    /// contents at the time.
+    pub(crate) headers: Vec<(String, u64)>,
+}
+
+/// A cache of the results of parsing each item of `bindgen` output,
+/// which can be passed from one run of autocxx to the next via
+/// [`crate::CodegenOptions::api_cache`]. Items whose tokens are unchanged
+/// since the previous run are then not parsed again.
+///
+/// Only parsing is done item-by-item in this way. bindgen itself still
+/// runs every time, and the later phases - analysis, garbage collection
+/// and code generation - need to consider all the APIs together, so
+/// always run in full. The saving is therefore limited to the cost of
+/// turning bindgen's output into APIs.
+///
+/// The results of parsing depend on the directives in the `include_cpp!`
+/// block, so the cache empties itself if it's used with a different
+/// configuration from last time.
+///
+/// A cache created using [`ApiCache::new_on_disk`] additionally stores
+/// the complete output of each run in a directory, so that it outlives
+/// the process - for example, between runs of a `build.rs`. If a later
+/// run has the same configuration and options, and none of the header
+/// files which bindgen read have changed, that output is reused and
+/// neither bindgen nor any of autocxx's own phases run at all. Headers
+/// are compared by their contents, so merely touching them does no
+/// harm. A header newly added to an include directory, such that it
+/// would now be found in preference to one which bindgen read last
+/// time, goes unnoticed; empty the directory if you do that.
+#[derive(Default)]
+pub struct ApiCache {
+    config_hash: Option<u64>,
+    previous: HashMap<u64, CachedItem>,
+    current: HashMap<u64, CachedItem>,
+    hits: usize,
+    misses: usize,
+    dir: Option<PathBuf>,
+    reused_output: bool,
+}
+
+impl ApiCache {
+    /// Creates a cache which also stores the output of each run in the
+    /// given directory, which will be created if need be.
+    pub fn new_on_disk(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: Some(dir.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Whether the most recent run reused the output of an earlier one
+    /// stored on disk, rather than running bindgen.
+    pub fn reused_output(&self) -> bool {
+        self.reused_output
+    }
+
+    /// The number of items served from the cache during the most recent run.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// The number of items parsed afresh during the most recent run.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Prepares for a new run. Anything not used during that run will be
+    /// forgotten afterwards.
+    pub(super) fn start_run(&mut self, config_hash: u64) {
+        self.previous = std::mem::take(&mut self.current);
+        if self.config_hash != Some(config_hash) {
+            self.previous.clear();
+            self.config_hash = Some(config_hash);
+        }
+        self.hits = 0;
+        self.misses = 0;
+        self.reused_output = false;
+    }
+
+    /// Works out the key under which we store the results of parsing
+    /// a given item, if it's one we can cache at all.
+    pub(super) fn key_for(item: &Item, ns: &Namespace) -> Option<u64> {
+        // Functions and impl blocks are parsed in conjunction with all the
+        // others in the same mod, and mods are made up of other items, so
+        // we can only cache self-contained items.
+        if !matches!(
+            item,
+            Item::Struct(_) | Item::Enum(_) | Item::Const(_) | Item::Type(_) | Item::Use(_)
+        ) {
+            return None;
+        }
+        let mut hasher = DefaultHasher::new();
+        ns.hash(&mut hasher);
+        item.to_token_stream().to_string().hash(&mut hasher);
+        Some(hasher.finish())
+    }
+
+    pub(super) fn get(&mut self, key: u64) -> Option<CachedItem> {
+        let cached = self.previous.remove(&key)?;
+        self.hits += 1;
+        self.current.insert(key, cached.clone());
+        Some(cached)
+    }
+
+    pub(super) fn insert(&mut self, key: u64, item: CachedItem) {
+        self.misses += 1;
+        self.current.insert(key, item);
+    }
+
+    /// Whether this cache stores anything on disk, in which case we must
+    /// find out which headers bindgen reads.
+    pub(crate) fn is_on_disk(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    fn output_path(&self, key: u64) -> Option<PathBuf> {
+        self.dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{key:016x}.json")))
+    }
+
+    /// Finds the output stored on disk under the given key, provided that
+    /// none of the headers from which it was generated have since changed.
+    pub(crate) fn load_output(&mut self, key: u64) -> Option<CachedOutput> {
+        let contents = std::fs::read(self.output_path(key)?).ok()?;
+        let output = CachedOutput::from_json(serde_json::from_slice(&contents).ok()?)?;
+        if output
+            .headers
+            .iter()
+            .any(|(header, hash)| hash_file(Path::new(header)) != Some(*hash))
+        {
+            return None;
+        }
+        self.hits = 0;
+        self.misses = 0;
+        self.reused_output = true;
+        Some(output)
+    }
+
+    /// Stores the output of this run on disk under the given key. The cache
+    /// is merely an optimization, so failure to do so is not an error.
+    pub(crate) fn store_output(&self, key: u64, output: &CachedOutput) {
+        let Some(path) = self.output_path(key) else {
+            return;
+        };
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, output.to_json().to_string()));
+        if let Err(err) = result {
+            log::warn!("Unable to store output in {}: {err}", path.display());
+        }
+    }
+}
+
+/// Hashes the contents of a header file, if it can be read.
+pub(crate) fn hash_file(path: &Path) -> Option<u64> {
+    let contents = std::fs::read(path).ok()?;
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+impl CachedOutput {
+    fn to_json(&self) -> Value {
+        let cpp = self.cpp.as_ref().map(|cpp| {
+            json!({
+                "header": String::from_utf8_lossy(&cpp.header),
+                "implementation": cpp.implementation.as_deref().map(String::from_utf8_lossy),
+                "header_name": cpp.header_name,
+            })
+        });
+        // Hashes are written as strings, since not every JSON reader can
+        // represent a u64 exactly.
+        let headers: Vec<_> = self
+            .headers
+            .iter()
+            .map(|(header, hash)| (header, hash.to_string()))
+            .collect();
+        json!({
+            "rs": self.rs.to_token_stream().to_string(),
+            "cpp": cpp,
+            "include_dependencies": self.include_dependencies,
+            "skipped": self.skipped,
+            "headers": headers,
+        })
+    }
+
+    fn from_json(value: Value) -> Option<Self> {
+        let string = |value: &Value| value.as_str().map(str::to_string);
+        let pair = |value: &Value| {
+            let pair = value.as_array()?;
+            Some((string(pair.first()?)?, string(pair.get(1)?)?))
+        };
+        let cpp = match &value["cpp"] {
+            Value::Null => None,
+            cpp => Some(CppFilePair {
+                header: string(&cpp["header"])?.into_bytes(),
+                implementation: match &cpp["implementation"] {
+                    Value::Null => None,
+                    implementation => Some(string(implementation)?.into_bytes()),
+                },
+                header_name: string(&cpp["header_name"])?,
+            }),
+        };
+        Some(Self {
+            rs: syn::parse_str(value["rs"].as_str()?).ok()?,
+            cpp,
+            include_dependencies: value["include_dependencies"]
+                .as_object()?
+                .iter()
+                .map(|(name, includes)| {
+                    let includes = includes.as_array()?.iter().map(string).collect();
+                    Some((name.clone(), includes?))
+                })
+                .collect::<Option<_>>()?,
+            skipped: value["skipped"]
+                .as_array()?
+                .iter()
+                .map(pair)
+                .collect::<Option<_>>()?,
+            headers: value["headers"]
+                .as_array()?
+                .iter()
+                .map(|header| {
+                    let (header, hash) = pair(header)?;
+                    Some((header, hash.parse().ok()?))
+                })
+                .collect::<Option<_>>()?,
+        })
+    }
+}
+
+/// Notes every header file which bindgen reads, so that we can tell
+/// whether output stored on disk is still valid, passing each on to
+/// any other recorder.
+#[derive(Debug)]
+pub(crate) struct HeaderRecorder {
+    pub(crate) headers: Rc<RefCell<Vec<String>>>,
+    pub(crate) inner: Option<Box<dyn RebuildDependencyRecorder>>,
+}
+
+impl RebuildDependencyRecorder for HeaderRecorder {
+    fn record_header_file_dependency(&self, filename: &str) {
+        self.headers.borrow_mut().push(filename.to_string());
+        if let Some(inner) = &self.inner {
+            inner.record_header_file_dependency(filename);
+        }
+    }
+}
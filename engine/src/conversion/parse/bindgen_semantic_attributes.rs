@@ -6,11 +6,13 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use indexmap::set::IndexSet as HashSet;
 use proc_macro2::{Ident, TokenStream};
 use syn::{
     parenthesized,
     parse::{Parse, Parser},
-    Attribute, LitStr,
+    punctuated::Punctuated,
+    Attribute, LitInt, LitStr, Token,
 };
 
 use crate::conversion::{
@@ -130,6 +132,68 @@ impl BindgenSemanticAttributes {
         self.string_if_present("original_name")
     }
 
+    /// The file in which the item was declared, if known.
+    pub(super) fn get_source_file(&self) -> Option<String> {
+        let location: Option<SourceLocation> = self.parse_if_present("source_location");
+        location.map(|location| location.file)
+    }
+
+    /// Whether the constructor or conversion operator is `explicit`,
+    /// including `explicit(true)`. Newer versions of bindgen tell us
+    /// so. Otherwise, we look at the declaration in the header itself,
+    /// between the end of the previous declaration and the item's name.
+    /// If we can't find it, we assume the worst and say it's explicit.
+    pub(super) fn is_explicit(&self) -> bool {
+        if self.has_attr("explicit") {
+            return true;
+        }
+        let Some((contents, offset)) = self.read_declaring_header() else {
+            return true;
+        };
+        contents.get(..offset).map_or(true, declaration_is_explicit)
+    }
+
+    /// The indices of any parameters declared as the given templates
+    /// specialized for a const type, e.g. `std::span<const float>` if
+    /// `templates` includes `span`, for use if bindgen doesn't tell us
+    /// about them with an `arg_type_const_template_arg` annotation. As
+    /// for `explicit`, we look at the declaration in the header. If we
+    /// can't find it, we say there are none, which is safe if less
+    /// convenient.
+    pub(super) fn get_const_template_params(&self, templates: &[&str]) -> HashSet<usize> {
+        let Some((contents, offset)) = self.read_declaring_header() else {
+            return HashSet::new();
+        };
+        contents
+            .get(offset..)
+            .map(|declaration| const_template_params(declaration, templates))
+            .unwrap_or_default()
+    }
+
+    /// The qualifiers following the parameter list of a method's
+    /// declaration in the header, e.g. `["const", "&&"]` for
+    /// `void f() const &&;`, or `None` if we can't find it.
+    pub(super) fn get_declared_method_qualifiers(&self) -> Option<Vec<String>> {
+        let (contents, offset) = self.read_declaring_header()?;
+        let qualifiers = method_qualifiers(contents.get(offset..)?)?;
+        Some(qualifiers.into_iter().map(str::to_string).collect())
+    }
+
+    /// The contents of the header which declares the item, and the byte
+    /// offset of the item's name within it, if bindgen told us those.
+    fn read_declaring_header(&self) -> Option<(String, usize)> {
+        let location: Option<SourceLocation> = self.parse_if_present("source_location");
+        let SourceLocation {
+            file,
+            offset: Some(offset),
+        } = location?
+        else {
+            return None;
+        };
+        let contents = std::fs::read_to_string(file).ok()?;
+        Some((contents, offset))
+    }
+
     /// Whether this is a move constructor or other special member.
     pub(super) fn special_member_kind(&self) -> Option<SpecialMemberKind> {
         self.string_if_present("special_member")
@@ -161,12 +225,188 @@ impl BindgenSemanticAttributes {
                 if let Ok(ls) = r {
                     results.rvalue_ref_params.insert(ls.into());
                 }
+            } else if a.is_ident("arg_type_const_template_arg") {
+                let r: Result<Ident, syn::Error> = a.parse_args();
+                if let Ok(ls) = r {
+                    results.const_template_arg_params.insert(ls.into());
+                }
             }
         }
         results
     }
 }
 
+/// Whether the text of a header up to the name of a declaration shows
+/// that declaration to be `explicit`. `explicit(false)` doesn't count,
+/// but we treat any other condition as true.
+fn declaration_is_explicit(preceding: &str) -> bool {
+    // Comments may contain anything, so remove them first.
+    let mut uncommented = String::new();
+    let mut remaining = preceding;
+    while let Some(start) = remaining.find('/') {
+        uncommented.push_str(&remaining[..start]);
+        let comment = &remaining[start..];
+        remaining = if comment.starts_with("//") {
+            comment.find('\n').map_or("", |end| &comment[end..])
+        } else if comment.starts_with("/*") {
+            comment.find("*/").map_or("", |end| &comment[end + 2..])
+        } else {
+            uncommented.push('/');
+            &comment[1..]
+        };
+    }
+    uncommented.push_str(remaining);
+    let specifiers = uncommented
+        .rsplit([';', '{', '}', ':'])
+        .next()
+        .unwrap_or_default();
+    let is_word_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    specifiers.match_indices("explicit").any(|(start, _)| {
+        let before = specifiers[..start].chars().next_back();
+        let after = &specifiers[start + "explicit".len()..];
+        if before.is_some_and(is_word_char) || after.starts_with(is_word_char) {
+            return false;
+        }
+        let condition: String = after
+            .trim_start()
+            .strip_prefix('(')
+            .and_then(|condition| condition.split_once(')'))
+            .map(|(condition, _)| condition.split_whitespace().collect())
+            .unwrap_or_default();
+        condition != "false"
+    })
+}
+
+/// Which parameters of a function are specializations of any of the
+/// given templates for a const type, given the text of a header from
+/// the function's name onwards.
+fn const_template_params(declaration: &str, templates: &[&str]) -> HashSet<usize> {
+    let Some(start) = declaration.find('(') else {
+        return HashSet::new();
+    };
+    let mut params = vec![String::new()];
+    let mut depth = 0usize;
+    for c in declaration[start + 1..].chars() {
+        match c {
+            ')' if depth == 0 => break,
+            ',' if depth == 0 => {
+                params.push(String::new());
+                continue;
+            }
+            '(' | '<' | '[' | '{' => depth += 1,
+            ')' | '>' | ']' | '}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        params.last_mut().unwrap().push(c);
+    }
+    let is_word_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let is_const_specialization = |param: &str, template: &str| {
+        param.find(template).is_some_and(|pos| {
+            let Some(element) = param[pos + template.len()..].trim_start().strip_prefix('<') else {
+                return false;
+            };
+            element
+                .split(['<', '>', ','])
+                .next()
+                .unwrap_or_default()
+                .split(|c| !is_word_char(c))
+                .any(|word| word == "const")
+        })
+    };
+    params
+        .iter()
+        .enumerate()
+        .filter(|(_, param)| {
+            templates
+                .iter()
+                .any(|template| is_const_specialization(param, template))
+        })
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// The text following the parenthesized expression at the start of
+/// `text`, or `None` if it doesn't start with one.
+fn skip_parenthesized(text: &str) -> Option<&str> {
+    let mut depth = 0usize;
+    for (idx, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 1 => return Some(&text[idx + 1..]),
+            ')' => depth = depth.checked_sub(1)?,
+            _ if depth == 0 => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The words and `&` or `&&` following the parameter list of a function,
+/// given the text of a header from the function's name onwards, up to
+/// its body, `;`, `=`, or trailing return type. Anything parenthesized,
+/// e.g. the condition of `noexcept(...)`, or within `[[...]]`, is
+/// skipped.
+fn method_qualifiers(declaration: &str) -> Option<Vec<&str>> {
+    // The name of `operator()` contains the only parentheses which
+    // aren't its parameter list.
+    let after_name = declaration
+        .strip_prefix("operator")
+        .map(|rest| rest.trim_start())
+        .and_then(|rest| rest.strip_prefix("()"))
+        .unwrap_or(declaration);
+    let params = &after_name[after_name.find('(')?..];
+    let mut remaining = skip_parenthesized(params)?;
+    let is_word_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut qualifiers = Vec::new();
+    loop {
+        remaining = remaining.trim_start();
+        if remaining.starts_with('(') {
+            remaining = skip_parenthesized(remaining)?;
+        } else if let Some(attribute) = remaining.strip_prefix("[[") {
+            remaining = &attribute[attribute.find("]]")? + 2..];
+        } else if remaining.starts_with('&') {
+            let len = if remaining.starts_with("&&") { 2 } else { 1 };
+            qualifiers.push(&remaining[..len]);
+            remaining = &remaining[len..];
+        } else if remaining.starts_with(is_word_char) {
+            let len = remaining
+                .find(|c| !is_word_char(c))
+                .unwrap_or(remaining.len());
+            qualifiers.push(&remaining[..len]);
+            remaining = &remaining[len..];
+        } else {
+            return Some(qualifiers);
+        }
+    }
+}
+
+/// The location recorded by bindgen's `source_location` annotation.
+/// We currently only care about the file and byte offset.
+struct SourceLocation {
+    file: String,
+    offset: Option<usize>,
+}
+
+impl Parse for SourceLocation {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let file: LitStr = input.parse()?;
+        // The file is followed by the line, column and byte offset.
+        let numbers = if input.is_empty() {
+            Punctuated::new()
+        } else {
+            input.parse::<Token![,]>()?;
+            Punctuated::<LitInt, Token![,]>::parse_terminated(input)?
+        };
+        Ok(Self {
+            file: file.value(),
+            offset: numbers
+                .iter()
+                .nth(2)
+                .and_then(|offset| offset.base10_parse().ok()),
+        })
+    }
+}
+
 #[derive(Debug)]
 struct BindgenSemanticAttribute {
     annotation_name: Ident,
@@ -203,3 +443,111 @@ impl Parse for BindgenSemanticAttribute {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use syn::{parse_quote, Attribute};
+
+    use super::{
+        const_template_params, declaration_is_explicit, method_qualifiers,
+        BindgenSemanticAttributes,
+    };
+    use crate::types::make_ident;
+
+    #[test]
+    fn test_is_explicit() {
+        let attrs: Vec<Attribute> = vec![
+            parse_quote! { #[cpp_semantics(explicit)] },
+            parse_quote! { #[doc = "A converting constructor"] },
+        ];
+        assert!(BindgenSemanticAttributes::new(&attrs).is_explicit());
+        // Without the annotation or a location, we can't tell.
+        assert!(BindgenSemanticAttributes::new(&attrs[1..]).is_explicit());
+    }
+
+    #[test]
+    fn test_declaration_is_explicit() {
+        assert!(declaration_is_explicit("struct A {\n  explicit "));
+        assert!(declaration_is_explicit("public:\n  constexpr explicit "));
+        assert!(declaration_is_explicit("};\n  explicit(true) "));
+        assert!(declaration_is_explicit("};\n  explicit /* yes */ "));
+        assert!(!declaration_is_explicit(
+            "struct A {\n  explicit A(int);\n  "
+        ));
+        assert!(!declaration_is_explicit("public:\n  explicit( false ) "));
+        assert!(!declaration_is_explicit("public:\n  // not explicit\n  "));
+        assert!(!declaration_is_explicit("public:\n  /* explicit */ "));
+        assert!(!declaration_is_explicit("public:\n  inexplicitly_named "));
+    }
+
+    #[test]
+    fn test_method_qualifiers() {
+        assert_eq!(method_qualifiers("f() &;"), Some(vec!["&"]));
+        assert_eq!(
+            method_qualifiers("f(int a = g()) const && noexcept(true) {"),
+            Some(vec!["const", "&&", "noexcept"])
+        );
+        assert_eq!(
+            method_qualifiers("operator()(std::function<void(int)> f) & [[gnu::pure]] override;"),
+            Some(vec!["&", "override"])
+        );
+        assert_eq!(
+            method_qualifiers("get() const & -> int&;"),
+            Some(vec!["const", "&"])
+        );
+        assert_eq!(
+            method_qualifiers("f() __restrict override;"),
+            Some(vec!["__restrict", "override"])
+        );
+        assert_eq!(method_qualifiers("f(int a) = delete;"), Some(vec![]));
+        assert_eq!(method_qualifiers("f(int a"), None);
+    }
+
+    #[test]
+    fn test_const_template_params() {
+        let params = |declaration| {
+            const_template_params(declaration, &["span"])
+                .into_iter()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            params("sum(std::span<const float> a, std::span<float> b);"),
+            vec![0]
+        );
+        assert_eq!(
+            params("fill(std::span<int> a,\n  std::span< const Point > b) const;"),
+            vec![1]
+        );
+        assert_eq!(
+            params("f(std::map<int, int> m, std::span<std::vector<const int>> s);"),
+            Vec::<usize>::new()
+        );
+        assert_eq!(params("g(std::span<float const> s)"), vec![0]);
+        assert_eq!(params("h()"), Vec::<usize>::new());
+        assert_eq!(
+            const_template_params(
+                "visit(std::reference_wrapper<Foo> a, std::span<int> b, std::reference_wrapper<const Foo> c)",
+                &["span", "reference_wrapper"]
+            )
+            .into_iter()
+            .collect::<Vec<_>>(),
+            vec![2]
+        );
+    }
+
+    #[test]
+    fn test_const_template_arg_params() {
+        let attrs: Vec<Attribute> = vec![
+            parse_quote! { #[cpp_semantics(arg_type_const_template_arg(values))] },
+            parse_quote! { #[cpp_semantics(arg_type_reference(out))] },
+        ];
+        let references =
+            BindgenSemanticAttributes::new(&attrs).get_reference_parameters_and_return();
+        assert!(references
+            .const_template_arg_params
+            .contains(&make_ident("values")));
+        assert!(!references
+            .const_template_arg_params
+            .contains(&make_ident("out")));
+    }
+}
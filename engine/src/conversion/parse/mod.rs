@@ -6,10 +6,14 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+mod alias_templates;
+mod api_cache;
 mod bindgen_semantic_attributes;
 mod extern_fun_signatures;
 mod parse_bindgen;
 mod parse_foreign_mod;
 
+pub use api_cache::ApiCache;
+pub(crate) use api_cache::{hash_file, CachedOutput, HeaderRecorder};
 pub(crate) use bindgen_semantic_attributes::BindgenSemanticAttributes;
 pub(crate) use parse_bindgen::ParseBindgen;
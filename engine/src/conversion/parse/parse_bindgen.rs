@@ -8,16 +8,24 @@
 
 use indexmap::map::IndexMap as HashMap;
 use indexmap::set::IndexSet as HashSet;
+use std::cell::RefCell;
 
 use crate::{
     conversion::{
-        api::{Api, ApiName, NullPhase, StructDetails, SubclassName, TypedefKind, UnanalyzedApi},
+        analysis::fun::function_wrapper::{CppFunctionBody, CppFunctionKind},
+        api::{
+            Api, ApiName, CppVisibility, DeletedOrDefaulted, FuncToConvert, NullPhase, Provenance,
+            References, StdMapEntryType, StructDetails, SubclassKind, SubclassName, TypedefKind,
+            UnanalyzedApi, Virtualness,
+        },
         apivec::ApiVec,
-        convert_error::LocatedConvertErrorFromRust,
+        convert_error::{LocatedConvertErrorFromRust, SkippedItems},
         ConvertError, ConvertErrorFromCpp,
     },
-    types::Namespace,
+    known_types::known_types,
+    minisyn::minisynize_punctuated,
     types::QualifiedName,
+    types::{make_ident, Namespace},
 };
 use crate::{
     conversion::{
@@ -26,19 +34,26 @@ use crate::{
     },
     types::validate_ident_ok_for_cxx,
 };
-use autocxx_parser::{IncludeCppConfig, RustPath};
-use syn::{parse_quote, Fields, Ident, Item, Type, TypePath, UseTree};
+use autocxx_parser::{FunctionInstantiation, IncludeCppConfig, RustPath};
+use syn::{
+    parse_quote, punctuated::Punctuated, token::Comma, Attribute, Fields, FnArg, GenericArgument,
+    Ident, Item, ItemStruct, ItemType, PathArguments, Type, TypeBareFn, TypePath, UseTree,
+};
 
 use super::{
     super::utilities::generate_utilities, bindgen_semantic_attributes::BindgenSemanticAttributes,
 };
 
+use super::alias_templates::resolve_alias_templates;
+use super::api_cache::{ApiCache, CachedItem};
 use super::parse_foreign_mod::ParseForeignMod;
 
 /// Parses a bindgen mod in order to understand the APIs within it.
 pub(crate) struct ParseBindgen<'a> {
     config: &'a IncludeCppConfig,
     apis: ApiVec<NullPhase>,
+    source_files: HashMap<QualifiedName, String>,
+    api_cache: Option<&'a RefCell<ApiCache>>,
 }
 
 fn api_name(ns: &Namespace, id: Ident, attrs: &BindgenSemanticAttributes) -> ApiName {
@@ -63,21 +78,33 @@ pub(crate) fn api_name_qualified(
 }
 
 impl<'a> ParseBindgen<'a> {
-    pub(crate) fn new(config: &'a IncludeCppConfig) -> Self {
+    pub(crate) fn new(
+        config: &'a IncludeCppConfig,
+        api_cache: Option<&'a RefCell<ApiCache>>,
+    ) -> Self {
+        if let Some(api_cache) = api_cache {
+            api_cache.borrow_mut().start_run(config.get_hash());
+        }
         ParseBindgen {
             config,
             apis: ApiVec::new(),
+            source_files: HashMap::new(),
+            api_cache,
         }
     }
 
     /// Parses items found in the `bindgen` output and returns a set of
-    /// `Api`s together with some other data.
+    /// `Api`s together with the file in which each was declared, where
+    /// bindgen told us. Any `generate!` directive which we couldn't obey
+    /// is recorded in `skipped`.
     pub(crate) fn parse_items(
         mut self,
         items: Vec<Item>,
         source_file_contents: &str,
-    ) -> Result<ApiVec<NullPhase>, ConvertError> {
-        let items = Self::find_items_in_root(items).map_err(ConvertError::Cpp)?;
+        skipped: &mut SkippedItems,
+    ) -> Result<(ApiVec<NullPhase>, HashMap<QualifiedName, String>), ConvertError> {
+        let mut items = Self::find_items_in_root(items).map_err(ConvertError::Cpp)?;
+        resolve_alias_templates(&mut items);
         if !self.config.exclude_utilities() {
             generate_utilities(&mut self.apis, self.config);
         }
@@ -85,10 +112,13 @@ impl<'a> ParseBindgen<'a> {
             .map_err(ConvertError::Rust)?;
         let root_ns = Namespace::new();
         self.parse_mod_items(items, root_ns);
-        self.confirm_all_generate_directives_obeyed()
+        self.instantiate_nested_enums();
+        self.instantiate_function_templates();
+        self.confirm_all_generate_directives_obeyed(skipped)
             .map_err(ConvertError::Cpp)?;
         self.replace_extern_cpp_types();
-        Ok(self.apis)
+        self.reject_impure_interfaces();
+        Ok((self.apis, self.source_files))
     }
 
     /// Some API items are not populated from bindgen output, but instead
@@ -97,11 +127,17 @@ impl<'a> ParseBindgen<'a> {
         &mut self,
         source_file_contents: &str,
     ) -> Result<(), LocatedConvertErrorFromRust> {
-        self.apis
-            .extend(self.config.subclasses.iter().map(|sc| Api::Subclass {
-                name: SubclassName::new(sc.subclass.clone().into()),
+        self.apis.extend(self.config.subclasses.iter().map(|sc| {
+            let kind = if self.config.is_interface_impl(&sc.subclass.to_string()) {
+                SubclassKind::InterfaceImpl
+            } else {
+                SubclassKind::UserDefined
+            };
+            Api::Subclass {
+                name: SubclassName::new(sc.subclass.clone().into(), kind),
                 superclass: QualifiedName::new_from_cpp_name(&sc.superclass),
-            }));
+            }
+        }));
         for fun in &self.config.extern_rust_funs {
             let id = fun.sig.ident.clone();
             self.apis.push(Api::RustFn {
@@ -135,13 +171,331 @@ impl<'a> ParseBindgen<'a> {
                     }
                 }),
         );
+        let pod_requests: HashSet<_> = self.config.get_pod_requests().iter().collect();
+        self.apis.extend(self.config.std_variants.iter().map(|sv| {
+            let name = ApiName::new_in_root_namespace(sv.rust_id.clone().into());
+            // Alternatives are passed by value between Rust and C++, so
+            // must be trivially copyable.
+            let unsupported_alternative = sv.alternatives.iter().find(|alt| {
+                !known_types().is_trivially_copyable(&QualifiedName::new_from_cpp_name(alt))
+                    && !pod_requests.contains(alt)
+            });
+            match unsupported_alternative {
+                Some(alt) => Api::IgnoredItem {
+                    err: ConvertErrorFromCpp::UnsupportedVariantAlternative(alt.clone()),
+                    ctx: Some(ErrorContext::new_for_item(sv.rust_id.clone().into())),
+                    name,
+                },
+                None => Api::StdVariant {
+                    name,
+                    cpp_type: QualifiedName::new(&Namespace::new(), sv.cpp_type_name().into()),
+                    alternatives: sv
+                        .alternatives
+                        .iter()
+                        .map(|alt| QualifiedName::new_from_cpp_name(alt))
+                        .collect(),
+                },
+            }
+        }));
+        // Keys and values are copied out of a map if they can be passed
+        // by value, and are otherwise returned by reference.
+        let entry_type = |cpp_name: &String| {
+            let ty = QualifiedName::new_from_cpp_name(cpp_name);
+            let by_value = known_types().is_trivially_copyable(&ty)
+                || known_types().is_ctype(&ty)
+                || pod_requests.contains(cpp_name);
+            let ty = known_types()
+                .known_type_type_path(&ty)
+                .map(|typ| QualifiedName::from_type_path(&typ))
+                .unwrap_or(ty);
+            StdMapEntryType { ty, by_value }
+        };
+        self.apis
+            .extend(self.config.get_std_maps().map(|sm| Api::StdMapIterator {
+                name: ApiName::new_in_root_namespace(sm.iterator_name().into()),
+                map_type: QualifiedName::new(&Namespace::new(), sm.rust_id.clone().into()),
+                key: entry_type(&sm.key),
+                value: entry_type(&sm.value),
+            }));
+        // Tuple elements are always copied into and out of the Rust tuple.
+        self.apis.extend(self.config.get_std_tuples().map(|st| {
+            let name = ApiName::new_in_root_namespace(st.rust_tuple_name().into());
+            let elements: Vec<_> = st.elements.iter().map(entry_type).collect();
+            let unsupported_element = st
+                .elements
+                .iter()
+                .zip(elements.iter())
+                .find(|(_, element)| !element.by_value);
+            match unsupported_element {
+                Some((element, _)) => Api::IgnoredItem {
+                    err: ConvertErrorFromCpp::UnsupportedTupleElement(element.clone()),
+                    ctx: Some(ErrorContext::new_for_item(st.rust_tuple_name().into())),
+                    name,
+                },
+                None => Api::StdTuple {
+                    name,
+                    tuple_type: QualifiedName::new(&Namespace::new(), st.rust_id.clone().into()),
+                    elements: elements.into_iter().map(|element| element.ty).collect(),
+                },
+            }
+        }));
+        // Likewise, range items are always copied out of the range.
+        self.apis
+            .extend(self.config.get_range_functions().map(|rf| {
+                let name = ApiName::new_in_root_namespace(rf.rust_id.clone().into());
+                let item = entry_type(&rf.item);
+                if item.by_value {
+                    Api::RangeIterator {
+                        name,
+                        function: rf.function.clone(),
+                        item: item.ty,
+                    }
+                } else {
+                    Api::IgnoredItem {
+                        err: ConvertErrorFromCpp::UnsupportedRangeItem(rf.item.clone()),
+                        ctx: Some(ErrorContext::new_for_item(rf.rust_id.clone().into())),
+                        name,
+                    }
+                }
+            }));
         Ok(())
     }
 
+    /// For each function template specialization listed using
+    /// `instantiate!`, we asked bindgen for a typedef of its function
+    /// type. Replace each such typedef with a function which calls the
+    /// specialization.
+    /// An enum nested within a class template, such as
+    /// `Container<T>::State`, can't be named in C++ without the template's
+    /// arguments, so we can't bind to it as it stands. Instead, for each
+    /// instantiation of the template listed using `concrete!`, e.g.
+    /// `concrete!("Container<int>", ContainerInt)`, we make a copy of the
+    /// enum called `ContainerInt_State`, which refers to
+    /// `Container<int>::State`.
+    fn instantiate_nested_enums(&mut self) {
+        let templates: HashSet<String> = self
+            .apis
+            .iter()
+            .filter_map(|api| match api {
+                Api::Struct { name, details, .. } if !details.item.generics.params.is_empty() => {
+                    Some(name.qualified_cpp_name())
+                }
+                _ => None,
+            })
+            .collect();
+        if templates.is_empty() {
+            return;
+        }
+        let apis = std::mem::replace(&mut self.apis, ApiVec::new());
+        for api in apis.into_iter() {
+            let Api::Enum { name, item, .. } = &api else {
+                self.apis.push(api);
+                continue;
+            };
+            let qualified_cpp_name = name.qualified_cpp_name();
+            let Some((template, enum_name)) = qualified_cpp_name
+                .rsplit_once("::")
+                .filter(|(template, _)| templates.contains(*template))
+            else {
+                self.apis.push(api);
+                continue;
+            };
+            for (cpp_definition, rust_id) in &self.config.concretes.0 {
+                let instantiated_template = cpp_definition
+                    .split('<')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .trim_start_matches("::");
+                if instantiated_template != template {
+                    continue;
+                }
+                let id = make_ident(format!("{rust_id}_{enum_name}"));
+                let mut item = item.clone();
+                item.ident = id.0.clone();
+                self.apis.push(Api::Enum {
+                    name: ApiName::new_in_root_namespace(id),
+                    item,
+                    cpp_definition: Some(format!("{cpp_definition}::{enum_name}")),
+                });
+            }
+            self.apis.push(Api::IgnoredItem {
+                name: name.clone(),
+                err: ConvertErrorFromCpp::EnumNestedInTemplate,
+                ctx: Some(ErrorContext::new_for_item(name.name.get_final_ident())),
+            });
+        }
+    }
+
+    fn instantiate_function_templates(&mut self) {
+        let config = self.config;
+        for fi in config.get_function_instantiations() {
+            let typedef_name = QualifiedName::new(
+                &Namespace::new(),
+                make_ident(config.get_function_instantiation_typedef_name(&fi.rust_id)),
+            );
+            let mut bare_fn = None;
+            self.apis.retain(|api| match api {
+                Api::Typedef {
+                    name,
+                    item: TypedefKind::Type(ity),
+                    ..
+                } if name.name == typedef_name => {
+                    bare_fn = Self::get_bare_fn(&ity.ty).cloned();
+                    false
+                }
+                _ => true,
+            });
+            let receiver_type = fi
+                .receiver_type
+                .as_deref()
+                .map(QualifiedName::new_from_cpp_name);
+            let name = match &receiver_type {
+                None => ApiName::new_in_root_namespace(fi.rust_id.clone().into()),
+                // Named like bindgen's methods, {class}_{method name}.
+                Some(receiver_type) => ApiName::new_with_cpp_name(
+                    receiver_type.get_namespace(),
+                    make_ident(format!("{}_{}", receiver_type.get_final_item(), fi.rust_id)).into(),
+                    Some(fi.rust_id.to_string()),
+                ),
+            };
+            self.apis.push(match bare_fn {
+                Some(bare_fn)
+                    if bare_fn.variadic.is_none()
+                        && (receiver_type.is_none() || !bare_fn.inputs.is_empty()) =>
+                {
+                    Api::Function {
+                        name,
+                        fun: Box::new(Self::make_instantiation_fn(fi, receiver_type, bare_fn)),
+                        analysis: (),
+                    }
+                }
+                _ => Api::IgnoredItem {
+                    err: ConvertErrorFromCpp::UnsupportedFunctionInstantiation(
+                        fi.cpp_definition.clone(),
+                    ),
+                    ctx: Some(ErrorContext::new_for_item(fi.rust_id.clone().into())),
+                    name,
+                },
+            });
+        }
+    }
+
+    /// Bindgen may represent a function type either as a bare function
+    /// or as an optional function pointer.
+    fn get_bare_fn(ty: &Type) -> Option<&TypeBareFn> {
+        match ty {
+            Type::BareFn(bare_fn) => Some(bare_fn),
+            Type::Path(typ) => {
+                let seg = typ.path.segments.last()?;
+                match &seg.arguments {
+                    PathArguments::AngleBracketed(args) if seg.ident == "Option" => {
+                        match args.args.first()? {
+                            GenericArgument::Type(ty) => Self::get_bare_fn(ty),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn make_instantiation_fn(
+        fi: &FunctionInstantiation,
+        receiver_type: Option<QualifiedName>,
+        bare_fn: TypeBareFn,
+    ) -> FuncToConvert {
+        let is_method = receiver_type.is_some();
+        let inputs: Punctuated<FnArg, Comma> = bare_fn
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| -> FnArg {
+                // For a member function template, the first parameter
+                // is the receiver.
+                let id = if is_method && i == 0 {
+                    make_ident("this").into()
+                } else {
+                    arg.name
+                        .as_ref()
+                        .map(|(id, _)| id.clone())
+                        .unwrap_or_else(|| make_ident(format!("arg{i}")).into())
+                };
+                let ty = &arg.ty;
+                parse_quote! { #id: #ty }
+            })
+            .collect();
+        let cpp_definition = &fi.cpp_definition;
+        let (doc, original_name, synthetic_cpp) = match fi.member_template_id() {
+            Some(template_id) if is_method => (
+                format!(
+                    "Calls the C++ member function template specialization `{cpp_definition}`."
+                ),
+                Some(fi.rust_id.to_string()),
+                (
+                    CppFunctionBody::MethodTemplateCall(template_id.to_string()),
+                    CppFunctionKind::Method,
+                ),
+            ),
+            _ => (
+                format!("Calls the C++ function template specialization `{cpp_definition}`."),
+                None,
+                (
+                    CppFunctionBody::FunctionTemplateCall(cpp_definition.to_string()),
+                    CppFunctionKind::Function,
+                ),
+            ),
+        };
+        let doc_attr: Attribute = parse_quote! { #[doc = #doc] };
+        FuncToConvert {
+            ident: fi.rust_id.clone().into(),
+            doc_attrs: vec![doc_attr.into()],
+            inputs: minisynize_punctuated(inputs),
+            output: bare_fn.output.into(),
+            vis: parse_quote! { pub },
+            virtualness: Virtualness::None,
+            cpp_vis: CppVisibility::Public,
+            special_member: None,
+            unused_template_param: false,
+            references: References::default(),
+            original_name,
+            self_ty: receiver_type,
+            synthesized_this_type: None,
+            add_to_trait: None,
+            synthetic_cpp: Some(synthetic_cpp),
+            is_deleted: DeletedOrDefaulted::Neither,
+            is_explicit: false,
+            must_use: false,
+            provenance: Provenance::SynthesizedOther,
+            variadic: false,
+        }
+    }
+
     /// We do this last, _after_ we've parsed all the APIs, because we might want to actually
     /// replace some of the existing APIs (structs/enums/etc.) with replacements.
     fn replace_extern_cpp_types(&mut self) {
         let pod_requests: HashSet<_> = self.config.get_pod_requests().iter().collect();
+        // Keep hold of any enums we're replacing with existing Rust enums,
+        // so that we can check the two agree.
+        let mut enum_items: HashMap<_, _> = self
+            .apis
+            .iter()
+            .filter_map(|api| match api {
+                Api::Enum { name, item, .. }
+                    if self
+                        .config
+                        .externs
+                        .0
+                        .get(&name.name.to_cpp_name())
+                        .map_or(false, |details| details.is_enum) =>
+                {
+                    Some((name.name.clone(), item.clone()))
+                }
+                _ => None,
+            })
+            .collect();
         let replacements: HashMap<_, _> = self
             .config
             .externs
@@ -149,13 +503,16 @@ impl<'a> ParseBindgen<'a> {
             .iter()
             .map(|(cpp_definition, details)| {
                 let qn = QualifiedName::new_from_cpp_name(cpp_definition);
-                let pod = pod_requests.contains(&qn.to_cpp_name());
+                // Enums can always be passed by value.
+                let pod = pod_requests.contains(&qn.to_cpp_name()) || details.is_enum;
+                let enum_item = enum_items.remove(&qn);
                 (
                     qn.clone(),
                     Api::ExternCppType {
                         name: ApiName::new_from_qualified_name(qn),
                         details: details.clone(),
                         pod,
+                        enum_item,
                     },
                 )
             })
@@ -165,6 +522,50 @@ impl<'a> ParseBindgen<'a> {
         self.apis.extend(replacements.into_iter().map(|(_, v)| v));
     }
 
+    /// Interfaces are implemented by forwarding every virtual method to
+    /// an arbitrary Rust implementation, so they can't be used for classes
+    /// which have virtual methods with an existing C++ implementation.
+    fn reject_impure_interfaces(&mut self) {
+        let impure_classes: HashSet<_> = self
+            .apis
+            .iter()
+            .filter_map(|api| match api {
+                Api::Function { fun, .. } if matches!(fun.virtualness, Virtualness::Virtual) => {
+                    fun.self_ty.clone()
+                }
+                _ => None,
+            })
+            .collect();
+        let rejected: Vec<_> = self
+            .apis
+            .iter()
+            .filter_map(|api| match api {
+                Api::Subclass { name, superclass }
+                    if matches!(name.1, SubclassKind::InterfaceImpl)
+                        && impure_classes.contains(superclass) =>
+                {
+                    Some((name.clone(), superclass.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        if rejected.is_empty() {
+            return;
+        }
+        let rejected_names: HashSet<_> =
+            rejected.iter().map(|(sub, _)| sub.0.name.clone()).collect();
+        self.apis.retain(|api| !rejected_names.contains(api.name()));
+        self.apis.extend(
+            rejected
+                .into_iter()
+                .map(|(sub, superclass)| Api::IgnoredItem {
+                    err: ConvertErrorFromCpp::InterfaceNotPureVirtual(superclass),
+                    ctx: Some(ErrorContext::new_for_item(sub.id())),
+                    name: sub.0,
+                }),
+        );
+    }
+
     fn find_items_in_root(items: Vec<Item>) -> Result<Vec<Item>, ConvertErrorFromCpp> {
         for item in items {
             match item {
@@ -191,12 +592,54 @@ impl<'a> ParseBindgen<'a> {
         let mut mod_converter = ParseForeignMod::new(ns.clone());
         let mut more_apis = ApiVec::new();
         for item in items {
-            report_any_error(&ns, &mut more_apis, || {
-                self.parse_item(item, &mut mod_converter, &ns)
-            });
+            match self
+                .api_cache
+                .and_then(|api_cache| ApiCache::key_for(&item, &ns).map(|key| (api_cache, key)))
+            {
+                Some((api_cache, key)) => {
+                    let cached = api_cache.borrow_mut().get(key);
+                    let cached = cached.unwrap_or_else(|| {
+                        let parsed = self.parse_item_in_isolation(item, &mut mod_converter, &ns);
+                        api_cache.borrow_mut().insert(key, parsed.clone());
+                        parsed
+                    });
+                    self.apis.extend(cached.apis.into_iter());
+                    more_apis.extend(cached.errors.into_iter());
+                    self.source_files.extend(cached.source_files);
+                }
+                None => {
+                    report_any_error(&ns, &mut more_apis, || {
+                        self.parse_item(item, &mut mod_converter, &ns)
+                    });
+                }
+            }
         }
         self.apis.append(&mut more_apis);
-        mod_converter.finished(&mut self.apis);
+        mod_converter.finished(&mut self.apis, &mut self.source_files);
+    }
+
+    /// Parses a single item, returning whatever we learnt from it rather
+    /// than adding it to everything else we've learnt, such that it can
+    /// be cached.
+    fn parse_item_in_isolation(
+        &mut self,
+        item: Item,
+        mod_converter: &mut ParseForeignMod,
+        ns: &Namespace,
+    ) -> CachedItem {
+        let apis = std::mem::take(&mut self.apis);
+        let source_files = std::mem::take(&mut self.source_files);
+        let mut errors = ApiVec::new();
+        report_any_error(ns, &mut errors, || self.parse_item(item, mod_converter, ns));
+        CachedItem {
+            apis: std::mem::replace(&mut self.apis, apis)
+                .into_iter()
+                .collect(),
+            errors: errors.into_iter().collect(),
+            source_files: std::mem::replace(&mut self.source_files, source_files)
+                .into_iter()
+                .collect(),
+        }
     }
 
     fn parse_item(
@@ -256,6 +699,7 @@ impl<'a> ParseBindgen<'a> {
                 };
                 if let Some(api) = api {
                     if !self.config.is_on_blocklist(&api.name().to_cpp_name()) {
+                        self.record_source_file(api.name(), &annotations);
                         self.apis.push(api);
                     }
                 }
@@ -266,8 +710,10 @@ impl<'a> ParseBindgen<'a> {
                 let api = UnanalyzedApi::Enum {
                     name: api_name_qualified(ns, e.ident.clone(), &annotations)?,
                     item: e.into(),
+                    cpp_definition: None,
                 };
                 if !self.config.is_on_blocklist(&api.name().to_cpp_name()) {
+                    self.record_source_file(api.name(), &annotations);
                     self.apis.push(api);
                 }
                 Ok(())
@@ -373,10 +819,41 @@ impl<'a> ParseBindgen<'a> {
             }
             Item::Type(ity) => {
                 let annotations = BindgenSemanticAttributes::new(&ity.attrs);
+                let name = api_name(ns, ity.ident.clone(), &annotations);
+                // A typedef listed using newtype_typedef! instead becomes
+                // a struct wrapping the type for which it's an alias, and
+                // is then treated just like any other struct.
+                if ity.generics.params.is_empty()
+                    && self.config.is_newtype_typedef(&name.name.to_cpp_name())
+                {
+                    let ItemType {
+                        attrs,
+                        vis,
+                        ident,
+                        ty,
+                        ..
+                    } = ity;
+                    let s: ItemStruct = parse_quote! {
+                        #(#attrs)*
+                        #[repr(transparent)]
+                        #vis struct #ident(pub #ty);
+                    };
+                    self.record_source_file(&name.name, &annotations);
+                    self.apis.push(UnanalyzedApi::Struct {
+                        name,
+                        details: Box::new(StructDetails {
+                            layout: None,
+                            item: s.into(),
+                            has_rvalue_reference_fields: false,
+                        }),
+                        analysis: (),
+                    });
+                    return Ok(());
+                }
                 // It's known that sometimes bindgen will give us duplicate typedefs with the
                 // same name - see test_issue_264.
                 self.apis.push(UnanalyzedApi::Typedef {
-                    name: api_name(ns, ity.ident.clone(), &annotations),
+                    name,
                     item: TypedefKind::Type(ity.into()),
                     old_tyname: None,
                     analysis: (),
@@ -390,6 +867,16 @@ impl<'a> ParseBindgen<'a> {
         }
     }
 
+    fn record_source_file(
+        &mut self,
+        name: &QualifiedName,
+        annotations: &BindgenSemanticAttributes,
+    ) {
+        if let Some(file) = annotations.get_source_file() {
+            self.source_files.insert(name.clone(), file);
+        }
+    }
+
     fn spot_forward_declaration(s: &Fields) -> bool {
         Self::spot_field(s, "_unused")
     }
@@ -404,7 +891,10 @@ impl<'a> ParseBindgen<'a> {
             .any(|id| id == desired_id)
     }
 
-    fn confirm_all_generate_directives_obeyed(&self) -> Result<(), ConvertErrorFromCpp> {
+    fn confirm_all_generate_directives_obeyed(
+        &self,
+        skipped: &mut SkippedItems,
+    ) -> Result<(), ConvertErrorFromCpp> {
         let api_names: HashSet<_> = self
             .apis
             .iter()
@@ -412,9 +902,10 @@ impl<'a> ParseBindgen<'a> {
             .collect();
         for generate_directive in self.config.must_generate_list() {
             if !api_names.contains(&generate_directive) {
-                return Err(ConvertErrorFromCpp::DidNotGenerateAnything(
-                    generate_directive,
-                ));
+                skipped.skip(
+                    generate_directive.clone(),
+                    ConvertErrorFromCpp::DidNotGenerateAnything(generate_directive),
+                )?;
             }
         }
         Ok(())
@@ -6,9 +6,13 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::conversion::api::{ApiName, NullPhase, Provenance};
+use crate::conversion::analysis::fun::function_wrapper::{CppFunctionBody, CppFunctionKind};
+use crate::conversion::api::{
+    ApiName, ArithmeticOperator, DeletedOrDefaulted, NullPhase, Provenance, References,
+    TraitSynthesis, Virtualness,
+};
 use crate::conversion::apivec::ApiVec;
-use crate::conversion::doc_attr::get_doc_attrs;
+use crate::conversion::doc_attr::{get_doc_attrs, has_must_use_attr};
 use crate::conversion::error_reporter::report_any_error;
 use crate::conversion::{
     api::{FuncToConvert, UnanalyzedApi},
@@ -18,10 +22,17 @@ use crate::conversion::{
 use crate::minisyn::{minisynize_punctuated, minisynize_vec};
 use crate::{
     conversion::ConvertErrorFromCpp,
-    types::{Namespace, QualifiedName},
+    types::{make_ident, Namespace, QualifiedName},
 };
+use indexmap::map::IndexMap;
 use std::collections::HashMap;
-use syn::{Block, Expr, ExprCall, ForeignItem, Ident, ImplItem, ItemImpl, Stmt, Type};
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{
+    parse_quote, Attribute, Block, Expr, ExprCall, ExprLit, FnArg, ForeignItem, ForeignItemStatic,
+    Ident, ImplItem, ItemImpl, Lit, Meta, MetaNameValue, Pat, ReturnType, Signature,
+    StaticMutability, Stmt, Type,
+};
 
 use super::bindgen_semantic_attributes::BindgenSemanticAttributes;
 
@@ -40,6 +51,8 @@ pub(crate) struct ParseForeignMod {
     // may actually be methods (static or otherwise). Mapping from
     // function name to type name.
     method_receivers: HashMap<Ident, QualifiedName>,
+    // The files in which each function was declared.
+    source_files: HashMap<Ident, String>,
     ignored_apis: ApiVec<NullPhase>,
 }
 
@@ -49,6 +62,7 @@ impl ParseForeignMod {
             ns,
             funcs_to_convert: Vec::new(),
             method_receivers: HashMap::new(),
+            source_files: HashMap::new(),
             ignored_apis: ApiVec::new(),
         }
     }
@@ -67,9 +81,185 @@ impl ParseForeignMod {
 
     fn parse_foreign_item(&mut self, i: ForeignItem) -> Result<(), ConvertErrorWithContext> {
         match i {
-            ForeignItem::Fn(item) => {
+            ForeignItem::Fn(mut item) => {
                 let annotations = BindgenSemanticAttributes::new(&item.attrs);
                 let doc_attrs = get_doc_attrs(&item.attrs);
+                let must_use = has_must_use_attr(&item.attrs);
+                if let Some(file) = annotations.get_source_file() {
+                    self.source_files.insert(item.sig.ident.clone(), file);
+                }
+                let mut references = annotations.get_reference_parameters_and_return();
+                // A ref-qualified method treats 'this' as the relevant sort of
+                // reference.
+                let link_name = get_link_name(&item.attrs);
+                let this_class = get_this_class(&item.sig);
+                let err = |err| {
+                    let ident = item.sig.ident.clone().into();
+                    let ctx = match this_class.clone() {
+                        Some(self_ty) => ErrorContext::new_for_method(self_ty, ident),
+                        None => ErrorContext::new_for_item(ident),
+                    };
+                    ConvertErrorWithContext(err, Some(ctx))
+                };
+                let qualifiers = match this_class {
+                    Some(_) => get_method_qualifiers(link_name.as_deref(), &annotations)
+                        .ok_or_else(|| err(ConvertErrorFromCpp::UnknownMethodQualifiers))?,
+                    None => MethodQualifiers::default(),
+                };
+                // We recognize operators by their mangled names, so we'd
+                // otherwise treat one we can't make sense of as an ordinary
+                // function which we can't call.
+                if item.sig.ident.to_string().contains("operator")
+                    && link_name.as_deref().is_some_and(|link_name| {
+                        get_itanium_nested_name(link_name).is_some()
+                            && get_itanium_operator_name(link_name).is_none()
+                    })
+                {
+                    return Err(err(ConvertErrorFromCpp::UnrecognizedOperator));
+                }
+                match qualifiers.ref_qualifier {
+                    Some(RefQualifier::LValue) => {
+                        references.ref_params.insert(make_ident("this"));
+                    }
+                    Some(RefQualifier::RValue) => {
+                        references.rvalue_ref_params.insert(make_ident("this"));
+                    }
+                    None => {}
+                }
+                references.restrict_this = qualifiers.restrict;
+                if references.const_template_arg_params.is_empty() {
+                    // Older bindgen doesn't annotate `std::span<const T>`
+                    // and friends, so look at the header instead.
+                    let indices =
+                        annotations.get_const_template_params(&["span", "reference_wrapper"]);
+                    references.const_template_arg_params = item
+                        .sig
+                        .inputs
+                        .iter()
+                        .filter_map(|arg| match arg {
+                            FnArg::Typed(pt) => match pt.pat.as_ref() {
+                                Pat::Ident(pp) if pp.ident != "this" => Some(pp.ident.clone()),
+                                _ => None,
+                            },
+                            _ => None,
+                        })
+                        .enumerate()
+                        .filter(|(idx, _)| indices.contains(idx))
+                        .map(|(_, ident)| ident.into())
+                        .collect();
+                }
+                let conversion_target = match &item.sig.output {
+                    ReturnType::Type(_, ty)
+                        if link_name.as_deref().is_some_and(is_conversion_operator) =>
+                    {
+                        get_conversion_target(ty)
+                    }
+                    _ => None,
+                };
+                // `operator->` returns a pointer to some class, which we
+                // instead return by reference. If it's const, that's
+                // just what `Deref` needs.
+                let arrow_target = match &mut item.sig.output {
+                    ReturnType::Type(_, ty)
+                        if link_name.as_deref().is_some_and(is_arrow_operator) =>
+                    {
+                        match ty.as_mut() {
+                            Type::Ptr(ptr) => {
+                                let target = get_conversion_target(&ptr.elem);
+                                if target.is_some() && qualifiers.is_const {
+                                    ptr.const_token = Some(Default::default());
+                                    ptr.mutability = None;
+                                }
+                                target
+                            }
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                };
+                if arrow_target.is_some() {
+                    references.ref_return = true;
+                }
+                let is_equality_operator = link_name.as_deref().is_some_and(is_equality_operator)
+                    && prepare_equality_operator(&mut item.sig, &mut references);
+                let arithmetic_operator = link_name
+                    .as_deref()
+                    .and_then(get_arithmetic_operator)
+                    .and_then(|op| {
+                        prepare_arithmetic_operator(&mut item.sig, &mut references)
+                            .map(|output| (op, output))
+                    });
+                let is_left_shift_operator =
+                    link_name.as_deref().is_some_and(is_left_shift_operator)
+                        && prepare_left_shift_operator(&mut item.sig, &mut references);
+                // Finding out whether something's `explicit` may mean reading
+                // the header, so we only do so where it matters: for
+                // conversion operators, and for constructors taking a
+                // single argument besides `this`.
+                let is_explicit = (conversion_target.is_some()
+                    || (item.sig.inputs.len() == 2
+                        && link_name.as_deref().map_or(true, is_constructor)))
+                    && annotations.is_explicit();
+                // We can't name `operator()`, `operator->`, `operator==`,
+                // `operator+` and friends, `operator<<` or `operator B()` in
+                // Rust, nor ask cxx to bind to them, so we give them names and
+                // generate C++ wrappers which invoke them. A const and
+                // non-explicit conversion operator may become a `From`
+                // implementation, too, a const `operator->` becomes `Deref`,
+                // `operator==` becomes `PartialEq` and `operator+` becomes
+                // `Add`.
+                let (original_name, synthetic_cpp, add_to_trait) =
+                    if link_name.as_deref().is_some_and(is_call_operator) {
+                        (
+                            Some("call".to_string()),
+                            Some((CppFunctionBody::CallOperator, CppFunctionKind::Method)),
+                            None,
+                        )
+                    } else if is_equality_operator {
+                        (
+                            Some("eq".to_string()),
+                            Some((CppFunctionBody::EqualityOperator, CppFunctionKind::Method)),
+                            Some(TraitSynthesis::PartialEq),
+                        )
+                    } else if let Some((op, output)) = arithmetic_operator {
+                        (
+                            Some(op.method_name().to_string()),
+                            Some((
+                                CppFunctionBody::ArithmeticOperator(op),
+                                CppFunctionKind::Method,
+                            )),
+                            Some(TraitSynthesis::Arithmetic { op, output }),
+                        )
+                    } else if is_left_shift_operator {
+                        (
+                            Some("shl".to_string()),
+                            Some((
+                                CppFunctionBody::LeftShiftOperator,
+                                CppFunctionKind::Function,
+                            )),
+                            None,
+                        )
+                    } else if let Some(target) = arrow_target {
+                        (
+                            Some("arrow".to_string()),
+                            Some((CppFunctionBody::ArrowOperator, CppFunctionKind::Method)),
+                            qualifiers
+                                .is_const
+                                .then_some(TraitSynthesis::Deref { target }),
+                        )
+                    } else if let Some(to_type) = conversion_target {
+                        (
+                            Some(format!("to_{}", to_type.get_final_item())),
+                            Some((
+                                CppFunctionBody::ConversionOperator(to_type.clone()),
+                                CppFunctionKind::Method,
+                            )),
+                            (qualifiers.is_const && !is_explicit)
+                                .then_some(TraitSynthesis::Conversion { to_type }),
+                        )
+                    } else {
+                        (annotations.get_original_name(), None, None)
+                    };
                 self.funcs_to_convert.push(FuncToConvert {
                     provenance: Provenance::Bindgen,
                     self_ty: None,
@@ -83,20 +273,33 @@ impl ParseForeignMod {
                     special_member: annotations.special_member_kind(),
                     unused_template_param: annotations
                         .has_attr("incomprehensible_param_in_arg_or_return"),
-                    references: annotations.get_reference_parameters_and_return(),
-                    original_name: annotations.get_original_name(),
+                    references,
+                    original_name,
                     synthesized_this_type: None,
-                    add_to_trait: None,
+                    add_to_trait,
                     is_deleted: annotations.get_deleted_or_defaulted(),
-                    synthetic_cpp: None,
+                    is_explicit,
+                    must_use,
+                    synthetic_cpp,
                     variadic: item.sig.variadic.is_some(),
                 });
                 Ok(())
             }
-            ForeignItem::Static(item) => Err(ConvertErrorWithContext(
-                ConvertErrorFromCpp::StaticData(item.ident.to_string()),
-                Some(ErrorContext::new_for_item(item.ident.into())),
-            )),
+            ForeignItem::Static(item) => {
+                let static_member = get_link_name(&item.attrs)
+                    .and_then(|link_name| get_static_member(&link_name, &self.ns))
+                    .filter(|(class, member)| item.ident == format!("{class}_{member}"));
+                match static_member {
+                    Some((class, member)) => {
+                        self.add_static_member_accessors(item, class, member);
+                        Ok(())
+                    }
+                    None => Err(ConvertErrorWithContext(
+                        ConvertErrorFromCpp::StaticData(item.ident.to_string()),
+                        Some(ErrorContext::new_for_item(item.ident.into())),
+                    )),
+                }
+            }
             _ => Err(ConvertErrorWithContext(
                 ConvertErrorFromCpp::UnexpectedForeignItem,
                 None,
@@ -104,6 +307,61 @@ impl ParseForeignMod {
         }
     }
 
+    /// bindgen gives us a static data member of a class, `A::counter`,
+    /// as a `static` called `A_counter`, which cxx can't refer to. Instead
+    /// we make static methods to get it and, unless it's const, to set
+    /// it, each implemented in C++.
+    fn add_static_member_accessors(
+        &mut self,
+        item: ForeignItemStatic,
+        class: String,
+        member: String,
+    ) {
+        let self_ty = QualifiedName::new(&self.ns, make_ident(class).into());
+        let cpp_vis = BindgenSemanticAttributes::new(&item.attrs).get_cpp_visibility();
+        let ty = &item.ty;
+        let mut accessors: Vec<(_, Punctuated<FnArg, Comma>, ReturnType, _)> = vec![(
+            "get",
+            Punctuated::new(),
+            parse_quote! { -> #ty },
+            CppFunctionBody::StaticMemberGet(self_ty.clone(), member.clone()),
+        )];
+        if matches!(item.mutability, StaticMutability::Mut(_)) {
+            accessors.push((
+                "set",
+                parse_quote! { value: #ty },
+                ReturnType::Default,
+                CppFunctionBody::StaticMemberSet(self_ty.clone(), member.clone()),
+            ));
+        }
+        for (verb, inputs, output, body) in accessors {
+            let ident = make_ident(format!("{}_autocxx_{verb}", item.ident));
+            self.method_receivers.insert(ident.clone(), self_ty.clone());
+            self.funcs_to_convert.push(FuncToConvert {
+                provenance: Provenance::SynthesizedOther,
+                self_ty: None,
+                ident: ident.into(),
+                doc_attrs: minisynize_vec(get_doc_attrs(&item.attrs)),
+                inputs: minisynize_punctuated(inputs),
+                output: output.into(),
+                vis: item.vis.clone().into(),
+                virtualness: Virtualness::None,
+                cpp_vis,
+                special_member: None,
+                unused_template_param: false,
+                references: References::default(),
+                original_name: Some(format!("{verb}_{member}")),
+                synthesized_this_type: None,
+                add_to_trait: None,
+                is_deleted: DeletedOrDefaulted::Neither,
+                is_explicit: false,
+                must_use: false,
+                synthetic_cpp: Some((body, CppFunctionKind::Function)),
+                variadic: false,
+            });
+        }
+    }
+
     /// Record information from impl blocks encountered in bindgen
     /// output.
     pub(crate) fn convert_impl_items(&mut self, imp: ItemImpl) {
@@ -127,9 +385,19 @@ impl ParseForeignMod {
 
     /// Indicate that all foreign mods and all impl blocks have been
     /// fed into us, and we should process that information to generate
-    /// the resulting APIs.
-    pub(crate) fn finished(mut self, apis: &mut ApiVec<NullPhase>) {
+    /// the resulting APIs. The files in which functions were declared
+    /// are recorded in `source_files`.
+    pub(crate) fn finished(
+        mut self,
+        apis: &mut ApiVec<NullPhase>,
+        source_files: &mut IndexMap<QualifiedName, String>,
+    ) {
         apis.append(&mut self.ignored_apis);
+        source_files.extend(
+            self.source_files
+                .drain()
+                .map(|(id, file)| (QualifiedName::new(&self.ns, id.into()), file)),
+        );
         while !self.funcs_to_convert.is_empty() {
             let mut fun = self.funcs_to_convert.remove(0);
             fun.self_ty = self.method_receivers.get(&fun.ident).cloned();
@@ -146,6 +414,475 @@ impl ParseForeignMod {
     }
 }
 
+enum RefQualifier {
+    LValue,
+    RValue,
+}
+
+#[derive(Default)]
+struct MethodQualifiers {
+    is_const: bool,
+    restrict: bool,
+    ref_qualifier: Option<RefQualifier>,
+}
+
+/// The C++ type to which a conversion operator converts, if it's one
+/// which we can name in C++, i.e. not a built-in type.
+fn get_conversion_target(ty: &Type) -> Option<QualifiedName> {
+    match ty {
+        Type::Path(typ) if typ.path.segments.first()?.ident == "root" => {
+            Some(QualifiedName::from_type_path(typ))
+        }
+        _ => None,
+    }
+}
+
+/// The mangled name of a function, if bindgen tells us.
+fn get_link_name(attrs: &[Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr| match &attr.meta {
+        Meta::NameValue(MetaNameValue {
+            path,
+            value:
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(link_name),
+                    ..
+                }),
+            ..
+        }) if path.is_ident("link_name") => Some(link_name.value()),
+        _ => None,
+    })
+}
+
+/// The nested name within an Itanium-mangled name, e.g. `K1A1fE`
+/// for `_ZNK1A1fEv`.
+fn get_itanium_nested_name(link_name: &str) -> Option<&str> {
+    let mangled = link_name.trim_start_matches('\u{1}');
+    mangled
+        .strip_prefix("__ZN")
+        .or_else(|| mangled.strip_prefix("_ZN"))
+}
+
+/// The identifiers making up an Itanium-mangled nested name which
+/// consists of nothing else, e.g. `["ns", "A", "counter"]` for
+/// `_ZN2ns1A7counterE`.
+fn get_itanium_nested_identifiers(link_name: &str) -> Option<Vec<&str>> {
+    let mut remaining = get_itanium_nested_name(link_name)?;
+    let mut identifiers = Vec::new();
+    while remaining != "E" {
+        let digits = remaining.chars().take_while(char::is_ascii_digit).count();
+        if digits == 0 {
+            return None;
+        }
+        let len = remaining[..digits].parse::<usize>().ok()?;
+        identifiers.push(remaining.get(digits..digits + len)?);
+        remaining = remaining.get(digits + len..)?;
+    }
+    Some(identifiers)
+}
+
+/// The identifiers making up an MSVC-mangled name of a static data
+/// member, outermost first, e.g. `["ns", "A", "counter"]` for
+/// `?counter@A@ns@@2HA`, where `2` says it's a public static member
+/// (`0` and `1` being private and protected). We don't cope with
+/// template arguments, which start with `?$`.
+fn get_msvc_static_member_identifiers(link_name: &str) -> Option<Vec<&str>> {
+    let mangled = link_name.trim_start_matches('\u{1}').strip_prefix('?')?;
+    let (scopes, encoding) = mangled.split_once("@@")?;
+    if scopes.contains('?') || !matches!(encoding.chars().next()?, '0'..='2') {
+        return None;
+    }
+    Some(scopes.rsplit('@').collect())
+}
+
+/// If this is the mangled name of a static data member of a class
+/// within the given namespace, the names of the class (as bindgen names
+/// it, so `A_B` for a nested class `A::B`) and of the member.
+fn get_static_member(link_name: &str, ns: &Namespace) -> Option<(String, String)> {
+    let identifiers = get_itanium_nested_identifiers(link_name)
+        .or_else(|| get_msvc_static_member_identifiers(link_name))?;
+    let (member, scopes) = identifiers.split_last()?;
+    if scopes.len() <= ns.depth() {
+        return None;
+    }
+    let (ns_scopes, class_scopes) = scopes.split_at(ns.depth());
+    if !ns.iter().eq(ns_scopes.iter()) {
+        return None;
+    }
+    Some((class_scopes.join("_"), member.to_string()))
+}
+
+/// The class of which this is a method, if it has a `this` parameter.
+fn get_this_class(sig: &Signature) -> Option<crate::minisyn::Ident> {
+    sig.inputs.iter().find_map(|arg| match arg {
+        FnArg::Typed(pt) => match (pt.pat.as_ref(), pt.ty.as_ref()) {
+            (Pat::Ident(pp), Type::Ptr(ptr)) if pp.ident == "this" => match ptr.elem.as_ref() {
+                Type::Path(typ) => Some(typ.path.segments.last()?.ident.clone().into()),
+                _ => None,
+            },
+            _ => None,
+        },
+        FnArg::Receiver(_) => None,
+    })
+}
+
+/// bindgen doesn't tell us whether a method is ref-qualified
+/// (`void f() &` or `void f() &&`) or `restrict`-qualified. A mangled
+/// link name does, if it's an Itanium one, or an MSVC one for a method
+/// of a class which isn't a template. Otherwise, we look at the method's
+/// declaration in the header. Returns `None` if we can't find out, in
+/// which case we mustn't guess, because calling a method with the wrong
+/// sort of `this` won't compile.
+fn get_method_qualifiers(
+    link_name: Option<&str>,
+    annotations: &BindgenSemanticAttributes,
+) -> Option<MethodQualifiers> {
+    link_name
+        .and_then(|link_name| {
+            get_itanium_method_qualifiers(link_name)
+                .or_else(|| get_msvc_method_qualifiers(link_name))
+        })
+        .or_else(|| {
+            let declared = annotations.get_declared_method_qualifiers()?;
+            let has = |qualifier| declared.iter().any(|q| q == qualifier);
+            Some(MethodQualifiers {
+                is_const: has("const"),
+                restrict: has("__restrict") || has("__restrict__"),
+                ref_qualifier: if has("&&") {
+                    Some(RefQualifier::RValue)
+                } else if has("&") {
+                    Some(RefQualifier::LValue)
+                } else {
+                    None
+                },
+            })
+        })
+}
+
+/// In an Itanium-mangled link name, the CVR-qualifiers come first in the
+/// nested name, followed by any ref-qualifier, e.g. `_ZNKR1A1fEv`.
+fn get_itanium_method_qualifiers(link_name: &str) -> Option<MethodQualifiers> {
+    let nested_name = get_itanium_nested_name(link_name)?;
+    let cvr_qualifiers: String = nested_name
+        .chars()
+        .take_while(|c| matches!(c, 'r' | 'V' | 'K'))
+        .collect();
+    let ref_qualifier = match nested_name[cvr_qualifiers.len()..].chars().next() {
+        Some('R') => Some(RefQualifier::LValue),
+        Some('O') => Some(RefQualifier::RValue),
+        _ => None,
+    };
+    Some(MethodQualifiers {
+        is_const: cvr_qualifiers.contains('K'),
+        restrict: cvr_qualifiers.contains('r'),
+        ref_qualifier,
+    })
+}
+
+/// In an MSVC-mangled link name, the (reversed) scopes of the name end
+/// with `@@`, followed by a letter saying whether it's a static, virtual
+/// or ordinary method, then for the latter two, any of `E` (64-bit), `F`
+/// (unaligned), `I` (restrict), `G` (`&`) or `H` (`&&`), and finally its
+/// CV-qualifiers, e.g. `?f@A@@QEGBAXXZ` for `void A::f() const &`. We
+/// can't find the end of the scopes if they include template arguments,
+/// which start with `?$`.
+fn get_msvc_method_qualifiers(link_name: &str) -> Option<MethodQualifiers> {
+    let mangled = link_name.trim_start_matches('\u{1}').strip_prefix('?')?;
+    // Constructors and destructors can't be qualified.
+    if mangled.starts_with("?0") || mangled.starts_with("?1") {
+        return Some(MethodQualifiers::default());
+    }
+    let (scopes, encoding) = mangled.split_once("@@")?;
+    if scopes.contains("?$") {
+        return None;
+    }
+    match encoding.chars().next()? {
+        // Methods with a `this`.
+        'A' | 'B' | 'E' | 'F' | 'I' | 'J' | 'M' | 'N' | 'Q' | 'R' | 'U' | 'V' => {}
+        // Static methods and free functions.
+        'C' | 'D' | 'K' | 'L' | 'S' | 'T' | 'Y' | 'Z' => return Some(MethodQualifiers::default()),
+        _ => return None,
+    }
+    let modifiers: String = encoding[1..]
+        .chars()
+        .take_while(|c| matches!(c, 'E' | 'F' | 'G' | 'H' | 'I'))
+        .collect();
+    let ref_qualifier = if modifiers.contains('H') {
+        Some(RefQualifier::RValue)
+    } else if modifiers.contains('G') {
+        Some(RefQualifier::LValue)
+    } else {
+        None
+    };
+    let cv_qualifiers = encoding[1 + modifiers.len()..].chars().next()?;
+    Some(MethodQualifiers {
+        is_const: matches!(cv_qualifiers, 'B' | 'D'),
+        restrict: modifiers.contains('I'),
+        ref_qualifier,
+    })
+}
+
+/// The remainder of an Itanium-mangled method name following the
+/// (length-prefixed) names of its enclosing namespaces and class, and
+/// any template arguments of the class. For an operator, this starts with
+/// its encoding, e.g. `clEv` for `_ZN1AclEv`, or `eqERKS0_` for
+/// `_ZNK3FooIiEeqERKS0_`. Returns `None` if we can't make sense of the
+/// template arguments.
+fn get_itanium_operator_name(link_name: &str) -> Option<&str> {
+    let nested_name = get_itanium_nested_name(link_name)?;
+    let mut remaining = nested_name.trim_start_matches(['r', 'V', 'K', 'R', 'O']);
+    remaining = remaining.strip_prefix("St").unwrap_or(remaining);
+    loop {
+        if remaining.starts_with('I') {
+            remaining = skip_itanium_template_args(remaining)?;
+            continue;
+        }
+        let digits = remaining.chars().take_while(char::is_ascii_digit).count();
+        if digits == 0 {
+            return Some(remaining);
+        }
+        let len = remaining[..digits].parse::<usize>().ok()?;
+        remaining = remaining.get(digits + len..)?;
+    }
+}
+
+/// The remainder of an Itanium-mangled name following the template
+/// arguments with which it starts, `I...E`. We understand types and
+/// literals, but not expressions or anything else unusual, for which
+/// we return `None`.
+fn skip_itanium_template_args(mangled: &str) -> Option<&str> {
+    let mut depth = 0usize;
+    let mut remaining = mangled;
+    loop {
+        // A length-prefixed name.
+        let digits = remaining.chars().take_while(char::is_ascii_digit).count();
+        if digits > 0 {
+            let len = remaining[..digits].parse::<usize>().ok()?;
+            remaining = remaining.get(digits + len..)?;
+            continue;
+        }
+        let c = remaining.chars().next()?;
+        remaining = &remaining[1..];
+        match c {
+            // Things which end with `E`.
+            'I' | 'J' | 'N' | 'F' => depth += 1,
+            'E' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(remaining);
+                }
+            }
+            // A literal, e.g. `Li3E`, whose value we skip.
+            'L' if !remaining.starts_with('_') => {
+                remaining = &remaining[remaining.find('E')? + 1..];
+            }
+            // Substitutions and template parameters, e.g. `S_`, `S0_`
+            // or `T_`, and well-known names such as `St` or `Sa`.
+            'S' | 'T' => {
+                let end = match remaining.chars().next()? {
+                    c if c.is_ascii_lowercase() => 1,
+                    _ => remaining.find('_')? + 1,
+                };
+                remaining = &remaining[end..];
+            }
+            // Arrays, e.g. `A3_i`.
+            'A' => remaining = &remaining[remaining.find('_')? + 1..],
+            // Two-letter builtin types and pack expansions, e.g. `Dn`
+            // or `Dp`.
+            'D' => remaining = remaining.get(1..)?,
+            // Qualifiers, pointers, references and one-letter builtin
+            // types.
+            'P' | 'R' | 'O' | 'K' | 'V' | 'r' | 'M' | 'v' | 'b' | 'c' | 'a' | 'h' | 's' | 't'
+            | 'i' | 'j' | 'l' | 'm' | 'x' | 'y' | 'n' | 'o' | 'f' | 'd' | 'e' | 'g' | 'z' => {}
+            _ => return None,
+        }
+    }
+}
+
+/// Whether this is a function call operator, `operator()`. Again,
+/// we work this out from the mangled name, where the operator is `cl`,
+/// or `??R` for MSVC.
+fn is_call_operator(link_name: &str) -> bool {
+    link_name.trim_start_matches('\u{1}').starts_with("??R")
+        || get_itanium_operator_name(link_name).is_some_and(|op| op.starts_with("clE"))
+}
+
+/// Whether this is a member access operator, `operator->`, which is
+/// `pt` when mangled, or `??C` for MSVC.
+fn is_arrow_operator(link_name: &str) -> bool {
+    link_name.trim_start_matches('\u{1}').starts_with("??C")
+        || get_itanium_operator_name(link_name).is_some_and(|op| op.starts_with("ptE"))
+}
+
+/// Like [`get_itanium_operator_name`], but also copes with operators
+/// which are free functions, whose mangled names are unnested, e.g.
+/// `_ZeqRK6MyTypei`.
+fn get_itanium_binary_operator_name(link_name: &str) -> Option<&str> {
+    let mangled = link_name.trim_start_matches('\u{1}');
+    get_itanium_operator_name(link_name).or_else(|| {
+        mangled
+            .strip_prefix("__Z")
+            .or_else(|| mangled.strip_prefix("_Z"))
+    })
+}
+
+/// Whether this is an equality operator, `operator==`, which is `eq`
+/// when mangled, or `??8` for MSVC. Unlike the operators above, this
+/// may be a free function.
+fn is_equality_operator(link_name: &str) -> bool {
+    link_name.trim_start_matches('\u{1}').starts_with("??8")
+        || get_itanium_binary_operator_name(link_name).is_some_and(|op| op.starts_with("eq"))
+}
+
+/// The binary arithmetic operator, if this is one. When mangled, these
+/// are `pl`, `mi`, `ml`, `dv` and `rm`, or `??H`, `??G`, `??D`, `??K`
+/// and `??L` for MSVC. The unary forms of `-` and `*` share these
+/// names, so we must also check the number of parameters.
+fn get_arithmetic_operator(link_name: &str) -> Option<ArithmeticOperator> {
+    const OPERATORS: [(&str, &str, ArithmeticOperator); 5] = [
+        ("pl", "??H", ArithmeticOperator::Add),
+        ("mi", "??G", ArithmeticOperator::Sub),
+        ("ml", "??D", ArithmeticOperator::Mul),
+        ("dv", "??K", ArithmeticOperator::Div),
+        ("rm", "??L", ArithmeticOperator::Rem),
+    ];
+    let mangled = link_name.trim_start_matches('\u{1}');
+    let itanium = get_itanium_binary_operator_name(link_name);
+    OPERATORS
+        .into_iter()
+        .find(|(itanium_op, msvc_op, _)| {
+            mangled.starts_with(msvc_op) || itanium.is_some_and(|op| op.starts_with(itanium_op))
+        })
+        .map(|(_, _, op)| op)
+}
+
+/// An `operator==` we can express as `PartialEq` returns `bool`, and
+/// takes its right-hand side by reference, which is how `PartialEq`
+/// wants it even if C++ takes it by value. Returns whether this is
+/// such an operator, having prepared it as per
+/// [`prepare_binary_operator`].
+fn prepare_equality_operator(sig: &mut Signature, references: &mut References) -> bool {
+    let returns_bool = match &sig.output {
+        ReturnType::Type(_, ty) => {
+            matches!(ty.as_ref(), Type::Path(typ) if typ.path.is_ident("bool"))
+        }
+        ReturnType::Default => false,
+    };
+    returns_bool && prepare_binary_operator(sig, references, true)
+}
+
+/// An arithmetic operator we can express as one of the `std::ops`
+/// traits returns some named type by value, which becomes the trait's
+/// `Output`. Its right-hand side may be taken by value, e.g. a scalar,
+/// or by reference. If this is such an operator, prepares it as per
+/// [`prepare_binary_operator`] and returns its output type.
+fn prepare_arithmetic_operator(
+    sig: &mut Signature,
+    references: &mut References,
+) -> Option<QualifiedName> {
+    let output = match &sig.output {
+        ReturnType::Type(_, ty) => match ty.as_ref() {
+            Type::Path(typ) => QualifiedName::from_type_path(typ),
+            _ => return None,
+        },
+        ReturnType::Default => return None,
+    };
+    prepare_binary_operator(sig, references, false).then_some(output)
+}
+
+/// A binary operator we can express as a trait takes its left-hand
+/// side as a const reference, either as the receiver of a const method
+/// or as the first parameter of a free function, and its right-hand
+/// side either by value or by const reference. If this is such an
+/// operator, we turn it into something which looks like a const
+/// method, by calling its left-hand side `this`, and, if
+/// `rhs_by_reference`, make it take its right-hand side by reference.
+/// Returns whether we did so.
+fn prepare_binary_operator(
+    sig: &mut Signature,
+    references: &mut References,
+    rhs_by_reference: bool,
+) -> bool {
+    if sig.inputs.len() != 2 {
+        return false;
+    }
+    let mut params = sig.inputs.iter_mut().filter_map(|arg| match arg {
+        FnArg::Typed(pt) => match pt.pat.as_mut() {
+            Pat::Ident(pp) => Some((&mut pp.ident, pt.ty.as_mut())),
+            _ => None,
+        },
+        FnArg::Receiver(_) => None,
+    });
+    let (Some((lhs, lhs_ty)), Some((rhs, rhs_ty))) = (params.next(), params.next()) else {
+        return false;
+    };
+    let is_const_ref = |name: &Ident, ty: &Type| {
+        matches!(ty, Type::Ptr(ptr) if ptr.const_token.is_some())
+            && (name == "this" || references.ref_params.contains(&name.clone().into()))
+    };
+    let rhs_is_ptr = matches!(rhs_ty, Type::Ptr(_));
+    if !is_const_ref(lhs, lhs_ty) || (rhs_is_ptr && !is_const_ref(rhs, rhs_ty)) {
+        return false;
+    }
+    if *lhs != "this" {
+        references.ref_params.shift_remove(&lhs.clone().into());
+        *lhs = make_ident("this").0;
+    }
+    if !rhs_is_ptr && rhs_by_reference {
+        *rhs_ty = parse_quote! { *const #rhs_ty };
+        references.ref_params.insert(rhs.clone().into());
+    }
+    true
+}
+
+/// Whether this is a left shift operator, `operator<<`, which is `ls`
+/// when mangled, or `??6` for MSVC. This may be a member or a free
+/// function.
+fn is_left_shift_operator(link_name: &str) -> bool {
+    link_name.trim_start_matches('\u{1}').starts_with("??6")
+        || get_itanium_binary_operator_name(link_name).is_some_and(|op| op.starts_with("ls"))
+}
+
+/// We bind only a free `operator<<`, which is typically a stream
+/// insertion operator, and often a friend of the type it writes. That
+/// usually returns a reference to the stream, which would have an
+/// ambiguous lifetime in Rust, and isn't useful for chaining calls
+/// there anyway, so we discard any returned reference. Returns whether
+/// this is such an operator.
+fn prepare_left_shift_operator(sig: &mut Signature, references: &mut References) -> bool {
+    let is_free_binary_function = sig.inputs.len() == 2
+        && sig.inputs.iter().all(|arg| match arg {
+            FnArg::Typed(pt) => !matches!(pt.pat.as_ref(), Pat::Ident(pp) if pp.ident == "this"),
+            FnArg::Receiver(_) => false,
+        });
+    if !is_free_binary_function {
+        return false;
+    }
+    if let ReturnType::Type(_, ty) = &sig.output {
+        if matches!(ty.as_ref(), Type::Ptr(_)) {
+            sig.output = ReturnType::Default;
+            references.ref_return = false;
+        }
+    }
+    true
+}
+
+/// Whether this is a user-defined conversion operator, `operator B()`,
+/// where the operator is `cv` followed by the target type, or `??B`
+/// for MSVC.
+fn is_conversion_operator(link_name: &str) -> bool {
+    link_name.trim_start_matches('\u{1}').starts_with("??B")
+        || get_itanium_operator_name(link_name).is_some_and(|op| op.starts_with("cv"))
+}
+
+/// Whether this is a constructor, which is encoded as `C1`, `C2` or
+/// `C3` (or `CI` for an inheriting constructor) in place of a name, or
+/// `??0` for MSVC.
+fn is_constructor(link_name: &str) -> bool {
+    link_name.trim_start_matches('\u{1}').starts_with("??0")
+        || get_itanium_operator_name(link_name)
+            .is_some_and(|op| ["C1", "C2", "C3", "CI"].iter().any(|c| op.starts_with(c)))
+}
+
 /// bindgen sometimes generates an impl fn called a which calls
 /// a function called a1(), if it's dealing with conflicting names.
 /// We actually care about the name a1, so we have to parse the
@@ -162,7 +899,13 @@ fn get_called_function(block: &Block) -> Option<&Ident> {
 
 #[cfg(test)]
 mod test {
-    use super::get_called_function;
+    use super::{
+        get_arithmetic_operator, get_called_function, get_itanium_method_qualifiers,
+        get_itanium_operator_name, get_msvc_method_qualifiers, get_static_member, is_call_operator,
+        is_conversion_operator, is_equality_operator, is_left_shift_operator, RefQualifier,
+    };
+    use crate::conversion::api::ArithmeticOperator;
+    use crate::types::Namespace;
     use syn::parse_quote;
     use syn::Block;
 
@@ -175,4 +918,151 @@ mod test {
         };
         assert_eq!(get_called_function(&b).unwrap().to_string(), "call_foo");
     }
+
+    #[test]
+    fn test_get_method_qualifiers() {
+        let itanium = get_itanium_method_qualifiers("_ZNKR1A1fEv").unwrap();
+        assert!(itanium.is_const);
+        assert!(matches!(itanium.ref_qualifier, Some(RefQualifier::LValue)));
+        let msvc = get_msvc_method_qualifiers("?f@A@@QEGBAXXZ").unwrap();
+        assert!(msvc.is_const);
+        assert!(matches!(msvc.ref_qualifier, Some(RefQualifier::LValue)));
+        let msvc = get_msvc_method_qualifiers("?f@A@ns@@QEHAAXXZ").unwrap();
+        assert!(!msvc.is_const);
+        assert!(matches!(msvc.ref_qualifier, Some(RefQualifier::RValue)));
+        let msvc = get_msvc_method_qualifiers("?f@A@@QEAAXXZ").unwrap();
+        assert!(msvc.ref_qualifier.is_none());
+        assert!(!msvc.restrict);
+        assert!(
+            get_itanium_method_qualifiers("_ZNr1A1fEv")
+                .unwrap()
+                .restrict
+        );
+        assert!(
+            get_msvc_method_qualifiers("?f@A@@QEIAAXXZ")
+                .unwrap()
+                .restrict
+        );
+        assert!(get_msvc_method_qualifiers("??0A@@QEAA@XZ").is_some());
+        // We can't tell where the scopes end if there are template
+        // arguments, nor read an Itanium name as an MSVC one.
+        assert!(get_msvc_method_qualifiers("?f@?$Foo@VBar@@@@QEGAAXXZ").is_none());
+        assert!(get_msvc_method_qualifiers("_ZNKR1A1fEv").is_none());
+    }
+
+    #[test]
+    fn test_is_call_operator() {
+        assert!(is_call_operator("_ZNK7FunctorclEj"));
+        assert!(is_call_operator("\u{1}__ZN2ns7FunctorclEv"));
+        assert!(is_call_operator("_ZNKR7FunctorclEv"));
+        assert!(is_call_operator("??RFunctor@@QEBAII@Z"));
+        assert!(!is_call_operator("_ZN2cl1fEv"));
+        assert!(!is_call_operator("_ZNK7Functor5applyEj"));
+        assert!(!is_call_operator("_Z5applyRK7Functorj"));
+    }
+
+    #[test]
+    fn test_get_itanium_operator_name() {
+        assert_eq!(
+            get_itanium_operator_name("_ZNK3FooIiEeqERKS0_"),
+            Some("eqERKS0_")
+        );
+        assert_eq!(
+            get_itanium_operator_name("_ZN2ns3FooINS_3BarELi3EPKcEplERKS3_"),
+            Some("plERKS3_")
+        );
+        assert_eq!(
+            get_itanium_operator_name("_ZNKSt6vectorIiSaIiEEixEm"),
+            Some("ixEm")
+        );
+        assert!(is_equality_operator("_ZNK3FooIiEeqERKS0_"));
+        // Expressions in template arguments are beyond us.
+        assert_eq!(
+            get_itanium_operator_name("_ZN3FooIXplLi1ELi2EEEeqERKS0_"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_conversion_operator() {
+        assert!(is_conversion_operator("_ZNK1Acv1BEv"));
+        assert!(is_conversion_operator("_ZN2ns1AcvNS_1BEEv"));
+        assert!(is_conversion_operator("??BA@@QEBA?AVB@@XZ"));
+        assert!(!is_conversion_operator("_ZN2cv1fEv"));
+        assert!(!is_conversion_operator("_ZNK7FunctorclEj"));
+    }
+
+    #[test]
+    fn test_is_equality_operator() {
+        assert!(is_equality_operator("_ZNK6MyTypeeqEi"));
+        assert!(is_equality_operator("_ZeqRK6MyTypei"));
+        assert!(is_equality_operator("\u{1}__ZN2nseqERKNS_6MyTypeEi"));
+        assert!(is_equality_operator("??8MyType@@QEBA_NH@Z"));
+        assert!(!is_equality_operator("_ZN2eq1fEv"));
+        assert!(!is_equality_operator("_Z2eqii"));
+        assert!(!is_equality_operator("_ZNK6MyTypeneEi"));
+    }
+
+    #[test]
+    fn test_is_left_shift_operator() {
+        assert!(is_left_shift_operator("_ZlsR6LoggerRK6Widget"));
+        assert!(is_left_shift_operator("_ZN2nslsERNS_6LoggerERKNS_6WidgetE"));
+        assert!(is_left_shift_operator("_ZNK6LoggerlsEi"));
+        assert!(is_left_shift_operator(
+            "??6@YAAEAVLogger@@AEAV0@AEBVWidget@@@Z"
+        ));
+        assert!(!is_left_shift_operator("_ZN2ls1fEv"));
+        assert!(!is_left_shift_operator("_Z2lsii"));
+        assert!(!is_left_shift_operator("_ZrsR6LoggerRK6Widget"));
+    }
+
+    #[test]
+    fn test_get_arithmetic_operator() {
+        assert!(matches!(
+            get_arithmetic_operator("_ZNK6MatrixplEd"),
+            Some(ArithmeticOperator::Add)
+        ));
+        assert!(matches!(
+            get_arithmetic_operator("_ZmiRK6MatrixS1_"),
+            Some(ArithmeticOperator::Sub)
+        ));
+        assert!(matches!(
+            get_arithmetic_operator("\u{1}__ZN2nsmlERKNS_6MatrixEd"),
+            Some(ArithmeticOperator::Mul)
+        ));
+        assert!(matches!(
+            get_arithmetic_operator("??KMatrix@@QEBA?AV0@N@Z"),
+            Some(ArithmeticOperator::Div)
+        ));
+        assert!(get_arithmetic_operator("_ZNK6MatrixeqEi").is_none());
+        assert!(get_arithmetic_operator("_ZN6MatrixpLEd").is_none());
+        assert!(get_arithmetic_operator("_Z4plusii").is_none());
+    }
+
+    #[test]
+    fn test_get_static_member() {
+        let ns = Namespace::new().push("ns".into());
+        assert_eq!(
+            get_static_member("_ZN2ns1A7counterE", &ns),
+            Some(("A".into(), "counter".into()))
+        );
+        assert_eq!(
+            get_static_member("\u{1}__ZN2ns1A1B7counterE", &ns),
+            Some(("A_B".into(), "counter".into()))
+        );
+        assert_eq!(get_static_member("_ZN2ns7counterE", &ns), None);
+        assert_eq!(get_static_member("_ZN5other1A7counterE", &ns), None);
+        assert_eq!(get_static_member("_ZN2ns1A1fEv", &ns), None);
+        assert_eq!(get_static_member("counter", &ns), None);
+        assert_eq!(
+            get_static_member("?counter@A@ns@@2HA", &ns),
+            Some(("A".into(), "counter".into()))
+        );
+        assert_eq!(
+            get_static_member("?counter@B@A@ns@@0HB", &ns),
+            Some(("A_B".into(), "counter".into()))
+        );
+        assert_eq!(get_static_member("?counter@ns@@3HA", &ns), None);
+        assert_eq!(get_static_member("?counter@?$A@H@ns@@2HA", &ns), None);
+    }
 }
@@ -281,6 +281,58 @@ impl TypeDatabase {
             .filter_map(|(_, td)| td.get_prelude_entry().map(|_| td.cpp_name.as_str()))
     }
 
+    /// Whether this is a built-in type which can be freely copied
+    /// between Rust and C++ by value.
+    pub(crate) fn is_trivially_copyable(&self, ty: &QualifiedName) -> bool {
+        self.get(ty)
+            .map(|td| matches!(td.behavior, Behavior::CByValue | Behavior::CByValueVecSafe))
+            .unwrap_or(false)
+    }
+
+    /// Whether this is a built-in type whose Rust equivalent implements
+    /// `Default`, so we can make a value of it for C++ to overwrite.
+    pub(crate) fn has_default(&self, ty: &QualifiedName) -> bool {
+        self.get(ty)
+            .map(|td| {
+                matches!(
+                    td.behavior,
+                    Behavior::CByValue
+                        | Behavior::CByValueVecSafe
+                        | Behavior::CVariableLengthByValue
+                )
+            })
+            .unwrap_or(false)
+    }
+
+    /// If this is an integer type, the primitive Rust type to which a
+    /// value must be converted in order to pass it as this type. For our
+    /// newtype wrappers around variable-length C integers, that's the
+    /// `std::os::raw` type which they wrap.
+    pub(crate) fn integer_primitive(&self, ty: &QualifiedName) -> Option<TypePath> {
+        let td = self.get(ty)?;
+        match td.behavior {
+            Behavior::CVariableLengthByValue => {
+                let segs = td
+                    .extra_non_canonical_name
+                    .as_ref()?
+                    .split("::")
+                    .map(make_ident);
+                Some(parse_quote! {
+                    ::#(#segs)::*
+                })
+            }
+            Behavior::CByValueVecSafe
+                if matches!(
+                    td.rs_name.as_str(),
+                    "i8" | "u8" | "i16" | "u16" | "i32" | "u32" | "i64" | "u64" | "isize" | "usize"
+                ) =>
+            {
+                Some(td.to_type_path())
+            }
+            _ => None,
+        }
+    }
+
     /// Whether this is one of the ctypes (mostly variable length integers)
     /// which we need to wrap.
     pub(crate) fn is_ctype(&self, ty: &QualifiedName) -> bool {
@@ -14,6 +14,7 @@
 #![cfg_attr(feature = "nightly", feature(doc_cfg))]
 
 mod ast_discoverer;
+mod cargo_cfg;
 mod conversion;
 mod cxxbridge;
 mod known_types;
@@ -29,13 +30,17 @@ mod builder;
 
 use autocxx_bindgen::BindgenError;
 use autocxx_parser::{IncludeCppConfig, UnsafePolicy};
-use conversion::BridgeConverter;
+use conversion::{hash_file, BridgeConverter, CachedOutput, HeaderRecorder};
+use indoc::indoc;
 use miette::{SourceOffset, SourceSpan};
 use parse_callbacks::AutocxxParseCallbacks;
 use parse_file::CppBuildable;
 use proc_macro2::TokenStream as TokenStream2;
 use regex::Regex;
 use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::{
@@ -67,6 +72,7 @@ use autocxx_bindgen as bindgen;
 pub use builder::{
     Builder, BuilderBuild, BuilderContext, BuilderError, BuilderResult, BuilderSuccess,
 };
+pub use conversion::{ApiCache, CoverageReport};
 pub use output_generators::{generate_rs_archive, generate_rs_single, RsOutput};
 pub use parse_file::{parse_file, ParseError, ParsedFile};
 
@@ -140,6 +146,8 @@ struct GenerationResults {
     #[allow(dead_code)]
     inc_dirs: Vec<PathBuf>,
     cxxgen_header_name: String,
+    include_dependencies: BTreeMap<String, Vec<String>>,
+    skipped: Vec<(String, String)>,
 }
 enum State {
     NotGenerated,
@@ -155,6 +163,40 @@ pub struct CodegenOptions<'a> {
     pub force_wrapper_gen: bool,
     /// Options about the C++ code generation.
     pub cpp_codegen_options: CppCodegenOptions<'a>,
+    /// Optionally, the results of parsing bindgen output on a previous
+    /// run, to avoid parsing unchanged items again, and perhaps the output
+    /// of earlier runs stored on disk, to avoid running at all if nothing
+    /// has changed. See [`ApiCache`].
+    pub api_cache: Option<&'a RefCell<ApiCache>>,
+    /// Where to put the `impl` blocks generated for each namespace.
+    pub impl_block_placement: ImplBlockPlacement,
+    /// Skip over any item which can't be converted, rather than failing
+    /// the whole run. The skipped items can be listed afterwards using
+    /// [`IncludeCppEngine::get_skipped_items`].
+    pub resilient: bool,
+    /// Mark `#[inline]` the Rust functions we generate which merely
+    /// forward calls to C++ or access fields, so that they can be inlined
+    /// even into other crates.
+    pub inline_wrappers: bool,
+    /// Mark `#[must_use]` the Rust functions we generate for C++ functions
+    /// declared `[[nodiscard]]`, or returning a type so declared. Finding
+    /// these attributes can slow bindgen down considerably.
+    pub nodiscard_as_must_use: bool,
+}
+
+/// Where to put the `impl` blocks which autocxx generates for the types
+/// in each C++ namespace. Only those blocks move: types, functions and
+/// everything else stay in the namespace's module, so no paths change.
+/// Bindings with very many methods may compile faster if their `impl`
+/// blocks are spread across several modules.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ImplBlockPlacement {
+    /// In the namespace's module, alongside everything else.
+    #[default]
+    InNamespaceMod,
+    /// In private sub-modules of the namespace's module, each holding at
+    /// most this many `impl` blocks.
+    InSubModsOf(usize),
 }
 
 const AUTOCXX_CLANG_ARGS: &[&str; 4] = &["-x", "c++", "-std=c++14", "-DBINDGEN"];
@@ -316,11 +358,49 @@ impl IncludeCppEngine {
     }
 
     fn build_header(&self) -> String {
+        // Function template specializations can't be allowlisted directly,
+        // so we give each one a name from which bindgen can learn its
+        // signature. For a member function template, that's the signature
+        // of an equivalent free function taking the receiver by pointer.
+        let instantiations = self.config.get_function_instantiations().map(|fi| {
+            let typedef_name = self
+                .config
+                .get_function_instantiation_typedef_name(&fi.rust_id);
+            match fi.receiver_type {
+                None => format!("typedef decltype({}) {typedef_name};\n", fi.cpp_definition),
+                Some(_) => format!(
+                    "typedef {}<decltype(&{})>::type {typedef_name};\n",
+                    self.config.get_method_signature_helper_name(),
+                    fi.cpp_definition,
+                ),
+            }
+        });
+        let method_signature_helper = self
+            .config
+            .get_function_instantiations()
+            .any(|fi| fi.receiver_type.is_some())
+            .then(|| {
+                let helper = self.config.get_method_signature_helper_name();
+                format!(
+                    indoc! {"
+                        template <typename F> struct {helper};
+                        template <typename R, typename C, typename... A> struct {helper}<R (C::*)(A...)> {{ typedef R type(C*, A...); }};
+                        template <typename R, typename C, typename... A> struct {helper}<R (C::*)(A...) const> {{ typedef R type(const C*, A...); }};
+                        #ifdef __cpp_noexcept_function_type
+                        template <typename R, typename C, typename... A> struct {helper}<R (C::*)(A...) noexcept> {{ typedef R type(C*, A...); }};
+                        template <typename R, typename C, typename... A> struct {helper}<R (C::*)(A...) const noexcept> {{ typedef R type(const C*, A...); }};
+                        #endif
+                    "},
+                    helper = helper
+                )
+            });
         join(
             self.config
                 .inclusions
                 .iter()
-                .map(|path| format!("#include \"{path}\"\n")),
+                .map(|path| format!("#include \"{path}\"\n"))
+                .chain(method_signature_helper)
+                .chain(instantiations),
             "",
         )
     }
@@ -329,6 +409,7 @@ impl IncludeCppEngine {
         &self,
         inc_dirs: &[PathBuf],
         extra_clang_args: &[&str],
+        codegen_options: &CodegenOptions,
     ) -> bindgen::Builder {
         let mut builder = bindgen::builder()
             .clang_args(make_clang_args(inc_dirs, extra_clang_args))
@@ -351,9 +432,19 @@ impl IncludeCppEngine {
             .represent_cxx_operators(true)
             .use_distinct_char16_t(true)
             .layout_tests(false); // TODO revisit later
+        if codegen_options.nodiscard_as_must_use {
+            builder = builder.enable_function_attribute_detection();
+        }
         for item in known_types().get_initial_blocklist() {
             builder = builder.blocklist_item(item);
         }
+        for item in self.config.get_opaque_fallbacks() {
+            builder = builder.opaque_type(item);
+        }
+        // Types deriving from this get a synthesized `shared_from_this`
+        // method instead, so there's no need to expose its internals,
+        // which vary between standard libraries.
+        builder = builder.opaque_type("std::enable_shared_from_this");
 
         // 3. Passes allowlist and other options to the bindgen::Builder equivalent
         //    to --output-style=cxx --allowlist=<as passed in>
@@ -394,6 +485,31 @@ impl IncludeCppEngine {
         }
     }
 
+    /// For each generated item, the entries of the `include_list` which it
+    /// relies upon. Keys are C++ names, with methods named `Type::method`.
+    /// This can be used to construct minimal translation units containing
+    /// only the headers required for a subset of the bindings.
+    /// Call `generate` first.
+    pub fn get_include_dependencies(&self) -> BTreeMap<String, Vec<String>> {
+        match &self.state {
+            State::NotGenerated => panic!("Generate first"),
+            State::Generated(gen_results) => gen_results.include_dependencies.clone(),
+            State::ParseOnly => BTreeMap::new(),
+        }
+    }
+
+    /// The C++ names of the items which were skipped over because they
+    /// couldn't be converted, each along with the reason why. This is
+    /// only populated if [`CodegenOptions::resilient`] was set.
+    /// Call `generate` first.
+    pub fn get_skipped_items(&self) -> &[(String, String)] {
+        match &self.state {
+            State::NotGenerated => panic!("Generate first"),
+            State::Generated(gen_results) => &gen_results.skipped,
+            State::ParseOnly => &[],
+        }
+    }
+
     /// Returns the name of the mod which this `include_cpp!` will generate.
     /// Can and should be used to ensure multiple mods in a file don't conflict.
     pub fn get_mod_name(&self) -> String {
@@ -443,26 +559,52 @@ impl IncludeCppEngine {
         }
 
         let mod_name = self.config.get_mod_name();
-        let mut builder = self.make_bindgen_builder(&inc_dirs, extra_clang_args);
-        if let Some(dep_recorder) = dep_recorder {
-            builder = builder.parse_callbacks(Box::new(AutocxxParseCallbacks(dep_recorder)));
+        // We name the cxxgen header now, rather than during conversion,
+        // since the name is one of the things which determines whether we
+        // can reuse output stored on disk.
+        let cxxgen_header_name = codegen_options
+            .cpp_codegen_options
+            .cxxgen_header_namer
+            .name_header();
+        let api_cache = codegen_options
+            .api_cache
+            .filter(|api_cache| api_cache.borrow().is_on_disk());
+        let output_key = self.output_cache_key(
+            &inc_dirs,
+            extra_clang_args,
+            codegen_options,
+            &cxxgen_header_name,
+        );
+        if let Some(output) =
+            api_cache.and_then(|api_cache| api_cache.borrow_mut().load_output(output_key))
+        {
+            // Header dependencies must be recorded anew on every run.
+            if let Some(dep_recorder) = &dep_recorder {
+                for (header, _) in &output.headers {
+                    dep_recorder.record_header_file_dependency(header);
+                }
+            }
+            self.state = State::Generated(Box::new(GenerationResults {
+                item_mod: output.rs,
+                cpp: output.cpp,
+                inc_dirs,
+                cxxgen_header_name,
+                include_dependencies: output.include_dependencies,
+                skipped: output.skipped,
+            }));
+            return Ok(());
         }
-        let header_contents = self.build_header();
-        self.dump_header_if_so_configured(&header_contents, &inc_dirs, extra_clang_args);
-        let header_and_prelude = format!("{}\n\n{}", known_types().get_prelude(), header_contents);
-        log::info!("Header and prelude for bindgen:\n{}", header_and_prelude);
-        builder = builder.header_contents("example.hpp", &header_and_prelude);
-
-        let bindings = builder.generate().map_err(Error::Bindgen)?;
-        let bindings = self.parse_bindings(bindings)?;
-
-        // Source code contents just used for diagnostics - if we don't have it,
-        // use a blank string and miette will not attempt to annotate it nicely.
-        let source_file_contents = self
-            .source_code
-            .as_ref()
-            .cloned()
-            .unwrap_or_else(|| Rc::new("".to_string()));
+        let headers_read = Rc::new(RefCell::new(Vec::new()));
+        let dep_recorder = match api_cache {
+            None => dep_recorder,
+            Some(_) => Some(Box::new(HeaderRecorder {
+                headers: headers_read.clone(),
+                inner: dep_recorder,
+            }) as Box<dyn RebuildDependencyRecorder>),
+        };
+        let (bindings, header_contents) =
+            self.run_bindgen(&inc_dirs, extra_clang_args, dep_recorder, codegen_options)?;
+        let source_file_contents = self.source_file_contents();
 
         let converter = BridgeConverter::new(&self.config.inclusions, &self.config);
 
@@ -473,6 +615,7 @@ impl IncludeCppEngine {
                 header_contents,
                 codegen_options,
                 &source_file_contents,
+                cxxgen_header_name,
             )
             .map_err(Error::Conversion)?;
         let mut items = conversion.rs;
@@ -489,15 +632,131 @@ impl IncludeCppEngine {
             "New bindings:\n{}",
             rust_pretty_printer::pretty_print(&new_bindings)
         );
+        if let Some(api_cache) = api_cache {
+            // We can't tell later whether a header we can't read has
+            // changed, so there's no point storing output generated from one.
+            let headers = headers_read
+                .borrow()
+                .iter()
+                .map(|header| Some((header.clone(), hash_file(Path::new(header))?)))
+                .collect::<Option<Vec<_>>>();
+            if let Some(headers) = headers {
+                api_cache.borrow().store_output(
+                    output_key,
+                    &CachedOutput {
+                        rs: new_bindings.clone(),
+                        cpp: conversion.cpp.clone(),
+                        include_dependencies: conversion.include_dependencies.clone(),
+                        skipped: conversion.skipped.clone(),
+                        headers,
+                    },
+                );
+            }
+        }
         self.state = State::Generated(Box::new(GenerationResults {
             item_mod: new_bindings,
             cpp: conversion.cpp,
             inc_dirs,
             cxxgen_header_name: conversion.cxxgen_header_name,
+            include_dependencies: conversion.include_dependencies,
+            skipped: conversion.skipped,
         }));
         Ok(())
     }
 
+    /// Estimate how much of the headers autocxx can bind, without
+    /// generating any code. This runs `bindgen` just as [`Self::generate`]
+    /// does, then reports which of the declarations it found autocxx
+    /// could parse and which it can't support, and why. This may be
+    /// useful when planning to adopt autocxx for an existing codebase;
+    /// combine it with `generate_all!` to consider the whole of a header.
+    pub fn estimate_coverage(
+        &self,
+        inc_dirs: Vec<PathBuf>,
+        extra_clang_args: &[&str],
+    ) -> Result<CoverageReport> {
+        let (bindings, _) = self.run_bindgen(
+            &inc_dirs,
+            extra_clang_args,
+            None,
+            &CodegenOptions::default(),
+        )?;
+        let source_file_contents = self.source_file_contents();
+        BridgeConverter::new(&self.config.inclusions, &self.config)
+            .estimate_coverage(bindings, &source_file_contents)
+            .map_err(Error::Conversion)
+    }
+
+    /// Works out the key under which an [`ApiCache`] stores the output of
+    /// this run on disk. This covers everything which influences that
+    /// output except the contents of the headers, which the cache checks
+    /// separately.
+    fn output_cache_key(
+        &self,
+        inc_dirs: &[PathBuf],
+        extra_clang_args: &[&str],
+        codegen_options: &CodegenOptions,
+        cxxgen_header_name: &str,
+    ) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        env!("CARGO_PKG_VERSION").hash(&mut hasher);
+        self.config.get_hash().hash(&mut hasher);
+        self.build_header().hash(&mut hasher);
+        inc_dirs.hash(&mut hasher);
+        extra_clang_args.hash(&mut hasher);
+        std::env::var_os("BINDGEN_EXTRA_CLANG_ARGS").hash(&mut hasher);
+        codegen_options.force_wrapper_gen.hash(&mut hasher);
+        codegen_options.impl_block_placement.hash(&mut hasher);
+        codegen_options.resilient.hash(&mut hasher);
+        codegen_options.inline_wrappers.hash(&mut hasher);
+        codegen_options.nodiscard_as_must_use.hash(&mut hasher);
+        let cpp_codegen_options = &codegen_options.cpp_codegen_options;
+        cpp_codegen_options
+            .suppress_system_headers
+            .hash(&mut hasher);
+        cpp_codegen_options.path_to_cxx_h.hash(&mut hasher);
+        cpp_codegen_options.path_to_cxxgen_h.hash(&mut hasher);
+        cpp_codegen_options.cxx_impl_annotations.hash(&mut hasher);
+        cpp_codegen_options
+            .autocxxgen_header_namer
+            .name_header(self.config.get_mod_name().to_string())
+            .hash(&mut hasher);
+        cxxgen_header_name.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Run `bindgen` over our headers, returning its output along with
+    /// the header contents we passed to it.
+    fn run_bindgen(
+        &self,
+        inc_dirs: &[PathBuf],
+        extra_clang_args: &[&str],
+        dep_recorder: Option<Box<dyn RebuildDependencyRecorder>>,
+        codegen_options: &CodegenOptions,
+    ) -> Result<(ItemMod, String)> {
+        let mut builder = self.make_bindgen_builder(inc_dirs, extra_clang_args, codegen_options);
+        if let Some(dep_recorder) = dep_recorder {
+            builder = builder.parse_callbacks(Box::new(AutocxxParseCallbacks(dep_recorder)));
+        }
+        let header_contents = self.build_header();
+        self.dump_header_if_so_configured(&header_contents, inc_dirs, extra_clang_args);
+        let header_and_prelude = format!("{}\n\n{}", known_types().get_prelude(), header_contents);
+        log::info!("Header and prelude for bindgen:\n{}", header_and_prelude);
+        builder = builder.header_contents("example.hpp", &header_and_prelude);
+
+        let bindings = builder.generate().map_err(Error::Bindgen)?;
+        Ok((self.parse_bindings(bindings)?, header_contents))
+    }
+
+    /// Source code contents just used for diagnostics - if we don't have it,
+    /// use a blank string and miette will not attempt to annotate it nicely.
+    fn source_file_contents(&self) -> Rc<String> {
+        self.source_code
+            .as_ref()
+            .cloned()
+            .unwrap_or_else(|| Rc::new("".to_string()))
+    }
+
     /// Return the include directories used for this include_cpp invocation.
     #[cfg(any(test, feature = "build"))]
     fn include_dirs(&self) -> impl Iterator<Item = &PathBuf> {
@@ -606,6 +865,7 @@ pub fn do_cxx_cpp_generation(
     let mut opt = cxx_gen::Opt::default();
     opt.cxx_impl_annotations
         .clone_from(&cpp_codegen_options.cxx_impl_annotations);
+    opt.cfg_evaluator = Box::new(cargo_cfg::CargoEnvCfgEvaluator);
     let cxx_generated = cxx_gen::generate_header_and_cc(rs, &opt)?;
     Ok(CppFilePair {
         header: strip_system_headers(
@@ -649,12 +909,15 @@ impl CppBuildable for IncludeCppEngine {
             State::ParseOnly => panic!("Cannot generate C++ in parse-only mode"),
             State::NotGenerated => panic!("Call generate() first"),
             State::Generated(gen_results) => {
-                let rs = gen_results.item_mod.to_token_stream();
-                files.push(do_cxx_cpp_generation(
-                    rs,
-                    cpp_codegen_options,
-                    gen_results.cxxgen_header_name.clone(),
-                )?);
+                // In cxx_free! mode there's no cxx::bridge for cxx to process.
+                if !self.config.is_cxx_free() {
+                    let rs = gen_results.item_mod.to_token_stream();
+                    files.push(do_cxx_cpp_generation(
+                        rs,
+                        cpp_codegen_options,
+                        gen_results.cxxgen_header_name.clone(),
+                    )?);
+                }
                 if let Some(cpp_file_pair) = &gen_results.cpp {
                     files.push(cpp_file_pair.clone());
                 }
@@ -16,7 +16,8 @@ use std::{
 };
 
 use autocxx_engine::{
-    Builder, BuilderBuild, BuilderContext, BuilderError, RebuildDependencyRecorder, HEADER,
+    parse_file, Builder, BuilderBuild, BuilderContext, BuilderError, ParsedFile,
+    RebuildDependencyRecorder, HEADER,
 };
 use log::info;
 use once_cell::sync::OnceCell;
@@ -405,6 +406,35 @@ pub fn do_run_test(
     )
 }
 
+/// Writes the given headers into a temporary directory, along with a Rust
+/// file whose `include_cpp!` includes each of them, and parses that file.
+/// This is for tests which ask autocxx about the headers - which items it
+/// could bind, say - rather than building anything. The directory should
+/// be passed to autocxx as the include path.
+pub fn parse_headers(headers: &[(&str, &str)], directives: TokenStream) -> (TempDir, ParsedFile) {
+    let tdir = tempdir().unwrap();
+    let hexathorpe = Token![#](Span::call_site());
+    let includes: Vec<_> = headers
+        .iter()
+        .map(|(filename, content)| {
+            write_to_file(&tdir, filename, &format!("#pragma once\n{content}"));
+            quote! {
+                #hexathorpe include #filename
+            }
+        })
+        .collect();
+    let rs = quote! {
+        include_cpp!(
+            #(#includes)*
+            safety!(unsafe_ffi)
+            #directives
+        );
+    };
+    let rs_path = write_to_file(&tdir, "input.rs", &rs.to_string());
+    let parsed = parse_file(rs_path, false).unwrap();
+    (tdir, parsed)
+}
+
 /// The [`BuilderContext`] used in autocxx's integration tests.
 pub struct TestBuilderContext;
 
@@ -6,7 +6,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use autocxx_engine::Builder;
+use autocxx_engine::{Builder, ImplBlockPlacement};
 
 use autocxx_integration_tests::{BuilderModifier, BuilderModifierFns, TestBuilderContext};
 
@@ -70,3 +70,36 @@ impl BuilderModifierFns for EnableAutodiscover {
         builder.auto_allowlist(true)
     }
 }
+
+pub(crate) struct SetImplBlockPlacement(pub(crate) ImplBlockPlacement);
+
+impl BuilderModifierFns for SetImplBlockPlacement {
+    fn modify_autocxx_builder<'a>(
+        &self,
+        builder: Builder<'a, TestBuilderContext>,
+    ) -> Builder<'a, TestBuilderContext> {
+        builder.impl_block_placement(self.0)
+    }
+}
+
+pub(crate) struct SetInlineWrappers;
+
+impl BuilderModifierFns for SetInlineWrappers {
+    fn modify_autocxx_builder<'a>(
+        &self,
+        builder: Builder<'a, TestBuilderContext>,
+    ) -> Builder<'a, TestBuilderContext> {
+        builder.inline_wrappers(true)
+    }
+}
+
+pub(crate) struct SetNodiscardAsMustUse;
+
+impl BuilderModifierFns for SetNodiscardAsMustUse {
+    fn modify_autocxx_builder<'a>(
+        &self,
+        builder: Builder<'a, TestBuilderContext>,
+    ) -> Builder<'a, TestBuilderContext> {
+        builder.nodiscard_as_must_use(true)
+    }
+}
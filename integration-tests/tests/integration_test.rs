@@ -9,21 +9,26 @@
 use crate::{
     builder_modifiers::{
         make_clang_arg_adder, make_clang_optional_arg_adder, make_cpp17_adder, EnableAutodiscover,
-        SetSuppressSystemHeaders,
+        SetImplBlockPlacement, SetInlineWrappers, SetNodiscardAsMustUse, SetSuppressSystemHeaders,
     },
     code_checkers::{
         make_error_finder, make_rust_code_finder, make_string_finder, CppMatcher,
         NoSystemHeadersChecker,
     },
 };
+use autocxx_engine::{
+    generate_rs_single, parse_file, ApiCache, CodegenOptions, ImplBlockPlacement, ParsedFile,
+};
 use autocxx_integration_tests::{
-    directives_from_lists, do_run_test, do_run_test_manual, run_generate_all_test, run_test,
-    run_test_ex, run_test_expect_fail, run_test_expect_fail_ex, BuilderModifier, TestError,
+    directives_from_lists, do_run_test, do_run_test_manual, parse_headers, run_generate_all_test,
+    run_test, run_test_ex, run_test_expect_fail, run_test_expect_fail_ex, BuilderModifier,
+    TestError,
 };
 use indoc::indoc;
 use itertools::Itertools;
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
+use std::cell::RefCell;
 use syn::{parse_quote, Token};
 use test_log::test;
 
@@ -455,6 +460,202 @@ fn test_take_pod_by_value() {
     run_test(cxx, hdr, rs, &["take_bob"], &["Bob"]);
 }
 
+#[test]
+fn test_cxx_free_pod_only() {
+    let cxx = indoc! {"
+        Point scale(Point p, int32_t factor) {
+            return Point { p.x * factor, p.y * factor };
+        }
+        int32_t sum(const Point* p) {
+            return p->x + p->y;
+        }
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Point {
+            int32_t x;
+            int32_t y;
+        };
+        Point scale(Point p, int32_t factor);
+        int32_t sum(const Point* p);
+    "};
+    let rs = quote! {
+        let p = unsafe { ffi::scale(ffi::Point { x: 2, y: 3 }, 4) };
+        assert_eq!(p.x, 8);
+        assert_eq!(unsafe { ffi::sum(&p) }, 20);
+    };
+    run_test_ex(
+        cxx,
+        hdr,
+        rs,
+        directives_from_lists(&["scale", "sum"], &["Point"], Some(quote! { cxx_free!() })),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_c_abi_shims() {
+    let cxx = indoc! {"
+        Point scale(Point p, int32_t factor) {
+            return Point { p.x * factor, p.y * factor };
+        }
+        int32_t geo::area(Point p) {
+            return p.x * p.y;
+        }
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <string>
+        struct Point {
+            int32_t x;
+            int32_t y;
+        };
+        Point scale(Point p, int32_t factor);
+        namespace geo {
+            int32_t area(Point p);
+        }
+        inline std::string describe() { return \"point\"; }
+    "};
+    let rs = quote! {
+        let p = ffi::scale(ffi::Point { x: 2, y: 3 }, 4);
+        assert_eq!(p.x, 8);
+        let p = unsafe { ffi::scale_c_abi(ffi::Point { x: 2, y: 3 }, 4) };
+        assert_eq!(p.y, 12);
+        assert_eq!(unsafe { ffi::geo::area_c_abi(p) }, 96);
+        // The shims can also be found by name, as a plugin host might.
+        extern "C" {
+            fn autocxx_c_abi_geo_area(p: ffi::Point) -> i32;
+        }
+        assert_eq!(unsafe { autocxx_c_abi_geo_area(p) }, 96);
+        // Functions which need cxx don't get a shim.
+        assert_eq!(ffi::describe().to_str().unwrap(), "point");
+    };
+    run_test_ex(
+        cxx,
+        hdr,
+        rs,
+        directives_from_lists(
+            &["scale", "geo::area", "describe"],
+            &["Point"],
+            Some(quote! { c_abi_shims!() }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_long_mapping_default() {
+    let hdr = indoc! {"
+        struct Sizes {
+            long a;
+            unsigned long b;
+        };
+        inline long sum(Sizes s) { return s.a + static_cast<long>(s.b); }
+    "};
+    let rs = quote! {
+        let a: std::os::raw::c_long = 2;
+        let b: std::os::raw::c_ulong = 3;
+        let s = ffi::Sizes { a, b };
+        assert_eq!(ffi::sum(s), autocxx::c_long(5));
+    };
+    run_test("", hdr, rs, &["sum"], &["Sizes"]);
+}
+
+// `long` is only 64 bits wide on some platforms.
+#[cfg_attr(windows, ignore)]
+#[test]
+fn test_long_mapping_pinned() {
+    let hdr = indoc! {"
+        struct Sizes {
+            long a;
+            unsigned long b[2];
+        };
+        inline long sum(Sizes s) { return s.a + static_cast<long>(s.b[0] + s.b[1]); }
+    "};
+    let rs = quote! {
+        let a: i64 = 2;
+        let b: [u64; 2] = [3, 4];
+        let s = ffi::Sizes { a, b };
+        assert_eq!(ffi::sum(s), autocxx::c_long(9));
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["sum"], &["Sizes"], Some(quote! { long_mapping!(i64) })),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_checked_integer_conversions_result() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline int twice(int a) { return a * 2; }
+        class Counter {
+        public:
+            Counter() : total(0) {}
+            void add(uint8_t amount) { total += amount; }
+            uint32_t get() const { return total; }
+        private:
+            uint32_t total;
+        };
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::twice(21i64).unwrap(), autocxx::c_int(42));
+        let err = ffi::twice(1i64 << 40).unwrap_err();
+        assert_eq!(err.param(), "a");
+        let mut counter = ffi::Counter::new().within_unique_ptr();
+        counter.pin_mut().add(200u32).unwrap();
+        assert!(counter.pin_mut().add(256u32).is_err());
+        assert!(counter.pin_mut().add(-1i32).is_err());
+        assert_eq!(counter.get(), 200);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["twice", "Counter"],
+            &[],
+            Some(quote! { checked_integer_conversions!(result) }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_checked_integer_conversions_panic() {
+    let hdr = indoc! {"
+        inline int twice(int a) { return a * 2; }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::twice(21i64), autocxx::c_int(42));
+        assert!(std::panic::catch_unwind(|| ffi::twice(1i64 << 40)).is_err());
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["twice"],
+            &[],
+            Some(quote! { checked_integer_conversions!(panic) }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
 #[test]
 fn test_negative_take_as_pod_with_destructor() {
     let cxx = indoc! {"
@@ -655,6 +856,49 @@ fn test_take_nested_pod_by_value() {
     run_test(cxx, hdr, rs, &["take_bob"], &["Bob"]);
 }
 
+#[test]
+fn test_take_overaligned_pod() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct alignas(16) Vec4 {
+            float x;
+            float y;
+            float z;
+            float w;
+        };
+        struct alignas(16) Small {
+            uint32_t a;
+        };
+        inline uint32_t misalignment(const Vec4& v) {
+            return reinterpret_cast<uintptr_t>(&v) % 16;
+        }
+        inline float sum(Vec4 v) {
+            return v.x + v.y + v.z + v.w;
+        }
+        inline Small make_small(uint32_t a) {
+            Small s;
+            s.a = a;
+            return s;
+        }
+    "};
+    let rs = quote! {
+        assert_eq!(std::mem::align_of::<ffi::Vec4>(), 16);
+        assert_eq!(std::mem::align_of::<ffi::Small>(), 16);
+        assert_eq!(std::mem::size_of::<ffi::Small>(), 16);
+        let v = ffi::Vec4 { x: 1.0, y: 2.0, z: 3.0, w: 4.0 };
+        assert_eq!(ffi::misalignment(&v), 0);
+        assert_eq!(ffi::sum(v), 10.0);
+        assert_eq!(ffi::make_small(5).a, 5);
+    };
+    run_test(
+        "",
+        hdr,
+        rs,
+        &["misalignment", "sum", "make_small"],
+        &["Vec4", "Small"],
+    );
+}
+
 #[test]
 fn test_take_nonpod_by_value() {
     let cxx = indoc! {"
@@ -1164,6 +1408,64 @@ fn test_enum_no_funcs() {
     run_test(cxx, hdr, rs, &["Bob"], &[]);
 }
 
+#[test]
+fn test_enum_try_from() {
+    let cxx = indoc! {"
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        enum Bob : uint8_t {
+            BOB_VALUE_1 = 2,
+            BOB_VALUE_2 = 7,
+        };
+    "};
+    let rs = quote! {
+        use std::convert::TryFrom;
+        assert!(ffi::Bob::try_from(7u8) == Ok(ffi::Bob::BOB_VALUE_2));
+        assert!(ffi::Bob::try_from(3u8) == Err(3u8));
+    };
+    run_test(cxx, hdr, rs, &["Bob"], &[]);
+}
+
+#[test]
+fn test_enum_bitflags() {
+    let cxx = indoc! {"
+        bool can_write(Permissions p) {
+            return (p & PERMISSION_WRITE) != 0;
+        }
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        enum Permissions : uint32_t {
+            PERMISSION_READ = 1,
+            PERMISSION_WRITE = 2,
+            PERMISSION_EXECUTE = 4,
+        };
+        bool can_write(Permissions p);
+    "};
+    let rs = quote! {
+        let p = ffi::Permissions::PERMISSION_READ | ffi::Permissions::PERMISSION_WRITE;
+        assert!(p.contains(ffi::Permissions::PERMISSION_WRITE));
+        assert!(!p.contains(ffi::Permissions::PERMISSION_EXECUTE));
+        assert_eq!(p.bits(), 3u32);
+        assert!(ffi::can_write(p));
+        assert!(!ffi::can_write(ffi::Permissions::PERMISSION_READ));
+    };
+    run_test_ex(
+        cxx,
+        hdr,
+        rs,
+        directives_from_lists(
+            &["can_write", "Permissions"],
+            &[],
+            Some(quote! { bitflags_enum!("Permissions") }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
 #[test]
 fn test_enum_with_funcs_as_pod() {
     let cxx = indoc! {"
@@ -1280,121 +1582,472 @@ fn test_pod_mut_method() {
 }
 
 #[test]
-fn test_define_int() {
-    let cxx = indoc! {"
-    "};
-    let hdr = indoc! {"
-        #define BOB 3
-    "};
-    let rs = quote! {
-        assert_eq!(ffi::BOB, 3);
-    };
-    run_test(cxx, hdr, rs, &["BOB"], &[]);
-}
-
-#[test]
-fn test_define_str() {
+fn test_pod_method_return_self_by_value() {
     let cxx = indoc! {"
+        Matrix Matrix::add(const Matrix& other) const {
+            Matrix result;
+            for (int i = 0; i < 4; i++) {
+                result.m[i] = m[i] + other.m[i];
+            }
+            return result;
+        }
     "};
     let hdr = indoc! {"
-        #define BOB \"foo\"
+        #include <cstdint>
+        struct Matrix {
+        public:
+            uint32_t m[4];
+            Matrix add(const Matrix& other) const;
+        };
     "};
     let rs = quote! {
-        assert_eq!(core::str::from_utf8(ffi::BOB).unwrap().trim_end_matches(char::from(0)), "foo");
+        let a = ffi::Matrix { m: [1, 2, 3, 4] };
+        let b = ffi::Matrix { m: [10, 20, 30, 40] };
+        let c: ffi::Matrix = a.add(&b);
+        assert_eq!(c.m, [11, 22, 33, 44]);
+        // Plain values can be fed straight back in.
+        assert_eq!(c.add(&a).m, [12, 24, 36, 48]);
     };
-    run_test(cxx, hdr, rs, &["BOB"], &[]);
+    run_test(cxx, hdr, rs, &[], &["Matrix"]);
 }
 
 #[test]
-fn test_i32_const() {
+fn test_pod_operator_return_self_by_value() {
     let cxx = indoc! {"
+        Matrix Matrix::operator+(Matrix other) const {
+            Matrix result;
+            for (int i = 0; i < 4; i++) {
+                result.m[i] = m[i] + other.m[i];
+            }
+            return result;
+        }
     "};
     let hdr = indoc! {"
         #include <cstdint>
-        const uint32_t BOB = 3;
+        struct Matrix {
+        public:
+            uint32_t m[4];
+            Matrix operator+(Matrix other) const;
+        };
     "};
     let rs = quote! {
-        assert_eq!(ffi::BOB, 3);
+        let a = ffi::Matrix { m: [1, 2, 3, 4] };
+        let b = ffi::Matrix { m: [10, 20, 30, 40] };
+        let c: ffi::Matrix = &a + b;
+        assert_eq!(c.m, [11, 22, 33, 44]);
+        let d: ffi::Matrix = c + a;
+        assert_eq!(d.m, [12, 24, 36, 48]);
     };
-    run_test(cxx, hdr, rs, &["BOB"], &[]);
+    run_test(cxx, hdr, rs, &[], &["Matrix"]);
 }
 
 #[test]
-fn test_negative_rs_nonsense() {
-    // Really just testing the test infrastructure.
-    let cxx = indoc! {"
-    "};
+fn test_friend_function() {
     let hdr = indoc! {"
         #include <cstdint>
-        const uint32_t BOB = 3;
+        namespace A {
+            class Widget {
+            public:
+                Widget() : secret(42) {}
+                friend uint32_t reveal(const Widget& w);
+            private:
+                uint32_t secret;
+            };
+            uint32_t reveal(const Widget& w);
+            inline uint32_t reveal(const Widget& w) {
+                return w.secret;
+            }
+        }
     "};
     let rs = quote! {
-        foo bar
+        let w = ffi::A::Widget::new().within_unique_ptr();
+        assert_eq!(ffi::A::reveal(&w), 42);
     };
-    run_test_expect_fail(cxx, hdr, rs, &["BOB"], &[]);
+    run_test("", hdr, rs, &["A::Widget", "A::reveal"], &[]);
 }
 
 #[test]
-fn test_negative_cpp_nonsense() {
-    // Really just testing the test infrastructure.
-    let cxx = indoc! {"
-    "};
+fn test_friend_stream_operator() {
     let hdr = indoc! {"
         #include <cstdint>
-        const uint32_t BOB = CAT;
+        namespace A {
+            class Log {
+            public:
+                Log() : total(0) {}
+                uint32_t get_total() const { return total; }
+                void add(uint32_t value) { total += value; }
+            private:
+                uint32_t total;
+            };
+            class Widget {
+            public:
+                Widget() : secret(42) {}
+                friend Log& operator<<(Log& log, const Widget& w);
+            private:
+                uint32_t secret;
+            };
+            Log& operator<<(Log& log, const Widget& w);
+            inline Log& operator<<(Log& log, const Widget& w) {
+                log.add(w.secret);
+                return log;
+            }
+        }
     "};
     let rs = quote! {
-        assert_eq!(ffi::BOB, 3);
+        let mut log = ffi::A::Log::new().within_unique_ptr();
+        let w = ffi::A::Widget::new().within_unique_ptr();
+        ffi::A::shl(log.pin_mut(), &w);
+        ffi::A::shl(log.pin_mut(), &w);
+        assert_eq!(log.get_total(), 84);
     };
-    run_test_expect_fail(cxx, hdr, rs, &["BOB"], &[]);
+    run_test_ex("", hdr, rs, quote! { generate_ns!("A") }, None, None, None);
 }
 
 #[test]
-fn test_negative_make_nonpod() {
+fn test_pass_pod_slice() {
     let cxx = indoc! {"
-        uint32_t take_bob(const Bob& a) {
-            return a.a;
+        uint32_t sum_x(const Point* points, size_t count) {
+            uint32_t total = 0;
+            for (size_t i = 0; i < count; i++) {
+                total += points[i].x;
+            }
+            return total;
         }
-        std::unique_ptr<Bob> make_bob(uint32_t a) {
-            auto b = std::make_unique<Bob>();
-            b->a = a;
-            return b;
+        void scale(Point* points, size_t count, uint32_t by) {
+            for (size_t i = 0; i < count; i++) {
+                points[i].x *= by;
+                points[i].y *= by;
+            }
         }
     "};
     let hdr = indoc! {"
         #include <cstdint>
-        #include <memory>
-        struct Bob {
-            uint32_t a;
+        #include <cstddef>
+        struct Point {
+            uint32_t x;
+            uint32_t y;
         };
-        std::unique_ptr<Bob> make_bob(uint32_t a);
-        uint32_t take_bob(const Bob& a);
+        uint32_t sum_x(const Point* points, size_t count);
+        void scale(Point* points, size_t count, uint32_t by);
     "};
     let rs = quote! {
-        ffi::Bob {};
-    };
-    let rs2 = quote! {
-        ffi::Bob { a: 12 };
-    };
-    let rs3 = quote! {
-        ffi::Bob { do_not_attempt_to_allocate_nonpod_types: [] };
+        let mut points = [ffi::Point { x: 1, y: 2 }, ffi::Point { x: 3, y: 4 }];
+        assert_eq!(ffi::sum_x(&points), 4);
+        assert_eq!(ffi::sum_x(&points[1..]), 3);
+        ffi::scale(&mut points, 10);
+        assert_eq!(points[1].y, 40);
+        assert_eq!(ffi::sum_x(&[]), 0);
     };
-    run_test_expect_fail(cxx, hdr, rs, &["take_bob", "Bob", "make_bob"], &[]);
-    run_test_expect_fail(cxx, hdr, rs2, &["take_bob", "Bob", "make_bob"], &[]);
-    run_test_expect_fail(cxx, hdr, rs3, &["take_bob", "Bob", "make_bob"], &[]);
+    run_test(cxx, hdr, rs, &["sum_x", "scale"], &["Point"]);
 }
 
 #[test]
-fn test_method_pass_pod_by_value() {
-    let cxx = indoc! {"
-        uint32_t Bob::get_bob(Anna) const {
-            return a;
-        }
-    "};
+fn test_pass_span() {
     let hdr = indoc! {"
         #include <cstdint>
-        struct Anna {
-            uint32_t a;
+        #include <span>
+        struct Point {
+            uint32_t x;
+            uint32_t y;
+        };
+        inline float sum(std::span<const float> values) {
+            float total = 0.0f;
+            for (float value : values) {
+                total += value;
+            }
+            return total;
+        }
+        inline void scale(std::span<float> values, float by) {
+            for (float& value : values) {
+                value *= by;
+            }
+        }
+        inline uint32_t sum_x(std::span<const Point> points) {
+            uint32_t total = 0;
+            for (const Point& point : points) {
+                total += point.x;
+            }
+            return total;
+        }
+        inline float sum3(std::span<const float, 3> values) {
+            return values[0] + values[1] + values[2];
+        }
+    "};
+    let rs = quote! {
+        let mut values = [1.0f32, 2.0, 3.5];
+        assert_eq!(ffi::sum(&values), 6.5);
+        ffi::scale(&mut values, 2.0);
+        assert_eq!(values, [2.0, 4.0, 7.0]);
+        assert_eq!(ffi::sum(&[]), 0.0);
+        let points = [ffi::Point { x: 1, y: 2 }, ffi::Point { x: 3, y: 4 }];
+        assert_eq!(ffi::sum_x(&points), 4);
+        assert_eq!(ffi::sum3(&values), 13.0);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["sum", "scale", "sum_x", "sum3"], &["Point"], None),
+        make_clang_arg_adder(&["-std=c++20"]),
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_pass_reference_wrapper() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <functional>
+        struct Foo {
+            uint32_t a;
+        };
+        inline uint32_t get_a(std::reference_wrapper<const Foo> foo) {
+            return foo.get().a;
+        }
+        inline void set_a(std::reference_wrapper<Foo> foo, uint32_t a) {
+            foo.get().a = a;
+        }
+        struct Holder {
+            Foo foo;
+            std::reference_wrapper<Foo> get_foo() const {
+                return std::ref(const_cast<Foo&>(foo));
+            }
+        };
+    "};
+    let rs = quote! {
+        let mut foo = ffi::Foo { a: 3 };
+        assert_eq!(ffi::get_a(&foo), 3);
+        ffi::set_a(std::pin::Pin::new(&mut foo), 4);
+        assert_eq!(foo.a, 4);
+        let holder = ffi::Holder { foo };
+        assert_eq!(holder.get_foo().a, 4);
+    };
+    run_test("", hdr, rs, &["get_a", "set_a"], &["Foo", "Holder"]);
+}
+
+#[test]
+fn test_pass_const_reference_wrapper_non_pod() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <functional>
+        #include <memory>
+        #include <string>
+        struct Foo {
+            uint32_t a;
+            std::string name;
+        };
+        inline std::unique_ptr<Foo> make_foo(uint32_t a) {
+            return std::make_unique<Foo>(Foo { a, \"foo\" });
+        }
+        inline uint32_t get_a(std::reference_wrapper<const Foo> foo) {
+            return foo.get().a;
+        }
+    "};
+    // A wrapper of a const type must take a shared reference, not a
+    // `Pin<&mut>`, even though bindgen drops the `const`. This
+    // wouldn't compile otherwise.
+    let rs = quote! {
+        let foo = ffi::make_foo(3);
+        assert_eq!(ffi::get_a(foo.as_ref().unwrap()), 3);
+    };
+    run_test("", hdr, rs, &["make_foo", "get_a"], &[]);
+}
+
+#[test]
+fn test_std_byte() {
+    let hdr = indoc! {"
+        #include <cstddef>
+        #include <cstdint>
+        inline uint32_t sum_bytes(const std::byte* data, size_t len) {
+            uint32_t total = 0;
+            for (size_t i = 0; i < len; i++) {
+                total += std::to_integer<uint32_t>(data[i]);
+            }
+            return total;
+        }
+        inline void fill_bytes(std::byte* data, size_t len, std::byte value) {
+            for (size_t i = 0; i < len; i++) {
+                data[i] = value;
+            }
+        }
+        inline std::byte invert(std::byte b) { return ~b; }
+    "};
+    let rs = quote! {
+        let mut buf = [1u8, 2, 3];
+        assert_eq!(ffi::sum_bytes(&buf), 6);
+        assert_eq!(ffi::sum_bytes(&buf[1..]), 5);
+        ffi::fill_bytes(&mut buf, 7u8);
+        assert_eq!(buf, [7, 7, 7]);
+        assert_eq!(ffi::invert(0x0fu8), 0xf0u8);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["sum_bytes", "fill_bytes", "invert"], &[], None),
+        make_cpp17_adder(),
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_no_slice_params() {
+    // Here the count is the number of points to fill, not the length of
+    // the array, so it mustn't become part of a slice.
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <cstddef>
+        struct Point {
+            uint32_t x;
+            uint32_t y;
+        };
+        inline void fill_ring(Point* ring, size_t count) {
+            for (size_t i = 0; i < count; i++) {
+                ring[i % 2].x += 1;
+            }
+        }
+    "};
+    let rs = quote! {
+        let mut ring = [ffi::Point { x: 0, y: 0 }, ffi::Point { x: 0, y: 0 }];
+        unsafe { ffi::fill_ring(ring.as_mut_ptr(), 5) };
+        assert_eq!(ring[0].x, 3);
+        assert_eq!(ring[1].x, 2);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["fill_ring"],
+            &["Point"],
+            Some(quote! {
+                no_slice_params!("fill_ring")
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_define_int() {
+    let cxx = indoc! {"
+    "};
+    let hdr = indoc! {"
+        #define BOB 3
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::BOB, 3);
+    };
+    run_test(cxx, hdr, rs, &["BOB"], &[]);
+}
+
+#[test]
+fn test_define_str() {
+    let cxx = indoc! {"
+    "};
+    let hdr = indoc! {"
+        #define BOB \"foo\"
+    "};
+    let rs = quote! {
+        assert_eq!(core::str::from_utf8(ffi::BOB).unwrap().trim_end_matches(char::from(0)), "foo");
+    };
+    run_test(cxx, hdr, rs, &["BOB"], &[]);
+}
+
+#[test]
+fn test_i32_const() {
+    let cxx = indoc! {"
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        const uint32_t BOB = 3;
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::BOB, 3);
+    };
+    run_test(cxx, hdr, rs, &["BOB"], &[]);
+}
+
+#[test]
+fn test_negative_rs_nonsense() {
+    // Really just testing the test infrastructure.
+    let cxx = indoc! {"
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        const uint32_t BOB = 3;
+    "};
+    let rs = quote! {
+        foo bar
+    };
+    run_test_expect_fail(cxx, hdr, rs, &["BOB"], &[]);
+}
+
+#[test]
+fn test_negative_cpp_nonsense() {
+    // Really just testing the test infrastructure.
+    let cxx = indoc! {"
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        const uint32_t BOB = CAT;
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::BOB, 3);
+    };
+    run_test_expect_fail(cxx, hdr, rs, &["BOB"], &[]);
+}
+
+#[test]
+fn test_negative_make_nonpod() {
+    let cxx = indoc! {"
+        uint32_t take_bob(const Bob& a) {
+            return a.a;
+        }
+        std::unique_ptr<Bob> make_bob(uint32_t a) {
+            auto b = std::make_unique<Bob>();
+            b->a = a;
+            return b;
+        }
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <memory>
+        struct Bob {
+            uint32_t a;
+        };
+        std::unique_ptr<Bob> make_bob(uint32_t a);
+        uint32_t take_bob(const Bob& a);
+    "};
+    let rs = quote! {
+        ffi::Bob {};
+    };
+    let rs2 = quote! {
+        ffi::Bob { a: 12 };
+    };
+    let rs3 = quote! {
+        ffi::Bob { do_not_attempt_to_allocate_nonpod_types: [] };
+    };
+    run_test_expect_fail(cxx, hdr, rs, &["take_bob", "Bob", "make_bob"], &[]);
+    run_test_expect_fail(cxx, hdr, rs2, &["take_bob", "Bob", "make_bob"], &[]);
+    run_test_expect_fail(cxx, hdr, rs3, &["take_bob", "Bob", "make_bob"], &[]);
+}
+
+#[test]
+fn test_method_pass_pod_by_value() {
+    let cxx = indoc! {"
+        uint32_t Bob::get_bob(Anna) const {
+            return a;
+        }
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Anna {
+            uint32_t a;
         };
         struct Bob {
         public:
@@ -1871,6 +2524,96 @@ fn test_method_return_nonpod_by_value() {
     run_test(cxx, hdr, rs, &["Anna"], &["Bob"]);
 }
 
+#[test]
+fn test_method_return_nonpod_by_value_without_copies() {
+    // The C++ wrapper placement-news the returned value into the storage
+    // which Rust provides, so whether it ends up in a UniquePtr or on the
+    // stack, it should never be copied or moved.
+    let cxx = indoc! {"
+        static uint32_t copies = 0;
+        static uint32_t moves = 0;
+        uint32_t get_copies() { return copies; }
+        uint32_t get_moves() { return moves; }
+        void note_copy() { copies++; }
+        void note_move() { moves++; }
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        uint32_t get_copies();
+        uint32_t get_moves();
+        void note_copy();
+        void note_move();
+        class Tracked {
+        public:
+            Tracked() : value(42) {}
+            Tracked(const Tracked& other) : value(other.value) { note_copy(); }
+            Tracked(Tracked&& other) : value(other.value) { note_move(); }
+            ~Tracked() {}
+            uint32_t get() const { return value; }
+        private:
+            uint32_t value;
+        };
+        class Factory {
+        public:
+            Factory() {}
+            Tracked make() const { return Tracked(); }
+        };
+    "};
+    let rs = quote! {
+        let factory = ffi::Factory::new().within_unique_ptr();
+        let a = factory.make().within_unique_ptr();
+        assert_eq!(a.get(), 42);
+        moveit! {
+            let b = factory.make();
+        }
+        assert_eq!(b.get(), 42);
+        assert_eq!(ffi::get_copies(), 0);
+        assert_eq!(ffi::get_moves(), 0);
+    };
+    run_test(
+        cxx,
+        hdr,
+        rs,
+        &["Factory", "Tracked", "get_copies", "get_moves"],
+        &[],
+    );
+}
+
+#[test]
+fn test_construct_within_maybe_uninit() {
+    let cxx = indoc! {"
+        static uint32_t live = 0;
+        uint32_t live_count() { return live; }
+        Widget::Widget(uint32_t value) : value(value) { live++; }
+        Widget::~Widget() { live--; }
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        uint32_t live_count();
+        class Widget {
+        public:
+            Widget(uint32_t value);
+            ~Widget();
+            uint32_t get() const { return value; }
+        private:
+            uint32_t value;
+        };
+    "};
+    let rs = quote! {
+        let mut arena = Box::pin(std::mem::MaybeUninit::<ffi::Widget>::uninit());
+        let widget = unsafe { ffi::Widget::new(7).within_maybe_uninit(arena.as_mut()) };
+        assert_eq!(widget.get(), 7);
+        assert_eq!(ffi::live_count(), 1);
+        unsafe { std::ptr::drop_in_place(std::pin::Pin::into_inner_unchecked(widget)) };
+        assert_eq!(ffi::live_count(), 0);
+        let widget = unsafe { ffi::Widget::new(8).within_maybe_uninit(arena.as_mut()) };
+        assert_eq!(widget.get(), 8);
+        unsafe { std::ptr::drop_in_place(std::pin::Pin::into_inner_unchecked(widget)) };
+        assert_eq!(ffi::live_count(), 0);
+    };
+    run_test(cxx, hdr, rs, &["Widget", "live_count"], &[]);
+}
+
 #[test]
 fn test_pass_string_by_value() {
     let cxx = indoc! {"
@@ -2213,19 +2956,101 @@ fn test_ns_func() {
 }
 
 #[test]
-fn test_overload_constructors() {
+fn test_ns_flatten() {
     let cxx = indoc! {"
-        Bob::Bob() {}
-        Bob::Bob(uint32_t _a) :a(_a) {}
+        uint32_t take_bob(A::B::C::Bob a) {
+            return a.a;
+        }
     "};
     let hdr = indoc! {"
         #include <cstdint>
-        #include <memory>
-        struct Bob {
-            Bob();
-            Bob(uint32_t a);
-            uint32_t a;
-            uint32_t b;
+        namespace A {
+            namespace B {
+                namespace C {
+                    struct Bob {
+                        uint32_t a;
+                        uint32_t b;
+                    };
+                }
+            }
+        }
+        uint32_t take_bob(A::B::C::Bob a);
+    "};
+    let rs = quote! {
+        let a = ffi::Bob { a: 12, b: 13 };
+        assert_eq!(ffi::take_bob(a), 12);
+    };
+    run_test_ex(
+        cxx,
+        hdr,
+        rs,
+        directives_from_lists(
+            &["take_bob"],
+            &["A::B::C::Bob"],
+            Some(quote! { flatten_namespaces!() }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_ns_flatten_collision() {
+    let cxx = indoc! {"
+        uint32_t take_bob(A::B::Bob a) {
+            return a.a;
+        }
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Bob {
+            uint32_t c;
+        };
+        namespace A {
+            namespace B {
+                struct Bob {
+                    uint32_t a;
+                    uint32_t b;
+                };
+            }
+        }
+        uint32_t take_bob(A::B::Bob a);
+    "};
+    let rs = quote! {
+        let a = ffi::A::Bob { a: 12, b: 13 };
+        assert_eq!(ffi::take_bob(a), 12);
+        let _ = ffi::Bob { c: 14 };
+    };
+    run_test_ex(
+        cxx,
+        hdr,
+        rs,
+        directives_from_lists(
+            &["take_bob"],
+            &["A::B::Bob", "Bob"],
+            Some(quote! { flatten_namespaces!() }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_overload_constructors() {
+    let cxx = indoc! {"
+        Bob::Bob() {}
+        Bob::Bob(uint32_t _a) :a(_a) {}
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <memory>
+        struct Bob {
+            Bob();
+            Bob(uint32_t a);
+            uint32_t a;
+            uint32_t b;
         };
     "};
     let rs = quote! {
@@ -2326,6 +3151,26 @@ fn test_overload_numeric_functions() {
     );
 }
 
+#[test]
+fn test_overload_functions_on_pointer_constness() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline uint32_t describe(const char*) { return 1; }
+        inline uint32_t describe(char*) { return 2; }
+        inline uint32_t measure(uint32_t*) { return 3; }
+        inline uint32_t measure(const uint32_t*) { return 4; }
+    "};
+    let rs = quote! {
+        let mut buf = [0 as std::os::raw::c_char; 4];
+        assert_eq!(unsafe { ffi::describe(buf.as_ptr()) }, 1);
+        assert_eq!(unsafe { ffi::describe_mut(buf.as_mut_ptr()) }, 2);
+        let mut val = 0u32;
+        assert_eq!(unsafe { ffi::measure_mut(&mut val) }, 3);
+        assert_eq!(unsafe { ffi::measure(&val) }, 4);
+    };
+    run_test("", hdr, rs, &["describe", "measure"], &[]);
+}
+
 #[test]
 fn test_overload_methods() {
     let cxx = indoc! {"
@@ -2368,6 +3213,283 @@ fn test_overload_methods() {
     run_test(cxx, hdr, rs, &["Norma"], &["Fred", "Bob"]);
 }
 
+#[test]
+fn test_overload_naming_by_parameter_types() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Fred {
+            uint32_t a;
+        };
+        struct Norma {
+            Norma() : a(0) {}
+            Norma(uint32_t v) : a(v) {}
+            uint32_t get() const { return a; }
+            uint32_t a;
+        };
+        struct Bob {
+            uint32_t a;
+            uint32_t daft(uint32_t) const { return 5; }
+            uint32_t daft(float) const { return 6; }
+        };
+        inline uint32_t daft(uint32_t) { return 1; }
+        inline uint32_t daft(uint8_t) { return 2; }
+        inline uint32_t daft(double, const Fred&) { return 3; }
+        inline uint32_t daft() { return 4; }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::daft(1u32), 1);
+        assert_eq!(ffi::daft_u8(1u8), 2);
+        let fred = ffi::Fred { a: 3 };
+        assert_eq!(ffi::daft_f64_fred(1.0, &fred), 3);
+        assert_eq!(ffi::daft_void(), 4);
+        let bob = ffi::Bob { a: 12 };
+        assert_eq!(bob.daft(1u32), 5);
+        assert_eq!(bob.daft_f32(1.0), 6);
+        let n = ffi::Norma::new().within_unique_ptr();
+        assert_eq!(n.get(), 0);
+        let n = ffi::Norma::new_u32(7).within_unique_ptr();
+        assert_eq!(n.get(), 7);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Norma", "daft", "daft1", "daft2", "daft3"],
+            &["Fred", "Bob"],
+            Some(quote! { overload_naming!(parameter_types) }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_call_operator() {
+    let cxx = indoc! {"
+        uint32_t Adder::operator()(uint32_t x) const { return x + offset; }
+        uint32_t apply_twice(const Adder& f, uint32_t x) { return f(f(x)); }
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Adder {
+            uint32_t offset;
+            uint32_t operator()(uint32_t x) const;
+        };
+        uint32_t apply_twice(const Adder& f, uint32_t x);
+    "};
+    let rs = quote! {
+        let f = ffi::Adder { offset: 3 };
+        assert_eq!(f.call(2), 5);
+        assert_eq!(ffi::apply_twice(&f, 2), 8);
+    };
+    run_test(cxx, hdr, rs, &["apply_twice"], &["Adder"]);
+}
+
+#[test]
+fn test_arrow_operator_deref() {
+    let cxx = indoc! {"
+        const Widget* WidgetPtr::operator->() const { return &widget; }
+        Widget* WidgetPtr::operator->() { return &widget; }
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        class Widget {
+        public:
+            Widget() : value(42) {}
+            uint32_t get() const { return value; }
+            void set(uint32_t v) { value = v; }
+        private:
+            uint32_t value;
+        };
+        class WidgetPtr {
+        public:
+            const Widget* operator->() const;
+            Widget* operator->();
+        private:
+            Widget widget;
+        };
+    "};
+    let rs = quote! {
+        let mut p = ffi::WidgetPtr::new().within_unique_ptr();
+        assert_eq!(p.get(), 42);
+        p.pin_mut().arrow().set(7);
+        assert_eq!(p.get(), 7);
+        static_assertions::assert_impl_all!(ffi::WidgetPtr: std::ops::Deref<Target = ffi::Widget>);
+    };
+    run_test(cxx, hdr, rs, &["Widget", "WidgetPtr"], &[]);
+}
+
+#[test]
+fn test_conversion_operator() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Meters {
+            uint32_t value;
+        };
+        struct Millimeters {
+            uint32_t value;
+        };
+        struct Kilometers {
+            uint32_t value;
+            operator Meters() const { return Meters { value * 1000 }; }
+            explicit operator Millimeters() const { return Millimeters { value * 1000000 }; }
+        };
+    "};
+    let rs = quote! {
+        let km = ffi::Kilometers { value: 3 };
+        let m = ffi::Meters::from(&km);
+        assert_eq!(m.value, 3000);
+        let mm = km.to_Millimeters();
+        assert_eq!(mm.value, 3000000);
+        static_assertions::assert_not_impl_any!(ffi::Millimeters: From<&'static ffi::Kilometers>);
+    };
+    run_test("", hdr, rs, &[], &["Meters", "Millimeters", "Kilometers"]);
+}
+
+#[test]
+fn test_equality_operator() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct MyType {
+            int32_t value;
+            bool operator==(const MyType& other) const { return value == other.value; }
+            bool operator==(int32_t other) const { return value == other; }
+        };
+    "};
+    let rs = quote! {
+        let my_value = ffi::MyType { value: 5 };
+        assert!(my_value == 5);
+        assert!(my_value != 6);
+        assert!(my_value == ffi::MyType { value: 5 });
+        assert!(my_value != ffi::MyType { value: 6 });
+    };
+    run_test("", hdr, rs, &[], &["MyType"]);
+}
+
+#[test]
+fn test_free_heterogeneous_equality_operator() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        namespace N {
+            class MyType {
+            public:
+                MyType() : value(5) {}
+                int32_t value;
+            private:
+                int32_t padding;
+            };
+            inline bool operator==(const MyType& lhs, int32_t rhs) { return lhs.value == rhs; }
+        }
+    "};
+    let rs = quote! {
+        let my_value = ffi::N::MyType::new().within_unique_ptr();
+        assert!(*my_value.as_ref().unwrap() == 5);
+        assert!(*my_value.as_ref().unwrap() != 6);
+    };
+    run_test_ex("", hdr, rs, quote! { generate_ns!("N") }, None, None, None);
+}
+
+#[test]
+fn test_arithmetic_operators() {
+    let hdr = indoc! {"
+        namespace N {
+            struct Matrix {
+                double a;
+                double b;
+                Matrix operator+(double x) const { return Matrix { a + x, b + x }; }
+                Matrix operator-(const Matrix& other) const {
+                    return Matrix { a - other.a, b - other.b };
+                }
+            };
+            inline Matrix operator*(const Matrix& m, double x) { return Matrix { m.a * x, m.b * x }; }
+        }
+    "};
+    let rs = quote! {
+        let matrix = ffi::N::Matrix { a: 1.0, b: 2.0 };
+        let sum = &matrix + 2.0;
+        assert_eq!(sum.a, 3.0);
+        assert_eq!(sum.b, 4.0);
+        let difference = &sum - &matrix;
+        assert_eq!(difference.a, 2.0);
+        assert_eq!(difference.b, 2.0);
+        let product = &matrix * 3.0;
+        assert_eq!(product.a, 3.0);
+        assert_eq!(product.b, 6.0);
+        let difference = sum - &matrix;
+        assert_eq!(difference.a, 2.0);
+        let product = matrix * 3.0;
+        assert_eq!(product.b, 6.0);
+        let sum = product + 2.0;
+        assert_eq!(sum.a, 5.0);
+        assert_eq!(sum.b, 8.0);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate_ns!("N")
+            generate_pod!("N::Matrix")
+        },
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_arithmetic_operator_output_kept_by_gc() {
+    // Direction isn't on the allowlist, and is reachable only through
+    // Position's operator-, which returns it, and the Sub impl for which
+    // names it as the Output.
+    let hdr = indoc! {"
+        #include <cstdint>
+        enum class Direction { Forward, Backward, Still };
+        struct Position {
+            int32_t value;
+            Direction operator-(const Position& other) const {
+                if (value > other.value) {
+                    return Direction::Forward;
+                }
+                return value < other.value ? Direction::Backward : Direction::Still;
+            }
+        };
+    "};
+    let rs = quote! {
+        let start = ffi::Position { value: 3 };
+        let end = ffi::Position { value: 5 };
+        let direction: ffi::Direction = &start - &end;
+        assert!(matches!(direction, ffi::Direction::Backward));
+        assert!(matches!(&end - &start, ffi::Direction::Forward));
+    };
+    run_test("", hdr, rs, &[], &["Position"]);
+}
+
+#[test]
+fn test_converting_constructor() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Meters {
+            Meters(uint32_t v) : value(v) {}
+            uint32_t value;
+        };
+        struct Millimeters {
+            explicit Millimeters(uint32_t v) : value(v) {}
+            uint32_t value;
+        };
+    "};
+    let rs = quote! {
+        let m = ffi::Meters::from(3u32);
+        assert_eq!(m.value, 3);
+        let m: ffi::Meters = 4u32.into();
+        assert_eq!(m.value, 4);
+        static_assertions::assert_not_impl_any!(ffi::Millimeters: From<u32>);
+    };
+    run_test("", hdr, rs, &[], &["Meters", "Millimeters"]);
+}
+
 #[test]
 fn test_ns_constructor() {
     let cxx = indoc! {"
@@ -2586,6 +3708,45 @@ fn test_destructor() {
     run_test(cxx, hdr, rs, &["WithDtor", "make_with_dtor"], &[]);
 }
 
+#[test]
+fn test_virtual_destructor_only() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline uint32_t& dtor_count() {
+            static uint32_t count = 0;
+            return count;
+        }
+        class A {
+        public:
+            A() {}
+            virtual ~A() { dtor_count()++; }
+            uint32_t get() const { return 3; }
+        };
+        inline uint32_t get_dtor_count() { return dtor_count(); }
+    "};
+    let rs = quote! {
+        {
+            moveit! { let a = ffi::A::new(); }
+            assert_eq!(a.get(), 3);
+        }
+        assert_eq!(ffi::get_dtor_count(), 1);
+        {
+            let b = ffi::A::new().within_unique_ptr();
+            assert_eq!(b.get(), 3);
+        }
+        assert_eq!(ffi::get_dtor_count(), 2);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["A", "get_dtor_count"], &[], None),
+        None,
+        None,
+        None,
+    );
+}
+
 #[test]
 fn test_nested_with_destructor() {
     // Regression test, naming the destructor in the generated C++ is a bit tricky.
@@ -2649,31 +3810,297 @@ fn test_static_func() {
 }
 
 #[test]
-fn test_static_func_wrapper() {
+fn test_c_string_return() {
     let hdr = indoc! {"
-        #include <cstdint>
         #include <string>
-        struct A {
-            std::string a;
-            static A CreateA(std::string a, std::string) {
-                A c;
-                c.a = a;
-                return c;
-            }
+        inline const char* version() { return \"1.2.3\"; }
+        inline const char* missing() { return nullptr; }
+        class Person {
+        public:
+            Person() : n(\"Ada\") {}
+            const char* name() const { return n.c_str(); }
+            const char* nickname() const { return nullptr; }
+        private:
+            std::string n;
         };
     "};
     let rs = quote! {
-        use ffi::ToCppString;
-        ffi::A::CreateA("a".into_cpp(), "b".into_cpp());
+        let v: Option<&'static std::ffi::CStr> = unsafe { ffi::version() };
+        assert_eq!(v.unwrap().to_str().unwrap(), "1.2.3");
+        assert!(unsafe { ffi::missing() }.is_none());
+        let p = ffi::Person::new().within_unique_ptr();
+        let name: String = p.name();
+        assert_eq!(name, "Ada");
+        assert_eq!(p.nickname(), "");
     };
-    run_test("", hdr, rs, &["A"], &[]);
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["version", "missing", "Person"],
+            &[],
+            Some(quote! {
+                c_string_return!("version", CStr)
+                c_string_return!("missing", CStr)
+                c_string_return!("Person::name", String)
+                c_string_return!("Person::nickname", String)
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
 }
 
 #[test]
-fn test_give_pod_typedef_by_value() {
-    let cxx = indoc! {"
-        Horace give_bob() {
-            Horace a;
+fn test_c_string_return_borrowed_from_method() {
+    let hdr = indoc! {"
+        #include <string>
+        class Person {
+        public:
+            Person() : n(\"Ada\") {}
+            const char* name() const { return n.c_str(); }
+            void rename(const std::string& name) { n = name; }
+        private:
+            std::string n;
+        };
+    "};
+    let rs = quote! {
+        let mut p = ffi::Person::new().within_box();
+        // The string borrows from `p`, so it has to go before `p` can be
+        // renamed or dropped.
+        let name: Option<&std::ffi::CStr> = p.name();
+        assert_eq!(name.unwrap().to_str().unwrap(), "Ada");
+        autocxx::cxx::let_cxx_string!(new_name = "Grace");
+        p.as_mut().rename(&new_name);
+        assert_eq!(p.name().unwrap().to_str().unwrap(), "Grace");
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Person"],
+            &[],
+            Some(quote! {
+                c_string_return!("Person::name", CStr)
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_cfg_gate() {
+    // enable_hugepages is declared but never defined, so any C++ calling
+    // it would fail to link.
+    let hdr = indoc! {"
+        #include <cstdint>
+        bool enable_hugepages();
+        inline uint32_t page_size() { return 4096; }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::page_size(), 4096);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["enable_hugepages", "page_size"],
+            &[],
+            Some(quote! {
+                cfg_gate!("enable_hugepages", autocxx_test_never_set)
+                cfg_gate!("page_size", not(autocxx_test_never_set))
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_singleton() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <string>
+        class Config {
+        public:
+            static Config& instance();
+            uint32_t get_verbosity() const { return verbosity; }
+        private:
+            Config() : verbosity(3) {}
+            std::string name;
+            uint32_t verbosity;
+        };
+    "};
+    let cxx = indoc! {"
+        Config& Config::instance() {
+            static Config config;
+            return config;
+        }
+    "};
+    let rs = quote! {
+        let config: &'static ffi::Config = ffi::Config::instance();
+        assert_eq!(config.get_verbosity(), 3);
+    };
+    run_test_ex(
+        cxx,
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Config"],
+            &[],
+            Some(quote! { singleton!("Config::instance") }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_return_borrows() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Key {
+            uint32_t idx;
+        };
+        class Table {
+        public:
+            Table() : values{10, 20, 30} {}
+            const uint32_t& lookup(const Key& key) const { return values[key.idx]; }
+        private:
+            uint32_t values[3];
+        };
+        inline const uint32_t& pick(const Table& table, const Key& key) {
+            return table.lookup(key);
+        }
+    "};
+    let rs = quote! {
+        let table = ffi::Table::new().within_unique_ptr();
+        let table = table.as_ref().unwrap();
+        let (a, b) = {
+            // The keys are dropped before the returned references are used.
+            let key1 = ffi::Key { idx: 1 };
+            let key2 = ffi::Key { idx: 2 };
+            (table.lookup(&key1), ffi::pick(table, &key2))
+        };
+        assert_eq!(*a, 20);
+        assert_eq!(*b, 30);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Table", "pick"],
+            &["Key"],
+            Some(quote! {
+                return_borrows!("Table::lookup", "self")
+                return_borrows!("pick", "table")
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_method_of() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        class Thing {
+        public:
+            Thing() : count(0) {}
+            uint32_t get_count() const { return count; }
+            uint32_t count;
+        };
+        inline void process(Thing& thing, uint32_t amount) {
+            thing.count += amount;
+        }
+        inline uint32_t doubled(const Thing& thing) {
+            return thing.get_count() * 2;
+        }
+    "};
+    let rs = quote! {
+        let mut thing = ffi::Thing::new().within_box();
+        thing.as_mut().process(3);
+        assert_eq!(thing.get_count(), 3);
+        assert_eq!(thing.doubled(), 6);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Thing", "process", "doubled"],
+            &[],
+            Some(quote! {
+                method_of!("process", "Thing")
+                method_of!("doubled", "Thing")
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_static_func_wrapper() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <string>
+        struct A {
+            std::string a;
+            static A CreateA(std::string a, std::string) {
+                A c;
+                c.a = a;
+                return c;
+            }
+        };
+    "};
+    let rs = quote! {
+        use ffi::ToCppString;
+        ffi::A::CreateA("a".into_cpp(), "b".into_cpp());
+    };
+    run_test("", hdr, rs, &["A"], &[]);
+}
+
+#[test]
+fn test_static_data_member() {
+    let cxx = indoc! {"
+        uint32_t Counter::count = 3;
+        const uint32_t Counter::limit = 10;
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        class Counter {
+        public:
+            static uint32_t count;
+            static const uint32_t limit;
+        };
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::Counter::get_count(), 3);
+        unsafe { ffi::Counter::set_count(ffi::Counter::get_count() + 1) };
+        assert_eq!(ffi::Counter::get_count(), 4);
+        assert_eq!(ffi::Counter::get_limit(), 10);
+    };
+    run_test(cxx, hdr, rs, &["Counter"], &[]);
+}
+
+#[test]
+fn test_give_pod_typedef_by_value() {
+    let cxx = indoc! {"
+        Horace give_bob() {
+            Horace a;
             a.a = 3;
             a.b = 4;
             return a;
@@ -4461,6 +5888,41 @@ fn test_vector_cycle_bare() {
     run_test("", hdr, rs, &["take_vec", "get_vec"], &[]);
 }
 
+#[test]
+fn test_template_argument_survives_gc() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <vector>
+        struct Item {
+            uint32_t value;
+        };
+        template<typename T> struct Holder {
+            T held;
+        };
+        class Inventory {
+        public:
+            Inventory() {
+                items.push_back(Item { 3 });
+                items.push_back(Item { 4 });
+                holder.held = Item { 5 };
+            }
+            const std::vector<Item>& get_items() const { return items; }
+            const Holder<Item>& get_holder() const { return holder; }
+        private:
+            std::vector<Item> items;
+            Holder<Item> holder;
+        };
+    "};
+    let rs = quote! {
+        let inventory = ffi::Inventory::new().within_unique_ptr();
+        let items = inventory.get_items();
+        assert_eq!(items.len(), 2);
+        let _first: &ffi::Item = items.get(0).unwrap();
+        let _holder = inventory.get_holder();
+    };
+    run_test("", hdr, rs, &["Inventory"], &[]);
+}
+
 #[test]
 fn test_cycle_up_of_vec() {
     let hdr = indoc! {"
@@ -4542,6 +6004,45 @@ fn test_typedef_in_pod_struct() {
     run_test("", hdr, rs, &["take_a"], &["A"]);
 }
 
+#[test]
+fn test_newtype_typedef() {
+    let hdr = indoc! {"
+        typedef int UserId;
+        typedef int GroupId;
+        inline UserId get_user() {
+            return 3;
+        }
+        inline GroupId group_for(UserId user) {
+            return user + 100;
+        }
+    "};
+    let rs = quote! {
+        let user = ffi::get_user();
+        assert_eq!(user.0, 3);
+        let group = ffi::group_for(user);
+        assert_eq!(i32::from(group), 103);
+        assert_eq!(ffi::group_for(ffi::UserId::from(5)).0, 105);
+        static_assertions::assert_not_impl_any!(ffi::UserId: From<ffi::GroupId>);
+        static_assertions::assert_not_impl_any!(ffi::GroupId: From<ffi::UserId>);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate!("get_user")
+            generate!("group_for")
+            generate_pod!("UserId")
+            generate_pod!("GroupId")
+            newtype_typedef!("UserId")
+            newtype_typedef!("GroupId")
+        },
+        None,
+        None,
+        None,
+    );
+}
+
 #[test]
 fn test_cint_in_pod_struct() {
     let hdr = indoc! {"
@@ -5088,6 +6589,50 @@ fn test_union_pod() {
     run_test_expect_fail("", hdr, rs, &[], &["A"]);
 }
 
+#[test]
+fn test_opaque_fallback() {
+    let hdr = indoc! {"
+    #include <cstdint>
+    union Value {
+        uint32_t a;
+        float b;
+    };
+    struct Holder {
+        Value value;
+        uint32_t tag;
+    };
+    inline Holder make_holder() {
+        Holder h;
+        h.value.a = 3;
+        h.tag = 7;
+        return h;
+    }
+    inline uint32_t get_value(const Holder& h) {
+        return h.value.a;
+    }
+    "};
+    let rs = quote! {
+        let h = ffi::make_holder();
+        assert_eq!(h.tag, 7);
+        assert_eq!(ffi::get_value(&h), 3);
+        assert_eq!(std::mem::size_of::<ffi::Holder>(), 8);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate!("make_holder")
+            generate!("get_value")
+            generate_pod!("Holder")
+            opaque_fallback!("Value")
+        },
+        None,
+        None,
+        None,
+    );
+}
+
 #[test]
 fn test_type_aliased_anonymous_union_ignored() {
     let hdr = indoc! {"
@@ -5543,6 +7088,150 @@ fn test_pointer_to_pointer() {
     run_test("", hdr, rs, &["operations_research::Solver"], &[]);
 }
 
+#[test]
+fn test_out_params() {
+    let cxx = indoc! {"
+        bool divide(uint32_t a, uint32_t b, uint32_t* quotient) {
+            if (b == 0) {
+                return false;
+            }
+            *quotient = a / b;
+            return true;
+        }
+        void split(uint32_t a, uint32_t* hi, uint32_t* lo) {
+            *hi = a >> 16;
+            *lo = a & 0xffff;
+        }
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        bool divide(uint32_t a, uint32_t b, uint32_t* quotient);
+        void split(uint32_t a, uint32_t* hi, uint32_t* lo);
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::divide(7, 2), (true, 3));
+        assert!(!ffi::divide(7, 0).0);
+        assert_eq!(ffi::split(0x12345), (1, 0x2345));
+    };
+    run_test_ex(
+        cxx,
+        hdr,
+        rs,
+        directives_from_lists(
+            &["divide", "split"],
+            &[],
+            Some(quote! {
+                out_param!("divide", "quotient")
+                out_param!("split", "hi")
+                out_param!("split", "lo")
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_out_param_without_default() {
+    // POD structs don't implement `Default`, so we've nothing to pass
+    // in for C++ to overwrite.
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Point {
+            uint32_t x;
+            uint32_t y;
+        };
+        inline void origin(Point* point) {
+            point->x = 0;
+            point->y = 0;
+        }
+    "};
+    let rs = quote! {
+        ffi::origin();
+    };
+    run_test_expect_fail_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["origin"],
+            &["Point"],
+            Some(quote! {
+                out_param!("origin", "point")
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_out_param_not_found() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline void split(uint32_t a, uint32_t* hi, uint32_t* lo) {
+            *hi = a >> 16;
+            *lo = a & 0xffff;
+        }
+    "};
+    run_test_expect_fail_ex(
+        "",
+        hdr,
+        quote! {},
+        directives_from_lists(
+            &["split"],
+            &[],
+            Some(quote! {
+                out_param!("split", "middle")
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_custom_type_conversion() {
+    let cxx = indoc! {"
+        Celsius warmer(Celsius c, double by) {
+            return Celsius(c.get() + by);
+        }
+    "};
+    let hdr = indoc! {"
+        class Celsius {
+        public:
+            explicit Celsius(double degrees) : degrees(degrees) {}
+            ~Celsius() {}
+            double get() const { return degrees; }
+        private:
+            double degrees;
+        };
+        Celsius warmer(Celsius c, double by);
+    "};
+    let rs = quote! {
+        let warmed: f64 = ffi::warmer(20.0, 1.5);
+        assert_eq!(warmed, 21.5);
+    };
+    run_test_ex(
+        cxx,
+        hdr,
+        rs,
+        directives_from_lists(
+            &["warmer"],
+            &[],
+            Some(quote! {
+                custom_type_conversion!("Celsius", f64, "Celsius({})", "{}.get()")
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
 #[test]
 fn test_defines_effective() {
     let hdr = indoc! {"
@@ -5896,75 +7585,392 @@ fn test_bridge_conflict_ty() {
             struct Key { int a; };
         }
     "};
-    let rs = quote! {};
-    run_test("", hdr, rs, &["a::Key", "b::Key"], &[]);
+    let rs = quote! {};
+    run_test("", hdr, rs, &["a::Key", "b::Key"], &[]);
+}
+
+#[test]
+fn test_bridge_conflict_ty_fn() {
+    let hdr = indoc! {"
+        namespace a {
+            struct Key { int a; };
+        }
+        namespace b {
+            inline void Key() {}
+        }
+    "};
+    let rs = quote! {};
+    run_test("", hdr, rs, &["a::Key", "b::Key"], &[]);
+}
+
+#[test]
+fn test_issue_506() {
+    let hdr = indoc! {"
+        namespace std {
+            template <class, class> class am;
+            typedef am<char, char> an;
+        } // namespace std
+        namespace be {
+            class bf {
+            virtual std::an bg() = 0;
+            };
+            class bh : bf {};
+        } // namespace be
+        namespace spanner {
+            class Database;
+            class Row {
+            public:
+            Row(be::bh *);
+            };
+        } // namespace spanner
+    "};
+    let rs = quote! {};
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["spanner::Database", "spanner::Row"], &[], None),
+        // This is normally a valid warning for generating bindings for this code, but we're doing
+        // it on purpose as a regression test on minimized code so we'll just ignore it.
+        make_clang_optional_arg_adder(&[], &["-Wno-delete-abstract-non-virtual-dtor"]),
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_private_inheritance() {
+    let hdr = indoc! {"
+        class A {
+        public:
+            void foo() {}
+            int a;
+        };
+        class B : A {
+        public:
+            void bar() {}
+            int b;
+        };
+    "};
+    let rs = quote! {
+        let mut b = ffi::B::new().within_unique_ptr();
+        b.pin_mut().bar();
+    };
+    run_test("", hdr, rs, &["A", "B"], &[]);
+    // The interface of a private base isn't available through B.
+    let rs = quote! {
+        let mut b = ffi::B::new().within_unique_ptr();
+        b.pin_mut().foo();
+    };
+    run_test_expect_fail("", hdr, rs, &["A", "B"], &[]);
+    let rs = quote! {
+        let b = ffi::B::new().within_unique_ptr();
+        let _: &ffi::A = b.as_ref().unwrap().as_ref();
+    };
+    run_test_expect_fail("", hdr, rs, &["A", "B"], &[]);
+}
+
+#[test]
+fn test_base_field_accessors() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct A {
+            uint32_t a;
+        };
+        struct B : public A {
+            uint32_t b;
+        };
+        struct C : public B {
+            uint32_t c;
+        };
+        inline C make_c() {
+            C c;
+            c.a = 1;
+            c.b = 2;
+            c.c = 3;
+            return c;
+        }
+        inline uint32_t get_a(const C& c) { return c.a; }
+    "};
+    let rs = quote! {
+        let mut c = ffi::make_c();
+        assert_eq!(*c.a(), 1);
+        assert_eq!(*c.b(), 2);
+        assert_eq!(c.c, 3);
+        *c.a_mut() = 4;
+        assert_eq!(ffi::get_a(&c), 4);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["make_c", "get_a"],
+            &["A", "B", "C"],
+            Some(quote! { base_field_accessors!("C") }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_base_field_accessors_not_pod() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <string>
+        struct A {
+            uint32_t a;
+        };
+        struct B : public A {
+            std::string b;
+        };
+    "};
+    run_test_expect_fail_ex(
+        "",
+        hdr,
+        quote! {},
+        directives_from_lists(&["B"], &[], Some(quote! { base_field_accessors!("B") })),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_base_field_accessors_clash() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct A {
+            uint32_t a;
+        };
+        struct B : public A {
+            uint32_t b;
+            uint32_t a_mut() const { return a + 1; }
+        };
+    "};
+    run_test_expect_fail_ex(
+        "",
+        hdr,
+        quote! {},
+        directives_from_lists(&[], &["B"], Some(quote! { base_field_accessors!("B") })),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_crtp() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        template <typename Derived> class Counter {
+        public:
+            uint32_t count() const {
+                return static_cast<const Derived*>(this)->base_count() + 1;
+            }
+        protected:
+            Derived* next = nullptr;
+        };
+        template <typename Derived> class Shape : public Counter<Derived> {
+        public:
+            uint32_t sides() const { return static_cast<const Derived*>(this)->corners(); }
+        };
+        class Widget : public Counter<Widget> {
+        public:
+            Widget() {}
+            uint32_t base_count() const { return 41; }
+            uint32_t value() const { return count(); }
+        };
+        class Square : public Shape<Square> {
+        public:
+            Square() {}
+            uint32_t base_count() const { return 1; }
+            uint32_t corners() const { return 4; }
+            uint32_t total() const { return sides() + count(); }
+        };
+    "};
+    let rs = quote! {
+        let w = ffi::Widget::new().within_unique_ptr();
+        assert_eq!(w.value(), 42);
+        let s = ffi::Square::new().within_unique_ptr();
+        assert_eq!(s.corners(), 4);
+        assert_eq!(s.total(), 6);
+    };
+    run_test("", hdr, rs, &["Widget", "Square", "Counter", "Shape"], &[]);
+}
+
+#[test]
+fn test_swappable() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <string>
+        #include <utility>
+        class Buffer {
+        public:
+            Buffer(uint32_t v) : contents(std::to_string(v)), swaps(0) {}
+            uint32_t get() const { return std::stoul(contents); }
+            uint32_t swap_count() const { return swaps; }
+            friend void swap(Buffer& a, Buffer& b) {
+                std::swap(a.contents, b.contents);
+                a.swaps++;
+                b.swaps++;
+            }
+        private:
+            std::string contents;
+            uint32_t swaps;
+        };
+    "};
+    let rs = quote! {
+        let mut a = ffi::Buffer::new(1).within_box();
+        let mut b = ffi::Buffer::new(2).within_box();
+        a.as_mut().swap(b.as_mut());
+        assert_eq!(a.get(), 2);
+        assert_eq!(b.get(), 1);
+        // The custom swap was used, rather than std::swap.
+        assert_eq!(a.swap_count(), 1);
+        assert_eq!(b.swap_count(), 1);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["Buffer"], &[], Some(quote! { swappable!("Buffer") })),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_enable_shared_from_this() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <memory>
+        class Node : public std::enable_shared_from_this<Node> {
+        public:
+            static std::shared_ptr<Node> create(uint32_t value) {
+                return std::shared_ptr<Node>(new Node(value));
+            }
+            static std::unique_ptr<Node> create_unowned(uint32_t value) {
+                return std::unique_ptr<Node>(new Node(value));
+            }
+            uint32_t get_value() const { return value; }
+        private:
+            Node(uint32_t value) : value(value) {}
+            uint32_t value;
+        };
+    "};
+    let rs = quote! {
+        let node = ffi::Node::create(42);
+        let again = node.shared_from_this();
+        assert!(!again.is_null());
+        assert_eq!(again.get_value(), 42);
+        assert!(std::ptr::eq(node.as_ref().unwrap(), again.as_ref().unwrap()));
+        let unowned = ffi::Node::create_unowned(7);
+        assert!(unowned.shared_from_this().is_null());
+    };
+    run_test("", hdr, rs, &["Node"], &[]);
 }
 
 #[test]
-fn test_bridge_conflict_ty_fn() {
+fn test_throwing_constructors() {
     let hdr = indoc! {"
-        namespace a {
-            struct Key { int a; };
-        }
-        namespace b {
-            inline void Key() {}
-        }
+        #include <cstdint>
+        #include <stdexcept>
+        class Connection {
+        public:
+            Connection(uint32_t port) : port(port) {
+                if (port == 0) {
+                    throw std::invalid_argument(\"port must be nonzero\");
+                }
+            }
+            uint32_t get_port() const { return port; }
+        private:
+            uint32_t port;
+        };
     "};
-    let rs = quote! {};
-    run_test("", hdr, rs, &["a::Key", "b::Key"], &[]);
+    let rs = quote! {
+        let c = ffi::Connection::try_new(80).unwrap();
+        assert_eq!(c.get_port(), 80);
+        let err = ffi::Connection::try_new(0).unwrap_err();
+        assert_eq!(err.what(), "port must be nonzero");
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Connection"],
+            &[],
+            Some(quote! { throwing_constructors!("Connection") }),
+        ),
+        None,
+        None,
+        None,
+    );
 }
 
 #[test]
-fn test_issue_506() {
+fn test_instantiate_enum_templated_function() {
     let hdr = indoc! {"
-        namespace std {
-            template <class, class> class am;
-            typedef am<char, char> an;
-        } // namespace std
-        namespace be {
-            class bf {
-            virtual std::an bg() = 0;
-            };
-            class bh : bf {};
-        } // namespace be
-        namespace spanner {
-            class Database;
-            class Row {
-            public:
-            Row(be::bh *);
-            };
-        } // namespace spanner
+        #include <cstdint>
+        enum class Mode { Slow, Fast };
+        template<Mode M> uint32_t run(uint32_t input) {
+            return M == Mode::Fast ? input * 2 : input;
+        }
     "};
-    let rs = quote! {};
+    let rs = quote! {
+        assert_eq!(ffi::run_fast(3), 6);
+        assert_eq!(ffi::run_slow(3), 3);
+    };
     run_test_ex(
         "",
         hdr,
         rs,
-        directives_from_lists(&["spanner::Database", "spanner::Row"], &[], None),
-        // This is normally a valid warning for generating bindings for this code, but we're doing
-        // it on purpose as a regression test on minimized code so we'll just ignore it.
-        make_clang_optional_arg_adder(&[], &["-Wno-delete-abstract-non-virtual-dtor"]),
+        quote! {
+            instantiate!("run<Mode::Fast>", run_fast)
+            instantiate!("run<Mode::Slow>", run_slow)
+        },
+        None,
         None,
         None,
     );
 }
 
 #[test]
-fn test_private_inheritance() {
+fn test_instantiate_member_function_template() {
     let hdr = indoc! {"
-        class A {
-        public:
-            void foo() {}
-            int a;
-        };
-        class B : A {
+        #include <cstdint>
+        class Container {
         public:
-            void bar() {}
-            int b;
+            Container() : value(42) {}
+            template<typename T> T get() const { return static_cast<T>(value); }
+            template<typename T> void set(T v) { value = static_cast<uint32_t>(v); }
+        private:
+            uint32_t value;
         };
     "};
-    let rs = quote! {};
-    run_test("", hdr, rs, &["A", "B"], &[]);
+    let rs = quote! {
+        let mut c = ffi::Container::new().within_unique_ptr();
+        assert_eq!(c.get_int(), autocxx::c_int(42));
+        c.pin_mut().set_double(7.0);
+        assert_eq!(c.get_int(), autocxx::c_int(7));
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate!("Container")
+            instantiate_method!("Container::get<int>", get_int)
+            instantiate_method!("Container::set<double>", set_double)
+        },
+        None,
+        None,
+        None,
+    );
 }
 
 #[test]
@@ -6307,6 +8313,136 @@ fn test_ref_qualified_method() {
     run_test("", hdr, rs, &["A"], &[]);
 }
 
+#[test]
+fn test_pin_box_immovable() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <mutex>
+        class Counter {
+        public:
+            Counter(uint32_t start) : value(start) {}
+            Counter(const Counter&) = delete;
+            Counter& operator=(const Counter&) = delete;
+            uint32_t increment() {
+                std::lock_guard<std::mutex> lock(mutex);
+                return ++value;
+            }
+        private:
+            std::mutex mutex;
+            uint32_t value;
+        };
+        inline Counter make_counter() { return Counter(10); }
+    "};
+    let rs = quote! {
+        let mut a: std::pin::Pin<Box<ffi::Counter>> = ffi::Counter::new(1);
+        assert_eq!(a.as_mut().increment(), 2);
+        let mut b: std::pin::Pin<Box<ffi::Counter>> = ffi::make_counter();
+        assert_eq!(b.as_mut().increment(), 11);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Counter", "make_counter"],
+            &[],
+            Some(quote! { pin_box!("Counter") }),
+        ),
+        make_cpp17_adder(),
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_restrict_pointers() {
+    let hdr = indoc! {"
+        #include <cstddef>
+        #define restrict __restrict
+        inline void add_arrays(float* restrict out, const float* restrict a,
+                               const float* restrict b, size_t n) {
+            for (size_t i = 0; i < n; i++) {
+                out[i] = a[i] + b[i];
+            }
+        }
+        struct Accumulator {
+            float total = 0.0f;
+            void add(const float* restrict values, size_t n) restrict {
+                for (size_t i = 0; i < n; i++) {
+                    total += values[i];
+                }
+            }
+            float get_total() const { return total; }
+        };
+    "};
+    let rs = quote! {
+        let a = [1.0f32, 2.0];
+        let b = [3.0f32, 4.0];
+        let mut out = [0.0f32; 2];
+        unsafe { ffi::add_arrays(out.as_mut_ptr(), a.as_ptr(), b.as_ptr(), 2) };
+        assert_eq!(out, [4.0, 6.0]);
+        let mut acc = ffi::Accumulator::new().within_box();
+        unsafe { acc.as_mut().add(out.as_ptr(), 2) };
+        assert_eq!(acc.get_total(), 10.0);
+    };
+    run_test("", hdr, rs, &["add_arrays", "Accumulator"], &[]);
+}
+
+#[test]
+fn test_returned_raii_guard() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline uint32_t& cleanup_count() {
+            static uint32_t count = 0;
+            return count;
+        }
+        inline uint32_t cleanups() { return cleanup_count(); }
+        class Guard {
+        public:
+            Guard() {}
+            Guard(const Guard&) = delete;
+            Guard& operator=(const Guard&) = delete;
+            ~Guard() { cleanup_count()++; }
+        };
+        template<typename T> class TypedGuard {
+        public:
+            TypedGuard() {}
+            TypedGuard(const TypedGuard&) = delete;
+            TypedGuard& operator=(const TypedGuard&) = delete;
+            ~TypedGuard() { cleanup_count()++; }
+            T value;
+        };
+        inline Guard begin_scope() { return Guard(); }
+        inline TypedGuard<uint32_t> begin_typed_scope() { return TypedGuard<uint32_t>(); }
+    "};
+    let rs = quote! {
+        {
+            let _guard = ffi::begin_scope().within_box();
+            assert_eq!(ffi::cleanups(), 0);
+        }
+        assert_eq!(ffi::cleanups(), 1);
+        {
+            moveit! { let _guard = ffi::begin_scope(); }
+            assert_eq!(ffi::cleanups(), 1);
+        }
+        assert_eq!(ffi::cleanups(), 2);
+        {
+            let _guard = ffi::begin_typed_scope();
+            assert_eq!(ffi::cleanups(), 2);
+        }
+        assert_eq!(ffi::cleanups(), 3);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["begin_scope", "begin_typed_scope", "cleanups"], &[], None),
+        make_cpp17_adder(),
+        None,
+        None,
+    );
+}
+
 #[cfg_attr(skip_windows_msvc_failing_tests, ignore)]
 #[cfg_attr(skip_windows_gnu_failing_tests, ignore)]
 #[test]
@@ -6423,6 +8559,116 @@ fn test_cint_vector() {
     run_test("", hdr, rs, &["give_vec"], &[]);
 }
 
+#[test]
+fn test_vector_slice_view() {
+    let hdr = indoc! {"
+        #include <vector>
+        #include <cstdint>
+        struct Point {
+            int32_t x;
+            int32_t y;
+        };
+        class Samples {
+        public:
+            Samples() : values{1.0f, 2.5f, 4.0f}, points{{1, 2}, {3, 4}} {}
+            const std::vector<float>& get_values() const { return values; }
+            const std::vector<Point>& get_points() const { return points; }
+            const float* values_data() const { return values.data(); }
+        private:
+            std::vector<float> values;
+            std::vector<Point> points;
+        };
+    "};
+    let rs = quote! {
+        let samples = ffi::Samples::new().within_unique_ptr();
+        let values: &[f32] = samples.get_values().as_slice();
+        assert_eq!(values, &[1.0, 2.5, 4.0]);
+        assert_eq!(values.as_ptr(), samples.values_data());
+        let points: &[ffi::Point] = samples.get_points().as_slice();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[1].y, 4);
+    };
+    run_test("", hdr, rs, &["Samples"], &["Point"]);
+}
+
+#[test]
+fn test_const_vector_getter_is_immutable() {
+    let hdr = indoc! {"
+        #include <vector>
+        class Samples {
+        public:
+            Samples() : values{1.0f, 2.5f} {}
+            const std::vector<float>& get_values() const { return values; }
+            const std::vector<float>& refresh() { values.push_back(4.0f); return values; }
+            std::vector<float>& get_values_mut() { return values; }
+        private:
+            std::vector<float> values;
+        };
+    "};
+    let rs = quote! {
+        let mut samples = ffi::Samples::new().within_unique_ptr();
+        let values: &cxx::CxxVector<f32> = samples.pin_mut().refresh();
+        assert_eq!(values.as_slice(), &[1.0, 2.5, 4.0]);
+        samples.pin_mut().get_values_mut().as_mut_slice()[0] = 0.5;
+        let values: &[f32] = samples.get_values().as_slice();
+        assert_eq!(values, &[0.5, 2.5, 4.0]);
+    };
+    run_test("", hdr, rs, &["Samples"], &[]);
+}
+
+#[test]
+fn test_const_vector_getter_cannot_be_mutated() {
+    let hdr = indoc! {"
+        #include <vector>
+        class Samples {
+        public:
+            Samples() : values{1.0f, 2.5f} {}
+            const std::vector<float>& get_values() const { return values; }
+        private:
+            std::vector<float> values;
+        };
+    "};
+    let rs = quote! {
+        let samples = ffi::Samples::new().within_unique_ptr();
+        samples.get_values().as_mut_slice()[0] = 0.5;
+    };
+    run_test_expect_fail("", hdr, rs, &["Samples"], &[]);
+}
+
+#[test]
+fn test_pass_rust_vec_as_vector_by_value() {
+    let hdr = indoc! {"
+        #include <vector>
+        #include <cstdint>
+        struct Point {
+            int32_t x;
+            int32_t y;
+        };
+        inline int32_t sum(std::vector<int32_t> values) {
+            int32_t total = 0;
+            for (auto v : values) {
+                total += v;
+            }
+            return total;
+        }
+        inline int32_t sum_x(std::vector<Point> points) {
+            int32_t total = 0;
+            for (auto& p : points) {
+                total += p.x;
+            }
+            return total;
+        }
+    "};
+    let rs = quote! {
+        let values: Vec<i32> = vec![1, 2, 3, 4];
+        assert_eq!(ffi::sum(values), 10);
+        assert_eq!(ffi::sum(Vec::<i32>::new()), 0);
+        let points = vec![ffi::Point { x: 1, y: 2 }, ffi::Point { x: 3, y: 4 }];
+        assert_eq!(ffi::sum_x(points), 4);
+    };
+    run_test("", hdr, rs, &["sum", "sum_x"], &["Point"]);
+}
+
 #[test]
 #[ignore] // https://github.com/google/autocxx/issues/422
 fn test_int_vector() {
@@ -6440,6 +8686,38 @@ fn test_int_vector() {
     run_test("", hdr, rs, &["give_vec"], &[]);
 }
 
+#[test]
+fn test_alias_template() {
+    let hdr = indoc! {"
+        #include <vector>
+        #include <memory>
+        #include <cstdint>
+        template<typename T> using Vec = std::vector<T>;
+        namespace geo {
+            template<typename T> using Coords = Vec<T>;
+        }
+        inline std::unique_ptr<Vec<uint32_t>> make_vec() {
+            return std::make_unique<Vec<uint32_t>>(Vec<uint32_t> {1, 2, 3});
+        }
+        inline uint32_t sum(const Vec<uint32_t>& v) {
+            uint32_t total = 0;
+            for (auto x : v) {
+                total += x;
+            }
+            return total;
+        }
+        inline uint32_t dimensions(const geo::Coords<double>& c) { return c.size(); }
+    "};
+    let rs = quote! {
+        let v = ffi::make_vec();
+        assert_eq!(v.as_ref().unwrap().as_slice(), &[1, 2, 3]);
+        assert_eq!(ffi::sum(v.as_ref().unwrap()), 6);
+        let c = cxx::CxxVector::<f64>::new();
+        assert_eq!(ffi::dimensions(c.as_ref().unwrap()), 0);
+    };
+    run_test("", hdr, rs, &["make_vec", "sum", "dimensions"], &[]);
+}
+
 #[test]
 fn test_size_t() {
     let hdr = indoc! {"
@@ -6464,6 +8742,55 @@ fn test_size_t() {
     );
 }
 
+#[test]
+fn test_nodiscard() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        [[nodiscard]] inline uint32_t compute() { return 4; }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::compute(), 4);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["compute"], &[], None),
+        Some(Box::new(SetNodiscardAsMustUse)),
+        Some(make_rust_code_finder(vec![quote! {
+            #[must_use]
+            pub fn compute() -> u32
+        }])),
+        None,
+    );
+}
+
+#[test]
+fn test_nodiscard_type() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct [[nodiscard]] Status {
+            uint32_t code;
+        };
+        inline Status check() { return Status { 0 }; }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::check().code, 0);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["check"], &["Status"], None),
+        Some(Box::new(SetNodiscardAsMustUse)),
+        Some(make_rust_code_finder(vec![quote! {
+            #[must_use]
+            pub fn check() -> Status
+        }])),
+        None,
+    );
+}
+
 #[test]
 fn test_deleted_function() {
     // We shouldn't generate bindings for deleted functions.
@@ -6472,11 +8799,56 @@ fn test_deleted_function() {
     let hdr = indoc! {"
         class A {
         public:
-            void foo() = delete;
+            void foo() = delete;
+        };
+    "};
+    let rs = quote! {};
+    run_test("", hdr, rs, &["A"], &[]);
+}
+
+#[test]
+fn test_deleted_copy_operations() {
+    // Deleted copy operations, whether declared directly or inherited from
+    // a base or a field, must not result in any copying traits.
+    let hdr = indoc! {"
+        #include <cstdint>
+        class NoCopy {
+        public:
+            NoCopy() {}
+            NoCopy(const NoCopy&) = delete;
+            NoCopy& operator=(const NoCopy&) = delete;
+            uint32_t get() const { return 3; }
+        };
+        class DerivedNoCopy : public NoCopy {
+        public:
+            DerivedNoCopy() {}
+        };
+        class HoldsNoCopy {
+        public:
+            HoldsNoCopy() {}
+        private:
+            NoCopy field;
+        };
+        class Copyable {
+        public:
+            Copyable() {}
         };
     "};
-    let rs = quote! {};
-    run_test("", hdr, rs, &["A"], &[]);
+    let rs = quote! {
+        static_assertions::assert_not_impl_any!(ffi::NoCopy: moveit::CopyNew, moveit::MoveNew, Clone);
+        static_assertions::assert_not_impl_any!(ffi::DerivedNoCopy: moveit::CopyNew, Clone);
+        static_assertions::assert_not_impl_any!(ffi::HoldsNoCopy: moveit::CopyNew, Clone);
+        static_assertions::assert_impl_all!(ffi::Copyable: moveit::CopyNew);
+        let a = ffi::NoCopy::new().within_unique_ptr();
+        assert_eq!(a.get(), 3);
+    };
+    run_test(
+        "",
+        hdr,
+        rs,
+        &["NoCopy", "DerivedNoCopy", "HoldsNoCopy", "Copyable"],
+        &[],
+    );
 }
 
 #[test]
@@ -6538,6 +8910,72 @@ fn test_take_nonpod_rvalue_from_stack() {
     run_test("", hdr, rs, &["A", "take_a"], &[]);
 }
 
+#[test]
+fn test_ref_qualified_methods() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <string>
+        struct A {
+            std::string a;
+            uint32_t which() const & { return 1; }
+            uint32_t which() && { return 2; }
+        };
+    "};
+    let rs = quote! {
+        let a = ffi::A::new().within_unique_ptr();
+        assert_eq!(a.which(), 1);
+        assert_eq!(ffi::A::which1(a), 2);
+
+        moveit! { let a2 = ffi::A::new() };
+        assert_eq!(a2.which(), 1);
+        assert_eq!(ffi::A::which1(a2), 2);
+    };
+    run_test("", hdr, rs, &["A"], &[]);
+}
+
+#[test]
+fn test_consuming_method() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <vector>
+        class Builder {
+        public:
+            Builder() {}
+            void add(uint32_t part) { parts.push_back(part); }
+            uint32_t build() {
+                uint32_t total = 0;
+                for (auto part : parts) {
+                    total += part;
+                }
+                parts.clear();
+                return total;
+            }
+        private:
+            std::vector<uint32_t> parts;
+        };
+    "};
+    let rs = quote! {
+        let mut b = ffi::Builder::new().within_unique_ptr();
+        b.pin_mut().add(2);
+        b.pin_mut().add(3);
+        // This takes ownership of b.
+        assert_eq!(ffi::Builder::build(b), 5);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Builder"],
+            &[],
+            Some(quote! { consuming_method!("Builder::build") }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
 #[test]
 fn test_overloaded_ignored_function() {
     // When overloaded functions are ignored during import, the placeholder
@@ -7003,6 +9441,50 @@ fn test_extern_cpp_type_manual() {
     do_run_test_manual("", hdr, rs, None, None).unwrap();
 }
 
+#[test]
+fn test_extern_cpp_enum() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        enum class Color : uint32_t {
+            Red,
+            Green = 5,
+            Blue,
+        };
+        inline Color next_color(Color c) {
+            switch (c) {
+                case Color::Red: return Color::Green;
+                case Color::Green: return Color::Blue;
+                default: return Color::Red;
+            }
+        }
+    "};
+    let rs = quote! {
+        let c: crate::Color = ffi::next_color(crate::Color::Red);
+        assert_eq!(c, crate::Color::Green);
+        assert_eq!(ffi::next_color(c), crate::Color::Blue);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate!("next_color")
+            extern_cpp_enum!("Color", crate::Color)
+        },
+        None,
+        None,
+        Some(quote! {
+            #[repr(u32)]
+            #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+            pub enum Color {
+                Red = 0,
+                Green = 5,
+                Blue = 6,
+            }
+        }),
+    );
+}
+
 #[test]
 fn test_issue486() {
     let hdr = indoc! {"
@@ -9254,6 +11736,89 @@ fn test_pv_subclass_namespaced_superclass() {
     );
 }
 
+#[test]
+fn test_interface_implemented_in_rust() {
+    let hdr = indoc! {"
+    #include <cstdint>
+    #include <memory>
+
+    class Observer {
+    public:
+        virtual uint32_t foo(uint32_t a) const = 0;
+        virtual void bar() = 0;
+        virtual ~Observer() {}
+    };
+    inline uint32_t call_observer(std::unique_ptr<Observer> obs) {
+        obs->bar();
+        return obs->foo(3);
+    }
+    "};
+    run_test_ex(
+        "",
+        hdr,
+        quote! {
+            let obs = ffi::Observer::from_rust_impl(MyObserver { b: 0 });
+            assert_eq!(ffi::call_observer(obs), 4);
+        },
+        quote! {
+            generate!("call_observer")
+            interface!("Observer")
+        },
+        None,
+        None,
+        Some(quote! {
+            struct MyObserver {
+                b: u32
+            }
+            impl ffi::Observer_methods for MyObserver {
+                fn foo(&self, a: u32) -> u32 {
+                    a + self.b
+                }
+                fn bar(&mut self) {
+                    self.b = 1;
+                }
+            }
+        }),
+    );
+}
+
+#[test]
+fn test_interface_not_pure_virtual() {
+    let hdr = indoc! {"
+    #include <cstdint>
+    #include <memory>
+
+    class Observer {
+    public:
+        virtual uint32_t foo() const = 0;
+        virtual void bar() {}
+        virtual ~Observer() {}
+    };
+    inline void take_observer(std::unique_ptr<Observer>) {}
+    "};
+    run_test_expect_fail_ex(
+        "",
+        hdr,
+        quote! {
+            ffi::take_observer(ffi::Observer::from_rust_impl(MyObserver));
+        },
+        quote! {
+            generate!("take_observer")
+            interface!("Observer")
+        },
+        None,
+        None,
+        Some(quote! {
+            struct MyObserver;
+            impl ffi::Observer_methods for MyObserver {
+                fn foo(&self) -> u32 {
+                    4
+                }
+            }
+        }),
+    );
+}
+
 #[test]
 fn test_no_constructor_make_unique() {
     let hdr = indoc! {"
@@ -9846,6 +12411,61 @@ fn test_no_rvo_move() {
     );
 }
 
+#[test]
+fn test_shim_prefix() {
+    let hdr = indoc! {"
+    #include <string>
+    #include <cstdint>
+    inline uint32_t get_len(std::string s) { return s.size(); }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::get_len("abc"), 3);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate!("get_len")
+            shim_prefix!("mylib_")
+        },
+        None,
+        Some(Box::new(CppMatcher::new(
+            &["mylib_get_len_0x"],
+            &["autocxx_wrapper"],
+        ))),
+        None,
+    );
+}
+
+#[test]
+fn test_shim_name() {
+    let hdr = indoc! {"
+    #include <string>
+    #include <cstdint>
+    inline uint32_t get_len(std::string s) { return s.size(); }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::get_len("abc"), 3);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate!("get_len")
+            shim_prefix!("mylib_")
+            shim_name!("get_len", "mylib_string_length")
+        },
+        None,
+        Some(Box::new(CppMatcher::new(
+            &["mylib_string_length("],
+            &["autocxx_wrapper", "mylib_get_len"],
+        ))),
+        None,
+    );
+}
+
 #[test]
 fn test_abstract_up() {
     let hdr = indoc! {"
@@ -9869,40 +12489,131 @@ fn test_abstract_up() {
 }
 
 #[test]
-fn test_abstract_up_multiple_bridge() {
+fn test_abstract_up_multiple_bridge() {
+    let hdr = indoc! {"
+    #include <memory>
+    class A {
+    public:
+        virtual void foo() const = 0;
+        virtual ~A() {}
+    };
+    class B : public A {
+    public:
+        void foo() const {}
+    };
+    inline std::unique_ptr<A> get_a() { return std::make_unique<B>(); }
+    "};
+    let hexathorpe = Token![#](Span::call_site());
+    let rs = quote! {
+        autocxx::include_cpp! {
+            #hexathorpe include "input.h"
+            safety!(unsafe_ffi)
+            generate!("A")
+        }
+        autocxx::include_cpp! {
+            #hexathorpe include "input.h"
+            safety!(unsafe_ffi)
+            name!(ffi2)
+            extern_cpp_type!("A", crate::ffi::A)
+            generate!("get_a")
+        }
+        fn main() {
+            let a = ffi2::get_a();
+            a.foo();
+        }
+    };
+    do_run_test_manual("", hdr, rs, None, None).unwrap();
+}
+
+#[test]
+fn test_upcast_unique_ptr() {
+    let hdr = indoc! {"
+    #include <cstdint>
+    #include <memory>
+    class Shape {
+    public:
+        virtual uint32_t sides() const = 0;
+        uint32_t doubled_sides() const { return sides() * 2; }
+        virtual ~Shape() {}
+    };
+    class Triangle : public Shape {
+    public:
+        uint32_t sides() const override { return 3; }
+    };
+    inline std::unique_ptr<Shape> make_shape() { return std::make_unique<Triangle>(); }
+    inline std::unique_ptr<Triangle> make_triangle() { return std::make_unique<Triangle>(); }
+    "};
+    let rs = quote! {
+        let shape = ffi::make_shape();
+        assert_eq!(shape.sides(), 3);
+        assert_eq!(shape.doubled_sides(), 6);
+        let triangle = ffi::make_triangle();
+        let shape: cxx::UniquePtr<ffi::Shape> = ffi::Triangle::upcast_to_Shape(triangle);
+        assert_eq!(shape.sides(), 3);
+        assert_eq!(shape.doubled_sides(), 6);
+    };
+    run_test(
+        "",
+        hdr,
+        rs,
+        &["Shape", "Triangle", "make_shape", "make_triangle"],
+        &[],
+    );
+}
+
+#[test]
+fn test_upcast_unique_ptr_inherited_virtual_destructor() {
+    let hdr = indoc! {"
+    #include <cstdint>
+    #include <memory>
+    class Shape {
+    public:
+        virtual uint32_t sides() const = 0;
+        virtual ~Shape() {}
+    };
+    class Polygon : public Shape {};
+    class Square : public Polygon {
+    public:
+        uint32_t sides() const override { return 4; }
+        ~Square() {}
+    };
+    inline std::unique_ptr<Square> make_square() { return std::make_unique<Square>(); }
+    "};
+    let rs = quote! {
+        let polygon: cxx::UniquePtr<ffi::Polygon> = ffi::Square::upcast_to_Polygon(ffi::make_square());
+        assert_eq!(polygon.sides(), 4);
+    };
+    run_test(
+        "",
+        hdr,
+        rs,
+        &["Shape", "Polygon", "Square", "make_square"],
+        &[],
+    );
+}
+
+#[test]
+fn test_no_upcast_unique_ptr_without_virtual_destructor() {
+    // Deleting a Triangle through a pointer to a Shape would be undefined
+    // behavior, so we mustn't allow it.
     let hdr = indoc! {"
+    #include <cstdint>
     #include <memory>
-    class A {
+    class Shape {
     public:
-        virtual void foo() const = 0;
-        virtual ~A() {}
+        virtual uint32_t sides() const = 0;
+        ~Shape() {}
     };
-    class B : public A {
+    class Triangle : public Shape {
     public:
-        void foo() const {}
+        uint32_t sides() const override { return 3; }
     };
-    inline std::unique_ptr<A> get_a() { return std::make_unique<B>(); }
+    inline std::unique_ptr<Triangle> make_triangle() { return std::make_unique<Triangle>(); }
     "};
-    let hexathorpe = Token![#](Span::call_site());
     let rs = quote! {
-        autocxx::include_cpp! {
-            #hexathorpe include "input.h"
-            safety!(unsafe_ffi)
-            generate!("A")
-        }
-        autocxx::include_cpp! {
-            #hexathorpe include "input.h"
-            safety!(unsafe_ffi)
-            name!(ffi2)
-            extern_cpp_type!("A", crate::ffi::A)
-            generate!("get_a")
-        }
-        fn main() {
-            let a = ffi2::get_a();
-            a.foo();
-        }
+        ffi::Triangle::upcast_to_Shape(ffi::make_triangle());
     };
-    do_run_test_manual("", hdr, rs, None, None).unwrap();
+    run_test_expect_fail("", hdr, rs, &["Shape", "Triangle", "make_triangle"], &[]);
 }
 
 #[test]
@@ -11479,6 +14190,222 @@ fn test_concretize() {
     );
 }
 
+#[test]
+fn test_concretize_nested_enum() {
+    let hdr = indoc! {"
+        template<typename T>
+        class Container {
+        public:
+            enum State {
+                Empty,
+                Full,
+            };
+            Container() : state(Empty), contents(nullptr) {}
+            State state;
+        private:
+            T* contents;
+        };
+    "};
+    let rs = quote! {
+        let state = ffi::ContainerOfInt_State::Full;
+        assert_eq!(state as u32, 1);
+        assert_eq!(ffi::ContainerOfInt_State::Empty as u32, 0);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            concrete!("Container<int>", ContainerOfInt)
+            generate!("Container")
+        },
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_concretize_integer_template_args() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        template<typename T, int N>
+        class FixedVector {
+        public:
+            uint32_t size() const { return N; }
+        private:
+            T contents[N];
+        };
+        inline FixedVector<float, 4> make_small() { return FixedVector<float, 4>(); }
+        inline FixedVector<float, 8> make_large() { return FixedVector<float, 8>(); }
+    "};
+    let rs = quote! {
+        let small: cxx::UniquePtr<ffi::FixedVector_float_N<4>> = ffi::make_small().within_unique_ptr();
+        let large: cxx::UniquePtr<ffi::FixedVector_float_N<8>> = ffi::make_large().within_unique_ptr();
+        let _: &ffi::FixedVectorOf4 = small.as_ref().unwrap();
+        let _: &ffi::FixedVectorOf8 = large.as_ref().unwrap();
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            concrete!("FixedVector<float, 4>", FixedVectorOf4)
+            concrete!("FixedVector<float, 8>", FixedVectorOf8)
+            generate!("make_small")
+            generate!("make_large")
+        },
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_concretize_std_map_iteration() {
+    let hdr = indoc! {"
+        #include <map>
+        inline std::map<int, int> make_squares() {
+            std::map<int, int> squares;
+            for (int i = 3; i > 0; i--) {
+                squares[i] = i * i;
+            }
+            return squares;
+        }
+    "};
+    let rs = quote! {
+        let squares = ffi::make_squares().within_unique_ptr();
+        let entries: Vec<_> = squares.as_ref().unwrap().iter().collect();
+        assert_eq!(entries, vec![
+            (autocxx::c_int(1), autocxx::c_int(1)),
+            (autocxx::c_int(2), autocxx::c_int(4)),
+            (autocxx::c_int(3), autocxx::c_int(9)),
+        ]);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            concrete!("std::map<int, int>", IntMap)
+            generate!("make_squares")
+        },
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_range_function_iteration() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <vector>
+        struct Point {
+            int32_t x;
+            int32_t y;
+        };
+        inline std::vector<uint32_t> primes() {
+            return {2, 3, 5, 7};
+        }
+        class Square {
+        public:
+            static const std::vector<Point>& corners() {
+                static const std::vector<Point> c{{0, 0}, {1, 0}, {1, 1}, {0, 1}};
+                return c;
+            }
+        };
+    "};
+    let rs = quote! {
+        let primes: Vec<u32> = ffi::Primes::new().collect();
+        assert_eq!(primes, vec![2, 3, 5, 7]);
+        let xs: Vec<i32> = ffi::Corners::new().map(|p| p.x).collect();
+        assert_eq!(xs, vec![0, 1, 1, 0]);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            range!("primes", "uint32_t", Primes)
+            range!("Square::corners", "Point", Corners)
+            generate_pod!("Point")
+        },
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_std_variant_round_trip() {
+    let cxx = indoc! {"
+        std::variant<uint32_t, bool> flip(const std::variant<uint32_t, bool>& v) {
+            if (v.index() == 0) {
+                return std::get<0>(v) != 0;
+            }
+            return std::get<1>(v) ? 1u : 0u;
+        }
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <variant>
+        std::variant<uint32_t, bool> flip(const std::variant<uint32_t, bool>& v);
+    "};
+    let rs = quote! {
+        let v = ffi::MyVariant::U32(3).into_cpp();
+        let flipped = ffi::flip(&v).within_unique_ptr();
+        assert!(matches!(ffi::MyVariant::from_cpp(&flipped), ffi::MyVariant::Bool(true)));
+        let v = ffi::MyVariant::Bool(false).into_cpp();
+        let flipped = ffi::flip(&v).within_unique_ptr();
+        assert!(matches!(ffi::MyVariant::from_cpp(&flipped), ffi::MyVariant::U32(0)));
+    };
+    run_test_ex(
+        cxx,
+        hdr,
+        rs,
+        quote! {
+            variant!("std::variant<uint32_t, bool>", MyVariant)
+            generate!("flip")
+        },
+        make_cpp17_adder(),
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_std_tuple_round_trip() {
+    let cxx = indoc! {"
+        std::tuple<int32_t, float, bool> bump(const std::tuple<int32_t, float, bool>& t) {
+            return std::make_tuple(std::get<0>(t) + 1, std::get<1>(t) * 2.0f, !std::get<2>(t));
+        }
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <tuple>
+        std::tuple<int32_t, float, bool> bump(const std::tuple<int32_t, float, bool>& t);
+    "};
+    let rs = quote! {
+        let t = ffi::Triple::from_tuple((3, 1.5, false));
+        assert_eq!(t.to_tuple(), (3, 1.5, false));
+        let bumped: ffi::TripleTuple = ffi::bump(&t);
+        assert_eq!(bumped, (4, 3.0, true));
+    };
+    run_test_ex(
+        cxx,
+        hdr,
+        rs,
+        quote! {
+            concrete!("std::tuple<int32_t, float, bool>", Triple)
+            generate!("bump")
+        },
+        None,
+        None,
+        None,
+    );
+}
+
 #[test]
 fn test_doc_comments_survive() {
     let hdr = indoc! {"
@@ -12419,6 +15346,333 @@ fn test_cpp_static_template() {
     );
 }
 
+#[test]
+fn test_impl_block_placement() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct A {
+            uint32_t a;
+            uint32_t get() const { return a; }
+        };
+        struct B {
+            uint32_t b;
+            uint32_t get() const { return b; }
+        };
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::A { a: 1 }.get(), 1);
+        assert_eq!(ffi::B { b: 2 }.get(), 2);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&[], &["A", "B"], None),
+        Some(Box::new(SetImplBlockPlacement(
+            ImplBlockPlacement::InSubModsOf(1),
+        ))),
+        Some(make_string_finder(vec!["autocxx_impls_1".into()])),
+        None,
+    );
+}
+
+#[test]
+fn test_include_dependencies() {
+    let input_h = indoc! {"
+        #include <cstdint>
+        inline uint32_t do_thing() { return 1; }
+    "};
+    let other_h = indoc! {"
+        #include <cstdint>
+        inline uint32_t do_other_thing() { return 2; }
+    "};
+    let (tdir, mut parsed) = parse_headers(
+        &[("input.h", input_h), ("other.h", other_h)],
+        quote! {
+            generate!("do_thing")
+            generate!("do_other_thing")
+        },
+    );
+    parsed
+        .resolve_all(
+            vec![tdir.path().to_path_buf()],
+            &[],
+            None,
+            &CodegenOptions::default(),
+        )
+        .unwrap();
+    let deps = parsed
+        .get_autocxxes()
+        .next()
+        .unwrap()
+        .get_include_dependencies();
+    assert_eq!(deps.get("do_thing"), Some(&vec!["input.h".to_string()]));
+    assert_eq!(
+        deps.get("do_other_thing"),
+        Some(&vec!["other.h".to_string()])
+    );
+}
+
+#[test]
+fn test_api_cache() {
+    let header = |b_type: &str| {
+        format!("#include <cstdint>\nstruct A {{ uint32_t a; }};\nstruct B {{ {b_type} b; }};")
+    };
+    let (tdir, parsed) = parse_headers(
+        &[("input.h", &header("uint32_t"))],
+        directives_from_lists(&[], &["A", "B"], None),
+    );
+    let api_cache = RefCell::new(ApiCache::default());
+    let codegen_options = CodegenOptions {
+        api_cache: Some(&api_cache),
+        ..Default::default()
+    };
+    // Returns the cache's hits and misses.
+    let run = |mut parsed: ParsedFile| {
+        parsed
+            .resolve_all(vec![tdir.path().to_path_buf()], &[], None, &codegen_options)
+            .unwrap();
+        let api_cache = api_cache.borrow();
+        (api_cache.hits(), api_cache.misses())
+    };
+    // Rewrites the header just as parse_headers did, since bindgen records
+    // the line on which it found each item.
+    let reparse = |b_type: &str| {
+        let content = format!("#pragma once\n{}", header(b_type));
+        std::fs::write(tdir.path().join("input.h"), content).unwrap();
+        parse_file(tdir.path().join("input.rs"), false).unwrap()
+    };
+    let (hits, items) = run(parsed);
+    assert_eq!(hits, 0);
+    assert_eq!(run(reparse("uint32_t")), (items, 0));
+    assert_eq!(run(reparse("uint64_t")), (items - 1, 1));
+}
+
+#[test]
+fn test_api_cache_on_disk() {
+    let header = |b_type: &str| {
+        format!("#include <cstdint>\nstruct A {{ uint32_t a; }};\nstruct B {{ {b_type} b; }};")
+    };
+    let (tdir, parsed) = parse_headers(
+        &[("input.h", &header("uint32_t"))],
+        directives_from_lists(&[], &["A", "B"], None),
+    );
+    let cache_dir = tdir.path().join("cache");
+    // Each run uses a new cache, just as each run of a build.rs would.
+    // Returns whether the run reused stored output, and the Rust it generated.
+    let run = |mut parsed: ParsedFile| {
+        let api_cache = RefCell::new(ApiCache::new_on_disk(&cache_dir));
+        let codegen_options = CodegenOptions {
+            api_cache: Some(&api_cache),
+            ..Default::default()
+        };
+        parsed
+            .resolve_all(vec![tdir.path().to_path_buf()], &[], None, &codegen_options)
+            .unwrap();
+        let rs: Vec<_> = parsed
+            .get_rs_outputs()
+            .map(|rs_output| generate_rs_single(rs_output).code)
+            .collect();
+        let reused_output = api_cache.borrow().reused_output();
+        (reused_output, rs)
+    };
+    let reparse = |b_type: &str| {
+        let content = format!("#pragma once\n{}", header(b_type));
+        std::fs::write(tdir.path().join("input.h"), content).unwrap();
+        parse_file(tdir.path().join("input.rs"), false).unwrap()
+    };
+    let (reused_output, rs) = run(parsed);
+    assert!(!reused_output);
+    assert_eq!(run(reparse("uint32_t")), (true, rs.clone()));
+    let (reused_output, changed_rs) = run(reparse("uint64_t"));
+    assert!(!reused_output);
+    assert_ne!(changed_rs, rs);
+    assert_eq!(run(reparse("uint64_t")), (true, changed_rs));
+}
+
+#[test]
+fn test_estimate_coverage() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Point {
+            uint32_t x;
+            uint32_t y;
+        };
+        inline uint32_t do_thing() { return 1; }
+        extern uint32_t COUNTER;
+    "};
+    let (tdir, parsed) = parse_headers(&[("input.h", hdr)], quote! { generate_all!() });
+    let report = parsed
+        .get_autocxxes()
+        .next()
+        .unwrap()
+        .estimate_coverage(vec![tdir.path().to_path_buf()], &[])
+        .unwrap();
+    assert_eq!(report.bindable_count(), 2);
+    assert_eq!(report.unsupported_count(), 1);
+    assert!(report.bindable.contains(&"Point".to_string()));
+    assert!(report.bindable.contains(&"do_thing".to_string()));
+    assert!(report.unsupported.iter().any(|(name, _)| name == "COUNTER"));
+    assert!(report.fraction_bindable() < 1.0);
+}
+
+#[test]
+fn test_estimate_coverage_template() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Point {
+            uint32_t x;
+            uint32_t y;
+        };
+        template <typename T> struct Holder {};
+        inline uint32_t do_thing() { return 1; }
+        extern uint32_t COUNTER;
+    "};
+    let (tdir, parsed) = parse_headers(&[("input.h", hdr)], quote! { generate_all!() });
+    let report = parsed
+        .get_autocxxes()
+        .next()
+        .unwrap()
+        .estimate_coverage(vec![tdir.path().to_path_buf()], &[])
+        .unwrap();
+    assert_eq!(report.bindable_count(), 2);
+    assert_eq!(report.unsupported_count(), 2);
+    let unsupported: Vec<_> = report.unsupported.iter().map(|(name, _)| name).collect();
+    assert!(unsupported.contains(&&"Holder".to_string()));
+    assert!(unsupported.contains(&&"COUNTER".to_string()));
+    assert_eq!(report.fraction_bindable(), 0.5);
+}
+
+#[test]
+fn test_resilient() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline uint32_t do_thing() { return 1; }
+        void log_it(const char* fmt, ...);
+    "};
+    let directives = quote! {
+        generate!("do_thing")
+        generate!("log_it")
+        generate!("missing")
+    };
+    let convert = |resilient| {
+        let (tdir, mut parsed) = parse_headers(&[("input.h", hdr)], directives.clone());
+        parsed
+            .resolve_all(
+                vec![tdir.path().to_path_buf()],
+                &[],
+                None,
+                &CodegenOptions {
+                    resilient,
+                    ..Default::default()
+                },
+            )
+            .map(|_| parsed)
+    };
+    assert!(convert(false).is_err());
+    let parsed = convert(true).unwrap();
+    let engine = parsed.get_autocxxes().next().unwrap();
+    let skipped: Vec<_> = engine
+        .get_skipped_items()
+        .iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert!(skipped.contains(&&"missing".to_string()));
+    assert!(skipped.contains(&&"log_it".to_string()));
+    assert!(!skipped.contains(&&"do_thing".to_string()));
+    assert!(engine.get_include_dependencies().contains_key("do_thing"));
+}
+
+#[test]
+fn test_inline_wrappers() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct A {
+            uint32_t a;
+        };
+        struct B : public A {
+            uint32_t b;
+        };
+    "};
+    run_test_ex(
+        "",
+        hdr,
+        quote! {},
+        directives_from_lists(&[], &["B"], Some(quote! { base_field_accessors!("B") })),
+        Some(Box::new(SetInlineWrappers)),
+        Some(make_rust_code_finder(vec![quote! {
+            #[inline]
+            pub fn a(&self) -> &u32
+        }])),
+        None,
+    );
+}
+
+#[test]
+fn test_extra_attributes() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline uint32_t do_thing() { return 1; }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::do_thing(), 1);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["do_thing"],
+            &[],
+            Some(quote! {
+                extra_attributes!("do_thing", #[cold] #[doc(alias = "perform")])
+            }),
+        ),
+        None,
+        Some(make_rust_code_finder(vec![quote! {
+            #[cold]
+            #[doc(alias = "perform")]
+            pub fn do_thing() -> u32
+        }])),
+        None,
+    );
+}
+
+#[test]
+fn test_item_visibility() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline uint32_t do_thing() { return 1; }
+        inline uint32_t do_internal_thing() { return 2; }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::do_thing(), 1);
+        assert_eq!(ffi::do_internal_thing(), 2);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["do_thing", "do_internal_thing"],
+            &[],
+            Some(quote! {
+                visibility!("do_internal_thing", pub(crate))
+            }),
+        ),
+        None,
+        Some(make_rust_code_finder(vec![
+            quote! {
+                pub(crate) use cxxbridge::do_internal_thing;
+            },
+            quote! {
+                pub use cxxbridge::do_thing;
+            },
+        ])),
+        None,
+    );
+}
+
 // Yet to test:
 // - Ifdef
 // - Out param pointers
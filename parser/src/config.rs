@@ -20,12 +20,15 @@ use quote::ToTokens;
 use quote::format_ident;
 use syn::{
     parse::{Parse, ParseStream},
-    Signature, Token, TypePath,
+    Signature, Token, Type, TypePath,
 };
 use syn::{Ident, Result as ParseResult};
 use thiserror::Error;
 
-use crate::{directives::get_directives, RustPath};
+use crate::{
+    directives::{get_directives, parse_std_template_args},
+    RustPath,
+};
 
 use quote::quote;
 
@@ -90,6 +93,173 @@ impl UnsafePolicy {
     }
 }
 
+/// How to represent C++ `long` and `unsigned long` in Rust. Their width
+/// differs between platforms (e.g. 64 bits on Linux but 32 on Windows)
+/// so by default they follow the target platform, but they may be pinned
+/// to a fixed width so that bindings look the same everywhere.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Default)]
+pub enum LongMapping {
+    /// `c_long` and `c_ulong`, whose width depends on the target.
+    #[default]
+    Platform,
+    /// `i32` and `u32`.
+    I32,
+    /// `i64` and `u64`.
+    I64,
+}
+
+impl Parse for LongMapping {
+    fn parse(input: ParseStream) -> ParseResult<Self> {
+        let id: syn::Ident = input.parse()?;
+        if id == "c_long" {
+            Ok(LongMapping::Platform)
+        } else if id == "i32" {
+            Ok(LongMapping::I32)
+        } else if id == "i64" {
+            Ok(LongMapping::I64)
+        } else {
+            Err(syn::Error::new(id.span(), "expected c_long, i32 or i64"))
+        }
+    }
+}
+
+impl ToTokens for LongMapping {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        tokens.extend(match self {
+            LongMapping::Platform => quote! { c_long },
+            LongMapping::I32 => quote! { i32 },
+            LongMapping::I64 => quote! { i64 },
+        })
+    }
+}
+
+impl LongMapping {
+    /// The Rust type to use in place of `long` (or `unsigned long`, if
+    /// `unsigned` is set), if it's pinned to a fixed width.
+    pub fn pinned_type(&self, unsigned: bool) -> Option<&'static str> {
+        match (self, unsigned) {
+            (LongMapping::Platform, _) => None,
+            (LongMapping::I32, false) => Some("i32"),
+            (LongMapping::I32, true) => Some("u32"),
+            (LongMapping::I64, false) => Some("i64"),
+            (LongMapping::I64, true) => Some("u64"),
+        }
+    }
+}
+
+/// How to present the `const char*` returned by a function listed using
+/// `c_string_return!`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub enum CStringReturn {
+    /// `Option<&'static CStr>`, which is `None` if the pointer is null.
+    Borrowed,
+    /// An owned `String`, copied from the C string.
+    Owned,
+}
+
+impl Parse for CStringReturn {
+    fn parse(input: ParseStream) -> ParseResult<Self> {
+        let id: syn::Ident = input.parse()?;
+        if id == "CStr" {
+            Ok(CStringReturn::Borrowed)
+        } else if id == "String" {
+            Ok(CStringReturn::Owned)
+        } else {
+            Err(syn::Error::new(id.span(), "expected CStr or String"))
+        }
+    }
+}
+
+impl ToTokens for CStringReturn {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        tokens.extend(match self {
+            CStringReturn::Borrowed => quote! { CStr },
+            CStringReturn::Owned => quote! { String },
+        })
+    }
+}
+
+/// How to name the Rust bindings for overloaded C++ functions, which
+/// Rust can't express directly. The first overload encountered always
+/// keeps the plain name; this controls what the others are called.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Default)]
+pub enum OverloadNaming {
+    /// Append a number: `foo`, `foo1`, `foo2`.
+    #[default]
+    Numeric,
+    /// Append an abbreviation of each parameter type: `foo`, `foo_u8`,
+    /// `foo_f64_bar`.
+    ParameterTypes,
+}
+
+impl Parse for OverloadNaming {
+    fn parse(input: ParseStream) -> ParseResult<Self> {
+        let id: syn::Ident = input.parse()?;
+        if id == "numeric" {
+            Ok(OverloadNaming::Numeric)
+        } else if id == "parameter_types" {
+            Ok(OverloadNaming::ParameterTypes)
+        } else {
+            Err(syn::Error::new(
+                id.span(),
+                "expected numeric or parameter_types",
+            ))
+        }
+    }
+}
+
+impl ToTokens for OverloadNaming {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        tokens.extend(match self {
+            OverloadNaming::Numeric => quote! { numeric },
+            OverloadNaming::ParameterTypes => quote! { parameter_types },
+        })
+    }
+}
+
+/// Whether and how integer arguments are checked as they're passed from
+/// Rust to C++. By default, Rust functions take exactly the integer type
+/// of each C++ parameter, so no conversion happens.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Default)]
+pub enum IntegerConversions {
+    /// Take the exact integer type of each parameter.
+    #[default]
+    Unchecked,
+    /// Accept any integer type, panicking if a value doesn't fit.
+    Panic,
+    /// Accept any integer type, returning an error if a value doesn't
+    /// fit.
+    Result,
+}
+
+impl Parse for IntegerConversions {
+    fn parse(input: ParseStream) -> ParseResult<Self> {
+        let id: syn::Ident = input.parse()?;
+        if id == "unchecked" {
+            Ok(IntegerConversions::Unchecked)
+        } else if id == "panic" {
+            Ok(IntegerConversions::Panic)
+        } else if id == "result" {
+            Ok(IntegerConversions::Result)
+        } else {
+            Err(syn::Error::new(
+                id.span(),
+                "expected unchecked, panic or result",
+            ))
+        }
+    }
+}
+
+impl ToTokens for IntegerConversions {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        tokens.extend(match self {
+            IntegerConversions::Unchecked => quote! { unchecked },
+            IntegerConversions::Panic => quote! { panic },
+            IntegerConversions::Result => quote! { result },
+        })
+    }
+}
+
 /// An entry in the allowlist.
 #[derive(Hash, Debug)]
 pub enum AllowlistEntry {
@@ -161,6 +331,95 @@ pub struct Subclass {
     pub subclass: Ident,
 }
 
+/// A `std::variant` instantiation which should be represented as a Rust
+/// enum.
+#[derive(Debug, Hash)]
+pub struct StdVariant {
+    pub rust_id: Ident,
+    pub alternatives: Vec<String>,
+}
+
+impl StdVariant {
+    /// The C++ type, normalized such that it can be matched against
+    /// the types found in function signatures.
+    pub fn cpp_definition(&self) -> String {
+        format!("std::variant<{}>", self.alternatives.join(", "))
+    }
+
+    /// The name of the opaque type used to represent the C++ variant
+    /// itself.
+    pub fn cpp_type_name(&self) -> Ident {
+        Ident::new(&format!("{}Cpp", self.rust_id), Span::call_site())
+    }
+}
+
+/// A `std::map` instantiation listed using `concrete!`, for which we
+/// generate a Rust iterator over its entries.
+#[derive(Debug)]
+pub struct StdMap {
+    pub rust_id: Ident,
+    pub key: String,
+    pub value: String,
+}
+
+impl StdMap {
+    /// The name of the Rust iterator type.
+    pub fn iterator_name(&self) -> Ident {
+        Ident::new(&format!("{}Iter", self.rust_id), Span::call_site())
+    }
+}
+
+/// A `std::tuple` instantiation listed using `concrete!`, which we can
+/// convert to and from a Rust tuple.
+#[derive(Debug)]
+pub struct StdTuple {
+    pub rust_id: Ident,
+    pub elements: Vec<String>,
+}
+
+impl StdTuple {
+    /// The name of the Rust tuple type alias.
+    pub fn rust_tuple_name(&self) -> Ident {
+        Ident::new(&format!("{}Tuple", self.rust_id), Span::call_site())
+    }
+}
+
+/// A C++ function returning a range, listed using `range!`, for which
+/// we generate a Rust iterator over the range's items.
+#[derive(Debug, Hash)]
+pub struct RangeFunction {
+    /// The C++ function, e.g. `primes` or `Sieve::primes`.
+    pub function: String,
+    /// The C++ type of the items in the range.
+    pub item: String,
+    /// The name of the Rust iterator type.
+    pub rust_id: Ident,
+}
+
+/// A specialization of a C++ function template, listed using
+/// `instantiate!` or `instantiate_method!`, for which we generate a
+/// concrete binding.
+#[derive(Debug, Hash)]
+pub struct FunctionInstantiation {
+    /// The C++ template-id, e.g. `run<Mode::Fast>` or `Container::get<int>`.
+    pub cpp_definition: String,
+    pub rust_id: Ident,
+    /// For a member function template, the class of which it's a member,
+    /// e.g. `Container`.
+    pub receiver_type: Option<String>,
+}
+
+impl FunctionInstantiation {
+    /// For a member function template, the template-id without the
+    /// class qualification, e.g. `get<int>`.
+    pub fn member_template_id(&self) -> Option<&str> {
+        let receiver_type = self.receiver_type.as_ref()?;
+        self.cpp_definition
+            .strip_prefix(receiver_type.as_str())?
+            .strip_prefix("::")
+    }
+}
+
 #[derive(Clone, Hash)]
 pub struct RustFun {
     pub path: RustPath,
@@ -177,10 +436,26 @@ impl std::fmt::Debug for RustFun {
     }
 }
 
+/// A user-supplied mapping between a C++ type and a Rust type, along
+/// with C++ expressions which convert between them. In each expression,
+/// `{}` stands for the value being converted.
+#[derive(Debug, Hash)]
+pub struct CustomTypeConversion {
+    pub cpp_type: String,
+    pub rust_type: Type,
+    /// Converts from the C++ representation of `rust_type` into `cpp_type`.
+    pub to_cpp: String,
+    /// Converts from `cpp_type` into the C++ representation of `rust_type`.
+    pub from_cpp: String,
+}
+
 #[derive(Debug, Clone, Hash)]
 pub struct ExternCppType {
     pub rust_path: TypePath,
     pub opaque: bool,
+    /// Whether this is an existing Rust enum, listed using
+    /// `extern_cpp_enum!`.
+    pub is_enum: bool,
 }
 
 /// Newtype wrapper so we can implement Hash.
@@ -218,14 +493,44 @@ pub struct IncludeCppConfig {
     pub(crate) pod_requests: Vec<String>,
     pub allowlist: Allowlist,
     pub(crate) blocklist: Vec<String>,
+    pub(crate) opaque_fallbacks: Vec<String>,
     pub(crate) constructor_blocklist: Vec<String>,
     pub instantiable: Vec<String>,
     pub(crate) exclude_utilities: bool,
+    pub(crate) flatten_namespaces: bool,
+    pub(crate) cxx_free: bool,
+    pub(crate) c_abi_shims: bool,
     pub(crate) mod_name: Option<Ident>,
+    pub(crate) shim_prefix: Option<String>,
+    pub(crate) shim_names: Vec<(String, String)>,
     pub rust_types: Vec<RustPath>,
     pub subclasses: Vec<Subclass>,
+    pub(crate) interfaces: Vec<String>,
     pub extern_rust_funs: Vec<RustFun>,
     pub concretes: ConcretesMap,
+    pub std_variants: Vec<StdVariant>,
+    pub(crate) function_instantiations: Vec<FunctionInstantiation>,
+    pub(crate) range_functions: Vec<RangeFunction>,
+    pub(crate) out_params: Vec<(String, String)>,
+    pub(crate) return_borrows: Vec<(String, String)>,
+    pub(crate) c_string_returns: Vec<(String, CStringReturn)>,
+    pub(crate) extra_attributes: Vec<(String, Vec<syn::Attribute>)>,
+    pub(crate) cfg_gates: Vec<(String, syn::Meta)>,
+    pub(crate) item_visibilities: Vec<(String, syn::Visibility)>,
+    pub(crate) method_of: Vec<(String, String)>,
+    pub(crate) singletons: Vec<String>,
+    pub(crate) no_slice_params: Vec<String>,
+    pub(crate) consuming_methods: Vec<String>,
+    pub(crate) bitflags_enums: Vec<String>,
+    pub(crate) newtype_typedefs: Vec<String>,
+    pub(crate) base_field_accessors: Vec<String>,
+    pub(crate) swappable: Vec<String>,
+    pub(crate) pin_box_types: Vec<String>,
+    pub(crate) throwing_constructors: Vec<String>,
+    pub(crate) custom_type_conversions: Vec<CustomTypeConversion>,
+    pub(crate) long_mapping: LongMapping,
+    pub(crate) overload_naming: OverloadNaming,
+    pub(crate) integer_conversions: IntegerConversions,
     pub externs: ExternCppTypeMap,
 }
 
@@ -281,12 +586,78 @@ impl IncludeCppConfig {
             .unwrap_or_else(|| Ident::new("ffi", Span::call_site()))
     }
 
+    /// A prefix to use for the names of C++ wrapper functions which we
+    /// generate, before the default uniquified names.
+    pub fn get_shim_prefix(&self) -> Option<&str> {
+        self.shim_prefix.as_deref()
+    }
+
+    /// The exact name to use for the C++ wrapper function for the given
+    /// function, if one was specified using `shim_name!`.
+    pub fn get_shim_name(&self, function: &str) -> Option<&str> {
+        self.shim_names
+            .iter()
+            .find(|(f, _)| f == function)
+            .map(|(_, name)| name.as_str())
+    }
+
     /// Whether to avoid generating the standard helpful utility
     /// functions which we normally include in every mod.
     pub fn exclude_utilities(&self) -> bool {
         self.exclude_utilities
     }
 
+    /// Whether to generate plain `#[repr(C)]` structs and `extern "C"`
+    /// functions rather than using cxx.
+    pub fn is_cxx_free(&self) -> bool {
+        self.cxx_free
+    }
+
+    /// Whether to generate an `extern "C"` shim for each free function
+    /// which can be called through one, as well as the usual bindings.
+    pub fn generates_c_abi_shims(&self) -> bool {
+        self.c_abi_shims
+    }
+
+    /// How `long` and `unsigned long` should be represented in Rust.
+    pub fn get_long_mapping(&self) -> LongMapping {
+        self.long_mapping
+    }
+
+    /// Whether and how integer arguments should be checked as they're
+    /// passed to C++.
+    pub fn get_integer_conversions(&self) -> IntegerConversions {
+        self.integer_conversions
+    }
+
+    /// How overloaded functions should be named in Rust.
+    pub fn get_overload_naming(&self) -> OverloadNaming {
+        self.overload_naming
+    }
+
+    /// The name of the `extern "C"` shim used to call a function
+    /// in `cxx_free!` mode.
+    pub fn get_cxx_free_shim_name(&self, fn_name: &str) -> String {
+        self.uniquify_name_per_mod(&format!("{fn_name}_autocxx_c"))
+    }
+
+    /// The name of the `extern "C"` shim generated by `c_abi_shims!`
+    /// for a function. Unlike the names of our other C++ wrappers, this
+    /// doesn't depend on the rest of the configuration, so that it stays
+    /// the same from one build to the next.
+    pub fn get_c_abi_shim_name(&self, fn_name: &str) -> String {
+        format!(
+            "{}{fn_name}",
+            self.get_shim_prefix().unwrap_or("autocxx_c_abi_")
+        )
+    }
+
+    /// Whether to collapse chains of nested namespaces which contain
+    /// only a single item, such that the item appears in an outer mod.
+    pub fn flatten_namespaces(&self) -> bool {
+        self.flatten_namespaces
+    }
+
     /// Items which the user has explicitly asked us to generate;
     /// we should raise an error if we weren't able to do so.
     pub fn must_generate_list(&self) -> Box<dyn Iterator<Item = String> + '_> {
@@ -316,6 +687,11 @@ impl IncludeCppConfig {
                     .map(AllowlistEntry::to_bindgen_item)
                     .chain(self.pod_requests.iter().cloned())
                     .chain(self.active_utilities())
+                    .chain(
+                        self.function_instantiations
+                            .iter()
+                            .map(|fi| self.get_function_instantiation_typedef_name(&fi.rust_id)),
+                    )
                     .chain(self.subclasses.iter().flat_map(|sc| {
                         [
                             format!("{}Cpp", sc.subclass),
@@ -365,6 +741,11 @@ impl IncludeCppConfig {
             || self.is_rust_fun(cpp_name)
             || self.is_rust_type_name(cpp_name)
             || self.is_concrete_type(cpp_name)
+            || self.is_std_variant(cpp_name)
+            || self.is_std_map_iterator(cpp_name)
+            || self.is_std_tuple(cpp_name)
+            || self.is_range_iterator(cpp_name)
+            || self.is_function_instantiation(cpp_name)
             || match &self.allowlist {
                 Allowlist::Unspecified(_) => panic!("Eek no allowlist yet"),
                 Allowlist::All => true,
@@ -383,14 +764,233 @@ impl IncludeCppConfig {
         self.constructor_blocklist.contains(&cpp_name.to_string())
     }
 
+    /// Whether the given parameter of the given function was listed
+    /// using `out_param!`.
+    pub fn is_out_param(&self, function: &str, param: &str) -> bool {
+        self.out_params
+            .iter()
+            .any(|(f, p)| f == function && p == param)
+    }
+
+    /// The functions and parameters listed using `out_param!`.
+    pub fn out_params(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.out_params
+            .iter()
+            .map(|(f, p)| (f.as_str(), p.as_str()))
+    }
+
+    /// The parameter from which the given function's returned reference
+    /// borrows, if one was specified using `return_borrows!`.
+    pub fn get_return_borrow(&self, function: &str) -> Option<&str> {
+        self.return_borrows
+            .iter()
+            .find(|(f, _)| f == function)
+            .map(|(_, p)| p.as_str())
+    }
+
+    /// How to present the C string returned by the given function, if
+    /// it was listed using `c_string_return!`.
+    pub fn get_c_string_return(&self, function: &str) -> Option<CStringReturn> {
+        self.c_string_returns
+            .iter()
+            .find(|(f, _)| f == function)
+            .map(|(_, mapping)| *mapping)
+    }
+
+    /// Any attributes to be attached to the Rust function generated for
+    /// the given function, as listed using `extra_attributes!`.
+    pub fn get_extra_attributes<'a>(
+        &'a self,
+        function: &'a str,
+    ) -> impl Iterator<Item = &'a syn::Attribute> + 'a {
+        self.extra_attributes
+            .iter()
+            .filter(move |(f, _)| f == function)
+            .flat_map(|(_, attrs)| attrs)
+    }
+
+    /// The `cfg` predicate under which the given function should be
+    /// available, if one was specified using `cfg_gate!`.
+    pub fn get_cfg_gate(&self, function: &str) -> Option<&syn::Meta> {
+        self.cfg_gates
+            .iter()
+            .find(|(f, _)| f == function)
+            .map(|(_, predicate)| predicate)
+    }
+
+    /// The visibility with which the given item should be made available
+    /// in the generated mod, if one was specified using `visibility!`.
+    pub fn get_item_visibility(&self, cpp_name: &str) -> Option<&syn::Visibility> {
+        self.item_visibilities
+            .iter()
+            .find(|(name, _)| name == cpp_name)
+            .map(|(_, vis)| vis)
+    }
+
+    /// The type of which the given free function should instead become
+    /// a method, if one was specified using `method_of!`.
+    pub fn get_method_of(&self, function: &str) -> Option<&str> {
+        self.method_of
+            .iter()
+            .find(|(f, _)| f == function)
+            .map(|(_, ty)| ty.as_str())
+    }
+
+    /// Whether the given function was listed using `no_slice_params!`.
+    pub fn is_no_slice_params(&self, function: &str) -> bool {
+        self.no_slice_params.iter().any(|f| f == function)
+    }
+
+    /// Whether the given function was listed using `singleton!`.
+    pub fn is_singleton(&self, function: &str) -> bool {
+        self.singletons.iter().any(|f| f == function)
+    }
+
+    /// Whether the given method was listed using `consuming_method!`.
+    pub fn is_consuming_method(&self, method: &str) -> bool {
+        self.consuming_methods.iter().any(|m| m == method)
+    }
+
+    /// Whether the given enum was listed using `bitflags_enum!`.
+    pub fn is_bitflags_enum(&self, cpp_name: &str) -> bool {
+        self.bitflags_enums.iter().any(|e| e == cpp_name)
+    }
+
+    /// Whether the given typedef was listed using `newtype_typedef!`,
+    /// such that it should become a distinct newtype rather than an
+    /// alias.
+    pub fn is_newtype_typedef(&self, cpp_name: &str) -> bool {
+        self.newtype_typedefs.iter().any(|s| s == cpp_name)
+    }
+
+    /// Whether the given struct was listed using `base_field_accessors!`.
+    pub fn wants_base_field_accessors(&self, cpp_name: &str) -> bool {
+        self.base_field_accessors.iter().any(|s| s == cpp_name)
+    }
+
+    /// The structs listed using `base_field_accessors!`.
+    pub fn base_field_accessors(&self) -> impl Iterator<Item = &str> {
+        self.base_field_accessors.iter().map(|s| s.as_str())
+    }
+
+    /// Whether the given type was listed using `swappable!`.
+    pub fn is_swappable(&self, cpp_name: &str) -> bool {
+        self.swappable.iter().any(|s| s == cpp_name)
+    }
+
+    /// Whether the given type was listed using `pin_box!`, such that
+    /// constructors and functions returning it should construct it
+    /// directly within a `Pin<Box<T>>`.
+    pub fn is_pin_box_type(&self, cpp_name: &str) -> bool {
+        self.pin_box_types.iter().any(|s| s == cpp_name)
+    }
+
+    /// Whether the given type was listed using `throwing_constructors!`,
+    /// such that we should offer fallible versions of its constructors.
+    pub fn has_throwing_constructors(&self, cpp_name: &str) -> bool {
+        self.throwing_constructors.iter().any(|s| s == cpp_name)
+    }
+
+    /// Any conversion registered for the given C++ type using
+    /// `custom_type_conversion!`.
+    pub fn get_custom_type_conversion(&self, cpp_type: &str) -> Option<&CustomTypeConversion> {
+        self.custom_type_conversions
+            .iter()
+            .find(|conversion| conversion.cpp_type == cpp_type)
+    }
+
     pub fn get_blocklist(&self) -> impl Iterator<Item = &String> {
         self.blocklist.iter()
     }
 
+    /// Types listed using `opaque_fallback!`, which bindgen should
+    /// represent as opaque blobs of the right size and alignment.
+    pub fn get_opaque_fallbacks(&self) -> impl Iterator<Item = &String> {
+        self.opaque_fallbacks.iter()
+    }
+
     fn is_concrete_type(&self, cpp_name: &str) -> bool {
         self.concretes.0.values().any(|val| *val == cpp_name)
     }
 
+    fn is_std_variant(&self, cpp_name: &str) -> bool {
+        self.std_variants.iter().any(|sv| sv.rust_id == cpp_name)
+    }
+
+    fn is_std_map_iterator(&self, cpp_name: &str) -> bool {
+        self.get_std_maps().any(|sm| sm.iterator_name() == cpp_name)
+    }
+
+    fn is_range_iterator(&self, cpp_name: &str) -> bool {
+        self.range_functions.iter().any(|rf| rf.rust_id == cpp_name)
+    }
+
+    fn is_std_tuple(&self, cpp_name: &str) -> bool {
+        self.get_std_tuples()
+            .any(|st| st.rust_tuple_name() == cpp_name)
+    }
+
+    fn is_function_instantiation(&self, cpp_name: &str) -> bool {
+        self.function_instantiations
+            .iter()
+            .any(|fi| fi.rust_id == cpp_name)
+    }
+
+    /// Any function template specializations listed using `instantiate!`
+    /// or `instantiate_method!`.
+    pub fn get_function_instantiations(&self) -> impl Iterator<Item = &FunctionInstantiation> {
+        self.function_instantiations.iter()
+    }
+
+    /// The name of the typedef through which we ask bindgen for the
+    /// signature of a function template specialization listed using
+    /// `instantiate!`.
+    pub fn get_function_instantiation_typedef_name(&self, rust_id: &Ident) -> String {
+        self.uniquify_name_per_mod(&format!("{rust_id}_autocxx_instantiation"))
+    }
+
+    /// The name of the C++ class template through which we find the
+    /// signature of a member function template specialization listed
+    /// using `instantiate_method!`.
+    pub fn get_method_signature_helper_name(&self) -> String {
+        self.uniquify_name_per_mod("autocxx_method_signature")
+    }
+
+    /// Any `std::map` instantiations listed using `concrete!`.
+    pub fn get_std_maps(&self) -> impl Iterator<Item = StdMap> + '_ {
+        self.concretes.0.iter().filter_map(|(definition, rust_id)| {
+            let mut args = parse_std_template_args(definition, "std::map")?;
+            // Maps with a custom comparator or allocator are
+            // iterated just the same.
+            if args.len() < 2 {
+                return None;
+            }
+            args.truncate(2);
+            let value = args.pop().unwrap();
+            let key = args.pop().unwrap();
+            Some(StdMap {
+                rust_id: rust_id.clone(),
+                key,
+                value,
+            })
+        })
+    }
+
+    /// Any `std::tuple` instantiations listed using `concrete!`.
+    pub fn get_std_tuples(&self) -> impl Iterator<Item = StdTuple> + '_ {
+        self.concretes.0.iter().filter_map(|(definition, rust_id)| {
+            Some(StdTuple {
+                rust_id: rust_id.clone(),
+                elements: parse_std_template_args(definition, "std::tuple")?,
+            })
+        })
+    }
+
+    /// Any functions returning ranges listed using `range!`.
+    pub fn get_range_functions(&self) -> impl Iterator<Item = &RangeFunction> {
+        self.range_functions.iter()
+    }
+
     /// Get a hash of the contents of this `include_cpp!` block.
     pub fn get_hash(&self) -> u64 {
         let mut s = DefaultHasher::new();
@@ -409,6 +1009,30 @@ impl IncludeCppConfig {
         self.uniquify_name_per_mod("autocxx_make_string")
     }
 
+    /// The name of one of the C++ helper functions used to inspect or
+    /// construct a `variant!`.
+    pub fn get_std_variant_fn_name(&self, rust_id: &str, suffix: &str) -> String {
+        self.uniquify_name_per_mod(&format!("{rust_id}_{suffix}"))
+    }
+
+    /// The name of one of the C++ helper functions or types used to
+    /// iterate over a `std::map` listed using `concrete!`.
+    pub fn get_std_map_item_name(&self, rust_id: &str, suffix: &str) -> String {
+        self.uniquify_name_per_mod(&format!("{rust_id}_{suffix}"))
+    }
+
+    /// The name of one of the C++ helper functions used to convert a
+    /// `std::tuple` listed using `concrete!` to or from a Rust tuple.
+    pub fn get_std_tuple_fn_name(&self, rust_id: &str, suffix: &str) -> String {
+        self.uniquify_name_per_mod(&format!("{rust_id}_{suffix}"))
+    }
+
+    /// The name of one of the C++ helper functions or types used to
+    /// iterate over a range listed using `range!`.
+    pub fn get_range_item_name(&self, rust_id: &str, suffix: &str) -> String {
+        self.uniquify_name_per_mod(&format!("{rust_id}_{suffix}"))
+    }
+
     pub fn is_rust_type(&self, id: &Ident) -> bool {
         let id_string = id.to_string();
         self.is_rust_type_name(&id_string) || self.is_subclass_holder(&id_string)
@@ -433,6 +1057,23 @@ impl IncludeCppConfig {
         uniquified.into_iter()
     }
 
+    /// The name of the subclass which autocxx synthesizes in order to
+    /// allow arbitrary Rust types to implement a given C++ interface.
+    pub fn interface_impl_name(interface: &str) -> Ident {
+        Ident::new(
+            &format!("{}_RustImpl", interface.replace("::", "_")),
+            Span::call_site(),
+        )
+    }
+
+    /// Whether this subclass was synthesized by autocxx to implement
+    /// an interface, as opposed to being written by the user.
+    pub fn is_interface_impl(&self, subclass: &str) -> bool {
+        self.interfaces
+            .iter()
+            .any(|interface| Self::interface_impl_name(interface) == subclass)
+    }
+
     pub fn is_subclass_holder(&self, id: &str) -> bool {
         self.subclasses
             .iter()
@@ -24,7 +24,7 @@ use crate::config::AllowlistErr;
 use crate::config::Allowlist;
 
 use crate::directive_names::{EXTERN_RUST_FUN, EXTERN_RUST_TYPE, SUBCLASS};
-use crate::{AllowlistEntry, IncludeCppConfig};
+use crate::{AllowlistEntry, CStringReturn, IncludeCppConfig};
 use crate::{ParseResult, RustFun, RustPath};
 
 pub(crate) struct DirectivesMap {
@@ -44,6 +44,12 @@ pub(crate) fn get_directives() -> &'static DirectivesMap {
         need_exclamation.insert("generate_ns".into(), Box::new(GenerateNs));
         need_exclamation.insert("generate_all".into(), Box::new(GenerateAll));
         need_exclamation.insert("safety".into(), Box::new(Safety));
+        need_exclamation.insert("long_mapping".into(), Box::new(LongMappingDirective));
+        need_exclamation.insert(
+            "checked_integer_conversions".into(),
+            Box::new(CheckedIntegerConversionsDirective),
+        );
+        need_exclamation.insert("overload_naming".into(), Box::new(OverloadNamingDirective));
         need_exclamation.insert(
             "pod".into(),
             Box::new(StringList(
@@ -93,19 +99,165 @@ pub(crate) fn get_directives() -> &'static DirectivesMap {
                 |config| &config.exclude_utilities,
             )),
         );
+        need_exclamation.insert(
+            "flatten_namespaces".into(),
+            Box::new(BoolFlag(
+                |config| &mut config.flatten_namespaces,
+                |config| &config.flatten_namespaces,
+            )),
+        );
+        need_exclamation.insert(
+            "cxx_free".into(),
+            Box::new(BoolFlag(
+                |config| &mut config.cxx_free,
+                |config| &config.cxx_free,
+            )),
+        );
+        need_exclamation.insert(
+            "c_abi_shims".into(),
+            Box::new(BoolFlag(
+                |config| &mut config.c_abi_shims,
+                |config| &config.c_abi_shims,
+            )),
+        );
         need_exclamation.insert("name".into(), Box::new(ModName));
+        need_exclamation.insert("shim_prefix".into(), Box::new(ShimPrefix));
+        need_exclamation.insert("shim_name".into(), Box::new(ShimName));
         need_exclamation.insert("concrete".into(), Box::new(Concrete));
+        need_exclamation.insert("variant".into(), Box::new(Variant));
+        need_exclamation.insert("range".into(), Box::new(Range));
+        need_exclamation.insert(
+            "instantiate".into(),
+            Box::new(Instantiate { member: false }),
+        );
+        need_exclamation.insert(
+            "instantiate_method".into(),
+            Box::new(Instantiate { member: true }),
+        );
+        need_exclamation.insert(
+            "out_param".into(),
+            Box::new(FunctionParamList(
+                |config| &mut config.out_params,
+                |config| &config.out_params,
+            )),
+        );
+        need_exclamation.insert(
+            "return_borrows".into(),
+            Box::new(FunctionParamList(
+                |config| &mut config.return_borrows,
+                |config| &config.return_borrows,
+            )),
+        );
+        need_exclamation.insert("c_string_return".into(), Box::new(CStringReturnDirective));
+        need_exclamation.insert("extra_attributes".into(), Box::new(ExtraAttributes));
+        need_exclamation.insert("cfg_gate".into(), Box::new(CfgGate));
+        need_exclamation.insert("visibility".into(), Box::new(ItemVisibility));
+        need_exclamation.insert(
+            "method_of".into(),
+            Box::new(FunctionParamList(
+                |config| &mut config.method_of,
+                |config| &config.method_of,
+            )),
+        );
+        need_exclamation.insert(
+            "custom_type_conversion".into(),
+            Box::new(CustomTypeConversion),
+        );
+        need_exclamation.insert(
+            "bitflags_enum".into(),
+            Box::new(StringList(
+                |config| &mut config.bitflags_enums,
+                |config| &config.bitflags_enums,
+            )),
+        );
+        need_exclamation.insert(
+            "newtype_typedef".into(),
+            Box::new(StringList(
+                |config| &mut config.newtype_typedefs,
+                |config| &config.newtype_typedefs,
+            )),
+        );
+        need_exclamation.insert(
+            "swappable".into(),
+            Box::new(StringList(
+                |config| &mut config.swappable,
+                |config| &config.swappable,
+            )),
+        );
+        need_exclamation.insert(
+            "opaque_fallback".into(),
+            Box::new(StringList(
+                |config| &mut config.opaque_fallbacks,
+                |config| &config.opaque_fallbacks,
+            )),
+        );
+        need_exclamation.insert(
+            "pin_box".into(),
+            Box::new(StringList(
+                |config| &mut config.pin_box_types,
+                |config| &config.pin_box_types,
+            )),
+        );
+        need_exclamation.insert(
+            "throwing_constructors".into(),
+            Box::new(StringList(
+                |config| &mut config.throwing_constructors,
+                |config| &config.throwing_constructors,
+            )),
+        );
+        need_exclamation.insert(
+            "base_field_accessors".into(),
+            Box::new(StringList(
+                |config| &mut config.base_field_accessors,
+                |config| &config.base_field_accessors,
+            )),
+        );
+        need_exclamation.insert(
+            "singleton".into(),
+            Box::new(StringList(
+                |config| &mut config.singletons,
+                |config| &config.singletons,
+            )),
+        );
+        need_exclamation.insert(
+            "no_slice_params".into(),
+            Box::new(StringList(
+                |config| &mut config.no_slice_params,
+                |config| &config.no_slice_params,
+            )),
+        );
+        need_exclamation.insert(
+            "consuming_method".into(),
+            Box::new(StringList(
+                |config| &mut config.consuming_methods,
+                |config| &config.consuming_methods,
+            )),
+        );
         need_exclamation.insert("rust_type".into(), Box::new(RustType { output: false }));
         need_exclamation.insert(EXTERN_RUST_TYPE.into(), Box::new(RustType { output: true }));
         need_exclamation.insert(SUBCLASS.into(), Box::new(Subclass));
+        need_exclamation.insert("interface".into(), Box::new(Interface));
         need_exclamation.insert(EXTERN_RUST_FUN.into(), Box::new(ExternRustFun));
         need_exclamation.insert(
             "extern_cpp_type".into(),
-            Box::new(ExternCppType { opaque: false }),
+            Box::new(ExternCppType {
+                opaque: false,
+                is_enum: false,
+            }),
         );
         need_exclamation.insert(
             "extern_cpp_opaque_type".into(),
-            Box::new(ExternCppType { opaque: true }),
+            Box::new(ExternCppType {
+                opaque: true,
+                is_enum: false,
+            }),
+        );
+        need_exclamation.insert(
+            "extern_cpp_enum".into(),
+            Box::new(ExternCppType {
+                opaque: false,
+                is_enum: true,
+            }),
         );
 
         DirectivesMap {
@@ -289,6 +441,84 @@ impl Directive for Safety {
     }
 }
 
+struct LongMappingDirective;
+
+impl Directive for LongMappingDirective {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        config.long_mapping = args.parse()?;
+        Ok(())
+    }
+
+    #[cfg(feature = "reproduction_case")]
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        let mapping = &config.long_mapping;
+        match config.long_mapping {
+            crate::LongMapping::Platform => Box::new(std::iter::empty()),
+            _ => Box::new(std::iter::once(mapping.to_token_stream())),
+        }
+    }
+}
+
+struct CheckedIntegerConversionsDirective;
+
+impl Directive for CheckedIntegerConversionsDirective {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        config.integer_conversions = args.parse()?;
+        Ok(())
+    }
+
+    #[cfg(feature = "reproduction_case")]
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        let conversions = &config.integer_conversions;
+        match config.integer_conversions {
+            crate::IntegerConversions::Unchecked => Box::new(std::iter::empty()),
+            _ => Box::new(std::iter::once(conversions.to_token_stream())),
+        }
+    }
+}
+
+struct OverloadNamingDirective;
+
+impl Directive for OverloadNamingDirective {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        config.overload_naming = args.parse()?;
+        Ok(())
+    }
+
+    #[cfg(feature = "reproduction_case")]
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        let naming = &config.overload_naming;
+        match config.overload_naming {
+            crate::OverloadNaming::Numeric => Box::new(std::iter::empty()),
+            _ => Box::new(std::iter::once(naming.to_token_stream())),
+        }
+    }
+}
+
 fn allowlist_err_to_syn_err(err: AllowlistErr, span: &Span) -> syn::Error {
     syn::Error::new(*span, format!("{err}"))
 }
@@ -386,6 +616,82 @@ impl Directive for ModName {
     }
 }
 
+struct ShimPrefix;
+
+impl Directive for ShimPrefix {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let prefix: syn::LitStr = args.parse()?;
+        let prefix_str = prefix.value();
+        if !is_valid_cpp_identifier(&prefix_str) {
+            return Err(syn::Error::new(
+                prefix.span(),
+                "shim_prefix must be a non-empty prefix for a C++ identifier",
+            ));
+        }
+        config.shim_prefix = Some(prefix_str);
+        Ok(())
+    }
+
+    #[cfg(feature = "reproduction_case")]
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        match &config.shim_prefix {
+            None => Box::new(std::iter::empty()),
+            Some(prefix) => Box::new(std::iter::once(quote! { #prefix })),
+        }
+    }
+}
+
+struct ShimName;
+
+impl Directive for ShimName {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let function: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let name: syn::LitStr = args.parse()?;
+        if !is_valid_cpp_identifier(&name.value()) {
+            return Err(syn::Error::new(
+                name.span(),
+                "shim_name must be a C++ identifier",
+            ));
+        }
+        config.shim_names.push((function.value(), name.value()));
+        Ok(())
+    }
+
+    #[cfg(feature = "reproduction_case")]
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.shim_names.iter().map(|(function, name)| {
+            quote! {
+                #function,#name
+            }
+        }))
+    }
+}
+
+fn is_valid_cpp_identifier(id: &str) -> bool {
+    id.chars()
+        .next()
+        .map(|c| c.is_ascii_alphabetic() || c == '_')
+        .unwrap_or(false)
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
 struct Concrete;
 
 impl Directive for Concrete {
@@ -407,9 +713,448 @@ impl Directive for Concrete {
         &self,
         config: &'a IncludeCppConfig,
     ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
-        Box::new(config.concretes.0.iter().map(|(k, v)| {
+        Box::new(
+            config
+                .concretes
+                .0
+                .iter()
+                .filter(|(_, v)| {
+                    !config
+                        .std_variants
+                        .iter()
+                        .any(|sv| sv.cpp_type_name() == **v)
+                })
+                .map(|(k, v)| {
+                    quote! {
+                        #k,#v
+                    }
+                }),
+        )
+    }
+}
+
+struct Instantiate {
+    member: bool,
+}
+
+impl Directive for Instantiate {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let definition: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let rust_id: syn::Ident = args.parse()?;
+        let cpp_definition = definition.value();
+        let Some(template_args_start) = cpp_definition.find('<') else {
+            return Err(syn::Error::new(
+                definition.span(),
+                if self.member {
+                    "expected a member function template specialization, e.g. \"Container::get<int>\""
+                } else {
+                    "expected a function template specialization, e.g. \"run<Mode::Fast>\""
+                },
+            ));
+        };
+        let receiver_type = if self.member {
+            match cpp_definition[..template_args_start].rfind("::") {
+                Some(class_end) => Some(cpp_definition[..class_end].to_string()),
+                None => {
+                    return Err(syn::Error::new(
+                        definition.span(),
+                        "expected the member function template to be qualified by its class, e.g. \"Container::get<int>\"",
+                    ))
+                }
+            }
+        } else {
+            None
+        };
+        config
+            .function_instantiations
+            .push(crate::config::FunctionInstantiation {
+                cpp_definition,
+                rust_id,
+                receiver_type,
+            });
+        Ok(())
+    }
+
+    #[cfg(feature = "reproduction_case")]
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        let member = self.member;
+        Box::new(
+            config
+                .function_instantiations
+                .iter()
+                .filter(move |fi| fi.receiver_type.is_some() == member)
+                .map(|fi| {
+                    let definition = &fi.cpp_definition;
+                    let rust_id = &fi.rust_id;
+                    quote! {
+                        #definition,#rust_id
+                    }
+                }),
+        )
+    }
+}
+
+struct Range;
+
+impl Directive for Range {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let function: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let item: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let rust_id: syn::Ident = args.parse()?;
+        config.range_functions.push(crate::config::RangeFunction {
+            function: function.value(),
+            item: item.value(),
+            rust_id,
+        });
+        Ok(())
+    }
+
+    #[cfg(feature = "reproduction_case")]
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.range_functions.iter().map(|rf| {
+            let function = &rf.function;
+            let item = &rf.item;
+            let rust_id = &rf.rust_id;
+            quote! {
+                #function,#item,#rust_id
+            }
+        }))
+    }
+}
+
+struct Variant;
+
+impl Directive for Variant {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let definition: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let rust_id: syn::Ident = args.parse()?;
+        let alternatives = parse_std_template_args(&definition.value(), "std::variant").ok_or_else(|| {
+            syn::Error::new(
+                definition.span(),
+                "expected a std::variant with at least one alternative, e.g. \"std::variant<uint32_t, bool>\"",
+            )
+        })?;
+        let std_variant = crate::config::StdVariant {
+            rust_id,
+            alternatives,
+        };
+        // The C++ type itself is made available just like any other
+        // concrete template instantiation.
+        config
+            .concretes
+            .0
+            .insert(std_variant.cpp_definition(), std_variant.cpp_type_name());
+        config.std_variants.push(std_variant);
+        Ok(())
+    }
+
+    #[cfg(feature = "reproduction_case")]
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.std_variants.iter().map(|sv| {
+            let definition = sv.cpp_definition();
+            let rust_id = &sv.rust_id;
+            quote! {
+                #definition,#rust_id
+            }
+        }))
+    }
+}
+
+/// Splits the arguments of an instantiation of the given standard
+/// library template, e.g. `std::variant<A, B<C, D>>` into
+/// `["A", "B<C, D>"]`.
+pub(crate) fn parse_std_template_args(definition: &str, template: &str) -> Option<Vec<String>> {
+    let definition = definition.trim();
+    let args = definition
+        .strip_prefix("::")
+        .unwrap_or(definition)
+        .strip_prefix(template)?
+        .trim_start()
+        .strip_prefix('<')?
+        .strip_suffix('>')?;
+    let mut alternatives = Vec::new();
+    let mut depth = 0usize;
+    let mut current = String::new();
+    for c in args.chars() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                alternatives.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    alternatives.push(current);
+    let alternatives: Vec<String> = alternatives
+        .into_iter()
+        .map(|alt| alt.trim().to_string())
+        .collect();
+    if depth != 0 || alternatives.iter().any(|alt| alt.is_empty()) {
+        None
+    } else {
+        Some(alternatives)
+    }
+}
+
+/// A directive naming a function and one of its parameters (or, for
+/// `method_of!`, the type to which it should belong).
+struct FunctionParamList<SET, GET>(SET, GET)
+where
+    SET: Fn(&mut IncludeCppConfig) -> &mut Vec<(String, String)>,
+    GET: Fn(&IncludeCppConfig) -> &Vec<(String, String)>;
+
+impl<SET, GET> Directive for FunctionParamList<SET, GET>
+where
+    SET: Fn(&mut IncludeCppConfig) -> &mut Vec<(String, String)> + Sync + Send,
+    GET: Fn(&IncludeCppConfig) -> &Vec<(String, String)> + Sync + Send,
+{
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let function: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let param: syn::LitStr = args.parse()?;
+        self.0(config).push((function.value(), param.value()));
+        Ok(())
+    }
+
+    #[cfg(feature = "reproduction_case")]
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(self.1(config).iter().map(|(function, param)| {
+            quote! {
+                #function,#param
+            }
+        }))
+    }
+}
+
+struct CStringReturnDirective;
+
+impl Directive for CStringReturnDirective {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let function: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let mapping: CStringReturn = args.parse()?;
+        config.c_string_returns.push((function.value(), mapping));
+        Ok(())
+    }
+
+    #[cfg(feature = "reproduction_case")]
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.c_string_returns.iter().map(|(function, mapping)| {
+            quote! {
+                #function,#mapping
+            }
+        }))
+    }
+}
+
+struct ExtraAttributes;
+
+impl Directive for ExtraAttributes {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let function: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let attrs = syn::Attribute::parse_outer(args)?;
+        if attrs.is_empty() {
+            return Err(syn::Error::new(
+                function.span(),
+                "expected at least one attribute, such as #[inline]",
+            ));
+        }
+        config.extra_attributes.push((function.value(), attrs));
+        Ok(())
+    }
+
+    #[cfg(feature = "reproduction_case")]
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.extra_attributes.iter().map(|(function, attrs)| {
             quote! {
-                #k,#v
+                #function, #(#attrs)*
+            }
+        }))
+    }
+}
+
+struct CfgGate;
+
+impl Directive for CfgGate {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let function: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let predicate: syn::Meta = args.parse()?;
+        config.cfg_gates.push((function.value(), predicate));
+        Ok(())
+    }
+
+    #[cfg(feature = "reproduction_case")]
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.cfg_gates.iter().map(|(function, predicate)| {
+            quote! {
+                #function, #predicate
+            }
+        }))
+    }
+}
+
+struct ItemVisibility;
+
+impl Directive for ItemVisibility {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let item: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        // There's no keyword for private visibility, so we accept
+        // `private` to mean that.
+        let vis = if args.peek(syn::Ident) {
+            let id: syn::Ident = args.parse()?;
+            if id != "private" {
+                return Err(syn::Error::new(
+                    id.span(),
+                    "expected a visibility such as pub, pub(crate) or private",
+                ));
+            }
+            syn::Visibility::Inherited
+        } else {
+            args.parse()?
+        };
+        config.item_visibilities.push((item.value(), vis));
+        Ok(())
+    }
+
+    #[cfg(feature = "reproduction_case")]
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(
+            config
+                .item_visibilities
+                .iter()
+                .map(|(item, vis)| match vis {
+                    syn::Visibility::Inherited => quote! {
+                        #item, private
+                    },
+                    _ => quote! {
+                        #item, #vis
+                    },
+                }),
+        )
+    }
+}
+
+struct CustomTypeConversion;
+
+impl Directive for CustomTypeConversion {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let cpp_type: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let rust_type: syn::Type = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let to_cpp: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let from_cpp: syn::LitStr = args.parse()?;
+        for snippet in [&to_cpp, &from_cpp] {
+            if !snippet.value().contains("{}") {
+                return Err(syn::Error::new(
+                    snippet.span(),
+                    "conversion expressions must contain {} to indicate the value being converted",
+                ));
+            }
+        }
+        config
+            .custom_type_conversions
+            .push(crate::config::CustomTypeConversion {
+                cpp_type: cpp_type.value(),
+                rust_type,
+                to_cpp: to_cpp.value(),
+                from_cpp: from_cpp.value(),
+            });
+        Ok(())
+    }
+
+    #[cfg(feature = "reproduction_case")]
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.custom_type_conversions.iter().map(|conversion| {
+            let cpp_type = &conversion.cpp_type;
+            let rust_type = &conversion.rust_type;
+            let to_cpp = &conversion.to_cpp;
+            let from_cpp = &conversion.from_cpp;
+            quote! {
+                #cpp_type, #rust_type, #to_cpp, #from_cpp
             }
         }))
     }
@@ -469,11 +1214,50 @@ impl Directive for Subclass {
         &self,
         config: &'a IncludeCppConfig,
     ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
-        Box::new(config.subclasses.iter().map(|sc| {
-            let superclass = &sc.superclass;
-            let subclass = &sc.subclass;
+        Box::new(
+            config
+                .subclasses
+                .iter()
+                .filter(|sc| !config.is_interface_impl(&sc.subclass.to_string()))
+                .map(|sc| {
+                    let superclass = &sc.superclass;
+                    let subclass = &sc.subclass;
+                    quote! {
+                        #superclass,#subclass
+                    }
+                }),
+        )
+    }
+}
+
+struct Interface;
+
+impl Directive for Interface {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let interface = args.parse::<syn::LitStr>()?.value();
+        // An interface is implemented using a subclass which autocxx
+        // synthesizes, rather than one provided by the user.
+        config.subclasses.push(crate::config::Subclass {
+            superclass: interface.clone(),
+            subclass: IncludeCppConfig::interface_impl_name(&interface),
+        });
+        config.interfaces.push(interface);
+        Ok(())
+    }
+
+    #[cfg(feature = "reproduction_case")]
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.interfaces.iter().map(|interface| {
             quote! {
-                #superclass,#subclass
+                #interface
             }
         }))
     }
@@ -514,6 +1298,7 @@ impl Directive for ExternRustFun {
 
 struct ExternCppType {
     opaque: bool,
+    is_enum: bool,
 }
 
 impl Directive for ExternCppType {
@@ -531,6 +1316,7 @@ impl Directive for ExternCppType {
             crate::config::ExternCppType {
                 rust_path,
                 opaque: self.opaque,
+                is_enum: self.is_enum,
             },
         );
         Ok(())
@@ -542,13 +1328,14 @@ impl Directive for ExternCppType {
         config: &'a IncludeCppConfig,
     ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
         let opaque_needed = self.opaque;
+        let enum_needed = self.is_enum;
         Box::new(
             config
                 .externs
                 .0
                 .iter()
                 .filter_map(move |(definition, details)| {
-                    if details.opaque == opaque_needed {
+                    if details.opaque == opaque_needed && details.is_enum == enum_needed {
                         let rust_path = &details.rust_path;
                         Some(quote! {
                             #definition, #rust_path
@@ -16,7 +16,9 @@ mod path;
 mod subclass_attrs;
 
 pub use config::{
-    AllowlistEntry, ExternCppType, IncludeCppConfig, RustFun, Subclass, UnsafePolicy,
+    AllowlistEntry, CStringReturn, CustomTypeConversion, ExternCppType, FunctionInstantiation,
+    IncludeCppConfig, IntegerConversions, LongMapping, OverloadNaming, RangeFunction, RustFun,
+    StdMap, StdTuple, StdVariant, Subclass, UnsafePolicy,
 };
 use file_locations::FileLocationStrategy;
 pub use multi_bindings::{MultiBindings, MultiBindingsErr};
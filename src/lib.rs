@@ -180,6 +180,102 @@ macro_rules! exclude_utilities {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
+/// Collapse chains of nested C++ namespaces which contain only a
+/// single item, so that the item is exposed in an outer Rust mod.
+/// For example, if `A::B::C::D` is the only item within namespace `A`,
+/// it will be available as `ffi::D` rather than `ffi::A::B::C::D`.
+/// Items are not moved if doing so would clash with an existing name
+/// in the outer mod.
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! flatten_namespaces {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Pin the Rust representation of `long` and `unsigned long` fields
+/// within POD structs. By default (`long_mapping!(c_long)`) these are
+/// `c_long` and `c_ulong`, whose width follows the target platform.
+/// `long_mapping!(i64)` makes them `i64` and `u64` instead, and
+/// `long_mapping!(i32)` makes them `i32` and `u32`, so that code using
+/// the bindings needn't vary between platforms. Compilation fails on
+/// any platform where `long` isn't actually the requested width.
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! long_mapping {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Check that integer arguments fit before passing them to C++. By
+/// default (`checked_integer_conversions!(unchecked)`) each Rust function
+/// takes exactly the integer type of the C++ parameter, so the caller
+/// must convert values themselves, perhaps using `as`, which silently
+/// truncates. Otherwise, integer parameters accept any type which can be
+/// converted using [`TryInto`]. With `checked_integer_conversions!(panic)`
+/// the function panics if a value doesn't fit, whilst with
+/// `checked_integer_conversions!(result)` it returns a `Result` whose
+/// error is an [`IntegerConversionError`]. Methods implementing traits,
+/// such as operators, are unaffected.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! checked_integer_conversions {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Choose how overloaded C++ functions are named in Rust. The first
+/// overload seen keeps the plain name. By default
+/// (`overload_naming!(numeric)`) the others get a numeric suffix:
+/// `foo1`, `foo2` and so on. `overload_naming!(parameter_types)` instead
+/// suffixes an abbreviation of each parameter type, for example
+/// `foo_u8` or `foo_f64_bar`, and constructors become `new_u32` rather
+/// than `new1`. Where two overloads would abbreviate the same way, the
+/// latter falls back to a numeric suffix.
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! overload_naming {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Generate bindings without using cxx at all, for headers which
+/// contain only POD types and free functions operating on them.
+/// Structs are emitted as plain `#[repr(C)]` types and each function
+/// is declared in an `extern "C"` block, calling a small C-linkage
+/// shim which autocxx generates. Such functions are `unsafe` to call.
+///
+/// Every item on the allowlist must be a type listed using
+/// [generate_pod], an enum, a constant, or a free function whose
+/// parameters and return type are all POD or built-in types, passed
+/// by value, pointer or reference. Otherwise code generation fails.
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! cxx_free {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Additionally generate an `extern "C"` shim for each free function
+/// whose parameters and return type are all POD or built-in types, so
+/// that it can be called through a stable C ABI - for instance by a
+/// plugin built with a different C++ compiler. For a function `foo`,
+/// the shim is exported from the generated C++ as
+/// `autocxx_c_abi_foo` (or, if you use [shim_prefix], with that prefix
+/// instead), with namespaces joined by underscores. It's also declared
+/// in Rust as `foo_c_abi` alongside the usual binding for `foo`; like
+/// any `extern "C"` function, it's `unsafe` to call.
+///
+/// This has no effect in [cxx_free] mode, where every function is
+/// already called through such a shim.
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! c_abi_shims {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
 /// Entirely block some type from appearing in the generated
 /// code. This can be useful if there is a type which is not
 /// understood by bindgen or autocxx, and incorrect code is
@@ -216,10 +312,45 @@ macro_rules! name {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
+/// A prefix for the names of the C++ wrapper functions which autocxx
+/// generates, for example `shim_prefix!("mylib_")`. By default these
+/// names are derived from the function name plus a hash of the
+/// [include_cpp] configuration; with this directive, a wrapper for
+/// `foo` is instead called `mylib_foo` followed by that hash, so that
+/// [include_cpp] invocations with the same prefix don't collide. To
+/// choose the whole name of a wrapper, use [shim_name].
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! shim_prefix {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// The exact name of the C++ wrapper function which autocxx generates
+/// for a function, if it needs one, for example
+/// `shim_name!("foo", "mylib_foo")`. You must ensure that the name
+/// doesn't collide with other symbols, including those from other
+/// [include_cpp] invocations, and that the function isn't overloaded,
+/// since each overload would be given the same name.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! shim_name {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
 /// A concrete type to make, for example
 /// `concrete!("Container<Contents>")`.
 /// All types must already be on the allowlist by having used
-/// `generate!` or similar.
+/// `generate!` or similar. For a `std::map`, such as
+/// `concrete!("std::map<int, int>", IntMap)`, this also generates an
+/// `iter` method yielding `(key, value)` pairs. For a `std::tuple`, such
+/// as `concrete!("std::tuple<int32_t, bool>", Pair)`, this also generates
+/// a Rust tuple type `PairTuple` along with `to_tuple` and `from_tuple`
+/// methods, and functions returning the `std::tuple` by value instead
+/// return the Rust tuple.
 ///
 /// A directive to be included inside
 /// [include_cpp] - see [include_cpp] for general information.
@@ -228,6 +359,366 @@ macro_rules! concrete {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
+/// A specialization of a C++ function template to bind, for example
+/// `instantiate!("run<Mode::Fast>", run_fast)`, which makes a Rust
+/// function `run_fast` calling `run<Mode::Fast>`. Template arguments
+/// may be types or non-type values such as enumerators. The template-id
+/// must name exactly one specialization; list each specialization you
+/// need separately. Any types used in its signature must be on the
+/// allowlist, e.g. using [generate].
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! instantiate {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// A specialization of a C++ member function template to bind, for
+/// example `instantiate_method!("Container::get<int>", get_int)`, which
+/// makes a Rust method `get_int` on `Container` calling `get<int>`. The
+/// template-id must be qualified by its class, which must be on the
+/// allowlist, and otherwise follows the same rules as [instantiate].
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! instantiate_method {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Generates a Rust iterator over the range returned by a C++ function,
+/// for example `range!("Library::titles", "BookTitle", Titles)`. The
+/// function must take no arguments, and may return any range with
+/// `begin()` and `end()`, either by value or by reference. This generates
+/// a type `Titles` whose `new` function calls the C++ function, and which
+/// implements [`Iterator`] yielding copies of the items. The item type
+/// must be a built-in type such as `uint32_t` or a type listed using
+/// `generate_pod!`.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! range {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Represents a `std::variant` instantiation as a Rust enum, for example
+/// `variant!("std::variant<uint32_t, bool>", MyVariant)`. This generates
+/// an opaque type `MyVariantCpp` for the C++ variant itself, which can be
+/// passed to and from C++ functions, and an enum `MyVariant` with one
+/// variant per alternative plus `ValuelessByException`. Use
+/// `MyVariant::from_cpp` and `MyVariant::into_cpp` to convert between the
+/// two. Each alternative must be a built-in type such as `uint32_t` or a
+/// type listed using `generate_pod!`.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! variant {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Registers a custom mapping between a C++ type and a Rust type, for
+/// example
+/// `custom_type_conversion!("Celsius", f64, "Celsius{ {} }", "{}.degrees")`.
+/// Wherever a function takes or returns the C++ type by value, the
+/// generated Rust function will instead use the Rust type, and
+/// autocxx will use the two C++ expressions to convert to and from the
+/// C++ type respectively. In each expression, `{}` stands for the value
+/// being converted. The Rust type must be something which cxx can pass
+/// by value, such as a primitive.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! custom_type_conversion {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Marks a pointer parameter of a function as an output parameter, for
+/// example `out_param!("parse", "out")`. Rather than taking a pointer,
+/// the generated Rust function will allocate the value itself and
+/// return it alongside any original return value, as a tuple if
+/// necessary. The generated function starts with a default value for
+/// the C++ function to overwrite, so the pointee type must be an
+/// integer, floating point or `bool` type; other functions listed this
+/// way are ignored. It's an error to name a function or parameter which
+/// autocxx can't find.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! out_param {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Declares which parameter a returned reference borrows from, for
+/// example `return_borrows!("Registry::lookup", "key")`. Normally a
+/// function returning a reference must take exactly one reference
+/// parameter, so that the returned reference can borrow from it. With
+/// this directive, the function may take several, and the generated
+/// signature ties the returned reference's lifetime to the named
+/// parameter alone, e.g. `fn lookup<'a>(self: &'a Registry, key: &Key)
+/// -> &'a Value`. Use `self` to refer to the receiver of a method.
+///
+/// This is an assertion on your part: nothing checks that the C++
+/// function really returns a reference into that parameter.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! return_borrows {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Turns a free function into a method of the type of its first
+/// parameter, for example `method_of!("process", "Thing")` makes
+/// `void process(Thing&)` available as `Thing::process`. The first
+/// parameter must be a pointer or reference to the named type, and
+/// becomes the receiver. Both the function and the type must also be
+/// listed using `generate!` or similar.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! method_of {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Generates a C++ typedef as a distinct newtype rather than an alias,
+/// for example `newtype_typedef!("UserId")`. For `typedef int UserId;`
+/// the generated type is `pub struct UserId(pub i32)`, so it can't be
+/// confused with other typedefs of the same type, along with `From`
+/// conversions to and from `i32`. Its representation is the same as
+/// the type for which it's an alias, so it can still be passed to and
+/// from C++ by value. The typedef must also be listed using
+/// [generate_pod].
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! newtype_typedef {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Generates a C++ enum as a set of bit flags, for example
+/// `bitflags_enum!("Permissions")`. Rather than a Rust `enum`, the
+/// generated type is a struct created using the `bitflags` crate, with
+/// one flag per enumerator. Its representation is the same as the
+/// enum's underlying type, so it can still be passed to and from C++.
+/// The enum must also be on the allowlist, e.g. using [generate].
+///
+/// This requires the `bitflags` feature of this crate.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! bitflags_enum {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Generates accessors on a struct for the public data members it
+/// inherits from its base classes, for example
+/// `base_field_accessors!("Derived")`. For each inherited field `x`,
+/// the generated type gains methods `x(&self) -> &T` and
+/// `x_mut(&mut self) -> &mut T` which reach into the base class
+/// subobject, so they respect the inherited layout. Fields hidden by
+/// a field of the same name in the derived class are skipped.
+/// The struct must be POD, e.g. using [generate_pod], and its base
+/// classes must be known to autocxx and mustn't be template
+/// instantiations. autocxx reports an error if that's not so, if the
+/// struct inherits no public fields, or if an accessor would have the
+/// same name as one of the struct's methods.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! base_field_accessors {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Constructs a type directly within a `Pin<Box<T>>`, for example
+/// `pin_box!("Counter")`. Ordinarily, constructors and functions
+/// returning a non-POD type by value return an `impl New`, which the
+/// caller must then emplace somewhere, e.g. using
+/// [`WithinBox::within_box`]. For types listed here, they instead
+/// return a `Pin<Box<T>>`, constructed in place. This is convenient
+/// for types which can be neither copied nor moved, such as those
+/// containing a mutex. The type must also be on the allowlist.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! pin_box {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Generates fallible versions of a type's constructors, for example
+/// `throwing_constructors!("Connection")`. C++ exceptions can't
+/// propagate into Rust, so a constructor which throws would otherwise
+/// abort the program. For types listed here, each constructor
+/// additionally gets a `try_new` associated function (numbered or named
+/// for any overloads in the same way as `new`), which returns a
+/// `Result<UniquePtr<T>, cxx::Exception>`. Any exception thrown during
+/// construction is returned as an `Err`.
+/// The type must also be on the allowlist, e.g. using [generate].
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! throwing_constructors {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Represents a type which autocxx can't otherwise map, for example
+/// `opaque_fallback!("Value")`, as an opaque blob of bytes of the same
+/// size and alignment as the C++ type. Structs containing such a type,
+/// and functions taking or returning it, can then still be generated,
+/// even as POD. The fallback type itself has no usable fields or
+/// methods, so it can only be passed back and forth to C++.
+/// This is an escape hatch for types such as unions which autocxx
+/// doesn't yet support; nothing is known about the C++ type other than
+/// its layout, so it's up to you to ensure that treating it as plain
+/// bytes is safe.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! opaque_fallback {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Generates a `swap` method for a type, for example
+/// `swappable!("Buffer")`, which exchanges the contents of two
+/// instances using the C++ `swap`: `a.as_mut().swap(b.as_mut())`.
+/// A custom `swap` found by argument-dependent lookup is preferred,
+/// falling back to `std::swap`. This is useful for types which can't
+/// be moved in Rust, and so can't use [`std::mem::swap`].
+/// The type must also be on the allowlist, e.g. using [generate].
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! swappable {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Declares that a method consumes the object on which it's called, for
+/// example `consuming_method!("Builder::build")`, typically leaving it
+/// moved-from. The method is then treated just like a `&&`-qualified
+/// method: instead of taking `self`, it's an associated function which
+/// takes ownership of the object as its first parameter, e.g. a
+/// `UniquePtr<Builder>`, so that Rust code can't use the object again
+/// afterwards. (Stable Rust doesn't allow `self: UniquePtr<Self>`.)
+/// All overloads of the method are affected.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! consuming_method {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Makes the Rust function generated for a C++ function available only
+/// under a given `cfg` predicate, for instance
+/// `cfg_gate!("enable_hugepages", target_os = "linux")`. This is useful
+/// where a header declares the function only within an `#ifdef`, so that
+/// code calling it must itself be conditionally compiled. The predicate
+/// is attached as `#[cfg(...)]` to everything generated in Rust for the
+/// function, and takes any of the forms `cfg` does, e.g.
+/// `all(unix, not(target_env = "musl"))`. The C++ side is generated only
+/// if the predicate holds, which is judged from the `CARGO_CFG_*` and
+/// `CARGO_FEATURE_*` variables Cargo gives a build script; elsewhere, no
+/// `cfg` is considered set.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! cfg_gate {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Sets the Rust visibility of a generated function or type, which is
+/// otherwise `pub`. For example, `visibility!("reset_cache", pub(crate))`
+/// keeps `reset_cache` from being part of your crate's public API.
+/// Any visibility Rust accepts may be given, or `private` to hide the
+/// item from everything outside the generated mod. Items are identified
+/// by their C++ name, including any namespace.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! visibility {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Presents the `const char*` returned by a function more conveniently
+/// than as a raw pointer. `c_string_return!("Person::name", CStr)` makes
+/// `name` return an `Option<&CStr>` borrowed from the `Person`, which is
+/// `None` if the pointer is null. For a free function or static method,
+/// such as `c_string_return!("version", CStr)`, the result is an
+/// `Option<&'static CStr>` and the function is `unsafe`, since only you
+/// can know that the string lives for the rest of the program, as is
+/// typical for C APIs returning constant strings. Otherwise,
+/// `c_string_return!("Person::name", String)`
+/// copies the string into an owned `String` (lossily, if it's not
+/// UTF-8), which is empty if the pointer is null.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! c_string_return {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Attaches attributes of your choosing to the Rust function generated for
+/// a C++ function or method, for instance
+/// `extra_attributes!("Engine::start", #[tracing::instrument(skip_all)])`.
+/// The attributes are passed through verbatim, so they may be
+/// procedural macro attributes. They're attached to the Rust wrapper
+/// function, which is always generated for a function listed here, and
+/// not to the underlying `cxx::bridge` declaration. All overloads of
+/// the function are affected.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! extra_attributes {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Declares that a static method or free function returns a reference to
+/// an object which lives for the rest of the program, for example
+/// `singleton!("Config::instance")`. The generated Rust function will
+/// return `&'static Config` instead of being rejected for lacking an
+/// input reference. Any mutable reference returned by C++ is exposed as a
+/// shared reference.
+///
+/// This is an assertion on your part: nothing checks that the object is
+/// never destroyed, nor that C++ doesn't mutate it while Rust holds the
+/// reference.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! singleton {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Keeps the pointer and count parameters of a function separate, for
+/// example `no_slice_params!("draw")`. Otherwise, a pointer to POD
+/// structs or `std::byte`s immediately followed by a `size_t` becomes a
+/// single slice parameter, which is wrong if the count isn't the length
+/// of the array.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! no_slice_params {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
 /// Specifies a global safety policy for functions generated
 /// from these headers. By default (without such a `safety!`
 /// directive) all such functions are marked as `unsafe` and
@@ -335,6 +826,25 @@ macro_rules! extern_cpp_type {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
+/// Indicates that a C++ enum is not to be generated by autocxx in this case,
+/// but instead should refer to some pre-existing Rust enum.
+///
+/// The syntax is:
+/// `extern_cpp_enum!("CppNameGoesHere", path::to::rust::Enum)`
+///
+/// The Rust enum must have a `#[repr]` matching the underlying type of the
+/// C++ enum, and a variant of the same name for each C++ enumerator.
+/// `autocxx` implements [`cxx::ExternType`] for the enum, treating it as
+/// [`cxx::kind::Trivial`], and checks at compile time that its size and
+/// the discriminant of each variant match the C++ definition.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! extern_cpp_enum {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
 /// Indicates that a C++ type is not to be generated by autocxx in this case,
 /// but instead should refer to some pre-existing Rust type. Unlike
 /// `extern_cpp_type!`, there's no need for the size and alignment of this
@@ -369,6 +879,25 @@ macro_rules! subclass {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
+/// Allow a C++ interface - that is, a class consisting only of pure
+/// virtual methods - to be implemented by any Rust type.
+/// `interface!("Observer")` makes the trait `Observer_methods` available
+/// for you to implement, and provides `Observer::from_rust_impl`, which
+/// accepts any such implementation and returns a
+/// `cxx::UniquePtr<Observer>` which can be passed to C++. Each virtual
+/// method call from C++ is then forwarded to your Rust implementation.
+///
+/// Unlike [`subclass`], you don't need to use the [`subclass::subclass`]
+/// attribute on your type, but you also can't call superclass methods, so
+/// this can't be used for classes with any non-pure virtual methods.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! interface {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
 /// Indicates that a C++ type can definitely be instantiated. This has effect
 /// only in a very specific case:
 /// * the type is a typedef to something else
@@ -407,6 +936,7 @@ macro_rules! usage {
     };
 }
 
+use std::mem::MaybeUninit;
 use std::pin::Pin;
 
 #[doc(hidden)]
@@ -418,7 +948,7 @@ pub use autocxx_macro::cpp_semantics;
 macro_rules! ctype_wrapper {
     ($r:ident, $c:expr, $d:expr) => {
         #[doc=$d]
-        #[derive(Debug, Eq, Copy, Clone, PartialEq, Hash)]
+        #[derive(Debug, Default, Eq, Copy, Clone, PartialEq, Hash)]
         #[allow(non_camel_case_types)]
         #[repr(transparent)]
         pub struct $r(pub ::std::os::raw::$r);
@@ -492,6 +1022,38 @@ unsafe impl cxx::ExternType for c_char16_t {
     type Kind = cxx::kind::Trivial;
 }
 
+/// The error returned by a function generated with
+/// `checked_integer_conversions!(result)` when an integer argument
+/// doesn't fit into the type of the corresponding C++ parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntegerConversionError {
+    param: &'static str,
+}
+
+impl IntegerConversionError {
+    #[doc(hidden)]
+    pub fn new(param: &'static str) -> Self {
+        Self { param }
+    }
+
+    /// The name of the parameter whose argument didn't fit.
+    pub fn param(&self) -> &'static str {
+        self.param
+    }
+}
+
+impl std::fmt::Display for IntegerConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "integer argument `{}` is out of range for its C++ type",
+            self.param
+        )
+    }
+}
+
+impl std::error::Error for IntegerConversionError {}
+
 /// autocxx couldn't generate these bindings.
 /// If you come across a method, type or function which refers to this type,
 /// it indicates that autocxx couldn't generate that binding. A documentation
@@ -601,6 +1163,27 @@ pub trait WithinBox {
     fn within_cpp_pin(self) -> CppPin<Self::Inner>;
 }
 
+/// Provides a utility function to emplace any [`moveit::New`] into storage
+/// which you've allocated yourself, for example within an arena.
+/// Automatically imported by the autocxx prelude
+/// and implemented by any (autocxx-related) [`moveit::New`].
+pub trait WithinMaybeUninit {
+    type Inner;
+    /// Create this item within the given storage, by calling the C++
+    /// constructor with placement `new`.
+    ///
+    /// # Safety
+    ///
+    /// The item won't be dropped automatically. You must drop it, for
+    /// example using [`std::ptr::drop_in_place`], before the storage is
+    /// freed or reused, since C++ objects may rely on not being moved
+    /// until they're destroyed.
+    unsafe fn within_maybe_uninit(
+        self,
+        slot: Pin<&mut MaybeUninit<Self::Inner>>,
+    ) -> Pin<&mut Self::Inner>;
+}
+
 use cxx::kind::Trivial;
 use cxx::ExternType;
 use moveit::Emplace;
@@ -630,6 +1213,17 @@ where
     }
 }
 
+impl<N, T> WithinMaybeUninit for N
+where
+    N: New<Output = T>,
+{
+    type Inner = T;
+    unsafe fn within_maybe_uninit(self, mut slot: Pin<&mut MaybeUninit<T>>) -> Pin<&mut T> {
+        self.new(slot.as_mut());
+        slot.map_unchecked_mut(|slot| slot.assume_init_mut())
+    }
+}
+
 /// Emulates the [`WithinUniquePtr`] trait, but for trivial (plain old data) types.
 /// This allows such types to behave identically if a type is changed from
 /// `generate!` to `generate_pod!`.
@@ -709,6 +1303,7 @@ pub mod prelude {
     pub use crate::ValueParam;
     pub use crate::WithinBox;
     pub use crate::WithinBoxTrivial;
+    pub use crate::WithinMaybeUninit;
     pub use crate::WithinUniquePtr;
     pub use crate::WithinUniquePtrTrivial;
     pub use cxx::UniquePtr;
@@ -720,6 +1315,11 @@ pub mod prelude {
 /// Re-export moveit for ease of consumers.
 pub use moveit;
 
+/// Re-export bitflags for use by types generated using [bitflags_enum].
+#[cfg(feature = "bitflags")]
+#[doc(hidden)]
+pub use bitflags;
+
 /// Re-export cxx such that clients can use the same version as
 /// us. This doesn't enable clients to avoid depending on the cxx
 /// crate too, unfortunately, since generated cxx::bridge code
@@ -6,7 +6,9 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use cxx::{memory::UniquePtrTarget, UniquePtr};
+use cxx::{
+    kind::Trivial, memory::UniquePtrTarget, vector::VectorElement, CxxVector, ExternType, UniquePtr,
+};
 use moveit::{AsMove, CopyNew, MoveNew, New};
 use std::{marker::PhantomPinned, mem::MaybeUninit, ops::Deref, pin::Pin};
 
@@ -20,6 +22,8 @@ use std::{marker::PhantomPinned, mem::MaybeUninit, ops::Deref, pin::Pin};
 /// To make it easy to pass such parameters from Rust, this trait exists.
 /// It is implemented both for references `&T` and for `UniquePtr<T>`,
 /// subject to the presence or absence of suitable copy and move constructors.
+/// A `std::vector` of trivial (POD) elements may also be passed as a Rust
+/// `Vec`, whose contents are copied into a new `std::vector`.
 /// This allows you to pass in parameters by copy (as is ergonomic and normal
 /// in C++) retaining the original parameter; or by move semantics thus
 /// destroying the object you're passing in. Simply use a reference if you want
@@ -190,6 +194,28 @@ where
     }
 }
 
+/// Passes a Rust [`Vec`] to C++ as a `std::vector`, by copying its
+/// contents into a newly constructed `std::vector`. This is only possible
+/// for trivial (POD) element types.
+unsafe impl<T> ValueParam<CxxVector<T>> for Vec<T>
+where
+    T: VectorElement + ExternType<Kind = Trivial>,
+{
+    type StackStorage = UniquePtr<CxxVector<T>>;
+
+    unsafe fn populate_stack_space(self, stack: Pin<&mut Option<Self::StackStorage>>) {
+        let mut vector = CxxVector::new();
+        for item in self {
+            vector.pin_mut().push(item);
+        }
+        vector.populate_stack_space(stack)
+    }
+
+    fn get_ptr(stack: Pin<&mut Self::StackStorage>) -> *mut CxxVector<T> {
+        <UniquePtr<CxxVector<T>> as ValueParam<CxxVector<T>>>::get_ptr(stack)
+    }
+}
+
 /// Explicitly force a value parameter to be taken using any type of [`crate::moveit::new::New`],
 /// i.e. a constructor.
 pub fn as_new<N: New>(constructor: N) -> impl ValueParam<N::Output> {